@@ -0,0 +1,246 @@
+// benches/grid_update.rs
+//
+// One frame of a 6x6 grid with a glyph lit, once everything has settled and
+// while the backbone style changes every frame so every idle segment is
+// restaged. Also drawing a fully lit grid, with the allocations each draw
+// makes counted, the same draw with more glow layers, and getting a grid for
+// a new instance by cloning the base grid or by resetting a pooled one.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use glyphvis::animation::{TransitionAnimationType, TransitionEngine};
+use glyphvis::config::TransitionConfig;
+use glyphvis::models::{Project, SegmentKey};
+use glyphvis::services::SegmentGraph;
+use glyphvis::views::{
+    CachedGrid, DrawStyle, GlowParams, GridInstance, SegmentAction, StyleUpdateMsg,
+};
+use nannou::prelude::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const FRAME: f32 = 1.0 / 60.0;
+
+// Counts every allocation the benchmark makes
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn lit_grid_6x6() -> (GridInstance, TransitionEngine) {
+    lit_grid_6x6_with(|project| {
+        project.glyphs["Hul1"]
+            .segments
+            .iter()
+            .map(|id| id.parse().unwrap())
+            .collect()
+    })
+}
+
+fn lit_grid_6x6_with(
+    glyph: impl Fn(&Project) -> HashSet<SegmentKey>,
+) -> (GridInstance, TransitionEngine) {
+    let mut project = Project::load("projects/ulsan.json").expect("ulsan project");
+    project.grid_x = 6;
+    project.grid_y = 6;
+    let grid = CachedGrid::new(&project);
+    let glyph = glyph(&project);
+    let graph = Rc::new(SegmentGraph::new(&grid));
+    let mut grid_instance = GridInstance::new(
+        "bench".to_string(),
+        &project,
+        "",
+        &grid,
+        graph,
+        pt2(0.0, 0.0),
+        0.0,
+        10.0,
+        5.0,
+    );
+    grid_instance.is_visible = true;
+    grid_instance.transition_next_animation_type = TransitionAnimationType::Immediate;
+    grid_instance.target_segments = Some(glyph);
+
+    let engine = TransitionEngine::new(TransitionConfig {
+        steps: 20,
+        frame_duration: 0.02,
+        wandering: 0.5,
+        density: 0.1,
+        seed: Some(1),
+        stroke_gap_steps: 0,
+        stagger: 0.0,
+    });
+
+    // let the glyph power on and settle
+    let draw = Draw::new();
+    for frame in 0..600 {
+        grid_instance.update(&draw, &engine, frame as f32 * FRAME, FRAME);
+        draw.reset();
+    }
+    (grid_instance, engine)
+}
+
+fn grid_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grid_update_6x6");
+
+    let (mut grid, engine) = lit_grid_6x6();
+    let draw = Draw::new();
+    let mut time = 10.0;
+    group.bench_function("settled", |b| {
+        b.iter(|| {
+            time += FRAME;
+            grid.update(&draw, &engine, time, FRAME);
+            draw.reset();
+        })
+    });
+
+    let (mut grid, engine) = lit_grid_6x6();
+    let mut frame = 0;
+    group.bench_function("backbone_changing", |b| {
+        b.iter(|| {
+            frame += 1;
+            time += FRAME;
+            let level = 0.1 + 0.1 * (frame % 2) as f32;
+            grid.backbone_style = DrawStyle {
+                color: rgba(level, level, level, 1.0),
+                stroke_weight: 5.0,
+            };
+            grid.update(&draw, &engine, time, FRAME);
+            draw.reset();
+        })
+    });
+    group.finish();
+}
+
+fn draw_fully_lit(c: &mut Criterion) {
+    let (grid, _) =
+        lit_grid_6x6_with(|project| CachedGrid::new(project).segments.keys().cloned().collect());
+    let draw = Draw::new();
+
+    // the first draw grows the Draw's buffers, so count the second
+    grid.grid.draw(&draw, None, None, 1.0, &HashSet::new());
+    draw.reset();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    grid.grid.draw(&draw, None, None, 1.0, &HashSet::new());
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    let primitives = draw.drain_commands().count();
+    draw.reset();
+    println!(
+        "draw_fully_lit_6x6: {} allocations and {} draw commands for {} segments",
+        allocations,
+        primitives,
+        grid.grid.segments.len()
+    );
+
+    c.bench_function("draw_fully_lit_6x6", |b| {
+        b.iter(|| {
+            grid.grid.draw(&draw, None, None, 1.0, &HashSet::new());
+            draw.reset();
+        })
+    });
+}
+
+// Recording the draw calls only, not GPU time. Each layer adds to the
+// no-glow cost.
+fn draw_glow(c: &mut Criterion) {
+    let mut group = c.benchmark_group("draw_glow_6x6");
+
+    let (grid, _) =
+        lit_grid_6x6_with(|project| CachedGrid::new(project).segments.keys().cloned().collect());
+    let draw = Draw::new();
+    for layers in [0, 1, 2, 4, 8] {
+        let glow = GlowParams {
+            layers,
+            spread: 0.6,
+            alpha_falloff: 0.35,
+        };
+        group.bench_function(format!("{}_layers", layers), |b| {
+            b.iter(|| {
+                grid.grid.draw(
+                    &draw,
+                    (layers > 0).then_some(&glow),
+                    None,
+                    1.0,
+                    &HashSet::new(),
+                );
+                draw.reset();
+            })
+        });
+    }
+    group.finish();
+}
+
+fn grid_create(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grid_create_6x6");
+
+    let (grid, _) = lit_grid_6x6();
+    let mut project = Project::load("projects/ulsan.json").expect("ulsan project");
+    project.grid_x = 6;
+    project.grid_y = 6;
+    let base = CachedGrid::new(&project);
+    group.bench_function("cloned", |b| b.iter(|| base.clone()));
+
+    // the first reset has a lit grid to put back, later ones an idle one
+    let mut pooled = grid.into_grid();
+    group.bench_function("pooled", |b| b.iter(|| pooled.reset()));
+    group.finish();
+}
+
+fn update_batch_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_batch_insert_6x6");
+
+    let mut project = Project::load("projects/ulsan.json").expect("ulsan project");
+    project.grid_x = 6;
+    project.grid_y = 6;
+    let grid = CachedGrid::new(&project);
+    let mut keys: Vec<SegmentKey> = grid.segments.keys().cloned().collect();
+    keys.sort();
+    let strings: Vec<String> = keys.iter().map(SegmentKey::to_string).collect();
+    let msg = StyleUpdateMsg::new(SegmentAction::InstantStyleChange, DrawStyle::default());
+
+    // the batch is cleared each frame and keeps its capacity
+    let mut batch: HashMap<String, StyleUpdateMsg> = HashMap::new();
+    group.bench_function("string_keys", |b| {
+        b.iter(|| {
+            batch.clear();
+            for id in &strings {
+                batch.insert(id.clone(), msg.clone());
+            }
+        })
+    });
+
+    let mut batch: HashMap<SegmentKey, StyleUpdateMsg> = HashMap::new();
+    group.bench_function("segment_keys", |b| {
+        b.iter(|| {
+            batch.clear();
+            for id in &keys {
+                batch.insert(id.clone(), msg.clone());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    grid_update,
+    draw_fully_lit,
+    draw_glow,
+    grid_create,
+    update_batch_insert
+);
+criterion_main!(benches);
@@ -0,0 +1,66 @@
+// benches/spatial_index.rs
+//
+// Point queries on a 10x10 grid, through the spatial index and by checking
+// every segment.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use glyphvis::models::{Project, SegmentKey};
+use glyphvis::utilities::segment_analysis;
+use glyphvis::views::CachedGrid;
+use nannou::prelude::*;
+
+fn grid_10x10() -> CachedGrid {
+    let mut project = Project::load("projects/debug.json").expect("debug project");
+    project.grid_x = 10;
+    project.grid_y = 10;
+    CachedGrid::new(&project)
+}
+
+fn query_points(grid: &CachedGrid) -> Vec<Point2> {
+    let mut points: Vec<Point2> = grid
+        .segments
+        .values()
+        .map(|segment| segment_analysis::get_segment_center(&segment.id, grid))
+        .collect();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.into_iter().step_by(7).collect()
+}
+
+fn linear_scan(grid: &CachedGrid, point: Point2, radius: f32) -> Vec<SegmentKey> {
+    let mut found: Vec<SegmentKey> = grid
+        .segments
+        .values()
+        .filter(|segment| {
+            segment_analysis::distance_to_point(&segment.draw_commands, point) <= radius
+        })
+        .map(|segment| segment.id.clone())
+        .collect();
+    found.sort();
+    found
+}
+
+fn segments_near(c: &mut Criterion) {
+    let grid = grid_10x10();
+    let points = query_points(&grid);
+    let radius = 5.0;
+
+    let mut group = c.benchmark_group("segments_near");
+    group.bench_function("spatial_index", |b| {
+        b.iter(|| {
+            for point in &points {
+                black_box(grid.spatial_index.segments_near(*point, radius));
+            }
+        })
+    });
+    group.bench_function("linear_scan", |b| {
+        b.iter(|| {
+            for point in &points {
+                black_box(linear_scan(&grid, *point, radius));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, segments_near);
+criterion_main!(benches);
@@ -0,0 +1,97 @@
+// src/animation/glyph_loop.rs
+//
+// Alternates a grid between two glyphs for idle states. Each glyph is held
+// for hold_time once its transition has finished.
+
+use crate::animation::TransitionAnimationType;
+
+#[derive(Clone)]
+pub struct GlyphLoop {
+    index_a: usize,
+    index_b: usize,
+    hold_time: f32,
+    pub animation_type: TransitionAnimationType,
+    current: Option<usize>, // the index last staged by the loop
+    hold_start: Option<f32>,
+}
+
+impl GlyphLoop {
+    pub fn new(
+        index_a: usize,
+        index_b: usize,
+        hold_time: f32,
+        animation_type: TransitionAnimationType,
+    ) -> Self {
+        Self {
+            index_a,
+            index_b,
+            hold_time: hold_time.max(0.0),
+            animation_type,
+            current: None,
+            hold_start: None,
+        }
+    }
+
+    // Returns the glyph index to stage this frame, if any. The hold only
+    // starts counting once the grid is no longer transitioning.
+    pub fn next_index(&mut self, transitioning: bool, time: f32) -> Option<usize> {
+        let Some(current) = self.current else {
+            // start the loop right away on the first glyph
+            self.current = Some(self.index_a);
+            return self.current;
+        };
+
+        if transitioning {
+            self.hold_start = None;
+            return None;
+        }
+
+        let hold_start = *self.hold_start.get_or_insert(time);
+        if time - hold_start < self.hold_time {
+            return None;
+        }
+
+        let next = if current == self.index_a {
+            self.index_b
+        } else {
+            self.index_a
+        };
+        self.current = Some(next);
+        self.hold_start = None;
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loop_alternates_after_hold() {
+        let mut glyph_loop = GlyphLoop::new(2, 5, 1.0, TransitionAnimationType::Immediate);
+        let transition_time = 0.5;
+        let dt = 1.0 / 60.0;
+
+        let mut staged: Vec<(usize, f32)> = Vec::new();
+        let mut transition_end = 0.0;
+        let mut time = 0.0;
+
+        while time < 6.0 {
+            let transitioning = time < transition_end;
+            if let Some(index) = glyph_loop.next_index(transitioning, time) {
+                staged.push((index, time));
+                transition_end = time + transition_time;
+            }
+            time += dt;
+        }
+
+        let indices: Vec<usize> = staged.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indices, vec![2, 5, 2, 5]);
+
+        // each glyph is staged after its predecessor's transition plus the hold
+        for pair in staged.windows(2) {
+            let interval = pair[1].1 - pair[0].1;
+            assert!((interval - 1.5).abs() < 2.0 * dt);
+        }
+    }
+}
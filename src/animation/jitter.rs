@@ -0,0 +1,128 @@
+// src/animation/jitter.rs
+//
+// A short positional shake for impact moments.
+//
+// The jitter is tracked as an offset from the grid's logical position
+// (GridInstance::current_position), so it rides on top of any movement
+// in progress and always settles back to zero when complete.
+
+use nannou::prelude::*;
+use rand::{rngs::StdRng, Rng};
+
+use crate::views::Transform2D;
+
+pub struct JitterAnimation {
+    amplitude: f32, // maximum distance from the logical position
+    frequency: f32, // new random offsets per second
+    start_time: f32,
+    duration: f32,
+    next_sample_time: f32,
+    current_offset: Vec2, // offset currently applied to the grid
+    complete: bool,
+    rng: StdRng,
+}
+
+impl JitterAnimation {
+    pub fn new(
+        amplitude: f32,
+        frequency: f32,
+        duration: f32,
+        start_time: f32,
+        rng: StdRng,
+    ) -> Self {
+        Self {
+            amplitude: amplitude.abs(),
+            frequency,
+            start_time,
+            duration,
+            next_sample_time: start_time,
+            current_offset: Vec2::ZERO,
+            complete: false,
+            rng,
+        }
+    }
+
+    // Returns the translation that moves the grid from the previously applied
+    // offset to the new one, or None if nothing changes this frame.
+    pub fn advance(&mut self, time: f32) -> Option<Transform2D> {
+        if self.complete {
+            return None;
+        }
+
+        let target_offset = if time - self.start_time >= self.duration {
+            // Return exactly to the logical position
+            self.complete = true;
+            Vec2::ZERO
+        } else if time >= self.next_sample_time {
+            self.next_sample_time = if self.frequency > 0.0 {
+                time + 1.0 / self.frequency
+            } else {
+                time
+            };
+            self.random_offset()
+        } else {
+            return None;
+        };
+
+        let delta = target_offset - self.current_offset;
+        self.current_offset = target_offset;
+
+        if delta == Vec2::ZERO {
+            return None;
+        }
+
+        Some(Transform2D {
+            translation: delta,
+            scale: Vec2::ONE,
+            rotation: 0.0,
+        })
+    }
+
+    pub fn current_offset(&self) -> Vec2 {
+        self.current_offset
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    // A random point within a circle of radius `amplitude`
+    fn random_offset(&mut self) -> Vec2 {
+        let angle = self.rng.gen_range(0.0..TAU);
+        let radius = self.amplitude * self.rng.gen_range(0.0f32..=1.0).sqrt();
+        vec2(angle.cos() * radius, angle.sin() * radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_jitter_stays_within_amplitude() {
+        let mut jitter = JitterAnimation::new(5.0, 30.0, 1.0, 0.0, StdRng::seed_from_u64(1));
+        let mut time = 0.0;
+        while time < 0.9 {
+            jitter.advance(time);
+            assert!(jitter.current_offset().length() <= 5.0 + 1e-4);
+            time += 1.0 / 60.0;
+        }
+    }
+
+    #[test]
+    fn test_jitter_returns_to_origin() {
+        let mut jitter = JitterAnimation::new(10.0, 60.0, 0.5, 2.0, StdRng::seed_from_u64(1));
+        let mut accumulated = Vec2::ZERO;
+        let mut time = 2.0;
+        while !jitter.is_complete() {
+            if let Some(transform) = jitter.advance(time) {
+                accumulated += transform.translation;
+            }
+            time += 1.0 / 60.0;
+        }
+        assert_eq!(jitter.current_offset(), Vec2::ZERO);
+        assert!(accumulated.length() < 1e-4);
+        assert!(jitter.advance(time).is_none());
+    }
+}
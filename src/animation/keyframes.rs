@@ -0,0 +1,125 @@
+// src/animation/keyframes.rs
+//
+// A choreographed gesture combining position, rotation and scale.
+//
+// The track is sampled each frame for absolute values; GridInstance turns
+// them into deltas around its current pivot.
+
+use crate::animation::EasingType;
+use nannou::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct Keyframe {
+    pub time: f32, // seconds from the start of the track
+    pub position: Point2,
+    pub rotation: f32, // degrees
+    pub scale: f32,
+    pub easing: EasingType, // easing into this keyframe from the previous one
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyframeSample {
+    pub position: Point2,
+    pub rotation: f32,
+    pub scale: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyframeTrack {
+    keyframes: Vec<Keyframe>,
+    start_time: f32,
+}
+
+impl KeyframeTrack {
+    // `initial` is the grid's state when the track starts. It leads into the
+    // first keyframe unless that keyframe is at time zero.
+    pub fn new(initial: KeyframeSample, mut keyframes: Vec<Keyframe>, start_time: f32) -> Self {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        if keyframes.first().is_none_or(|first| first.time > 0.0) {
+            keyframes.insert(
+                0,
+                Keyframe {
+                    time: 0.0,
+                    position: initial.position,
+                    rotation: initial.rotation,
+                    scale: initial.scale,
+                    easing: EasingType::Linear,
+                },
+            );
+        }
+
+        Self {
+            keyframes,
+            start_time,
+        }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |last| last.time)
+    }
+
+    pub fn is_complete(&self, time: f32) -> bool {
+        time - self.start_time >= self.duration()
+    }
+
+    pub fn sample(&self, time: f32) -> KeyframeSample {
+        let t = time - self.start_time;
+
+        // after the end, land exactly on the last keyframe
+        let next_index = match self.keyframes.iter().position(|k| k.time > t) {
+            Some(0) => return Self::values(&self.keyframes[0]),
+            Some(index) => index,
+            None => return Self::values(self.keyframes.last().unwrap()),
+        };
+
+        let from = &self.keyframes[next_index - 1];
+        let to = &self.keyframes[next_index];
+        let progress = to.easing.apply((t - from.time) / (to.time - from.time));
+
+        KeyframeSample {
+            position: from.position.lerp(to.position, progress),
+            rotation: from.rotation + (to.rotation - from.rotation) * progress,
+            scale: from.scale + (to.scale - from.scale) * progress,
+        }
+    }
+
+    fn values(keyframe: &Keyframe) -> KeyframeSample {
+        KeyframeSample {
+            position: keyframe.position,
+            rotation: keyframe.rotation,
+            scale: keyframe.scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_track_leads_in_from_initial_state() {
+        let initial = KeyframeSample {
+            position: pt2(0.0, 0.0),
+            rotation: 0.0,
+            scale: 1.0,
+        };
+        let keyframes = vec![Keyframe {
+            time: 2.0,
+            position: pt2(100.0, 50.0),
+            rotation: 90.0,
+            scale: 2.0,
+            easing: EasingType::Linear,
+        }];
+        let track = KeyframeTrack::new(initial, keyframes, 10.0);
+
+        assert_eq!(track.sample(10.0), initial);
+        let halfway = track.sample(11.0);
+        assert_eq!(halfway.position, pt2(50.0, 25.0));
+        assert_eq!(halfway.rotation, 45.0);
+        assert_eq!(halfway.scale, 1.5);
+
+        assert!(track.is_complete(12.0));
+        assert_eq!(track.sample(12.5).position, pt2(100.0, 50.0));
+    }
+}
@@ -1,14 +1,29 @@
+pub mod glyph_loop;
+pub mod jitter;
+pub mod keyframes;
+pub mod morph;
 pub mod movement;
+pub mod orbit;
+pub mod path_movement;
 pub mod slide_movement;
 pub mod stretch;
 pub mod stroke_order;
 pub mod transition;
 
-pub use movement::{EasingType, MovementChange, MovementEngine, TimedMovement};
-pub use slide_movement::SlideAnimation;
+pub use glyph_loop::GlyphLoop;
+pub use jitter::JitterAnimation;
+pub use keyframes::{Keyframe, KeyframeSample, KeyframeTrack};
+pub use movement::{
+    EasingType, MovementChange, MovementEngine, MovementLeg, TimedMovement,
+    DEFAULT_INSTANT_MOVE_DURATION,
+};
+pub use orbit::OrbitAnimation;
+pub use path_movement::PathMovement;
+pub use slide_movement::{SlideAnimation, INSTANT_SLIDE_DURATION};
 pub use stretch::StretchAnimation;
 pub use transition::{
-    Transition, TransitionAnimationType, TransitionEngine, TransitionTriggerType, TransitionUpdates,
+    crossfade_changes, Transition, TransitionAnimationType, TransitionEngine,
+    TransitionTriggerType, TransitionUpdates,
 };
 
 use nannou::prelude::*;
@@ -0,0 +1,150 @@
+// src/animation/morph.rs
+//
+// extension to TransitionEngine that crawls the lit segments along the
+// SegmentGraph from the old glyph to the new one
+
+use crate::{animation::transition::SegmentChange, models::SegmentKey, views::GridInstance};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// For each incoming segment, the shortest graph path from the nearest
+// currently active segment to it. Incoming segments with no path are
+// returned separately.
+pub fn find_morph_paths(
+    grid_instance: &GridInstance,
+    target_segments: &HashSet<SegmentKey>,
+) -> (Vec<Vec<SegmentKey>>, Vec<SegmentKey>) {
+    let start_segments = &grid_instance.current_active_segments;
+
+    let mut incoming: Vec<&SegmentKey> = target_segments.difference(start_segments).collect();
+    incoming.sort();
+
+    let mut paths = Vec::new();
+    let mut unreachable = Vec::new();
+
+    for segment_id in incoming {
+        match grid_instance
+            .graph()
+            .find_path_to_nearest(segment_id, start_segments)
+        {
+            Some(mut path) => {
+                // search ran from the new segment, crawl runs toward it
+                path.reverse();
+                paths.push(path);
+            }
+            None => unreachable.push(segment_id.clone()),
+        }
+    }
+
+    (paths, unreachable)
+}
+
+// Turns the crawl paths into transition steps.
+//
+// A segment at position k along a path is lit for steps k-1 and k, so the
+// light hands off from one segment to the next. Incoming segments stay on
+// once reached and outgoing segments go out as the crawl leaves them.
+pub fn schedule_morph(
+    paths: &[Vec<SegmentKey>],
+    start_segments: &HashSet<SegmentKey>,
+    target_segments: &HashSet<SegmentKey>,
+) -> Vec<Vec<SegmentChange>> {
+    // timeline t = step + 1, where t = 0 is the state before the transition
+    let mut lit_times: HashMap<&SegmentKey, BTreeSet<usize>> = HashMap::new();
+    let mut arrival_times: HashMap<&SegmentKey, usize> = HashMap::new();
+
+    for segment_id in start_segments.difference(target_segments) {
+        lit_times.entry(segment_id).or_default().extend([0, 1]);
+    }
+
+    for path in paths {
+        for (k, segment_id) in path.iter().enumerate() {
+            let in_start = start_segments.contains(segment_id);
+            let in_target = target_segments.contains(segment_id);
+
+            if in_target && !in_start {
+                let arrival = arrival_times.entry(segment_id).or_insert(k);
+                *arrival = (*arrival).min(k);
+            } else if !in_target {
+                lit_times.entry(segment_id).or_default().extend([k, k + 1]);
+            }
+        }
+    }
+
+    let last_time = lit_times
+        .values()
+        .filter_map(|times| times.last().map(|t| t + 1))
+        .chain(arrival_times.values().copied())
+        .max()
+        .unwrap_or(0);
+
+    let mut changes_by_step: Vec<Vec<SegmentChange>> = (0..last_time).map(|_| Vec::new()).collect();
+
+    for (segment_id, times) in &lit_times {
+        let mut was_lit = start_segments.contains(*segment_id);
+        for t in 1..=last_time {
+            let is_lit = times.contains(&t);
+            if is_lit != was_lit {
+                changes_by_step[t - 1].push(SegmentChange {
+                    segment_id: (*segment_id).clone(),
+                    turn_on: is_lit,
+                    style: None,
+                });
+                was_lit = is_lit;
+            }
+        }
+    }
+
+    for (segment_id, arrival) in &arrival_times {
+        changes_by_step[arrival.saturating_sub(1)].push(SegmentChange {
+            segment_id: (*segment_id).clone(),
+            turn_on: true,
+            style: None,
+        });
+    }
+
+    for step in &mut changes_by_step {
+        step.sort_by(|a, b| a.segment_id.cmp(&b.segment_id));
+    }
+    changes_by_step
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(ids: &[&str]) -> Vec<SegmentKey> {
+        ids.iter().map(|id| SegmentKey::off_grid(*id)).collect()
+    }
+
+    fn set(ids: &[&str]) -> HashSet<SegmentKey> {
+        keys(ids).into_iter().collect()
+    }
+
+    fn step_ids(step: &[SegmentChange]) -> Vec<(&str, bool)> {
+        step.iter()
+            .map(|change| (change.segment_id.element.as_str(), change.turn_on))
+            .collect()
+    }
+
+    #[test]
+    fn test_crawl_hands_off_along_path() {
+        let path = keys(&["A", "B", "C"]);
+        let changes = schedule_morph(&[path], &set(&["A"]), &set(&["C"]));
+
+        assert_eq!(changes.len(), 3);
+        assert_eq!(step_ids(&changes[0]), vec![("B", true)]);
+        assert_eq!(step_ids(&changes[1]), vec![("A", false), ("C", true)]);
+        assert_eq!(step_ids(&changes[2]), vec![("B", false)]);
+    }
+
+    #[test]
+    fn test_incoming_segment_on_path_stays_lit() {
+        // B is itself incoming, so it must not go out after C lights
+        let paths = vec![keys(&["A", "B"]), keys(&["A", "B", "C"])];
+        let changes = schedule_morph(&paths, &set(&["A"]), &set(&["A", "B", "C"]));
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(step_ids(&changes[0]), vec![("B", true)]);
+        assert_eq!(step_ids(&changes[1]), vec![("C", true)]);
+    }
+}
@@ -16,6 +16,50 @@ pub enum EasingType {
     EaseInOut,
     EaseIn,
     EaseOut,
+    EaseInOutCubic,
+    EaseOutElastic,
+}
+
+impl EasingType {
+    // Map linear progress (0.0-1.0) onto the easing curve
+    pub fn apply(&self, t: f32) -> f32 {
+        match self {
+            EasingType::Linear => t,
+            EasingType::EaseInOut => ease_in_out(t),
+            EasingType::EaseIn => ease_in(t),
+            EasingType::EaseOut => ease_out(t),
+            EasingType::EaseInOutCubic => ease_in_out_cubic(t),
+            EasingType::EaseOutElastic => ease_out_elastic(t),
+        }
+    }
+}
+
+impl TryFrom<&str> for EasingType {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().replace(['_', '-'], "").as_str() {
+            "linear" => Ok(EasingType::Linear),
+            "easeinout" => Ok(EasingType::EaseInOut),
+            "easein" => Ok(EasingType::EaseIn),
+            "easeout" => Ok(EasingType::EaseOut),
+            "easeinoutcubic" => Ok(EasingType::EaseInOutCubic),
+            "easeoutelastic" => Ok(EasingType::EaseOutElastic),
+            _ => Err(format!(
+                "Invalid easing: '{}'. Expected one of: linear, easeinout, easein, easeout, easeinoutcubic, easeoutelastic",
+                value
+            )),
+        }
+    }
+}
+
+// One leg of a queued multi-leg movement. Built into a movement only when it
+// starts, so it begins wherever the previous leg ended.
+#[derive(Debug, Clone)]
+pub struct MovementLeg {
+    pub target: Point2,
+    pub duration: f32,
+    pub easing: EasingType,
 }
 
 #[derive(Debug, Clone)]
@@ -23,21 +67,35 @@ pub struct MovementChange {
     pub transform: Transform2D,
 }
 
+// Default time for a zero-duration move to complete, before the interval
+// between consecutive position commands is known
+pub const DEFAULT_INSTANT_MOVE_DURATION: f32 = 1.0 / 60.0;
+
+// Follows a stream of position commands, e.g. from OSC. Each move completes
+// over `duration`, usually the interval between commands, measured against
+// the frame clock so a slow or dropped frame catches up instead of lagging.
 #[derive(Debug, Clone)]
 pub struct InstantMovement {
     target_position: Point2,
-    last_position: Point2,
+    start_position: Point2,
     trigger_time: f32, // time when command was received
-    duration: f32,     // usually equal to time between frame updates (1.0/60.0)
+    duration: f32,
+    complete: bool,
 }
 
 impl InstantMovement {
-    pub fn new(target_position: Point2, last_position: Point2, trigger_time: f32) -> Self {
+    pub fn new(
+        target_position: Point2,
+        start_position: Point2,
+        trigger_time: f32,
+        duration: f32,
+    ) -> Self {
         Self {
             target_position,
-            last_position,
+            start_position,
             trigger_time,
-            duration: 1.0 / 60.0,
+            duration: duration.max(f32::EPSILON),
+            complete: false,
         }
     }
 }
@@ -52,47 +110,30 @@ impl Animation for InstantMovement {
         let elapsed = time - self.trigger_time;
         let progress = (elapsed / self.duration).clamp(0.0, 1.0);
 
-        // Snap to exact target when very close to completion
-        if progress > 0.99 {
-            // Direct snap to target
-            let delta = self.target_position - current_position;
-
-            // Only return a transform if the delta is significant
-            if delta.length() < 0.001 {
-                return None;
-            }
-
-            let transform = Transform2D {
-                translation: delta,
-                scale: 1.0,
-                rotation: 0.0,
-            };
-
-            self.last_position = self.target_position;
-            return Some(MovementChange { transform });
-        }
-
-        let adjusted_target =
-            interpolate_position(current_position, self.target_position, progress);
+        let position = if progress >= 1.0 {
+            // land exactly on the target
+            self.complete = true;
+            self.target_position
+        } else {
+            interpolate_position(self.start_position, self.target_position, progress)
+        };
 
-        // Apply threshold to avoid tiny movements
-        let delta = adjusted_target - current_position;
-        if delta.length() < 0.001 {
+        let delta = position - current_position;
+        if delta == Vec2::ZERO {
             return None;
         }
 
-        let transform = Transform2D {
-            translation: delta,
-            scale: 1.0,
-            rotation: 0.0,
-        };
-
-        self.last_position = adjusted_target;
-        Some(MovementChange { transform })
+        Some(MovementChange {
+            transform: Transform2D {
+                translation: delta,
+                scale: Vec2::ONE,
+                rotation: 0.0,
+            },
+        })
     }
 
     fn is_complete(&self) -> bool {
-        self.last_position == self.target_position
+        self.complete
     }
 }
 
@@ -186,8 +227,9 @@ impl MovementEngine {
         target_position: Point2,
         current_position: Point2,
         trigger_time: f32,
+        duration: f32,
     ) -> InstantMovement {
-        InstantMovement::new(target_position, current_position, trigger_time)
+        InstantMovement::new(target_position, current_position, trigger_time, duration)
     }
 
     fn generate_movement_changes(
@@ -206,12 +248,7 @@ impl MovementEngine {
             } else {
                 1.0
             };
-            let eased_t = match self.config.easing {
-                EasingType::Linear => t,
-                EasingType::EaseInOut => ease_in_out(t),
-                EasingType::EaseIn => ease_in(t),
-                EasingType::EaseOut => ease_out(t),
-            };
+            let eased_t = self.config.easing.apply(t);
 
             // if this isn't the first step, calculate the delta from previous step
             let previous_t = if step == 0 {
@@ -219,12 +256,7 @@ impl MovementEngine {
             } else {
                 (step - 1) as f32 / (self.steps - 1) as f32
             };
-            let previous_eased_t = match self.config.easing {
-                EasingType::Linear => previous_t,
-                EasingType::EaseInOut => ease_in_out(previous_t),
-                EasingType::EaseIn => ease_in(previous_t),
-                EasingType::EaseOut => ease_out(previous_t),
-            };
+            let previous_eased_t = self.config.easing.apply(previous_t);
 
             let translation_delta = total_translation * (eased_t - previous_eased_t);
             //let rotation_delta = total_rotation * (eased_t - previous_eased_t);
@@ -233,7 +265,7 @@ impl MovementEngine {
             let transform = Transform2D {
                 translation: translation_delta,
                 rotation: 0.0,
-                scale: 1.0,
+                scale: Vec2::ONE,
             };
 
             changes.push(MovementChange { transform });
@@ -258,8 +290,104 @@ fn ease_out(t: f32) -> f32 {
     t * (2.0 - t)
 }
 
+fn ease_in_out_cubic(t: f32) -> f32 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+// overshoots the target and settles with a decaying oscillation
+fn ease_out_elastic(t: f32) -> f32 {
+    if t <= 0.0 {
+        0.0
+    } else if t >= 1.0 {
+        1.0
+    } else {
+        let c4 = (2.0 * PI) / 3.0;
+        2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * c4).sin() + 1.0
+    }
+}
+
 fn interpolate_position(last_position: Point2, target_position: Point2, progress: f32) -> Point2 {
     let interp_x = last_position.x + (target_position.x - last_position.x) * progress;
     let interp_y = last_position.y + (target_position.y - last_position.y) * progress;
     pt2(interp_x, interp_y)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instant_movement_follows_frame_clock() {
+        let start = pt2(0.0, 0.0);
+        let target = pt2(30.0, -15.0);
+        let duration = 1.0 / 30.0;
+        let mut movement = InstantMovement::new(target, start, 0.0, duration);
+
+        // irregular frames, including a dropped one
+        let frame_times = [0.004, 0.011, 0.013, 0.029, 0.031, 0.05];
+        let expected_speed = (target - start).length() / duration;
+
+        let mut position = start;
+        let mut previous_time = 0.0;
+        for time in frame_times {
+            if let Some(change) = movement.advance(position, time) {
+                let distance = change.transform.translation.length();
+                position += change.transform.translation;
+
+                let frame_time = time.min(duration) - previous_time;
+                if frame_time > 0.0 {
+                    assert!((distance / frame_time - expected_speed).abs() < 1.0);
+                }
+            }
+            previous_time = time.min(duration);
+        }
+
+        assert!(movement.is_complete());
+        assert_eq!(position, target);
+    }
+
+    #[test]
+    fn test_ease_in_out_cubic_curve() {
+        let easing = EasingType::EaseInOutCubic;
+        assert_eq!(easing.apply(0.0), 0.0);
+        assert_eq!(easing.apply(1.0), 1.0);
+        assert!((easing.apply(0.5) - 0.5).abs() < 1e-6);
+        assert!((easing.apply(0.25) - 0.0625).abs() < 1e-6);
+        assert!((easing.apply(0.75) - 0.9375).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ease_out_elastic_curve() {
+        let easing = EasingType::EaseOutElastic;
+        assert_eq!(easing.apply(0.0), 0.0);
+        assert_eq!(easing.apply(1.0), 1.0);
+
+        // overshoots the target before settling
+        let peak = (1..100)
+            .map(|i| easing.apply(i as f32 / 100.0))
+            .fold(f32::MIN, f32::max);
+        assert!(peak > 1.0);
+        assert!((easing.apply(0.99) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_easing_from_str() {
+        assert!(matches!(
+            EasingType::try_from("EaseOutElastic"),
+            Ok(EasingType::EaseOutElastic)
+        ));
+        assert!(matches!(
+            EasingType::try_from("ease_in_out_cubic"),
+            Ok(EasingType::EaseInOutCubic)
+        ));
+        assert!(matches!(
+            EasingType::try_from("linear"),
+            Ok(EasingType::Linear)
+        ));
+        assert!(EasingType::try_from("bouncy").is_err());
+    }
+}
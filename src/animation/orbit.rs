@@ -0,0 +1,101 @@
+// src/animation/orbit.rs
+//
+// Circles a grid around a world-space point.
+//
+// Like KeyframeTrack, the orbit is sampled for an absolute position and
+// rotation each frame; GridInstance applies the difference as deltas.
+
+use nannou::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct OrbitAnimation {
+    center: Point2,
+    radius: f32,
+    start_angle: f32,   // degrees, angle of the grid around the center at start
+    angular_speed: f32, // degrees per second, positive is counter-clockwise
+    start_rotation: f32,
+    face_path: bool, // turn with the orbit instead of staying upright
+    start_time: f32,
+    duration: Option<f32>, // None orbits until stopped
+}
+
+impl OrbitAnimation {
+    // Without a radius the grid orbits at its current distance from the center.
+    // A duration of zero or less orbits indefinitely.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        center: Point2,
+        radius: Option<f32>,
+        angular_speed: f32,
+        duration: f32,
+        face_path: bool,
+        start_position: Point2,
+        start_rotation: f32,
+        start_time: f32,
+    ) -> Self {
+        let offset = start_position - center;
+        Self {
+            center,
+            radius: radius.unwrap_or(offset.length()).abs(),
+            start_angle: offset.y.atan2(offset.x).to_degrees(),
+            angular_speed,
+            start_rotation,
+            face_path,
+            start_time,
+            duration: (duration > 0.0).then_some(duration),
+        }
+    }
+
+    pub fn is_complete(&self, time: f32) -> bool {
+        self.duration
+            .is_some_and(|duration| time - self.start_time >= duration)
+    }
+
+    // Position and rotation (degrees) at `time`. Past the end of a timed
+    // orbit this holds the final values.
+    pub fn sample(&self, time: f32) -> (Point2, f32) {
+        let mut elapsed = (time - self.start_time).max(0.0);
+        if let Some(duration) = self.duration {
+            elapsed = elapsed.min(duration);
+        }
+
+        let swept = self.angular_speed * elapsed;
+        let angle = (self.start_angle + swept).to_radians();
+        let position = self.center + vec2(angle.cos(), angle.sin()) * self.radius;
+
+        let rotation = if self.face_path {
+            self.start_rotation + swept
+        } else {
+            self.start_rotation
+        };
+
+        (position, rotation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarter_orbit() {
+        let orbit = OrbitAnimation::new(
+            pt2(10.0, 0.0),
+            None,
+            90.0,
+            1.0,
+            true,
+            pt2(110.0, 0.0),
+            0.0,
+            5.0,
+        );
+
+        let (position, rotation) = orbit.sample(6.0);
+        assert!(position.distance(pt2(10.0, 100.0)) < 1e-3);
+        assert_eq!(rotation, 90.0);
+        assert!(orbit.is_complete(6.0));
+
+        // holds the final values after the end
+        assert_eq!(orbit.sample(7.0), orbit.sample(6.0));
+    }
+}
@@ -0,0 +1,134 @@
+// src/animation/path_movement.rs
+//
+// Moves a grid along a Catmull-Rom spline from its current position
+// through a list of waypoints.
+
+use crate::{
+    animation::{Animation, MovementChange},
+    views::Transform2D,
+};
+use nannou::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct PathMovement {
+    points: Vec<Point2>, // start position followed by the waypoints
+    start_time: f32,
+    duration: f32,
+    complete: bool,
+}
+
+impl PathMovement {
+    pub fn new(start: Point2, waypoints: &[Point2], duration: f32, start_time: f32) -> Self {
+        let mut points = Vec::with_capacity(waypoints.len() + 1);
+        points.push(start);
+        points.extend_from_slice(waypoints);
+
+        Self {
+            points,
+            start_time,
+            duration,
+            complete: false,
+        }
+    }
+
+    // Position on the path at progress t (0.0-1.0). Each waypoint gets an equal
+    // share of the duration.
+    pub fn sample(&self, t: f32) -> Point2 {
+        let last = self.points.len() - 1;
+        if last == 0 || t >= 1.0 {
+            return self.points[last];
+        }
+        if t <= 0.0 {
+            return self.points[0];
+        }
+
+        let scaled = t * last as f32;
+        let i = (scaled.floor() as usize).min(last - 1);
+        let u = scaled - i as f32;
+
+        // End points are duplicated so the curve starts and ends on them
+        let p0 = self.points[i.saturating_sub(1)];
+        let p1 = self.points[i];
+        let p2 = self.points[i + 1];
+        let p3 = self.points[(i + 2).min(last)];
+
+        catmull_rom(p0, p1, p2, p3, u)
+    }
+}
+
+impl Animation for PathMovement {
+    fn should_update(&mut self, _dt: f32) -> bool {
+        // sampled from time, so update every frame
+        true
+    }
+
+    fn advance(&mut self, current_position: Point2, time: f32) -> Option<MovementChange> {
+        let progress = if self.duration > 0.0 {
+            ((time - self.start_time) / self.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        if progress >= 1.0 {
+            self.complete = true;
+        }
+
+        let delta = self.sample(progress) - current_position;
+        if delta == Vec2::ZERO {
+            return None;
+        }
+
+        Some(MovementChange {
+            transform: Transform2D {
+                translation: delta,
+                scale: Vec2::ONE,
+                rotation: 0.0,
+            },
+        })
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+}
+
+fn catmull_rom(p0: Point2, p1: Point2, p2: Point2, p3: Point2, u: f32) -> Point2 {
+    let u2 = u * u;
+    let u3 = u2 * u;
+    0.5 * ((2.0 * p1)
+        + (-p0 + p2) * u
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * u2
+        + (-p0 + 3.0 * p1 - 3.0 * p2 + p3) * u3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_passes_through_waypoints() {
+        let waypoints = [pt2(100.0, 0.0), pt2(100.0, 100.0), pt2(0.0, 100.0)];
+        let path = PathMovement::new(pt2(0.0, 0.0), &waypoints, 3.0, 0.0);
+
+        assert_eq!(path.sample(0.0), pt2(0.0, 0.0));
+        assert!((path.sample(1.0 / 3.0) - waypoints[0]).length() < 1e-3);
+        assert!((path.sample(2.0 / 3.0) - waypoints[1]).length() < 1e-3);
+        assert_eq!(path.sample(1.0), waypoints[2]);
+    }
+
+    #[test]
+    fn test_path_tracks_position_exactly() {
+        let waypoints = [pt2(50.0, 20.0), pt2(-30.0, 80.0)];
+        let mut path = PathMovement::new(pt2(0.0, 0.0), &waypoints, 1.0, 0.0);
+        let mut position = pt2(0.0, 0.0);
+        let mut time = 0.0;
+
+        while !path.is_complete() {
+            time += 1.0 / 60.0;
+            if let Some(change) = path.advance(position, time) {
+                position += change.transform.translation;
+            }
+        }
+        assert!((position - waypoints[1]).length() < 1e-4);
+    }
+}
@@ -2,7 +2,10 @@
 //
 // Tears the rows and columns of a grid apart visually.
 
-use crate::models::Axis;
+use crate::{animation::EasingType, models::Axis};
+
+// Slides without a duration snap into place within a frame
+pub const INSTANT_SLIDE_DURATION: f32 = 1.0 / 60.0;
 
 pub struct SlideAnimation {
     pub axis: Axis,
@@ -12,4 +15,6 @@ pub struct SlideAnimation {
     pub target_position: f32,
     pub start_time: f32,
     pub duration: f32,
+    pub easing: EasingType,
+    pub wrap: bool, // segments leaving the grid re-enter from the opposite side
 }
@@ -1,15 +1,15 @@
 // src/animation/stretch.rs
 
 use crate::{
-    models::{Axis, EdgeType, PathElement, ViewBox},
-    services::SegmentGraph,
+    models::{Axis, EdgeType, PathElement, SegmentKey, ViewBox},
+    services::{GraphDelta, NodeId, SegmentGraph},
     views::{CachedGrid, CachedSegment, SegmentType},
 };
 use nannou::prelude::*;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 
 pub struct StretchAnimation {
-    pub segment_ids: HashSet<String>,
+    pub segment_ids: HashSet<SegmentKey>,
     pub axis: Axis,
     pub current_amount: f32,
     pub target_amount: f32,
@@ -20,49 +20,37 @@ pub struct StretchAnimation {
 impl StretchAnimation {
     pub fn new(
         grid: &mut CachedGrid,
-        current_grid_position: &Point2,
         graph: &SegmentGraph,
+        graph_delta: &mut GraphDelta,
         axis: Axis,
         target_amount: f32,
         start_time: f32,
     ) -> Self {
-        // the points where stretch_segments should be placed
-        let mut stretch_points = Vec::new();
-
         // the boundaries between tiles in the grid
         let mut boundary_segments = boundary_segments(grid, axis);
 
         // the outer boundaries of the grid are excluded
         boundary_segments.retain(|id| !is_outer_boundary(grid, grid.segment(id).unwrap()));
 
-        // grid segments that intersect the boundary segments.
-        // whether or not these are active will determine the style of the
-        // stretch segments
-        let mut neighbors = HashSet::new();
-
-        // set which type of neighbor we are looking for
-        let neighbor_segment_type = match axis {
-            Axis::X => SegmentType::Horizontal,
-            Axis::Y => SegmentType::Vertical,
-        };
-
-        // iter through the boundary segments and gather the neighbors and intersection points
-        for segment in &boundary_segments {
-            graph
-                .neighbors_of(segment)
-                .iter()
-                .filter_map(|id| grid.segment(id))
-                .filter(|s| s.segment_type == neighbor_segment_type)
-                .for_each(|s| {
-                    neighbors.insert(s.id.clone());
-                    stretch_points.push(graph.get_connection_point(segment, &s.id).unwrap());
-                });
-        }
+        // the junctions where stretch_segments should be placed
+        let (_, stretch_nodes) = boundary_crossings(grid, graph, &boundary_segments, axis);
 
         let mut segment_ids = HashSet::new();
 
-        for point in stretch_points {
-            let segment = generate_stretch_segment(point, current_grid_position, axis);
+        for node in stretch_nodes {
+            let Some(point) = graph.node_point(node) else {
+                continue;
+            };
+            let segment = generate_stretch_segment(&point, axis);
+
+            // joined to everything at the junction, so graph effects reach it
+            let connections: Vec<(SegmentKey, Point2)> = graph
+                .segments_at_node(node)
+                .iter()
+                .map(|id| (id.clone(), point))
+                .collect();
+            graph_delta.add_segment(&segment.id, &connections);
+
             segment_ids.insert(segment.id.clone());
             grid.add_stretch_segment(segment);
         }
@@ -82,27 +70,17 @@ impl StretchAnimation {
     }
 }
 
-fn generate_stretch_segment(
-    start_point: &Point2,
-    current_grid_position: &Point2,
-    axis: Axis,
-) -> CachedSegment {
+// Placed at the junction in grid-local space, like every other segment
+fn generate_stretch_segment(start_point: &Point2, axis: Axis) -> CachedSegment {
     let axis_label = match axis {
         Axis::X => 'x',
         Axis::Y => 'y',
     };
 
-    let x1 = match axis {
-        Axis::X => start_point.x + current_grid_position.x,
-        Axis::Y => start_point.x,
-    };
-    let y1 = match axis {
-        Axis::X => start_point.y,
-        Axis::Y => start_point.y + current_grid_position.y,
-    };
+    let (x1, y1) = (start_point.x, start_point.y);
 
     CachedSegment::new(
-        format!("stretch-{}-{:?}", axis_label, current_grid_position),
+        SegmentKey::off_grid(format!("stretch-{}-{:?}", axis_label, start_point)),
         (0, 0), // unused for stretch segment
         &PathElement::Line {
             x1,
@@ -122,7 +100,41 @@ fn generate_stretch_segment(
     )
 }
 
-pub fn boundary_segments(grid: &CachedGrid, axis: Axis) -> HashSet<String> {
+// The junctions where segments cross the boundary segments, in id order, and
+// the crossing segments themselves. Whether or not those are active will determine the
+// style of the stretch segments.
+pub fn boundary_crossings(
+    grid: &CachedGrid,
+    graph: &SegmentGraph,
+    boundary_segments: &HashSet<SegmentKey>,
+    axis: Axis,
+) -> (HashSet<SegmentKey>, Vec<NodeId>) {
+    // set which type of neighbor we are looking for
+    let neighbor_segment_type = match axis {
+        Axis::X => SegmentType::Horizontal,
+        Axis::Y => SegmentType::Vertical,
+    };
+
+    let mut crossing_segments = HashSet::new();
+    let mut crossing_nodes = BTreeSet::new();
+    for segment in boundary_segments {
+        for &node in graph.nodes_of_segment(segment) {
+            for id in graph.segments_at_node(node) {
+                if grid
+                    .segment(id)
+                    .is_some_and(|s| s.segment_type == neighbor_segment_type)
+                {
+                    crossing_segments.insert(id.clone());
+                    crossing_nodes.insert(node);
+                }
+            }
+        }
+    }
+
+    (crossing_segments, crossing_nodes.into_iter().collect())
+}
+
+pub fn boundary_segments(grid: &CachedGrid, axis: Axis) -> HashSet<SegmentKey> {
     let mut boundary_segments = HashSet::new();
     for segment in grid.segments.values() {
         match axis {
@@ -5,8 +5,10 @@
 
 use crate::{
     animation::transition::SegmentChange,
-    services::SegmentGraph,
-    views::{CachedGrid, CachedSegment, DrawCommand, GridInstance, SegmentType},
+    models::SegmentKey,
+    services::GraphView,
+    utilities::segment_analysis::get_segment_position,
+    views::{CachedGrid, CachedSegment, DrawStyle, GridInstance, SegmentType},
 };
 
 use nannou::prelude::*;
@@ -14,20 +16,32 @@ use std::collections::{HashMap, HashSet, VecDeque};
 
 #[derive(Clone)]
 struct Stroke {
-    segments: Vec<String>,
-    start_segment: String,
-    end_segment: String,
+    segments: Vec<SegmentKey>,
+    start_segment: SegmentKey,
+    end_segment: SegmentKey,
     primary_type: SegmentType,
     start_position: Point2,
 }
 
+// Lightest tint given to the last stroke, as a fraction of the way to white
+const MAX_STROKE_TINT: f32 = 0.6;
+
 pub fn generate_stroke_order(
     grid_instance: &GridInstance,
-    start_segments: &HashSet<String>,
-    target_segments: &HashSet<String>,
-) -> Vec<String> {
+    start_segments: &HashSet<SegmentKey>,
+    target_segments: &HashSet<SegmentKey>,
+) -> Vec<SegmentKey> {
+    generate_ordered_strokes(grid_instance, start_segments, target_segments).concat()
+}
+
+// Like generate_stroke_order, but keeps each stroke's segments together
+pub fn generate_ordered_strokes(
+    grid_instance: &GridInstance,
+    start_segments: &HashSet<SegmentKey>,
+    target_segments: &HashSet<SegmentKey>,
+) -> Vec<Vec<SegmentKey>> {
     let grid = &grid_instance.grid;
-    let graph = &grid_instance.graph;
+    let graph = &grid_instance.graph();
 
     // Find segments to turn on
     let segments_to_turn_on: HashSet<_> = target_segments
@@ -65,89 +79,165 @@ pub fn generate_stroke_order(
     order_strokes_with_connections(ordered_strokes, &stroke_connections)
 }
 
-pub fn convert_to_transition_changes(
-    ordered_segments: Vec<String>,
+// Writes the glyph stroke by stroke, each stroke a little lighter than the last
+pub fn generate_tinted_stroke_changes(
     grid_instance: &GridInstance,
+    start_segments: &HashSet<SegmentKey>,
+    target_segments: &HashSet<SegmentKey>,
+    stroke_gap_steps: usize,
 ) -> Vec<Vec<SegmentChange>> {
-    let start_segments = &grid_instance.current_active_segments;
-    let target_segments = grid_instance.target_segments.as_ref().unwrap();
+    let strokes = generate_ordered_strokes(grid_instance, start_segments, target_segments);
+    let base_style = &grid_instance.target_style;
+    let stroke_count = strokes.len();
 
-    // First, handle segments that need to be turned on
-    let mut changes = Vec::new();
+    let stroke_changes = strokes.into_iter().enumerate().map(|(i, stroke)| {
+        let tint = if stroke_count > 1 {
+            MAX_STROKE_TINT * i as f32 / (stroke_count - 1) as f32
+        } else {
+            0.0
+        };
+        let color = base_style.color;
+        let style = DrawStyle {
+            color: rgba(
+                color.red + (1.0 - color.red) * tint,
+                color.green + (1.0 - color.green) * tint,
+                color.blue + (1.0 - color.blue) * tint,
+                color.alpha,
+            ),
+            stroke_weight: base_style.stroke_weight,
+        };
+
+        stroke
+            .into_iter()
+            .map(|segment_id| {
+                vec![SegmentChange {
+                    segment_id,
+                    turn_on: true,
+                    style: Some(style.clone()),
+                }]
+            })
+            .collect()
+    });
+
+    let mut changes = join_strokes(stroke_changes, stroke_gap_steps);
+    changes.extend(turn_off_changes(grid_instance));
+    changes
+}
 
+pub fn convert_to_transition_changes(
+    ordered_segments: Vec<SegmentKey>,
+    grid_instance: &GridInstance,
+) -> Vec<Vec<SegmentChange>> {
+    convert_strokes_to_transition_changes(vec![ordered_segments], 0, grid_instance)
+}
+
+// Like convert_to_transition_changes, but pauses for stroke_gap_steps empty
+// steps at each stroke boundary so the writing breathes between strokes
+pub fn convert_strokes_to_transition_changes(
+    strokes: Vec<Vec<SegmentKey>>,
+    stroke_gap_steps: usize,
+    grid_instance: &GridInstance,
+) -> Vec<Vec<SegmentChange>> {
     // Create a change for each segment to be turned on (one at a time)
-    for segment_id in ordered_segments {
-        changes.push(vec![SegmentChange {
-            segment_id,
-            turn_on: true,
-        }]);
-    }
+    let stroke_changes = strokes.into_iter().map(|stroke| {
+        stroke
+            .into_iter()
+            .map(|segment_id| {
+                vec![SegmentChange {
+                    segment_id,
+                    turn_on: true,
+                    style: None,
+                }]
+            })
+            .collect()
+    });
+    let mut changes = join_strokes(stroke_changes, stroke_gap_steps);
 
     // Now handle segments that need to be turned off
-    let segments_to_turn_off: Vec<_> = start_segments
+    changes.extend(turn_off_changes(grid_instance));
+
+    changes
+}
+
+fn join_strokes(
+    strokes: impl Iterator<Item = Vec<Vec<SegmentChange>>>,
+    gap_steps: usize,
+) -> Vec<Vec<SegmentChange>> {
+    let mut changes = Vec::new();
+    for (i, stroke) in strokes.enumerate() {
+        if i > 0 {
+            changes.extend((0..gap_steps).map(|_| Vec::new()));
+        }
+        changes.extend(stroke);
+    }
+    changes
+}
+
+// A single step turning off everything that isn't in the target glyph
+fn turn_off_changes(grid_instance: &GridInstance) -> Option<Vec<SegmentChange>> {
+    let start_segments = &grid_instance.current_active_segments;
+    let target_segments = grid_instance.target_segments.as_ref()?;
+
+    let turn_off_changes: Vec<_> = start_segments
         .difference(target_segments)
-        .cloned()
+        .map(|segment_id| SegmentChange {
+            segment_id: segment_id.clone(),
+            turn_on: false,
+            style: None,
+        })
         .collect();
 
-    if !segments_to_turn_off.is_empty() {
-        let turn_off_changes = segments_to_turn_off
-            .into_iter()
-            .map(|segment_id| SegmentChange {
-                segment_id,
-                turn_on: false,
-            })
-            .collect();
+    (!turn_off_changes.is_empty()).then_some(turn_off_changes)
+}
 
-        changes.push(turn_off_changes);
-    }
+// Turn segments off one at a time, un-writing them in reverse stroke order
+pub fn generate_erase_changes(
+    grid_instance: &GridInstance,
+    outgoing_segments: &HashSet<SegmentKey>,
+) -> Vec<Vec<SegmentChange>> {
+    let mut ordered_segments =
+        generate_stroke_order(grid_instance, &HashSet::new(), outgoing_segments);
+    ordered_segments.reverse();
 
-    changes
+    ordered_segments
+        .into_iter()
+        .map(|segment_id| {
+            vec![SegmentChange {
+                segment_id,
+                turn_on: false,
+                style: None,
+            }]
+        })
+        .collect()
 }
 
 fn group_segments_into_strokes(
-    segments: &HashSet<String>,
+    segments: &HashSet<SegmentKey>,
     grid: &CachedGrid,
-    graph: &SegmentGraph,
+    graph: &GraphView,
 ) -> Vec<Stroke> {
     let mut strokes = Vec::new();
-    let mut visited: HashSet<String> = HashSet::new();
+    let mut visited: HashSet<SegmentKey> = HashSet::new();
 
-    for segment_id in segments {
+    // sorted, so the same segments always make the same strokes in the same order
+    let mut sorted_segments: Vec<&SegmentKey> = segments.iter().collect();
+    sorted_segments.sort();
+
+    for segment_id in sorted_segments {
         if visited.contains(segment_id) {
             continue;
         }
 
-        let mut stroke_segments = Vec::new();
-        let mut queue = VecDeque::new();
-        queue.push_back(segment_id.clone());
-
-        while let Some(current) = queue.pop_front() {
-            if visited.contains(&current) {
-                continue;
-            }
-            visited.insert(current.clone());
-            stroke_segments.push(current.clone());
-
-            // Get current segment type
-            let current_segment = grid.segments.get(&current).unwrap();
-
-            // Explore connected segments
-            for neighbor in graph.neighbors_of(&current) {
-                if !segments.contains(&neighbor) || visited.contains(&neighbor) {
-                    continue;
-                }
-
-                // get neighbor type
-                let neighbor_segment = grid.segments.get(&neighbor).unwrap();
-
-                // add to stroke if types are compatible
-                if are_compatible_segments(current_segment, neighbor_segment) {
-                    queue.push_back(neighbor);
-                }
-            }
-        }
-
-        if !stroke_segments.is_empty() {
+        let group = collect_stroke(
+            segment_id,
+            segments,
+            &mut visited,
+            grid,
+            graph,
+            &HashSet::new(),
+        );
+
+        for stroke_segments in split_at_articulations(group, grid, graph) {
             // Determine primary type and start position
             let primary_type = get_primary_segment_type(&stroke_segments, grid);
             let start_segment = determine_stroke_start(&stroke_segments, grid, &primary_type);
@@ -155,8 +245,8 @@ fn group_segments_into_strokes(
 
             strokes.push(Stroke {
                 segments: stroke_segments,
+                end_segment: start_segment.clone(), // set once the stroke is ordered
                 start_segment,
-                end_segment: "".to_string(),
                 primary_type,
                 start_position,
             });
@@ -165,6 +255,115 @@ fn group_segments_into_strokes(
     strokes
 }
 
+// Breadth first from `start` through compatible neighbors among `segments`,
+// never crossing one of the `cuts` connections
+fn collect_stroke(
+    start: &SegmentKey,
+    segments: &HashSet<SegmentKey>,
+    visited: &mut HashSet<SegmentKey>,
+    grid: &CachedGrid,
+    graph: &GraphView,
+    cuts: &HashSet<(SegmentKey, SegmentKey)>,
+) -> Vec<SegmentKey> {
+    let mut stroke_segments = Vec::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(start.clone());
+
+    while let Some(current) = queue.pop_front() {
+        if visited.contains(&current) {
+            continue;
+        }
+        visited.insert(current.clone());
+        stroke_segments.push(current.clone());
+
+        // Get current segment type
+        let Some(current_segment) = grid.segment(&current) else {
+            continue;
+        };
+
+        // Explore connected segments
+        for neighbor in graph.neighbors(&current).iter() {
+            if !segments.contains(neighbor)
+                || visited.contains(neighbor)
+                || cuts.contains(&connection_key(&current, neighbor))
+            {
+                continue;
+            }
+
+            // get neighbor type
+            let Some(neighbor_segment) = grid.segment(neighbor) else {
+                continue;
+            };
+
+            // add to stroke if types are compatible
+            if are_compatible_segments(current_segment, neighbor_segment) {
+                queue.push_back(neighbor.clone());
+            }
+        }
+    }
+    stroke_segments
+}
+
+// Compatible segments can still belong to separate strokes that happen to
+// touch, like an arc branching off a curve. A chain can be drawn in one go,
+// but a group that forks can't, so where the group forks at an articulation
+// segment, the branches that change type there become strokes of their own.
+fn split_at_articulations(
+    group: Vec<SegmentKey>,
+    grid: &CachedGrid,
+    graph: &GraphView,
+) -> Vec<Vec<SegmentKey>> {
+    let members: HashSet<SegmentKey> = group.iter().cloned().collect();
+    let segment_type = |id: &SegmentKey| grid.segment(id).map(|segment| segment.segment_type);
+    let forks: HashSet<SegmentKey> = graph
+        .articulation_points(&members)
+        .into_iter()
+        .filter(|id| {
+            let branches = graph
+                .neighbors(id)
+                .iter()
+                .filter(|n| members.contains(*n))
+                .count();
+            branches >= 3
+        })
+        .collect();
+
+    let cuts: HashSet<(SegmentKey, SegmentKey)> = graph
+        .bridges(&members)
+        .into_iter()
+        .filter(|(a, b)| forks.contains(a) || forks.contains(b))
+        .filter(|(a, b)| segment_type(a) != segment_type(b))
+        .collect();
+    if cuts.is_empty() {
+        return vec![group];
+    }
+
+    let mut visited = HashSet::new();
+    let mut pieces = Vec::new();
+    for id in &group {
+        if !visited.contains(id) {
+            pieces.push(collect_stroke(
+                id,
+                &members,
+                &mut visited,
+                grid,
+                graph,
+                &cuts,
+            ));
+        }
+    }
+    pieces
+}
+
+// The same key bridges() gives a connection, lower id first
+fn connection_key(first: &SegmentKey, second: &SegmentKey) -> (SegmentKey, SegmentKey) {
+    if first < second {
+        (first.clone(), second.clone())
+    } else {
+        (second.clone(), first.clone())
+    }
+}
+
 // Check if two segments should be part of the same stroke
 fn are_compatible_segments(seg1: &CachedSegment, seg2: &CachedSegment) -> bool {
     if seg1.segment_type == seg2.segment_type {
@@ -193,7 +392,7 @@ fn are_compatible_segments(seg1: &CachedSegment, seg2: &CachedSegment) -> bool {
 }
 
 // Get the most common segment type in a stroke
-fn get_primary_segment_type(segments: &[String], grid: &CachedGrid) -> SegmentType {
+fn get_primary_segment_type(segments: &[SegmentKey], grid: &CachedGrid) -> SegmentType {
     let mut type_counts: HashMap<SegmentType, usize> = HashMap::new();
 
     for id in segments {
@@ -202,19 +401,20 @@ fn get_primary_segment_type(segments: &[String], grid: &CachedGrid) -> SegmentTy
         }
     }
 
+    // ties go to the type sort_connected_strokes puts first
     type_counts
         .into_iter()
-        .max_by_key(|&(_, count)| count)
+        .max_by_key(|(typ, count)| (*count, std::cmp::Reverse(get_type_priority(typ))))
         .map(|(typ, _)| typ)
         .unwrap_or(SegmentType::Unknown)
 }
 
 // Determine the starting segment for a stroke
 fn determine_stroke_start(
-    segments: &[String],
+    segments: &[SegmentKey],
     grid: &CachedGrid,
     primary_type: &SegmentType,
-) -> String {
+) -> SegmentKey {
     match primary_type {
         SegmentType::Horizontal => {
             // For horizontal strokes, start at leftmost
@@ -244,6 +444,35 @@ fn determine_stroke_start(
                 .unwrap()
                 .clone()
         }
+        SegmentType::DiagonalDown => {
+            // Diagonals are written from the top, so down-right ones start
+            // at the leftmost
+            segments
+                .iter()
+                .min_by(|a, b| {
+                    let pos_a = get_segment_position(a, grid).x;
+                    let pos_b = get_segment_position(b, grid).x;
+                    pos_a
+                        .partial_cmp(&pos_b)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap()
+                .clone()
+        }
+        SegmentType::DiagonalUp => {
+            // and up-right ones at the rightmost
+            segments
+                .iter()
+                .max_by(|a, b| {
+                    let pos_a = get_segment_position(a, grid).x;
+                    let pos_b = get_segment_position(b, grid).x;
+                    pos_a
+                        .partial_cmp(&pos_b)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap()
+                .clone()
+        }
         SegmentType::ArcTopLeft
         | SegmentType::ArcTopRight
         | SegmentType::ArcBottomLeft
@@ -275,153 +504,12 @@ fn determine_stroke_start(
     }
 }
 
-// Get position for a segment (using the starting point)
-fn get_segment_position(segment_id: &str, grid: &CachedGrid) -> Point2 {
-    if let Some(segment) = grid.segments.get(segment_id) {
-        // Use the appropriate point based on segment type
-        match segment.segment_type {
-            SegmentType::Horizontal => find_leftmost_point(&segment.draw_commands),
-            SegmentType::Vertical => find_topmost_point(&segment.draw_commands),
-            SegmentType::ArcTopLeft => find_topmost_point(&segment.draw_commands),
-            SegmentType::ArcTopRight => find_topmost_point(&segment.draw_commands),
-            SegmentType::ArcBottomLeft => find_leftmost_point(&segment.draw_commands),
-            SegmentType::ArcBottomRight => find_rightmost_point(&segment.draw_commands),
-            SegmentType::Unknown => find_average_point(&segment.draw_commands),
-        }
-    } else {
-        Point2::new(0.0, 0.0)
-    }
-}
-
-// Helper functions to find specific points in draw commands
-fn find_leftmost_point(commands: &[DrawCommand]) -> Point2 {
-    let mut leftmost = Point2::new(f32::MAX, 0.0);
-
-    for cmd in commands {
-        match cmd {
-            DrawCommand::Line { start, end } => {
-                if start.x < leftmost.x {
-                    leftmost = *start;
-                }
-                if end.x < leftmost.x {
-                    leftmost = *end;
-                }
-            }
-            DrawCommand::Arc { points } => {
-                for point in points {
-                    if point.x < leftmost.x {
-                        leftmost = *point;
-                    }
-                }
-            }
-            DrawCommand::Circle { center, .. } => {
-                if center.x < leftmost.x {
-                    leftmost = *center;
-                }
-            }
-        }
-    }
-
-    leftmost
-}
-
-// Similarly implement other point-finding functions
-fn find_topmost_point(commands: &[DrawCommand]) -> Point2 {
-    let mut topmost = Point2::new(0.0, f32::MAX);
-
-    for cmd in commands {
-        match cmd {
-            DrawCommand::Line { start, end } => {
-                // Note: Lower y value is higher in screen coordinates
-                if start.y < topmost.y {
-                    topmost = *start;
-                }
-                if end.y < topmost.y {
-                    topmost = *end;
-                }
-            }
-            DrawCommand::Arc { points } => {
-                for point in points {
-                    if point.y < topmost.y {
-                        topmost = *point;
-                    }
-                }
-            }
-            DrawCommand::Circle { center, .. } => {
-                if center.y < topmost.y {
-                    topmost = *center;
-                }
-            }
-        }
-    }
-
-    topmost
-}
-
-fn find_rightmost_point(commands: &[DrawCommand]) -> Point2 {
-    let mut rightmost = Point2::new(f32::MIN, 0.0);
-
-    for cmd in commands {
-        match cmd {
-            DrawCommand::Line { start, end } => {
-                if start.x > rightmost.x {
-                    rightmost = *start;
-                }
-                if end.x > rightmost.x {
-                    rightmost = *end;
-                }
-            }
-            DrawCommand::Arc { points } => {
-                for point in points {
-                    if point.x > rightmost.x {
-                        rightmost = *point;
-                    }
-                }
-            }
-            DrawCommand::Circle { center, .. } => {
-                if center.x > rightmost.x {
-                    rightmost = *center;
-                }
-            }
-        }
-    }
-
-    rightmost
-}
-
-fn find_average_point(commands: &[DrawCommand]) -> Point2 {
-    let mut sum = Point2::new(0.0, 0.0);
-    let mut count = 0;
-
-    for cmd in commands {
-        match cmd {
-            DrawCommand::Line { start, end } => {
-                sum += *start;
-                sum += *end;
-                count += 2;
-            }
-            DrawCommand::Arc { points } => {
-                for point in points {
-                    sum += *point;
-                    count += 1;
-                }
-            }
-            DrawCommand::Circle { center, .. } => {
-                sum += *center;
-                count += 1;
-            }
-        }
-    }
-
-    if count > 0 {
-        sum / count as f32
-    } else {
-        Point2::new(0.0, 0.0)
-    }
-}
-
 // Determine start point for arc segments
-fn determine_arc_start(segments: &[String], grid: &CachedGrid, arc_type: &SegmentType) -> String {
+fn determine_arc_start(
+    segments: &[SegmentKey],
+    grid: &CachedGrid,
+    arc_type: &SegmentType,
+) -> SegmentKey {
     // For different arc types, starting points differ
     match arc_type {
         SegmentType::ArcTopLeft => {
@@ -490,12 +578,12 @@ fn determine_arc_start(segments: &[String], grid: &CachedGrid, arc_type: &Segmen
 
 fn order_strokes_with_connections(
     strokes: Vec<Stroke>,
-    connections: &HashMap<String, Vec<String>>,
-) -> Vec<String> {
+    connections: &HashMap<SegmentKey, Vec<SegmentKey>>,
+) -> Vec<Vec<SegmentKey>> {
     // Now we'll reorder based on connected strokes
     let mut final_order = Vec::new();
-    let mut processed_strokes: HashSet<String> = HashSet::new();
-    let mut remaining_strokes: HashSet<String> =
+    let mut processed_strokes: HashSet<SegmentKey> = HashSet::new();
+    let mut remaining_strokes: HashSet<SegmentKey> =
         strokes.iter().map(|s| s.start_segment.clone()).collect();
 
     while !remaining_strokes.is_empty() {
@@ -506,10 +594,8 @@ fn order_strokes_with_connections(
             .find(|s| s.start_segment == next_stroke_id)
             .unwrap();
 
-        // Add all segments from this stroke to the final order
-        for segment_id in &next_stroke.segments {
-            final_order.push(segment_id.clone());
-        }
+        // Add this stroke to the final order
+        final_order.push(next_stroke.segments.clone());
 
         // Mark this stroke as processed
         processed_strokes.insert(next_stroke_id.clone());
@@ -520,7 +606,7 @@ fn order_strokes_with_connections(
             let mut sorted_connected_ids = connected_stroke_ids.clone();
             sorted_connected_ids.sort();
             // Find unprocessed connected strokes
-            let available_connected: Vec<&String> = sorted_connected_ids
+            let available_connected: Vec<&SegmentKey> = sorted_connected_ids
                 .iter()
                 .filter(|id| !processed_strokes.contains(*id))
                 .collect();
@@ -539,10 +625,8 @@ fn order_strokes_with_connections(
                     // Process the highest priority connected stroke next
                     let next_connected = sorted_connected[0];
 
-                    // Add all segments from this connected stroke
-                    for segment_id in &next_connected.segments {
-                        final_order.push(segment_id.clone());
-                    }
+                    // Add this connected stroke
+                    final_order.push(next_connected.segments.clone());
 
                     // Mark as processed
                     processed_strokes.insert(next_connected.start_segment.clone());
@@ -556,7 +640,7 @@ fn order_strokes_with_connections(
 }
 
 // Helper to find the next stroke from remaining strokes
-fn find_next_stroke(ordered_strokes: &[Stroke], remaining: &HashSet<String>) -> String {
+fn find_next_stroke(ordered_strokes: &[Stroke], remaining: &HashSet<SegmentKey>) -> SegmentKey {
     for stroke in ordered_strokes {
         if remaining.contains(&stroke.start_segment) {
             return stroke.start_segment.clone();
@@ -610,17 +694,20 @@ fn get_type_priority(segment_type: &SegmentType) -> u8 {
         SegmentType::ArcBottomRight => 4,
         SegmentType::Horizontal => 5,
         SegmentType::Vertical => 6,
-        SegmentType::Unknown => 7, // Lowest priority
+        SegmentType::DiagonalDown => 7,
+        SegmentType::DiagonalUp => 8,
+        SegmentType::Unknown => 9, // Lowest priority
     }
 }
 
 fn order_strokes_by_position(
     mut strokes: Vec<Stroke>,
-    connections: &HashMap<String, Vec<String>>,
+    connections: &HashMap<SegmentKey, Vec<SegmentKey>>,
     grid: &CachedGrid,
 ) -> Vec<Stroke> {
     let mut result = Vec::new();
-    let mut remaining: HashSet<String> = strokes.iter().map(|s| s.start_segment.clone()).collect();
+    let mut remaining: HashSet<SegmentKey> =
+        strokes.iter().map(|s| s.start_segment.clone()).collect();
 
     // Sort strokes by quadrant and position for initial ordering
     strokes.sort_by(|a, b| {
@@ -762,8 +849,11 @@ fn order_strokes_by_position(
 }
 
 // Function to identify connections between different segment types
-fn identify_connections(strokes: &[Stroke], graph: &SegmentGraph) -> HashMap<String, Vec<String>> {
-    let mut connections: HashMap<String, Vec<String>> = HashMap::new();
+fn identify_connections(
+    strokes: &[Stroke],
+    graph: &GraphView,
+) -> HashMap<SegmentKey, Vec<SegmentKey>> {
+    let mut connections: HashMap<SegmentKey, Vec<SegmentKey>> = HashMap::new();
 
     // Sort strokes by ID for deterministic processing
     let sorted_strokes = {
@@ -774,15 +864,13 @@ fn identify_connections(strokes: &[Stroke], graph: &SegmentGraph) -> HashMap<Str
 
     for stroke in &sorted_strokes {
         // For regular strokes, check connections at the end segment
-        if !stroke.end_segment.is_empty() {
-            add_connections_from_segment(
-                &stroke.end_segment,
-                &stroke.start_segment,
-                strokes,
-                graph,
-                &mut connections,
-            );
-        }
+        add_connections_from_segment(
+            &stroke.end_segment,
+            &stroke.start_segment,
+            strokes,
+            graph,
+            &mut connections,
+        );
 
         // For arcs, also check connections at ALL segments in the stroke
         if is_arc_type(&stroke.primary_type) {
@@ -814,24 +902,23 @@ fn identify_connections(strokes: &[Stroke], graph: &SegmentGraph) -> HashMap<Str
 
 // Helper function to add connections from a specific segment
 fn add_connections_from_segment(
-    segment_id: &str,
-    source_stroke_id: &str,
+    segment_id: &SegmentKey,
+    source_stroke_id: &SegmentKey,
     strokes: &[Stroke],
-    graph: &SegmentGraph,
-    connections: &mut HashMap<String, Vec<String>>,
+    graph: &GraphView,
+    connections: &mut HashMap<SegmentKey, Vec<SegmentKey>>,
 ) {
     // Find all segments connected to this segment
-    let mut connected_segments = graph.neighbors_of(segment_id);
-    connected_segments.sort();
+    let connected_segments = graph.neighbors(segment_id);
 
     // Find which strokes these segments belong to
-    let connected_stroke_ids: Vec<String> = connected_segments
-        .into_iter()
+    let connected_stroke_ids: Vec<SegmentKey> = connected_segments
+        .iter()
         .filter_map(|connected_segment| {
             // Find which stroke this segment belongs to
             strokes
                 .iter()
-                .find(|s| s.segments.contains(&connected_segment))
+                .find(|s| s.segments.contains(connected_segment))
                 .map(|s| s.start_segment.clone())
         })
         .filter(|id| id != source_stroke_id) // Don't include self-connections
@@ -839,7 +926,7 @@ fn add_connections_from_segment(
 
     if !connected_stroke_ids.is_empty() {
         connections
-            .entry(source_stroke_id.to_string())
+            .entry(source_stroke_id.clone())
             .or_default()
             .extend(connected_stroke_ids);
     }
@@ -875,8 +962,8 @@ fn is_arc_type(segment_type: &SegmentType) -> bool {
 fn order_segments_in_stroke(
     stroke: &Stroke,
     grid: &CachedGrid,
-    graph: &SegmentGraph,
-) -> (Vec<String>, String) {
+    graph: &GraphView,
+) -> (Vec<SegmentKey>, SegmentKey) {
     let mut ordered = Vec::new();
     let mut visited = HashSet::new();
 
@@ -891,10 +978,10 @@ fn order_segments_in_stroke(
         let mut best_score = f32::MAX;
 
         // Find unvisited neighbors
-        for neighbor in graph.neighbors_of(&current) {
-            if stroke.segments.contains(&neighbor) && !visited.contains(&neighbor) {
+        for neighbor in graph.neighbors(&current).iter() {
+            if stroke.segments.contains(neighbor) && !visited.contains(neighbor) {
                 // Score based on position relative to current segment's flow
-                let score = score_next_segment(&current, &neighbor, grid, &stroke.primary_type);
+                let score = score_next_segment(&current, neighbor, grid, &stroke.primary_type);
                 if score < best_score {
                     best_score = score;
                     best_next = Some(neighbor.clone());
@@ -925,8 +1012,8 @@ fn order_segments_in_stroke(
 
 // Score next segment based on natural writing flow
 fn score_next_segment(
-    current: &str,
-    next: &str,
+    current: &SegmentKey,
+    next: &SegmentKey,
     grid: &CachedGrid,
     primary_type: &SegmentType,
 ) -> f32 {
@@ -973,6 +1060,24 @@ fn score_next_segment(
                     0.0
                 }
         }
+        SegmentType::DiagonalDown => {
+            // For down-right diagonals, prefer moving right
+            (next_pos - current_pos).length()
+                + if next_pos.x < current_pos.x {
+                    1000.0
+                } else {
+                    0.0
+                }
+        }
+        SegmentType::DiagonalUp => {
+            // For up-right diagonals, written from the top, prefer moving left
+            (next_pos - current_pos).length()
+                + if next_pos.x > current_pos.x {
+                    1000.0
+                } else {
+                    0.0
+                }
+        }
         SegmentType::ArcTopLeft
         | SegmentType::ArcTopRight
         | SegmentType::ArcBottomLeft
@@ -992,3 +1097,141 @@ fn score_next_segment(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Project;
+    use crate::services::SegmentGraph;
+    use crate::test_helpers::test_grid_instance;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_stroke_order_is_the_same_every_run() {
+        // every build hashes its sets and maps differently
+        let stroke_order = || {
+            let grid_instance = test_grid_instance();
+            let target: HashSet<SegmentKey> = grid_instance.grid.segments.keys().cloned().collect();
+            generate_stroke_order(&grid_instance, &HashSet::new(), &target)
+        };
+
+        let first = stroke_order();
+        assert!(!first.is_empty());
+        for _ in 0..5 {
+            assert_eq!(stroke_order(), first);
+        }
+    }
+
+    // The ordered strokes for a glyph of the Ulsan project
+    fn ulsan_strokes(glyph: &str) -> Vec<Vec<SegmentKey>> {
+        let project = Project::load("projects/ulsan.json").unwrap();
+        let grid = CachedGrid::new(&project);
+        let graph = Rc::new(SegmentGraph::new(&grid));
+        let grid_instance = GridInstance::new(
+            "test".to_string(),
+            &project,
+            "",
+            &grid,
+            graph,
+            pt2(0.0, 0.0),
+            0.0,
+            10.0,
+            5.0,
+        );
+        let target: HashSet<SegmentKey> = project.glyphs[glyph]
+            .segments
+            .iter()
+            .map(|id| id.parse().unwrap())
+            .collect();
+        generate_ordered_strokes(&grid_instance, &HashSet::new(), &target)
+    }
+
+    fn stroke(ids: &[&str]) -> Vec<SegmentKey> {
+        ids.iter().map(|id| id.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_strokes_split_where_a_curve_forks() {
+        // Before, the tail of the curve was written with it:
+        // [3,4 arc-4, 4,4 arc-1, 4,3 arc-3, 4,4 arc-2, 4,4 arc-4]
+        let strokes = ulsan_strokes("BbingR1");
+        assert!(strokes.contains(&stroke(&[
+            "3,4 : arc-4",
+            "4,4 : arc-1",
+            "4,3 : arc-3",
+            "4,4 : arc-2"
+        ])));
+        assert!(strokes.contains(&stroke(&["4,4 : arc-4"])));
+
+        // Before: [3,3 arc-1, 3,2 arc-3, 2,2 arc-4, 3,2 arc-4, 4,2 arc-3]
+        let strokes = ulsan_strokes("Hul12");
+        let arcs: Vec<&Vec<SegmentKey>> = strokes
+            .iter()
+            .filter(|stroke| stroke[0].element.contains("arc") && stroke[0].tile.0 != 1)
+            .collect();
+        assert_eq!(
+            arcs,
+            [
+                &stroke(&["2,2 : arc-4"]),
+                &stroke(&["3,3 : arc-1", "3,2 : arc-3", "3,2 : arc-4"]),
+                &stroke(&["4,2 : arc-3"]),
+            ]
+        );
+
+        // a plain chain of arcs is still one stroke
+        assert!(ulsan_strokes("BbingR1").contains(&stroke(&["4,2 : arc-4", "4,2 : arc-3"])));
+    }
+
+    #[test]
+    fn test_diagonals_are_written_from_the_top() {
+        let mut project = Project::load("projects/debug.json").unwrap();
+        // each diagonal of the tile in thirds, with the paths drawn against
+        // the writing direction so it isn't just following the SVG
+        project.svg_base_tile = r#"<svg id="diagonals" viewBox="0 0 90 90">
+            <path id="down-1" d="M30,30L0,0" style="fill:none;stroke:black;stroke-width:5px;"/>
+            <path id="down-2" d="M60,60L30,30" style="fill:none;stroke:black;stroke-width:5px;"/>
+            <path id="down-3" d="M90,90L60,60" style="fill:none;stroke:black;stroke-width:5px;"/>
+            <path id="up-1" d="M60,30L90,0" style="fill:none;stroke:black;stroke-width:5px;"/>
+            <path id="up-2" d="M30,60L60,30" style="fill:none;stroke:black;stroke-width:5px;"/>
+            <path id="up-3" d="M0,90L30,60" style="fill:none;stroke:black;stroke-width:5px;"/>
+        </svg>"#
+            .to_string();
+        project.grid_x = 1;
+        project.grid_y = 1;
+        let grid = CachedGrid::new(&project);
+        assert_eq!(
+            grid.segments[&SegmentKey::new((1, 1), "down-1")].segment_type,
+            SegmentType::DiagonalDown
+        );
+        assert_eq!(
+            grid.segments[&SegmentKey::new((1, 1), "up-1")].segment_type,
+            SegmentType::DiagonalUp
+        );
+
+        let graph = Rc::new(SegmentGraph::new(&grid));
+        let grid_instance = GridInstance::new(
+            "test".to_string(),
+            &project,
+            "",
+            &grid,
+            graph,
+            pt2(0.0, 0.0),
+            0.0,
+            10.0,
+            5.0,
+        );
+        let strokes = |ids: &[&str]| {
+            let target: HashSet<SegmentKey> = stroke(ids).into_iter().collect();
+            generate_ordered_strokes(&grid_instance, &HashSet::new(), &target)
+        };
+
+        assert_eq!(
+            strokes(&["1,1 : down-3", "1,1 : down-2", "1,1 : down-1"]),
+            [stroke(&["1,1 : down-1", "1,1 : down-2", "1,1 : down-3"])]
+        );
+        assert_eq!(
+            strokes(&["1,1 : up-3", "1,1 : up-2", "1,1 : up-1"]),
+            [stroke(&["1,1 : up-1", "1,1 : up-2", "1,1 : up-3"])]
+        );
+    }
+}
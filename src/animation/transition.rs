@@ -8,20 +8,31 @@
 // the next glyph.
 
 use crate::{
-    animation::stroke_order, config::TransitionConfig, services::SegmentGraph, views::GridInstance,
+    animation::{morph, stroke_order},
+    config::TransitionConfig,
+    models::{Axis, SegmentKey},
+    services::GraphView,
+    utilities::segment_analysis,
+    views::{DrawStyle, GridInstance},
+};
+use nannou::prelude::Point2;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
 };
-use rand::{thread_rng, Rng};
-use std::collections::{HashSet, VecDeque};
 
 pub struct TransitionUpdates {
-    pub segments_on: HashSet<String>,
-    pub segments_off: HashSet<String>,
+    pub segments_on: HashSet<SegmentKey>,
+    pub segments_off: HashSet<SegmentKey>,
+    pub styles: HashMap<SegmentKey, DrawStyle>, // per-segment styles for segments_on
 }
 
 #[derive(Debug)]
 pub struct SegmentChange {
-    pub segment_id: String,
+    pub segment_id: SegmentKey,
     pub turn_on: bool,
+    pub style: Option<DrawStyle>, // overrides the grid's target_style when turning on
 }
 
 pub struct Transition {
@@ -29,6 +40,7 @@ pub struct Transition {
     current_step: usize,
     frame_timer: f32,
     frame_duration: f32,
+    paused: bool,
     pub animation_type: TransitionAnimationType,
 }
 
@@ -39,13 +51,113 @@ pub enum TransitionTriggerType {
     Manual,
 }
 
-#[derive(Default, Clone, Copy, PartialEq)]
+#[derive(Default, Clone, PartialEq)]
 pub enum TransitionAnimationType {
     #[default]
     Immediate,
     Random,
     Writing,
     Overwrite,
+    Dissolve,
+    Scanline {
+        axis: Axis, // X reveals a row at a time, Y a column
+        reverse: bool,
+    },
+    Ripple {
+        origin: Point2, // in grid-local coordinates
+    },
+    Morph,
+    Checkerboard,
+    Flood {
+        seed: String, // segment id the glyph spreads out from
+    },
+}
+
+impl TransitionAnimationType {
+    // Matches the names accepted by the OSC animation type argument
+    pub fn name(&self) -> &'static str {
+        match self {
+            TransitionAnimationType::Immediate => "immediate",
+            TransitionAnimationType::Random => "random",
+            TransitionAnimationType::Writing => "writing",
+            TransitionAnimationType::Overwrite => "overwrite",
+            TransitionAnimationType::Dissolve => "dissolve",
+            TransitionAnimationType::Scanline { .. } => "scanline",
+            TransitionAnimationType::Ripple { .. } => "ripple",
+            TransitionAnimationType::Morph => "morph",
+            TransitionAnimationType::Checkerboard => "checkerboard",
+            TransitionAnimationType::Flood { .. } => "flood",
+        }
+    }
+
+    // The number OSC sends for this type
+    pub fn code(&self) -> i32 {
+        match self {
+            TransitionAnimationType::Random => 0,
+            TransitionAnimationType::Immediate => 1,
+            TransitionAnimationType::Writing => 2,
+            TransitionAnimationType::Overwrite => 3,
+            TransitionAnimationType::Dissolve => 4,
+            TransitionAnimationType::Scanline { .. } => 5,
+            TransitionAnimationType::Checkerboard => 6,
+            TransitionAnimationType::Morph => 7,
+            TransitionAnimationType::Ripple { .. } => 8,
+            TransitionAnimationType::Flood { .. } => 9,
+        }
+    }
+}
+
+// Types that take parameters come back with their defaults: a forward
+// Scanline along x, a Ripple from the grid origin and a Flood with no seed
+impl TryFrom<i32> for TransitionAnimationType {
+    type Error = String;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TransitionAnimationType::Random),
+            1 => Ok(TransitionAnimationType::Immediate),
+            2 => Ok(TransitionAnimationType::Writing),
+            3 => Ok(TransitionAnimationType::Overwrite),
+            4 => Ok(TransitionAnimationType::Dissolve),
+            5 => Ok(TransitionAnimationType::Scanline {
+                axis: Axis::X,
+                reverse: false,
+            }),
+            6 => Ok(TransitionAnimationType::Checkerboard),
+            7 => Ok(TransitionAnimationType::Morph),
+            8 => Ok(TransitionAnimationType::Ripple {
+                origin: Point2::ZERO,
+            }),
+            9 => Ok(TransitionAnimationType::Flood {
+                seed: String::new(),
+            }),
+            _ => Err(format!(
+                "Invalid transition animation type: {}. Expected 0 to 9",
+                value
+            )),
+        }
+    }
+}
+
+impl TryFrom<&str> for TransitionAnimationType {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let code = match value.to_lowercase().as_str() {
+            "random" => 0,
+            "immediate" => 1,
+            "writing" => 2,
+            "overwrite" => 3,
+            "dissolve" => 4,
+            "scanline" => 5,
+            "checker" | "checkerboard" => 6,
+            "morph" => 7,
+            "ripple" => 8,
+            "flood" => 9,
+            _ => return Err(format!("Unknown transition animation type: {}", value)),
+        };
+        Self::try_from(code)
+    }
 }
 
 impl Transition {
@@ -59,11 +171,29 @@ impl Transition {
             current_step: 0,
             frame_timer: 0.0,
             frame_duration,
+            paused: false,
             animation_type,
         }
     }
 
+    // A paused transition holds its step and accumulated frame time
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
     pub fn should_auto_advance(&mut self, dt: f32) -> bool {
+        if self.paused {
+            return false;
+        }
+
         self.frame_timer += dt;
         if self.frame_timer >= self.frame_duration {
             self.frame_timer -= self.frame_duration;
@@ -79,11 +209,15 @@ impl Transition {
 
             let mut segments_on = HashSet::new();
             let mut segments_off = HashSet::new();
+            let mut styles = HashMap::new();
 
             // Process all changes for this step
             for change in current_changes {
                 if change.turn_on {
                     segments_on.insert(change.segment_id.clone());
+                    if let Some(style) = &change.style {
+                        styles.insert(change.segment_id.clone(), style.clone());
+                    }
                 } else {
                     segments_off.insert(change.segment_id.clone());
                 }
@@ -93,6 +227,7 @@ impl Transition {
             Some(TransitionUpdates {
                 segments_on,
                 segments_off,
+                styles,
             })
         } else {
             None
@@ -103,21 +238,89 @@ impl Transition {
         self.current_step >= self.changes.len()
     }
 
+    // (completed steps, total steps)
+    pub fn progress(&self) -> (usize, usize) {
+        (self.current_step, self.changes.len())
+    }
+
     pub fn is_immediate_type(&self) -> bool {
         matches!(self.animation_type, TransitionAnimationType::Immediate)
     }
+
+    // The steps that haven't been applied yet, for handing over to an
+    // interrupting transition
+    pub fn take_remaining_changes(&mut self) -> Vec<Vec<SegmentChange>> {
+        self.changes
+            .split_off(self.current_step.min(self.changes.len()))
+    }
+}
+
+// Plays what's left of an interrupted transition alongside the new one.
+//
+// Old changes are only kept where they agree with the new target, so the
+// crossfade can't leave anything lit that the new glyph doesn't have. Each
+// segment changes at most once, at its earliest scheduled step.
+pub fn crossfade_changes(
+    remaining: Vec<Vec<SegmentChange>>,
+    new_changes: Vec<Vec<SegmentChange>>,
+    target_segments: &HashSet<SegmentKey>,
+) -> Vec<Vec<SegmentChange>> {
+    let step_count = remaining.len().max(new_changes.len());
+    let mut merged: Vec<Vec<SegmentChange>> = (0..step_count).map(|_| Vec::new()).collect();
+    let mut scheduled: HashSet<SegmentKey> = HashSet::new();
+
+    let mut old_steps = remaining.into_iter();
+    let mut new_steps = new_changes.into_iter();
+    for step in merged.iter_mut() {
+        let old = old_steps
+            .next()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|change| change.turn_on == target_segments.contains(&change.segment_id));
+        let new = new_steps.next().unwrap_or_default();
+
+        for change in old.chain(new) {
+            if scheduled.insert(change.segment_id.clone()) {
+                step.push(change);
+            }
+        }
+    }
+
+    merged
 }
 
 // Generates the frames of the Transition
 pub struct TransitionEngine {
     pub default_config: TransitionConfig,
+
+    // Randomness for generated changes lives here so it can be seeded
+    rng: RefCell<StdRng>,
 }
 
 // The thing that generates the Transition
 impl TransitionEngine {
     pub fn new(config: TransitionConfig) -> Self {
+        let rng = RefCell::new(Self::seeded_rng(config.seed));
         Self {
             default_config: config,
+            rng,
+        }
+    }
+
+    // process OSC /transition/seed. None goes back to unrepeatable randomness.
+    pub fn set_seed(&self, seed: Option<u64>) {
+        *self.rng.borrow_mut() = Self::seeded_rng(seed);
+    }
+
+    // An rng for other effects, repeatable whenever the engine is seeded
+    pub fn child_rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.rng.borrow_mut().gen())
+    }
+
+    fn seeded_rng(seed: Option<u64>) -> StdRng {
+        match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
         }
     }
 
@@ -147,16 +350,38 @@ impl TransitionEngine {
                 let first_change_segments = HashSet::new();
                 let target_segments = grid_instance.target_segments.as_ref().unwrap();
 
-                // first, clear the grid
-                let mut changes =
-                    self.generate_immediate_changes(grid_instance, &first_change_segments);
+                // first, clear the grid, either all at once or by erasing
+                // the outgoing glyph in reverse stroke order
+                let mut changes = if grid_instance.transition_use_reverse_erase {
+                    stroke_order::generate_erase_changes(
+                        grid_instance,
+                        &grid_instance.current_active_segments,
+                    )
+                } else {
+                    self.generate_immediate_changes(grid_instance, &first_change_segments)
+                };
 
                 // then, generate changes to write the glyph
-                changes.extend(self.generate_stroke_order_changes(
-                    grid_instance,
-                    &first_change_segments,
-                    target_segments,
-                ));
+                let stroke_gap_steps = self.config_for(grid_instance).stroke_gap_steps;
+                if grid_instance.transition_use_stroke_tint {
+                    changes.extend(stroke_order::generate_tinted_stroke_changes(
+                        grid_instance,
+                        &first_change_segments,
+                        target_segments,
+                        stroke_gap_steps,
+                    ));
+                } else {
+                    let strokes = stroke_order::generate_ordered_strokes(
+                        grid_instance,
+                        &first_change_segments,
+                        target_segments,
+                    );
+                    changes.extend(stroke_order::convert_strokes_to_transition_changes(
+                        strokes,
+                        stroke_gap_steps,
+                        grid_instance,
+                    ));
+                }
                 changes
             }
             TransitionAnimationType::Overwrite => {
@@ -166,13 +391,37 @@ impl TransitionEngine {
 
                 self.generate_stroke_order_changes(grid_instance, &start_segments, target_segments)
             }
+            TransitionAnimationType::Dissolve => {
+                let target_segments = grid_instance.target_segments.as_ref().unwrap();
+                self.generate_dissolve_changes(grid_instance, target_segments)
+            }
+            TransitionAnimationType::Scanline { axis, reverse } => {
+                let target_segments = grid_instance.target_segments.as_ref().unwrap();
+                self.generate_scanline_changes(grid_instance, target_segments, axis, reverse)
+            }
+            TransitionAnimationType::Ripple { origin } => {
+                let target_segments = grid_instance.target_segments.as_ref().unwrap();
+                self.generate_ripple_changes(grid_instance, target_segments, origin)
+            }
+            TransitionAnimationType::Morph => {
+                let target_segments = grid_instance.target_segments.as_ref().unwrap();
+                self.generate_morph_changes(grid_instance, target_segments)
+            }
+            TransitionAnimationType::Checkerboard => {
+                let target_segments = grid_instance.target_segments.as_ref().unwrap();
+                self.generate_checkerboard_changes(grid_instance, target_segments)
+            }
+            TransitionAnimationType::Flood { seed } => {
+                let target_segments = grid_instance.target_segments.as_ref().unwrap();
+                self.generate_flood_changes(grid_instance, target_segments, &seed)
+            }
         }
     }
 
     pub fn generate_immediate_changes(
         &self,
         grid_instance: &GridInstance,
-        target_segments: &HashSet<String>,
+        target_segments: &HashSet<SegmentKey>,
     ) -> Vec<Vec<SegmentChange>> {
         let start_segments = &grid_instance.current_active_segments;
         let mut single_step = Vec::new();
@@ -182,6 +431,7 @@ impl TransitionEngine {
             single_step.push(SegmentChange {
                 segment_id: seg.clone(),
                 turn_on: false,
+                style: None,
             });
         }
 
@@ -190,30 +440,28 @@ impl TransitionEngine {
             single_step.push(SegmentChange {
                 segment_id: seg.clone(),
                 turn_on: true,
+                style: None,
             });
         }
 
         // Return a single step with all changes
+        single_step.sort_by(|a, b| a.segment_id.cmp(&b.segment_id));
         vec![single_step]
     }
 
     pub fn generate_random_changes(
         &self,
         grid_instance: &GridInstance,
-        target_segments: &HashSet<String>,
+        target_segments: &HashSet<SegmentKey>,
     ) -> Vec<Vec<SegmentChange>> {
         let grid = &grid_instance.grid;
         let target_style = &grid_instance.target_style;
-        let segment_graph = &grid_instance.graph;
+        let segment_graph = &grid_instance.graph();
         let start_segments = &grid_instance.current_active_segments;
 
-        let config = if let Some(config) = &grid_instance.transition_config {
-            config
-        } else {
-            &self.default_config
-        };
+        let config = self.config_for(grid_instance);
 
-        let mut rng = thread_rng();
+        let mut rng = self.rng.borrow_mut();
         let mut changes_by_step: Vec<Vec<SegmentChange>> =
             (0..config.steps).map(|_| Vec::new()).collect();
         let mut pending_changes = Vec::new();
@@ -249,6 +497,9 @@ impl TransitionEngine {
             }
         }
 
+        // Fixed order so a seeded rng always makes the same choices
+        pending_changes.sort();
+
         // Calculate changes per step based on density
         let changes_per_step = (pending_changes.len() as f32 * config.density).ceil() as usize;
 
@@ -267,6 +518,7 @@ impl TransitionEngine {
                     step_changes.push(SegmentChange {
                         segment_id: seg.clone(),
                         turn_on: is_add,
+                        style: None,
                     });
                     changes_this_step += 1;
 
@@ -276,6 +528,7 @@ impl TransitionEngine {
                             step_changes.push(SegmentChange {
                                 segment_id: neighbor_seg.clone(),
                                 turn_on: *neighbor_is_add,
+                                style: None,
                             });
                             changes_this_step += 1;
                             false // Remove from pending_changes
@@ -294,6 +547,7 @@ impl TransitionEngine {
                     last.push(SegmentChange {
                         segment_id: seg,
                         turn_on: is_add,
+                        style: None,
                     });
                 }
             }
@@ -306,11 +560,405 @@ impl TransitionEngine {
         changes_by_step
     }
 
+    // Turns segments on and off in a shuffled order spread across the steps.
+    // Density sets the fraction of all changes made in each step, but never so
+    // few that the transition runs past config.steps.
+    pub fn generate_dissolve_changes(
+        &self,
+        grid_instance: &GridInstance,
+        target_segments: &HashSet<SegmentKey>,
+    ) -> Vec<Vec<SegmentChange>> {
+        let start_segments = &grid_instance.current_active_segments;
+
+        let config = self.config_for(grid_instance);
+
+        // Sort before shuffling so a seeded rng always gives the same order
+        let mut pending_changes: Vec<SegmentChange> = start_segments
+            .difference(target_segments)
+            .map(|seg| SegmentChange {
+                segment_id: seg.clone(),
+                turn_on: false,
+                style: None,
+            })
+            .chain(
+                target_segments
+                    .difference(start_segments)
+                    .map(|seg| SegmentChange {
+                        segment_id: seg.clone(),
+                        turn_on: true,
+                        style: None,
+                    }),
+            )
+            .collect();
+        pending_changes.sort_by(|a, b| a.segment_id.cmp(&b.segment_id));
+        pending_changes.shuffle(&mut *self.rng.borrow_mut());
+
+        if pending_changes.is_empty() {
+            return Vec::new();
+        }
+
+        let steps = config.steps.max(1);
+        let min_per_step = pending_changes.len().div_ceil(steps);
+        let changes_per_step =
+            ((pending_changes.len() as f32 * config.density).ceil() as usize).max(min_per_step);
+
+        let mut changes_by_step = Vec::new();
+        let mut pending_changes = pending_changes.into_iter().peekable();
+        while pending_changes.peek().is_some() {
+            changes_by_step.push(pending_changes.by_ref().take(changes_per_step).collect());
+        }
+        changes_by_step
+    }
+
+    // Sweeps across the grid one row (or column) per step, switching both
+    // outgoing and incoming segments as the line passes. Always takes one
+    // step per row or column, whatever the configured steps.
+    pub fn generate_scanline_changes(
+        &self,
+        grid_instance: &GridInstance,
+        target_segments: &HashSet<SegmentKey>,
+        axis: Axis,
+        reverse: bool,
+    ) -> Vec<Vec<SegmentChange>> {
+        let grid = &grid_instance.grid;
+        let start_segments = &grid_instance.current_active_segments;
+
+        // Axis::X matches slide: rows are indexed by the tile's y coordinate
+        let line_count = match axis {
+            Axis::X => grid.dimensions.1,
+            Axis::Y => grid.dimensions.0,
+        } as usize;
+        if line_count == 0 {
+            return self.generate_immediate_changes(grid_instance, target_segments);
+        }
+
+        let mut changes_by_step: Vec<Vec<SegmentChange>> =
+            (0..line_count).map(|_| Vec::new()).collect();
+
+        let pending_changes = start_segments
+            .difference(target_segments)
+            .map(|seg| (seg, false))
+            .chain(
+                target_segments
+                    .difference(start_segments)
+                    .map(|seg| (seg, true)),
+            );
+
+        for (seg, turn_on) in pending_changes {
+            let Some(segment) = grid.segments.get(seg) else {
+                continue;
+            };
+            let line = match axis {
+                Axis::X => segment.tile_coordinate.1,
+                Axis::Y => segment.tile_coordinate.0,
+            } as usize;
+
+            // tile coordinates start at 1
+            let mut step = line.saturating_sub(1).min(line_count - 1);
+            if reverse {
+                step = line_count - 1 - step;
+            }
+            changes_by_step[step].push(SegmentChange {
+                segment_id: seg.clone(),
+                turn_on,
+                style: None,
+            });
+        }
+
+        for step in &mut changes_by_step {
+            step.sort_by(|a, b| a.segment_id.cmp(&b.segment_id));
+        }
+        changes_by_step
+    }
+
+    // Flips the tiles of one parity, then the other, like a two-phase shutter.
+    // With more than two steps, each phase sweeps across in diagonal bands.
+    // Outgoing segments take the opposite phase so old and new interleave.
+    pub fn generate_checkerboard_changes(
+        &self,
+        grid_instance: &GridInstance,
+        target_segments: &HashSet<SegmentKey>,
+    ) -> Vec<Vec<SegmentChange>> {
+        let grid = &grid_instance.grid;
+        let start_segments = &grid_instance.current_active_segments;
+        let config = self.config_for(grid_instance);
+
+        // diagonals of each parity, counted along x + y
+        let diagonal_count = (grid.dimensions.0 + grid.dimensions.1).saturating_sub(1) as usize;
+        let diagonals_per_phase = diagonal_count.div_ceil(2).max(1);
+        let bands_per_phase = (config.steps / 2).clamp(1, diagonals_per_phase);
+
+        let mut changes_by_step: Vec<Vec<SegmentChange>> =
+            (0..bands_per_phase * 2).map(|_| Vec::new()).collect();
+
+        let pending_changes = start_segments
+            .difference(target_segments)
+            .map(|seg| (seg, false))
+            .chain(
+                target_segments
+                    .difference(start_segments)
+                    .map(|seg| (seg, true)),
+            );
+
+        for (seg, turn_on) in pending_changes {
+            let Some(segment) = grid.segments.get(seg) else {
+                continue;
+            };
+            // tile coordinates start at 1
+            let (x, y) = segment.tile_coordinate;
+            let diagonal = (x + y).saturating_sub(2) as usize;
+
+            let mut phase = diagonal % 2;
+            if !turn_on {
+                phase = 1 - phase;
+            }
+            let band = (diagonal / 2) * bands_per_phase / diagonals_per_phase;
+
+            changes_by_step[phase * bands_per_phase + band].push(SegmentChange {
+                segment_id: seg.clone(),
+                turn_on,
+                style: None,
+            });
+        }
+
+        changes_by_step.retain(|step| !step.is_empty());
+        for step in &mut changes_by_step {
+            step.sort_by(|a, b| a.segment_id.cmp(&b.segment_id));
+        }
+        changes_by_step
+    }
+
+    // Lit segments crawl along the graph from the old glyph to the new one.
+    // New segments with no connection to the old glyph dissolve in over the
+    // length of the crawl.
+    pub fn generate_morph_changes(
+        &self,
+        grid_instance: &GridInstance,
+        target_segments: &HashSet<SegmentKey>,
+    ) -> Vec<Vec<SegmentChange>> {
+        let start_segments = &grid_instance.current_active_segments;
+
+        // nothing to crawl from or to
+        if start_segments.is_empty() || target_segments.is_empty() {
+            return self.generate_dissolve_changes(grid_instance, target_segments);
+        }
+
+        let (paths, mut unreachable) = morph::find_morph_paths(grid_instance, target_segments);
+        let mut changes_by_step = morph::schedule_morph(&paths, start_segments, target_segments);
+
+        if !unreachable.is_empty() {
+            unreachable.sort();
+            unreachable.shuffle(&mut *self.rng.borrow_mut());
+
+            if changes_by_step.is_empty() {
+                changes_by_step.push(Vec::new());
+            }
+            let per_step = unreachable.len().div_ceil(changes_by_step.len());
+            for (step, segments) in changes_by_step.iter_mut().zip(unreachable.chunks(per_step)) {
+                step.extend(segments.iter().map(|seg| SegmentChange {
+                    segment_id: seg.clone(),
+                    turn_on: true,
+                    style: None,
+                }));
+            }
+        }
+
+        changes_by_step
+    }
+
+    // Segments change in rings spreading out from the origin. The old glyph
+    // collapses and the new one blooms in the same wave.
+    pub fn generate_ripple_changes(
+        &self,
+        grid_instance: &GridInstance,
+        target_segments: &HashSet<SegmentKey>,
+        origin: Point2,
+    ) -> Vec<Vec<SegmentChange>> {
+        let grid = &grid_instance.grid;
+        let start_segments = &grid_instance.current_active_segments;
+
+        let config = self.config_for(grid_instance);
+
+        let mut pending_changes: Vec<(f32, SegmentChange)> = start_segments
+            .difference(target_segments)
+            .map(|seg| (seg, false))
+            .chain(
+                target_segments
+                    .difference(start_segments)
+                    .map(|seg| (seg, true)),
+            )
+            .map(|(seg, turn_on)| {
+                let distance = segment_analysis::get_segment_center(seg, grid).distance(origin);
+                (
+                    distance,
+                    SegmentChange {
+                        segment_id: seg.clone(),
+                        turn_on,
+                        style: None,
+                    },
+                )
+            })
+            .collect();
+
+        if pending_changes.is_empty() {
+            return Vec::new();
+        }
+
+        pending_changes.sort_by(|a, b| {
+            a.0.total_cmp(&b.0)
+                .then_with(|| a.1.segment_id.cmp(&b.1.segment_id))
+        });
+
+        // Each step covers a ring of equal width so the wave spreads at a
+        // constant speed, even through empty space
+        let steps = config.steps.max(1);
+        let max_distance = pending_changes.last().map_or(0.0, |(d, _)| *d);
+        let ring_width = max_distance / steps as f32;
+
+        let mut changes_by_step: Vec<Vec<SegmentChange>> = (0..steps).map(|_| Vec::new()).collect();
+        for (distance, change) in pending_changes {
+            let step = if ring_width > 0.0 {
+                ((distance / ring_width) as usize).min(steps - 1)
+            } else {
+                0
+            };
+            changes_by_step[step].push(change);
+        }
+
+        // nothing left to show once the outermost ring has changed
+        while changes_by_step.last().is_some_and(|step| step.is_empty()) {
+            changes_by_step.pop();
+        }
+        changes_by_step
+    }
+
+    // The glyph spreads from the seed segment through the graph, a ring of
+    // connected segments per level, so it creeps along strokes instead of
+    // Ripple's even circles. Old segments drain back toward the seed over the
+    // same steps. Levels are spread evenly over the configured steps.
+    pub fn generate_flood_changes(
+        &self,
+        grid_instance: &GridInstance,
+        target_segments: &HashSet<SegmentKey>,
+        seed: &str,
+    ) -> Vec<Vec<SegmentChange>> {
+        let graph = &grid_instance.graph();
+        let start_segments = &grid_instance.current_active_segments;
+
+        let mut turning_on: Vec<&SegmentKey> = target_segments.difference(start_segments).collect();
+        let mut turning_off: Vec<&SegmentKey> =
+            start_segments.difference(target_segments).collect();
+        if turning_on.is_empty() && turning_off.is_empty() {
+            return Vec::new();
+        }
+        turning_on.sort();
+        turning_off.sort();
+
+        // flood from the seed, or the target segment nearest to it. With no
+        // glyph to come, the old one just drains back to the seed.
+        let key = grid_instance.grid.key(seed);
+        let found = match key {
+            Some(key)
+                if target_segments.contains(&key)
+                    || (target_segments.is_empty() && graph.contains(&key)) =>
+            {
+                Some(key)
+            }
+            Some(key) if graph.contains(&key) => graph
+                .find_path_to_nearest(&key, target_segments)
+                .and_then(|path| path.last().cloned()),
+            _ => None,
+        };
+        let Some(seed) = found.or_else(|| {
+            let fallback = target_segments
+                .iter()
+                .min()
+                .or(turning_off.first().copied())
+                .cloned();
+            if let Some(fallback) = &fallback {
+                println!(
+                    "Warning: flood seed '{}' isn't a segment that reaches the glyph, starting from {}",
+                    seed, fallback
+                );
+            }
+            fallback
+        }) else {
+            return Vec::new();
+        };
+
+        // segments the flood can't reach go last
+        let levels = graph.hop_distances(&seed);
+        let level_of = |id: &SegmentKey| levels.get(id).copied().unwrap_or(usize::MAX);
+        let deepest = turning_on
+            .iter()
+            .chain(&turning_off)
+            .map(|id| level_of(id))
+            .filter(|level| *level != usize::MAX)
+            .max()
+            .unwrap_or(0)
+            + 1;
+
+        let steps = self.config_for(grid_instance).steps.max(1);
+        let step_of = |level: usize| (level.min(deepest) * steps / (deepest + 1)).min(steps - 1);
+
+        let mut changes_by_step: Vec<Vec<SegmentChange>> = (0..steps).map(|_| Vec::new()).collect();
+        for id in turning_on {
+            changes_by_step[step_of(level_of(id))].push(SegmentChange {
+                segment_id: id.clone(),
+                turn_on: true,
+                style: None,
+            });
+        }
+        for id in turning_off {
+            let reversed = deepest - level_of(id).min(deepest);
+            changes_by_step[step_of(reversed)].push(SegmentChange {
+                segment_id: id.clone(),
+                turn_on: false,
+                style: None,
+            });
+        }
+
+        while changes_by_step.last().is_some_and(|step| step.is_empty()) {
+            changes_by_step.pop();
+        }
+        changes_by_step
+    }
+
+    // Random delays before each segment turning on takes effect, so a batch
+    // doesn't all flip on the same frame. Empty when stagger is off.
+    pub fn stagger_delays(
+        &self,
+        grid_instance: &GridInstance,
+        segments_on: &HashSet<SegmentKey>,
+    ) -> Vec<(SegmentKey, f32)> {
+        let stagger = self.config_for(grid_instance).stagger;
+        if stagger <= 0.0 {
+            return Vec::new();
+        }
+
+        let mut segment_ids: Vec<&SegmentKey> = segments_on.iter().collect();
+        segment_ids.sort();
+
+        let mut rng = self.rng.borrow_mut();
+        segment_ids
+            .into_iter()
+            .map(|segment_id| (segment_id.clone(), rng.gen_range(0.0..stagger)))
+            .collect()
+    }
+
+    // A grid's own config from /transition/update takes precedence
+    pub fn config_for<'a>(&'a self, grid_instance: &'a GridInstance) -> &'a TransitionConfig {
+        grid_instance
+            .transition_config
+            .as_ref()
+            .unwrap_or(&self.default_config)
+    }
+
     pub fn generate_stroke_order_changes(
         &self,
         grid_instance: &GridInstance,
-        start_segments: &HashSet<String>,
-        target_segments: &HashSet<String>,
+        start_segments: &HashSet<SegmentKey>,
+        target_segments: &HashSet<SegmentKey>,
     ) -> Vec<Vec<SegmentChange>> {
         // Call into the stroke order module
         let ordered_segments =
@@ -322,28 +970,27 @@ impl TransitionEngine {
 
     fn find_nearest_connected(
         &self,
-        segment: &str,
-        active_segments: &HashSet<String>,
-        graph: &SegmentGraph,
-    ) -> Option<String> {
+        segment: &SegmentKey,
+        active_segments: &HashSet<SegmentKey>,
+        graph: &GraphView,
+    ) -> Option<SegmentKey> {
         // Get all neighbors from the graph
         let mut visited = HashSet::new();
         let mut queue = VecDeque::new();
-        queue.push_back(segment.to_string());
-        visited.insert(segment.to_string());
+        queue.push_back(segment.clone());
+        visited.insert(segment.clone());
 
         // Breadth-first search through connected segments
         while let Some(current) = queue.pop_front() {
             // If this neighbor is in our target set, we found our match
-            if active_segments.contains(&current) && current != segment {
+            if active_segments.contains(&current) && current != *segment {
                 return Some(current);
             }
 
             // Add unvisited neighbors to queue
-            for neighbor in graph.neighbors_of(&current) {
-                if !visited.contains(&neighbor) {
-                    visited.insert(neighbor.clone());
-                    queue.push_back(neighbor);
+            for neighbor in graph.neighbors(&current).iter() {
+                if visited.insert(neighbor.clone()) {
+                    queue.push_back(neighbor.clone());
                 }
             }
         }
@@ -355,3 +1002,324 @@ impl TransitionEngine {
         &self.default_config
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Project;
+    use crate::test_helpers::{test_config, test_engine, test_grid_instance};
+    use nannou::prelude::*;
+
+    fn test_transition() -> Transition {
+        let changes = (0..3)
+            .map(|i| {
+                vec![SegmentChange {
+                    segment_id: SegmentKey::off_grid(format!("seg{}", i)),
+                    turn_on: true,
+                    style: None,
+                }]
+            })
+            .collect();
+        Transition::new(TransitionAnimationType::Writing, changes, 0.1)
+    }
+
+    #[test]
+    fn test_animation_types_round_trip_through_codes_and_names() {
+        for code in 0..10 {
+            let animation_type = TransitionAnimationType::try_from(code).unwrap();
+            assert_eq!(animation_type.code(), code);
+            assert!(TransitionAnimationType::try_from(animation_type.name()) == Ok(animation_type));
+        }
+        assert_eq!(
+            TransitionAnimationType::try_from("Checker").map(|t| t.code()),
+            Ok(6)
+        );
+        assert!(TransitionAnimationType::try_from(10).is_err());
+        assert!(TransitionAnimationType::try_from("sideways").is_err());
+    }
+
+    #[test]
+    fn test_progress_counts_steps() {
+        let mut transition = test_transition();
+        assert_eq!(transition.progress(), (0, 3));
+        transition.advance();
+        transition.advance();
+        assert_eq!(transition.progress(), (2, 3));
+        assert_eq!(transition.animation_type.name(), "writing");
+    }
+
+    #[test]
+    fn test_pause_holds_accumulated_time() {
+        let mut transition = test_transition();
+        assert!(!transition.should_auto_advance(0.06));
+
+        transition.pause();
+        assert!(!transition.should_auto_advance(1.0));
+
+        // resumes from 0.06, not from zero and not from 1.06
+        transition.resume();
+        assert!(transition.should_auto_advance(0.05));
+        assert!(!transition.should_auto_advance(0.05));
+    }
+
+    fn seeded_changes(seed: u64) -> Vec<Vec<(SegmentKey, bool)>> {
+        let mut grid_instance = test_grid_instance();
+
+        let mut segment_ids: Vec<SegmentKey> =
+            grid_instance.grid.segments.keys().cloned().collect();
+        segment_ids.sort();
+        grid_instance.current_active_segments = segment_ids.iter().step_by(3).cloned().collect();
+        grid_instance.target_segments = Some(segment_ids.iter().step_by(2).cloned().collect());
+
+        let engine = TransitionEngine::new(TransitionConfig {
+            seed: Some(seed),
+            ..test_config(10, 0.1)
+        });
+
+        [
+            TransitionAnimationType::Random,
+            TransitionAnimationType::Dissolve,
+        ]
+        .into_iter()
+        .flat_map(|typ| engine.generate_changes(&grid_instance, typ))
+        .map(|step| {
+            step.into_iter()
+                .map(|change| (change.segment_id, change.turn_on))
+                .collect()
+        })
+        .collect()
+    }
+
+    // Recorded before changes could carry their own styles. Random doesn't
+    // set any, so it has to step through exactly the same frames as it did.
+    #[test]
+    fn test_unstyled_types_step_as_before() {
+        let project = Project::load("projects/debug.json").unwrap();
+        let mut grid_instance = test_grid_instance();
+        grid_instance.target_segments = Some(
+            project
+                .get_glyph("1,1 entire")
+                .unwrap()
+                .segments
+                .iter()
+                .map(|id| id.parse().unwrap())
+                .collect(),
+        );
+        let engine = TransitionEngine::new(TransitionConfig {
+            seed: Some(42),
+            ..test_config(10, 0.1)
+        });
+        let changes = engine.generate_changes(&grid_instance, TransitionAnimationType::Random);
+        let mut transition = Transition::new(TransitionAnimationType::Random, changes, 0.1);
+
+        let expected = [
+            ["arc-1", "ver-1-1"],
+            ["arc-2", "hor-3-1"],
+            ["hor-2-2", "ver-2-1"],
+            ["hor-1-2", "hor-2-1"],
+            ["arc-4", "hor-3-2"],
+            ["ver-3-1", "ver-3-2"],
+            ["hor-1-1", "ver-2-2"],
+            ["arc-3", "ver-1-2"],
+        ];
+        for elements in expected {
+            let updates = transition.advance().unwrap();
+            let mut segments_on: Vec<SegmentKey> = updates.segments_on.into_iter().collect();
+            segments_on.sort();
+            assert_eq!(
+                segments_on,
+                elements.map(|element| SegmentKey::new((1, 1), element))
+            );
+            assert!(updates.segments_off.is_empty());
+            assert!(updates.styles.is_empty());
+        }
+        assert!(transition.advance().is_none());
+    }
+
+    #[test]
+    fn test_seeded_generation_repeats() {
+        assert_eq!(seeded_changes(42), seeded_changes(42));
+    }
+
+    fn step_count(grid_instance: &GridInstance, typ: TransitionAnimationType, gap: usize) -> usize {
+        let engine = TransitionEngine::new(TransitionConfig {
+            stroke_gap_steps: gap,
+            ..test_config(10, 0.1)
+        });
+        engine.generate_changes(grid_instance, typ).len()
+    }
+
+    #[test]
+    fn test_grid_configs_set_their_own_steps() {
+        let mut segment_ids: Vec<SegmentKey> =
+            test_grid_instance().grid.segments.keys().cloned().collect();
+        segment_ids.sort();
+        let glyph_pair = |grid_instance: &mut GridInstance| {
+            grid_instance.current_active_segments =
+                segment_ids.iter().step_by(3).cloned().collect();
+            grid_instance.target_segments = Some(segment_ids.iter().step_by(2).cloned().collect());
+        };
+        let engine = test_engine(10, 0.1);
+
+        let mut default_grid = test_grid_instance();
+        glyph_pair(&mut default_grid);
+        let mut quick_grid = test_grid_instance();
+        glyph_pair(&mut quick_grid);
+        quick_grid.update_transition_config(
+            Some(4),
+            Some(0.05),
+            None,
+            None,
+            None,
+            &engine.default_config,
+        );
+
+        let typ = TransitionAnimationType::Random;
+        assert_eq!(
+            engine.generate_changes(&default_grid, typ.clone()).len(),
+            10
+        );
+        assert_eq!(engine.generate_changes(&quick_grid, typ).len(), 4);
+        assert_eq!(engine.config_for(&default_grid).frame_duration, 0.1);
+        assert_eq!(engine.config_for(&quick_grid).frame_duration, 0.05);
+    }
+
+    #[test]
+    fn test_stroke_gaps_only_pace_writing() {
+        let mut grid_instance = test_grid_instance();
+
+        // opposite corners of the grid can't share a stroke
+        let target = HashSet::from([
+            SegmentKey::new((1, 1), "arc-1"),
+            SegmentKey::new((4, 4), "arc-1"),
+        ]);
+        let strokes =
+            stroke_order::generate_ordered_strokes(&grid_instance, &HashSet::new(), &target);
+        assert_eq!(strokes.len(), 2);
+        grid_instance.target_segments = Some(target);
+
+        // the gap goes between the two strokes only
+        let writing = TransitionAnimationType::Writing;
+        assert_eq!(
+            step_count(&grid_instance, writing.clone(), 3),
+            step_count(&grid_instance, writing, 0) + 3
+        );
+
+        let overwrite = TransitionAnimationType::Overwrite;
+        assert_eq!(
+            step_count(&grid_instance, overwrite.clone(), 3),
+            step_count(&grid_instance, overwrite, 0)
+        );
+    }
+
+    #[test]
+    fn test_ripple_spreads_out_from_origin() {
+        let mut grid_instance = test_grid_instance();
+        let all: HashSet<SegmentKey> = grid_instance.grid.segments.keys().cloned().collect();
+        grid_instance.target_segments = Some(all.clone());
+
+        // the origin is grid-local, wherever the grid has been put
+        grid_instance.move_by(vec2(300.0, -150.0));
+        grid_instance.rotate_in_place(40.0);
+        grid_instance.scale_in_place(2.0);
+
+        let engine = test_engine(10, 0.1);
+        let origin = pt2(1.0, -1.0);
+        let changes =
+            engine.generate_changes(&grid_instance, TransitionAnimationType::Ripple { origin });
+        assert_eq!(changes.len(), 10);
+        assert_eq!(changes.iter().flatten().count(), all.len());
+        assert!(changes.iter().flatten().all(|change| change.turn_on));
+
+        // each ring lies wholly outside the one before it
+        let distance = |change: &SegmentChange| {
+            segment_analysis::get_segment_center(&change.segment_id, &grid_instance.grid)
+                .distance(origin)
+        };
+        let rings: Vec<(f32, f32)> = changes
+            .iter()
+            .filter(|step| !step.is_empty())
+            .map(|step| {
+                step.iter()
+                    .map(distance)
+                    .fold((f32::MAX, 0.0), |(near, far), d| (near.min(d), far.max(d)))
+            })
+            .collect();
+        assert!(rings.len() > 1);
+        let nearest = changes
+            .iter()
+            .flatten()
+            .map(distance)
+            .fold(f32::MAX, f32::min);
+        assert_eq!(rings[0].0, nearest);
+        for pair in rings.windows(2) {
+            assert!(pair[0].1 <= pair[1].0);
+        }
+    }
+
+    #[test]
+    fn test_checkerboard_phases_interleave() {
+        let mut grid_instance = test_grid_instance();
+        let mut segment_ids: Vec<SegmentKey> =
+            grid_instance.grid.segments.keys().cloned().collect();
+        segment_ids.sort();
+        grid_instance.current_active_segments = segment_ids.iter().step_by(3).cloned().collect();
+        grid_instance.target_segments = Some(segment_ids.iter().step_by(2).cloned().collect());
+
+        let engine = test_engine(2, 0.1);
+        let changes =
+            engine.generate_changes(&grid_instance, TransitionAnimationType::Checkerboard);
+        assert_eq!(changes.len(), 2);
+
+        // incoming even tiles flip with outgoing odd tiles, then the reverse
+        for (phase, step) in changes.iter().enumerate() {
+            for change in step {
+                let (x, y) = grid_instance.grid.segments[&change.segment_id].tile_coordinate;
+                let even = (x + y) % 2 == 0;
+                assert_eq!(even == change.turn_on, phase == 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_flood_spreads_in_graph_order() {
+        let mut grid_instance = test_grid_instance();
+        let all: HashSet<SegmentKey> = grid_instance.grid.segments.keys().cloned().collect();
+        let engine = test_engine(1000, 0.1);
+        let flood = |seed: &str| TransitionAnimationType::Flood {
+            seed: seed.to_string(),
+        };
+        // the hop distance of each change, in the order they happen
+        let levels = |grid_instance: &GridInstance,
+                      changes: &[Vec<SegmentChange>],
+                      seed: &str|
+         -> Vec<usize> {
+            let hops = grid_instance.graph().hop_distances(&seed.parse().unwrap());
+            changes
+                .iter()
+                .flat_map(|step| step.iter().map(|change| hops[&change.segment_id]))
+                .collect()
+        };
+
+        grid_instance.target_segments = Some(all.clone());
+        let changes = engine.generate_changes(&grid_instance, flood("2,2 : arc-1"));
+        assert_eq!(changes[0].len(), 1);
+        assert_eq!(changes[0][0].segment_id, "2,2 : arc-1");
+        assert!(levels(&grid_instance, &changes, "2,2 : arc-1").is_sorted());
+
+        // an unknown seed starts from the first target segment
+        let first = all.iter().min().unwrap().clone();
+        let changes = engine.generate_changes(&grid_instance, flood("nowhere"));
+        assert_eq!(changes[0][0].segment_id, first);
+
+        // the old glyph drains back into the seed
+        grid_instance.current_active_segments = all;
+        grid_instance.target_segments = Some(HashSet::new());
+        let changes = engine.generate_changes(&grid_instance, flood("2,2 : arc-1"));
+        let mut drained = levels(&grid_instance, &changes, "2,2 : arc-1");
+        assert!(changes.iter().flatten().all(|change| !change.turn_on));
+        assert_eq!(drained.last(), Some(&0));
+        drained.reverse();
+        assert!(drained.is_sorted());
+    }
+}
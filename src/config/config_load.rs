@@ -17,6 +17,10 @@ pub struct Config {
     pub style: StyleConfig,
     pub speed: SpeedConfig,
     pub animation: AnimationConfig,
+    #[serde(default)]
+    pub background: BackgroundConfig,
+    #[serde(default)]
+    pub palettes: Palettes,
 }
 
 impl Config {
@@ -68,6 +72,23 @@ impl Config {
         }
     }
 
+    pub fn resolve_background_image_path(&self) -> Option<PathBuf> {
+        let image = self.background.image.as_ref()?;
+        if Path::new(image).is_absolute() {
+            Some(PathBuf::from(image))
+        } else {
+            // If path is relative, resolve it relative to the executable or working directory
+            if let Some(exe_dir) = std::env::current_exe()
+                .ok()
+                .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+            {
+                Some(exe_dir.join(image))
+            } else {
+                Some(PathBuf::from(image))
+            }
+        }
+    }
+
     pub fn resolve_output_dir(&self) -> PathBuf {
         if Path::new(&self.paths.output_directory).is_absolute() {
             PathBuf::from(&self.paths.output_directory)
@@ -84,6 +105,22 @@ impl Config {
         }
     }
 
+    pub fn resolve_presets_dir(&self) -> PathBuf {
+        if Path::new(&self.paths.presets_directory).is_absolute() {
+            PathBuf::from(&self.paths.presets_directory)
+        } else {
+            // If path is relative, resolve it relative to the executable or working directory
+            if let Some(exe_dir) = std::env::current_exe()
+                .ok()
+                .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+            {
+                exe_dir.join(&self.paths.presets_directory)
+            } else {
+                PathBuf::from(&self.paths.presets_directory)
+            }
+        }
+    }
+
     pub fn resolve_output_dir_as_str(&self) -> String {
         let path = if Path::new(&self.paths.output_directory).is_absolute() {
             PathBuf::from(&self.paths.output_directory)
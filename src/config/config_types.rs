@@ -3,7 +3,10 @@
 // Config types for the app
 
 use crate::animation::EasingType;
-use serde::Deserialize;
+use crate::services::segment_graph::DEFAULT_CONNECTION_THRESHOLD;
+use nannou::color::{rgba, Rgba};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Deserialize)]
 pub struct RenderConfig {
@@ -11,6 +14,12 @@ pub struct RenderConfig {
     pub texture_height: u32,
     pub texture_samples: u32,
     pub arc_resolution: u32,
+    #[serde(default = "default_grid_pool_size")]
+    pub grid_pool_size: usize, // destroyed grids kept for reuse, 0 always clones
+}
+
+fn default_grid_pool_size() -> usize {
+    4
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,12 +32,185 @@ pub struct WindowConfig {
 pub struct FrameRecorderConfig {
     pub frame_limit: u32,
     pub fps: u64,
+    #[serde(default = "default_output_mode")]
+    pub output_mode: String, // "h264", "png", "jpg" or "gif"
+    #[serde(default = "default_gif_width")]
+    pub gif_width: u32, // GIFs are scaled down to this width
+    #[serde(default = "default_file_prefix")]
+    pub file_prefix: String, // takes are named <file_prefix>_<YYYYMMDD_HHMMSS>
+    #[serde(default)]
+    pub crop: Option<CropConfig>, // record only this part of the texture
+    #[serde(default)]
+    pub capture_resolution: Option<[u32; 2]>, // record at this size, not the texture's
+    #[serde(default)]
+    pub replay_seconds: f32, // kept between takes for /recorder/saveback, 0 is off
+    #[serde(default = "default_replay_max_mb")]
+    pub replay_max_mb: usize, // memory the replay buffer may use
+    #[serde(default)]
+    pub quality: RecordingQuality,
+    #[serde(default = "default_ffmpeg_path")]
+    pub ffmpeg_path: String, // the ffmpeg binary, looked up on PATH unless it's a path
+    #[serde(default = "default_ffmpeg_profile")]
+    pub ffmpeg_profile: String, // which of the ffmpeg profiles below previews use
+    #[serde(default)]
+    pub encoder: Option<String>, // replaces the profile's codec, "auto" picks hardware
+    #[serde(default)]
+    pub ffmpeg: HashMap<String, FfmpegConfig>,
+}
+
+fn default_output_mode() -> String {
+    "h264".to_string()
+}
+
+fn default_gif_width() -> u32 {
+    480
+}
+
+fn default_replay_max_mb() -> usize {
+    512
+}
+
+fn default_file_prefix() -> String {
+    "output".to_string()
+}
+
+fn default_ffmpeg_path() -> String {
+    "ffmpeg".to_string()
+}
+
+fn default_ffmpeg_profile() -> String {
+    "master".to_string()
+}
+
+// A region of the texture in pixels, from its top left corner
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct CropConfig {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+// Video recordings are either quick h264 previews or ProRes masters for delivery
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingQuality {
+    #[default]
+    Preview,
+    Master,
+}
+
+impl FrameRecorderConfig {
+    // ProRes 422 HQ for masters. Previews use the selected ffmpeg profile, or
+    // the built-in settings if it isn't defined, with `encoder` if it's set.
+    pub fn ffmpeg_config(&self) -> FfmpegConfig {
+        if self.quality == RecordingQuality::Master {
+            return FfmpegConfig::prores_hq();
+        }
+        let mut config = self.preview_profile();
+        if let Some(encoder) = &self.encoder {
+            config.codec = encoder.clone();
+        }
+        config
+    }
+
+    fn preview_profile(&self) -> FfmpegConfig {
+        match self.ffmpeg.get(&self.ffmpeg_profile) {
+            Some(ffmpeg) => ffmpeg.clone(),
+            None => {
+                if !self.ffmpeg.is_empty() {
+                    println!(
+                        "No ffmpeg profile named '{}', using defaults",
+                        self.ffmpeg_profile
+                    );
+                }
+                FfmpegConfig::default()
+            }
+        }
+    }
+}
+
+// Encoder settings for video recording, passed straight to ffmpeg
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FfmpegConfig {
+    pub codec: String,
+    pub crf: Option<u32>,
+    pub bitrate: Option<String>, // e.g. "20M"
+    pub preset: Option<String>,
+    pub pix_fmt: String,
+    pub extra_args: Vec<String>, // added just before the output file
+    pub extension: String,       // container of the output file
+}
+
+impl FfmpegConfig {
+    pub fn prores_hq() -> Self {
+        Self {
+            codec: "prores_ks".to_string(),
+            crf: None,
+            bitrate: None,
+            preset: None,
+            pix_fmt: "yuv422p10le".to_string(),
+            extra_args: ["-profile:v", "3", "-vendor", "apl0"] // 3 is 422 HQ
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect(),
+            extension: "mov".to_string(),
+        }
+    }
+
+    // Keeps the alpha channel, at 4444 quality
+    pub fn prores_4444() -> Self {
+        Self {
+            pix_fmt: "yuva444p10le".to_string(),
+            extra_args: ["-profile:v", "4", "-vendor", "apl0", "-alpha_bits", "16"]
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect(),
+            ..Self::prores_hq()
+        }
+    }
+
+    // QuickTime Animation: lossless with alpha, and large
+    pub fn qtrle() -> Self {
+        Self {
+            codec: "qtrle".to_string(),
+            crf: None,
+            bitrate: None,
+            preset: None,
+            pix_fmt: "argb".to_string(),
+            extra_args: Vec::new(),
+            extension: "mov".to_string(),
+        }
+    }
+}
+
+impl Default for FfmpegConfig {
+    fn default() -> Self {
+        Self {
+            codec: "libx264".to_string(),
+            crf: Some(10),
+            bitrate: None,
+            preset: Some("medium".to_string()),
+            pix_fmt: "yuv420p".to_string(),
+            extra_args: Vec::new(),
+            extension: "mp4".to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct StyleConfig {
     pub default_stroke_weight: f32,
     pub default_backbone_stroke_weight: f32,
+    #[serde(default)]
+    pub glow_layers: u32, // 0 turns glow off for new grids
+    #[serde(default)]
+    pub glow_spread: f32,
+    #[serde(default)]
+    pub glow_alpha: f32,
+    #[serde(default)]
+    pub type_weights: HashMap<String, f32>, // stroke weight multipliers by segment type name
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,11 +222,97 @@ pub struct SpeedConfig {
 pub struct PathConfig {
     pub project_file: String,
     pub output_directory: String,
+    #[serde(default = "default_presets_directory")]
+    pub presets_directory: String,
+    // How close segment endpoints must be to count as connected, in SVG units
+    #[serde(default = "default_connection_threshold")]
+    pub connection_threshold: f32,
+}
+
+fn default_presets_directory() -> String {
+    "presets".to_string()
+}
+
+fn default_connection_threshold() -> f32 {
+    DEFAULT_CONNECTION_THRESHOLD
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackgroundConfig {
+    #[serde(default)]
+    pub image: Option<String>, // PNG drawn behind all grids
+    #[serde(default = "default_image_opacity")]
+    pub image_opacity: f32,
+    #[serde(default)]
+    pub persistence: f32, // 0.0 clears every frame, up to 0.97 leaves trails
+}
+
+impl Default for BackgroundConfig {
+    fn default() -> Self {
+        Self {
+            image: None,
+            image_opacity: default_image_opacity(),
+            persistence: 0.0,
+        }
+    }
+}
+
+fn default_image_opacity() -> f32 {
+    1.0
+}
+
+// Named brand colors, so show control can send "palette:NAME:INDEX"
+// instead of RGBA floats
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Palettes(pub HashMap<String, Vec<PaletteColor>>);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PaletteColor {
+    pub name: String,
+    pub rgba: [f32; 4],
+}
+
+impl Palettes {
+    // `entry` is a position in the palette or one of its color names
+    pub fn resolve(&self, palette: &str, entry: &str) -> Result<Rgba, String> {
+        let colors = self
+            .0
+            .get(palette)
+            .ok_or_else(|| format!("Unknown palette '{}'", palette))?;
+
+        let color = match entry.parse::<usize>() {
+            Ok(index) => colors.get(index).ok_or_else(|| {
+                format!(
+                    "Palette '{}' has {} colors, no index {}",
+                    palette,
+                    colors.len(),
+                    index
+                )
+            })?,
+            Err(_) => colors
+                .iter()
+                .find(|color| color.name == entry)
+                .ok_or_else(|| format!("Palette '{}' has no color '{}'", palette, entry))?,
+        };
+
+        let [r, g, b, a] = color.rgba;
+        Ok(rgba(r, g, b, a))
+    }
+
+    // Palettes by name, in a stable order for listing
+    pub fn sorted(&self) -> Vec<(&String, &Vec<PaletteColor>)> {
+        let mut palettes: Vec<_> = self.0.iter().collect();
+        palettes.sort_by(|a, b| a.0.cmp(b.0));
+        palettes
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct OscConfig {
     pub rx_port: u16,
+    #[serde(default)]
+    pub feedback_port: Option<u16>, // where replies and notifications are sent
 }
 
 /************************* Animation Configs ********************/
@@ -54,6 +322,8 @@ pub struct AnimationConfig {
     pub power_off: PowerOffConfig,
     pub background_flash: BackgroundFlashConfig,
     pub transition: TransitionConfig,
+    #[serde(default)]
+    pub envelope: Option<EnvelopeConfig>, // the original flash when not set
 }
 
 #[derive(Debug, Deserialize)]
@@ -67,18 +337,35 @@ pub struct PowerOffConfig {
     pub fade_duration: f32,
 }
 
+// Segment power on/off shape for new grids, in seconds except sustain_level
+#[derive(Debug, Deserialize)]
+pub struct EnvelopeConfig {
+    pub attack: f32,
+    #[serde(default)]
+    pub hold: f32,
+    pub decay: f32,
+    pub sustain_level: f32, // 1.0 skips the flash
+    pub release: f32,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BackgroundFlashConfig {
     pub flash_duration: f32,
     pub fade_duration: f32,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TransitionConfig {
     pub steps: usize,        // Total number of frames to generate
     pub frame_duration: f32, // Time between frame changes
     pub wandering: f32,      // How much randomness in timing (0.0-1.0)
     pub density: f32,        // How many segments can change per frame (0.0-1.0)
+    #[serde(default)]
+    pub seed: Option<u64>, // Fixes the random choices so renders repeat exactly
+    #[serde(default)]
+    pub stroke_gap_steps: usize, // Empty steps between strokes in Writing transitions
+    #[serde(default)]
+    pub stagger: f32, // Max random delay in seconds before each segment turns on
 }
 
 #[derive(Debug, Clone)]
@@ -86,3 +373,30 @@ pub struct MovementConfig {
     pub duration: f32,
     pub easing: EasingType,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_resolves_by_index_and_name() {
+        let palettes: Palettes = toml::from_str(
+            r#"
+            brand = [
+                { name = "red", rgba = [1.0, 0.0, 0.0, 1.0] },
+                { name = "teal", rgba = [0.0, 0.5, 0.5, 1.0] },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(palettes.resolve("brand", "1"), Ok(rgba(0.0, 0.5, 0.5, 1.0)));
+        assert_eq!(
+            palettes.resolve("brand", "red"),
+            Ok(rgba(1.0, 0.0, 0.0, 1.0))
+        );
+        assert!(palettes.resolve("brand", "2").is_err());
+        assert!(palettes.resolve("brand", "blue").is_err());
+        assert!(palettes.resolve("other", "0").is_err());
+    }
+}
@@ -1,8 +1,11 @@
 pub mod config_load;
 pub mod config_types;
+pub mod presets;
 
 pub use config_load::Config;
 pub use config_types::{
-    AnimationConfig, FrameRecorderConfig, MovementConfig, OscConfig, PathConfig, RenderConfig,
-    SpeedConfig, StyleConfig, TransitionConfig, WindowConfig,
+    AnimationConfig, BackgroundConfig, CropConfig, EnvelopeConfig, FfmpegConfig,
+    FrameRecorderConfig, MovementConfig, OscConfig, PaletteColor, Palettes, PathConfig,
+    RecordingQuality, RenderConfig, SpeedConfig, StyleConfig, TransitionConfig, WindowConfig,
 };
+pub use presets::{load_presets, EffectDescriptor, EffectPreset, PresetColor};
@@ -0,0 +1,231 @@
+// src/config/presets.rs
+//
+// Effect presets: named bundles of effects applied to a grid in one go.
+// Each preset is a TOML file in the presets directory, named after the file,
+// holding a list of [[effect]] tables:
+//
+//   [[effect]]
+//   type = "backbone_fade"
+//   color = [0.1, 0.1, 0.3]
+//   duration = 2.0
+//
+//   [[effect]]
+//   type = "backbone_pulse"
+//   period = 4.0
+//   depth = 0.5
+//
+// A file with any mistake in it is rejected whole, so a preset never
+// applies halfway.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct EffectPreset {
+    pub effects: Vec<EffectDescriptor>,
+}
+
+// The file as written, each effect kept with where it sits in the file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPreset {
+    #[serde(default)]
+    effect: Vec<toml::Spanned<toml::Table>>,
+}
+
+// One effect and its parameters, the same ones its OSC command takes
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum EffectDescriptor {
+    BackboneFade {
+        color: PresetColor,
+        duration: f32,
+    },
+    BackbonePulse {
+        period: f32,
+        depth: f32,
+    },
+    BackboneStrobe {
+        color: PresetColor,
+        hz: f32,
+        duty: f32,
+        duration: f32,
+    },
+    BackboneStroke {
+        stroke_weight: f32,
+    },
+    GlyphColor {
+        color: PresetColor,
+        #[serde(default)]
+        duration: f32, // 0 changes it instantly
+    },
+    GlyphPulse {
+        period: f32,
+        depth: f32,
+    },
+    Colorful {
+        enabled: bool,
+    },
+    PowerEffect {
+        enabled: bool,
+    },
+    Rainbow {
+        rate: f32,
+        spread: f32,
+    },
+    Flicker {
+        rate: f32,
+        depth: f32,
+        duration: f32,
+    },
+    NoiseColor {
+        scale: f32,
+        speed: f32,
+        range: f32,
+    },
+    Sparkle {
+        rate: f32,
+        lifetime: f32,
+        color: PresetColor,
+    },
+    Glow {
+        layers: u32,
+        spread: f32,
+        alpha: f32,
+    },
+    Envelope {
+        attack: f32,
+        #[serde(default)]
+        hold: f32,
+        decay: f32,
+        sustain_level: f32,
+        release: f32,
+    },
+    Dimmer {
+        level: f32,
+    },
+}
+
+// An [r, g, b] or [r, g, b, a] color
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "Vec<f32>")]
+pub struct PresetColor(pub [f32; 4]);
+
+impl TryFrom<Vec<f32>> for PresetColor {
+    type Error = String;
+
+    fn try_from(channels: Vec<f32>) -> Result<Self, Self::Error> {
+        match channels[..] {
+            [r, g, b] => Ok(PresetColor([r, g, b, 1.0])),
+            [r, g, b, a] => Ok(PresetColor([r, g, b, a])),
+            _ => Err(format!(
+                "color needs 3 or 4 numbers, got {}",
+                channels.len()
+            )),
+        }
+    }
+}
+
+impl EffectPreset {
+    // Errors carry the line of the mistake. TOML syntax errors point at it
+    // directly; a bad effect points at the [[effect]] table it's in.
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let raw: RawPreset = toml::from_str(content).map_err(|err| err.to_string())?;
+
+        let mut effects = Vec::with_capacity(raw.effect.len());
+        for table in raw.effect {
+            let line = content[..table.span().start].matches('\n').count() + 1;
+            let effect = EffectDescriptor::deserialize(table.into_inner())
+                .map_err(|err| format!("effect at line {}: {}", line, err.message()))?;
+            effects.push(effect);
+        }
+        Ok(EffectPreset { effects })
+    }
+}
+
+// Loads every .toml file in `dir` into `presets`, keyed by file name.
+// A file that fails to load is reported and keeps whatever version of that
+// preset was loaded before, so a bad edit mid-show doesn't lose it.
+pub fn load_presets(dir: &Path, presets: &mut HashMap<String, EffectPreset>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            println!("No presets loaded from {}: {}", dir.display(), err);
+            return;
+        }
+    };
+
+    let mut loaded = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("toml") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        let result = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| EffectPreset::from_toml(&content));
+        match result {
+            Ok(preset) => {
+                presets.insert(name.to_string(), preset);
+                loaded += 1;
+            }
+            Err(err) => eprintln!("Preset {} failed to load:\n{}", path.display(), err),
+        }
+    }
+    println!("Loaded {} presets from {}", loaded, dir.display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_parses_and_rejects_mistakes_whole() {
+        let preset = EffectPreset::from_toml(
+            r#"
+            [[effect]]
+            type = "backbone_fade"
+            color = [0.1, 0.1, 0.3]
+            duration = 2.0
+
+            [[effect]]
+            type = "colorful"
+            enabled = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            preset.effects,
+            vec![
+                EffectDescriptor::BackboneFade {
+                    color: PresetColor([0.1, 0.1, 0.3, 1.0]),
+                    duration: 2.0,
+                },
+                EffectDescriptor::Colorful { enabled: true },
+            ]
+        );
+
+        // a misspelled parameter in the second effect rejects the lot
+        let err = EffectPreset::from_toml(
+            r#"
+            [[effect]]
+            type = "backbone_pulse"
+            period = 4.0
+            depth = 0.5
+
+            [[effect]]
+            type = "rainbow"
+            rate = 0.1
+            sprad = 0.5
+            "#,
+        )
+        .unwrap_err();
+        assert!(err.contains("line 7"), "{}", err);
+    }
+}
@@ -0,0 +1,204 @@
+// src/controllers/automation.rs
+//
+// Timed OSC messages read from a text file, for offline renders. Each line is
+// a time in seconds, an address and its arguments:
+//
+//   # time  address        args
+//   0.0     /grid/create   g1 hangeul 0.0 0.0 0.0
+//   1.5     /grid/glyph    g1 3 "random"
+//
+// Numbers with a decimal point are floats, other numbers ints, and anything
+// else a string. Quote a string that holds spaces or looks like a number.
+
+use nannou_osc as osc;
+use std::fs;
+use std::path::Path;
+
+pub struct Automation {
+    events: Vec<(f32, osc::Message)>, // in time order
+    next: usize,
+}
+
+impl Automation {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("Can't read {}: {}", path.display(), err))?;
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut events = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let event = parse_line(line).map_err(|err| format!("line {}: {}", index + 1, err))?;
+            events.push(event);
+        }
+        // stable, so messages at the same time keep their file order
+        events.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Self { events, next: 0 })
+    }
+
+    // The messages due by `time` that haven't been handed out yet
+    pub fn due(&mut self, time: f32) -> Vec<osc::Message> {
+        let mut messages = Vec::new();
+        while let Some((event_time, message)) = self.events.get(self.next) {
+            if *event_time > time {
+                break;
+            }
+            messages.push(message.clone());
+            self.next += 1;
+        }
+        messages
+    }
+
+    // How many messages not handed out yet have `name` as their first
+    // argument, which is the grid for /grid messages
+    pub fn pending_for(&self, name: &str) -> usize {
+        self.events[self.next..]
+            .iter()
+            .filter(|(_, message)| {
+                matches!(message.args.first(), Some(osc::Type::String(first)) if first == name)
+            })
+            .count()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+fn parse_line(line: &str) -> Result<(f32, osc::Message), String> {
+    let tokens = tokenize(line)?;
+    let [(time, false), (addr, false), args @ ..] = &tokens[..] else {
+        return Err("expected a time and an address".to_string());
+    };
+    let time: f32 = time
+        .parse()
+        .map_err(|_| format!("'{}' isn't a time", time))?;
+    if !addr.starts_with('/') {
+        return Err(format!("'{}' isn't an OSC address", addr));
+    }
+
+    let args = args
+        .iter()
+        .map(|(token, quoted)| {
+            if *quoted {
+                osc::Type::String(token.clone())
+            } else if let Ok(int) = token.parse::<i32>() {
+                osc::Type::Int(int)
+            } else if let Ok(float) = token.parse::<f32>() {
+                osc::Type::Float(float)
+            } else {
+                osc::Type::String(token.clone())
+            }
+        })
+        .collect();
+
+    Ok((
+        time,
+        osc::Message {
+            addr: addr.clone(),
+            args,
+        },
+    ))
+}
+
+// Splits on whitespace, keeping "quoted strings" whole. Each token comes with
+// whether it was quoted.
+fn tokenize(line: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut token = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => token.push(c),
+                    None => return Err("unclosed quote".to_string()),
+                }
+            }
+            tokens.push((token, true));
+        } else if c == '#' {
+            break; // comment to the end of the line
+        } else {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+            tokens.push((token, false));
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_automation_parses_and_releases_in_time_order() {
+        let mut automation = Automation::parse(
+            r#"
+            # a comment
+            2.0  /grid/glyph g1 3 "random"
+            0.0  /grid/create g1 hangeul 0.0 10 "1.5"  # trailing comment
+            "#,
+        )
+        .unwrap();
+        assert_eq!(automation.len(), 2);
+
+        let first = automation.due(1.0);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].addr, "/grid/create");
+        assert_eq!(
+            first[0].args,
+            vec![
+                osc::Type::String("g1".to_string()),
+                osc::Type::String("hangeul".to_string()),
+                osc::Type::Float(0.0),
+                osc::Type::Int(10),
+                osc::Type::String("1.5".to_string()),
+            ]
+        );
+        assert!(automation.due(1.5).is_empty());
+        assert_eq!(automation.due(2.0)[0].addr, "/grid/glyph");
+
+        let err = Automation::parse("0.0 /grid/glyph\nsoon /grid/glyph")
+            .err()
+            .unwrap();
+        assert!(err.starts_with("line 2"), "{}", err);
+    }
+
+    #[test]
+    fn test_pending_messages_are_counted_by_grid() {
+        let mut automation = Automation::parse(
+            r#"
+            0.0  /grid/create g1 hangeul 0.0 0.0 0.0
+            1.0  /grid/glyph g1 3 "random"
+            2.0  /grid/glyph g2 3 "random"
+            3.0  /grid/show g1
+            "#,
+        )
+        .unwrap();
+        assert_eq!(automation.pending_for("g1"), 3);
+        automation.due(1.0);
+        assert_eq!(automation.pending_for("g1"), 1);
+        assert_eq!(automation.pending_for("g2"), 1);
+        assert_eq!(automation.pending_for("hangeul"), 0);
+    }
+}
@@ -1,4 +1,8 @@
 // src/controllers/mod.rs
 
+pub mod automation;
+pub mod mouse;
 pub mod osc;
-pub use osc::{OscCommand, OscController, OscSender};
+pub use automation::Automation;
+pub use mouse::MouseEditor;
+pub use osc::{AnimationArgs, ColorArg, OscCommand, OscController, OscSender};
@@ -0,0 +1,256 @@
+// src/controllers/mouse.rs
+//
+// Edit mode for designing glyphs on the live output. Clicking a segment
+// toggles it in a scratch set for the grid under the cursor, which is shown
+// on that grid right away. Shift-dragging moves a grid and the scroll wheel
+// scales it.
+//
+// Points here are in scene units, where the grids live. window_to_scene()
+// brings the window's mouse positions there.
+
+use crate::models::{data_model::Glyph, SegmentKey};
+use crate::views::GridInstance;
+use nannou::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+// How far from a segment a click still picks it, in scene units
+pub const CLICK_TOLERANCE: f32 = 8.0;
+
+// Scale change per line the scroll wheel turns
+const SCROLL_SCALE_STEP: f32 = 1.1;
+
+#[derive(Default)]
+pub struct MouseEditor {
+    enabled: bool,
+    scratch: HashMap<String, BTreeSet<SegmentKey>>, // grid name -> segments toggled on
+    drag: Option<(String, Point2)>,                 // grid being dragged, last point
+}
+
+impl MouseEditor {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // Returns whether edit mode is now on. Scratch sets are kept either way.
+    pub fn toggle(&mut self) -> bool {
+        self.enabled = !self.enabled;
+        self.drag = None;
+        self.enabled
+    }
+
+    // Flips a segment in the grid's scratch set and returns the set. A grid's
+    // first edit starts from `lit`, what it's showing at the time.
+    pub fn toggle_segment(
+        &mut self,
+        grid: &str,
+        segment: &SegmentKey,
+        lit: impl FnOnce() -> BTreeSet<SegmentKey>,
+    ) -> &BTreeSet<SegmentKey> {
+        let scratch = self.scratch.entry(grid.to_string()).or_insert_with(lit);
+        if !scratch.remove(segment) {
+            scratch.insert(segment.clone());
+        }
+        scratch
+    }
+
+    pub fn scratch(&self, grid: &str) -> Option<&BTreeSet<SegmentKey>> {
+        self.scratch.get(grid)
+    }
+
+    // A grid's scratch set in the project file's glyph format
+    pub fn scratch_glyph(&self, grid: &str, name: &str) -> Option<Glyph> {
+        self.scratch.get(grid).map(|segments| Glyph {
+            name: name.to_string(),
+            segments: segments.iter().map(SegmentKey::to_string).collect(),
+        })
+    }
+
+    // Keeps a renamed grid's scratch set and drag
+    pub fn rename_grid(&mut self, grid: &str, new_name: &str) {
+        if let Some(scratch) = self.scratch.remove(grid) {
+            self.scratch.insert(new_name.to_string(), scratch);
+        }
+        if let Some((dragged, _)) = self.drag.as_mut().filter(|(dragged, _)| dragged == grid) {
+            *dragged = new_name.to_string();
+        }
+    }
+
+    pub fn start_drag(&mut self, grid: &str, point: Point2) {
+        self.drag = Some((grid.to_string(), point));
+    }
+
+    // The grid being dragged and how far it moved since the last point
+    pub fn drag_to(&mut self, point: Point2) -> Option<(&str, Vec2)> {
+        let (grid, last) = self.drag.as_mut()?;
+        let offset = point - *last;
+        *last = point;
+        Some((grid.as_str(), offset))
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+}
+
+// The texture fills the window, stretched to its shape, and the scene is
+// drawn to the texture at `scale_factor` pixels per unit. Both are centered,
+// so only the sizes matter.
+pub fn window_to_scene(
+    point: Point2,
+    window_size: Vec2,
+    texture_size: Vec2,
+    scale_factor: f32,
+) -> Point2 {
+    point * texture_size / (window_size * scale_factor)
+}
+
+// The factor one scroll event scales a grid by. Pixel deltas, from
+// touchpads, count a line per 20 pixels.
+pub fn scroll_scale_factor(delta: MouseScrollDelta) -> f32 {
+    let lines = match delta {
+        MouseScrollDelta::LineDelta(_, y) => y,
+        MouseScrollDelta::PixelDelta(position) => position.y as f32 / 20.0,
+    };
+    SCROLL_SCALE_STEP.powf(lines)
+}
+
+// The segment under `point` on the topmost visible grid that has one there.
+// Grids are drawn in the order given, so the last one with a hit is on top.
+pub fn topmost_hit<'a>(
+    grids: impl IntoIterator<Item = &'a GridInstance>,
+    point: Point2,
+    tolerance: f32,
+) -> Option<(&'a str, SegmentKey)> {
+    grids
+        .into_iter()
+        .filter(|grid| grid.is_visible)
+        .filter_map(|grid| {
+            grid.segment_at(point, tolerance)
+                .map(|segment| (grid.id.as_str(), segment))
+        })
+        .last()
+}
+
+// The topmost visible grid whose bounding box holds `point`
+pub fn topmost_grid<'a>(
+    grids: impl IntoIterator<Item = &'a GridInstance>,
+    point: Point2,
+) -> Option<&'a str> {
+    grids
+        .into_iter()
+        .filter(|grid| grid.is_visible && grid.bounding_box().contains(point))
+        .map(|grid| grid.id.as_str())
+        .last()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Project;
+    use crate::services::SegmentGraph;
+    use crate::views::CachedGrid;
+    use std::rc::Rc;
+
+    fn grid_at(name: &str, project: &Project, position: Point2) -> GridInstance {
+        let grid = CachedGrid::new(project);
+        let graph = Rc::new(SegmentGraph::new(&grid));
+        let mut grid = GridInstance::new(
+            name.to_string(),
+            project,
+            "",
+            &grid,
+            graph,
+            position,
+            0.0,
+            10.0,
+            5.0,
+        );
+        grid.is_visible = true;
+        grid
+    }
+
+    #[test]
+    fn test_window_points_land_on_the_scene() {
+        let window = vec2(800.0, 600.0);
+        let texture = vec2(3200.0, 1200.0);
+        // the window's corner is the texture's, whatever the stretch
+        assert_eq!(
+            window_to_scene(pt2(400.0, -300.0), window, texture, 2.0),
+            pt2(800.0, -300.0)
+        );
+        assert_eq!(
+            window_to_scene(pt2(0.0, 0.0), window, texture, 2.0),
+            pt2(0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_clicks_toggle_the_scratch_set() {
+        let mut editor = MouseEditor::default();
+        assert!(editor.toggle());
+
+        let arc = SegmentKey::new((1, 1), "arc-1");
+        let line = SegmentKey::new((1, 1), "hor-1-1");
+        let lit = || BTreeSet::from([arc.clone()]);
+        editor.toggle_segment("g1", &line, lit);
+        let set = editor.toggle_segment("g1", &arc, || unreachable!());
+        assert_eq!(set, &BTreeSet::from([line.clone()]));
+
+        let glyph = editor.scratch_glyph("g1", "draft").unwrap();
+        assert_eq!(glyph.segments, ["1,1 : hor-1-1"]);
+        assert!(editor.scratch_glyph("g2", "draft").is_none());
+    }
+
+    #[test]
+    fn test_renamed_grids_keep_their_edits() {
+        let mut editor = MouseEditor::default();
+        let arc = SegmentKey::new((1, 1), "arc-1");
+        editor.toggle_segment("g1", &arc, BTreeSet::new);
+        editor.start_drag("g1", pt2(0.0, 0.0));
+
+        editor.rename_grid("g1", "title");
+        assert!(editor.scratch("g1").is_none());
+        assert_eq!(editor.scratch("title"), Some(&BTreeSet::from([arc])));
+        assert_eq!(
+            editor.drag_to(pt2(1.0, 0.0)),
+            Some(("title", vec2(1.0, 0.0)))
+        );
+    }
+
+    #[test]
+    fn test_drags_report_each_step() {
+        let mut editor = MouseEditor::default();
+        assert!(editor.drag_to(pt2(5.0, 5.0)).is_none());
+
+        editor.start_drag("g1", pt2(0.0, 0.0));
+        assert_eq!(
+            editor.drag_to(pt2(10.0, 5.0)),
+            Some(("g1", vec2(10.0, 5.0)))
+        );
+        assert_eq!(
+            editor.drag_to(pt2(12.0, 0.0)),
+            Some(("g1", vec2(2.0, -5.0)))
+        );
+        editor.end_drag();
+        assert!(editor.drag_to(pt2(20.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_the_grid_drawn_last_wins() {
+        let project = Project::load("projects/debug.json").unwrap();
+        // overlapping, with the second 100 to the right
+        let grids = [
+            grid_at("under", &project, pt2(0.0, 0.0)),
+            grid_at("over", &project, pt2(100.0, 0.0)),
+        ];
+
+        // the left edge is only the first grid's
+        let (grid, _) = topmost_hit(&grids, pt2(-200.0, 10.0), 1.0).unwrap();
+        assert_eq!(grid, "under");
+        let (grid, _) = topmost_hit(&grids, pt2(0.0, 10.0), 1.0).unwrap();
+        assert_eq!(grid, "over");
+        assert_eq!(topmost_grid(&grids, pt2(-150.0, 0.0)), Some("under"));
+        assert_eq!(topmost_grid(&grids, pt2(50.0, 0.0)), Some("over"));
+        assert_eq!(topmost_grid(&grids, pt2(1000.0, 0.0)), None);
+    }
+}
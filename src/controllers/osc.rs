@@ -1,36 +1,283 @@
 // src/controllers/osc/mod.rs
 // OSC Controller
 
+use crate::animation::TransitionAnimationType;
+use crate::config::TransitionConfig;
+use crate::services::RecorderStatus;
 use nannou_osc as osc;
 use std::error::Error;
 
+// Parameters for the transition animation types that take them
+#[derive(Debug, Default, Clone)]
+pub struct AnimationArgs {
+    pub origin: Option<(f32, f32)>, // grid-local origin for Ripple
+    pub axis: Option<String>,       // sweep axis for Scanline
+    pub reverse: bool,              // sweep Scanline from the far side
+    pub seed: Option<String>,       // segment id Flood starts from
+}
+
+// A color sent as floats, or as a "palette:NAME:INDEX" reference that is
+// looked up in the configured palettes when the command runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorArg {
+    Rgba(f32, f32, f32, f32),
+    Palette { palette: String, entry: String },
+}
+
 #[derive(Debug)]
 pub enum OscCommand {
     RecorderStart {},
     RecorderStop {},
+    RecorderPause {},
+    RecorderResume {},
+    RecorderCrop {
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+    },
+    RecorderCropOff {},
+    RecorderScreenshot {},
+    RecorderStatus {},
+    RecorderSaveback {
+        seconds: f32,
+        continue_recording: bool,
+    },
     GridBackboneFade {
+        name: String,
+        color: ColorArg,
+        duration: f32,
+    },
+    GridBackboneStrobe {
         name: String,
         r: f32,
         g: f32,
         b: f32,
-        a: f32,
+        hz: f32,
+        duty: f32,
         duration: f32,
     },
+    GridBackbonePulse {
+        name: String,
+        period: f32,
+        depth: f32,
+    },
+    GridBackbonePulseStop {
+        name: String,
+    },
+    GridSparkle {
+        name: String,
+        rate: f32,
+        lifetime: f32,
+        color: ColorArg, // alpha is 1.0 when sent as floats
+    },
+    GridSparkleStop {
+        name: String,
+    },
+    GridChase {
+        name: String,
+        start: Option<String>, // None picks a random segment
+        end: Option<String>,
+        speed: f32,
+        width: i32,
+    },
+    GridChaseStop {
+        name: String,
+    },
+    GridHeatmap {
+        name: String,
+        enabled: bool,
+        decay: Option<f32>, // None keeps the grid's current decay time
+    },
+    GridPreset {
+        name: String,
+        preset: String,
+    },
+    GridComponent {
+        name: String,
+        segment_id: String,
+        on: bool,
+    },
+    GridTag {
+        name: String,
+        tag: String,
+        on: bool,
+        color: Option<ColorArg>, // sets the tagged segments' overrides when turning on
+    },
+    GridTags {
+        name: String,
+    },
+    GridTileMask {
+        name: String,
+        tile: (u32, u32),
+        visible: bool,
+    },
+    GridTileMaskRect {
+        name: String,
+        from: (u32, u32),
+        to: (u32, u32),
+        visible: bool,
+    },
+    GridTileMaskClear {
+        name: String,
+    },
+    PresetReload {},
+    GraphRebuild {},
+    DebugGraph {
+        name: String,
+        enabled: bool,
+        labels: bool, // node degree labels
+    },
     GridBackboneStroke {
         name: String,
         stroke_weight: f32,
     },
+    GridBackboneVisible {
+        name: String,
+        visible: bool,
+        duration: f32, // of the fade out when hiding, 0.0 hides at once
+    },
+    GridTypeWeight {
+        name: String,
+        segment_type: String, // a SegmentType name, or "arc" or "diagonal" for all of them
+        multiplier: f32,
+    },
     GridCreate {
         name: String,
         show: String,
         position: (f32, f32),
         rotation: f32,
     },
+    GridDestroy {
+        name: String,
+    },
+    GridRename {
+        name: String,
+        new_name: String,
+    },
+    GridArray {
+        base_name: String, // grids are named <base_name>_<row>_<col>, from 1
+        show: String,
+        cols: i32,
+        rows: i32,
+        spacing: (f32, f32), // between grid centers, in grid widths and heights
+        origin: (f32, f32),  // center of the whole array
+    },
     GridMove {
         name: String,
         x: f32,
         y: f32,
         duration: f32,
+        easing: Option<String>,
+        clear_queue: bool,
+    },
+    GridMoveQueue {
+        name: String,
+        legs: Vec<(f32, f32, f32, Option<String>)>, // x, y, duration, easing
+    },
+    GridKeyframes {
+        name: String,
+        keyframes: Vec<(f32, f32, f32, f32, f32, String)>, // time, x, y, rotation, scale, easing
+    },
+    GridOrbit {
+        name: String,
+        center: (f32, f32),
+        degrees_per_second: f32,
+        duration: f32, // <= 0 orbits until stopped
+        face_path: bool,
+        radius: Option<f32>,
+    },
+    GridOrbitStop {
+        name: String,
+    },
+    GridMovePath {
+        name: String,
+        duration: f32,
+        waypoints: Vec<(f32, f32)>,
+    },
+    GridJitter {
+        name: String,
+        amplitude: f32,
+        frequency: f32,
+        duration: f32,
+    },
+    GridFlicker {
+        name: String,
+        rate: f32,
+        depth: f32,
+        duration: f32,
+    },
+    GridEnvelope {
+        name: String,
+        attack: f32,
+        decay: f32,
+        sustain_level: f32,
+        release: f32,
+        hold: f32,
+    },
+    GridNoiseColor {
+        name: String,
+        scale: f32,
+        speed: f32,
+        range: f32, // hue degrees either way, 0 turns it off
+    },
+    GridDimmer {
+        name: String,
+        level: f32,
+    },
+    MasterBrightness {
+        level: f32,
+    },
+    MasterBlackout {
+        enabled: bool,
+    },
+    GridGlow {
+        name: String,
+        layers: i32,
+        spread: f32,
+        alpha: f32,
+    },
+    GridGlyphColorFade {
+        name: String,
+        color: ColorArg,
+        duration: f32,
+    },
+    GridGradient {
+        name: String,
+        color_a: (f32, f32, f32, f32),
+        color_b: (f32, f32, f32, f32),
+        axis: String,
+    },
+    GridGradientOff {
+        name: String,
+    },
+    GridOutline {
+        name: String,
+        color: (f32, f32, f32, f32),
+        extra_weight: f32,
+    },
+    GridOutlineOff {
+        name: String,
+    },
+    GridBands {
+        name: String,
+        axis: String,
+        width: i32,
+        color_a: (f32, f32, f32),
+        color_b: (f32, f32, f32),
+    },
+    GridBandsOff {
+        name: String,
+    },
+    GridRainbow {
+        name: String,
+        rate: f32,
+        spread: f32,
+    },
+    GridPulse {
+        name: String,
+        period: f32,
+        depth: f32,
     },
     GridRotate {
         name: String,
@@ -40,50 +287,108 @@ pub enum OscCommand {
         name: String,
         scale: f32,
     },
+    GridScaleXY {
+        name: String,
+        scale: (f32, f32), // along the grid's own x and y axes
+    },
     GridSlide {
         name: String,
         axis: String,
         number: i32,
         position: f32,
+        duration: Option<f32>,
+        easing: Option<String>,
+        wrap: bool,
+    },
+    GridSlideClamp {
+        name: String,
+        enabled: bool,
+    },
+    GridSlideReset {
+        name: String,
+        duration: f32,
+        easing: Option<String>,
+    },
+    GridGet {
+        name: String,
+    },
+    GridSlideState {
+        name: String,
+    },
+    GridSnapshot {
+        name: String,
+        slot: String,
+    },
+    GridRestore {
+        name: String,
+        slot: String,
+    },
+    GridCenter {
+        name: String,
+    },
+    GridHitTest {
+        name: String,
+        point: (f32, f32),
+        tolerance: Option<f32>,
     },
     BackgroundFlash {
-        r: f32,
-        g: f32,
-        b: f32,
+        color: ColorArg, // alpha is ignored
         duration: f32,
     },
     BackgroundColorFade {
-        r: f32,
-        g: f32,
-        b: f32,
+        color: ColorArg, // alpha is ignored
         duration: f32,
     },
+    BackgroundPersistence {
+        persistence: f32,
+    },
+    BackgroundImage {
+        path: String, // empty clears the image
+        opacity: Option<f32>,
+    },
     GridGlyph {
         grid_name: String,
         glyph_index: usize,
         animation_type_msg: i32,
+        animation_args: AnimationArgs,
     },
     GridInstantGlyphColor {
         grid_name: String,
-        r: f32,
-        g: f32,
-        b: f32,
-        a: f32,
+        color: ColorArg,
     },
     GridNextGlyph {
         grid_name: String,
         animation_type_msg: i32,
+        animation_args: AnimationArgs,
     },
     GridNextGlyphColor {
         grid_name: String,
-        r: f32,
-        g: f32,
-        b: f32,
-        a: f32,
+        color: ColorArg,
+    },
+    GridSegmentColor {
+        grid_name: String,
+        segment_id: String,
+        color: ColorArg,
+    },
+    GridSegmentColorClear {
+        grid_name: String,
+        segment_id: Option<String>,
     },
     GridNoGlyph {
         grid_name: String,
         animation_type_msg: i32,
+        animation_args: AnimationArgs,
+    },
+    GridLoop {
+        grid_name: String,
+        index_a: usize,
+        index_b: usize,
+        hold_time: f32,
+        animation_type_msg: i32,
+        animation_args: AnimationArgs,
+    },
+    GridLoopStop {
+        grid_name: String,
     },
     GridOverwrite {
         grid_name: String,
@@ -102,13 +407,40 @@ pub enum OscCommand {
         grid_name: String,
         setting: bool,
     },
+    GridColorfulMode {
+        grid_name: String,
+        mode: String, // "uniform" or "persegment"
+    },
     GridSetPowerEffect {
         grid_name: String,
         setting: bool,
     },
+    GridSetReverseErase {
+        grid_name: String,
+        setting: bool,
+    },
+    GridSetStrokeTint {
+        grid_name: String,
+        setting: bool,
+    },
+    GridSetCrossfade {
+        grid_name: String,
+        setting: bool,
+    },
     GridTransitionTrigger {
         grid_name: String,
     },
+    GridTransitionPause {
+        grid_name: String,
+        paused: bool,
+    },
+    GridSpeed {
+        grid_name: String,
+        speed: f32,
+    },
+    GridTransitionProgress {
+        grid_name: String,
+    },
     GridTransitionAuto {
         grid_name: String,
     },
@@ -118,12 +450,17 @@ pub enum OscCommand {
         frame_duration: Option<f32>,
         wandering: Option<f32>,
         density: Option<f32>,
+        stroke_gap_steps: Option<usize>,
     },
+    TransitionSeed {
+        seed: Option<u64>, // None returns to an unseeded rng
+    },
+    PaletteList {},
 }
 
 pub struct OscController {
     command_queue: Vec<OscCommand>,
-    receiver: osc::Receiver,
+    receiver: Option<osc::Receiver>, // None when rendering offline
 }
 
 impl OscController {
@@ -132,279 +469,1146 @@ impl OscController {
 
         Ok(Self {
             command_queue: Vec::new(),
-            receiver,
+            receiver: Some(receiver),
         })
     }
 
+    // Doesn't listen on the network. Messages only come in through handle_message().
+    pub fn offline() -> Self {
+        Self {
+            command_queue: Vec::new(),
+            receiver: None,
+        }
+    }
+
     pub fn process_messages(&mut self) {
-        for (packet, _addr) in self.receiver.try_iter() {
-            for message in packet.into_msgs() {
-                match message.addr.as_str() {
-                    "/recorder/start" => {
-                        self.command_queue.push(OscCommand::RecorderStart {});
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        let messages: Vec<osc::Message> = receiver
+            .try_iter()
+            .flat_map(|(packet, _addr)| packet.into_msgs())
+            .collect();
+        for message in messages {
+            self.handle_message(message);
+        }
+    }
+
+    // Checks one message and queues its command.
+    //
+    // Queries like /grid/get and /grid/slidestate are answered on their own
+    // address. A reply always carries arguments the query doesn't, so the
+    // query patterns below never match one, and replies that loop back to
+    // this port are ignored.
+    pub fn handle_message(&mut self, message: osc::Message) {
+        match message.addr.as_str() {
+            "/recorder/start" => {
+                self.command_queue.push(OscCommand::RecorderStart {});
+            }
+            "/recorder/stop" => {
+                self.command_queue.push(OscCommand::RecorderStop {});
+            }
+            "/recorder/pause" => {
+                self.command_queue.push(OscCommand::RecorderPause {});
+            }
+            "/recorder/resume" => {
+                self.command_queue.push(OscCommand::RecorderResume {});
+            }
+            "/recorder/crop" => {
+                if let [osc::Type::Int(x), osc::Type::Int(y), osc::Type::Int(w), osc::Type::Int(h)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::RecorderCrop {
+                        x: *x,
+                        y: *y,
+                        w: *w,
+                        h: *h,
+                    });
+                }
+            }
+            "/recorder/cropoff" => {
+                self.command_queue.push(OscCommand::RecorderCropOff {});
+            }
+            "/recorder/screenshot" => {
+                self.command_queue.push(OscCommand::RecorderScreenshot {});
+            }
+            "/recorder/status" => {
+                self.command_queue.push(OscCommand::RecorderStatus {});
+            }
+            "/recorder/saveback" => {
+                if let [osc::Type::Float(seconds), options @ ..] = &message.args[..] {
+                    // optional flag to keep recording from where the replay ends
+                    let continue_recording = match options {
+                        [] => false,
+                        [osc::Type::Int(flag)] => *flag != 0,
+                        _ => {
+                            println!("/recorder/saveback options are [continue]");
+                            false
+                        }
+                    };
+                    self.command_queue.push(OscCommand::RecorderSaveback {
+                        seconds: *seconds,
+                        continue_recording,
+                    });
+                }
+            }
+            "/grid/backbone_fade" => {
+                if let [osc::Type::String(name), rest @ ..] = &message.args[..] {
+                    if let Some((color, [osc::Type::Float(duration)])) = color_arg(rest, 4) {
+                        self.command_queue.push(OscCommand::GridBackboneFade {
+                            name: name.clone(),
+                            color,
+                            duration: *duration,
+                        });
                     }
-                    "/recorder/stop" => {
-                        self.command_queue.push(OscCommand::RecorderStop {});
+                }
+            }
+            "/grid/sparkle" => {
+                if let [osc::Type::String(name), osc::Type::Float(rate), osc::Type::Float(lifetime), rest @ ..] =
+                    &message.args[..]
+                {
+                    if let Some((color, [])) = color_arg(rest, 3) {
+                        self.command_queue.push(OscCommand::GridSparkle {
+                            name: name.clone(),
+                            rate: *rate,
+                            lifetime: *lifetime,
+                            color,
+                        });
                     }
-                    "/grid/backbone_fade" => {
-                        if let [osc::Type::String(name), osc::Type::Float(r), osc::Type::Float(g), osc::Type::Float(b), osc::Type::Float(a), osc::Type::Float(duration)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridBackboneFade {
-                                name: name.clone(),
-                                r: *r,
-                                g: *g,
-                                b: *b,
-                                a: *a,
-                                duration: *duration,
-                            });
-                        }
+                }
+            }
+            "/grid/sparklestop" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridSparkleStop { name: name.clone() });
+                }
+            }
+            "/grid/chase" => {
+                if let [osc::Type::String(name), osc::Type::String(start), osc::Type::String(end), osc::Type::Float(speed), osc::Type::Int(width)] =
+                    &message.args[..]
+                {
+                    let endpoint = |id: &String| (id != "random").then(|| id.clone());
+                    self.command_queue.push(OscCommand::GridChase {
+                        name: name.clone(),
+                        start: endpoint(start),
+                        end: endpoint(end),
+                        speed: *speed,
+                        width: *width,
+                    });
+                }
+            }
+            "/grid/chasestop" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridChaseStop { name: name.clone() });
+                }
+            }
+            "/grid/heatmap" => {
+                if let [osc::Type::String(name), osc::Type::Int(enabled), options @ ..] =
+                    &message.args[..]
+                {
+                    let decay = match options {
+                        [osc::Type::Float(decay), ..] => Some(*decay),
+                        _ => None,
+                    };
+                    self.command_queue.push(OscCommand::GridHeatmap {
+                        name: name.clone(),
+                        enabled: *enabled != 0,
+                        decay,
+                    });
+                }
+            }
+            "/grid/preset" => {
+                if let [osc::Type::String(name), osc::Type::String(preset)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridPreset {
+                        name: name.clone(),
+                        preset: preset.clone(),
+                    });
+                }
+            }
+            "/grid/component" => {
+                if let [osc::Type::String(name), osc::Type::String(segment_id), state] =
+                    &message.args[..]
+                {
+                    let on = match state {
+                        osc::Type::String(state) if state == "on" => Some(true),
+                        osc::Type::String(state) if state == "off" => Some(false),
+                        osc::Type::Int(state) => Some(*state != 0),
+                        _ => None,
+                    };
+                    if let Some(on) = on {
+                        self.command_queue.push(OscCommand::GridComponent {
+                            name: name.clone(),
+                            segment_id: segment_id.clone(),
+                            on,
+                        });
                     }
-                    "/grid/backbone_stroke" => {
-                        if let [osc::Type::String(name), osc::Type::Float(stroke_weight)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridBackboneStroke {
-                                name: name.clone(),
-                                stroke_weight: *stroke_weight,
-                            });
-                        }
+                }
+            }
+            "/grid/tag" => {
+                if let [osc::Type::String(name), osc::Type::String(tag), state, rest @ ..] =
+                    &message.args[..]
+                {
+                    let on = match state {
+                        osc::Type::String(state) if state == "on" => Some(true),
+                        osc::Type::String(state) if state == "off" => Some(false),
+                        osc::Type::Int(state) => Some(*state != 0),
+                        _ => None,
+                    };
+                    let color = match rest {
+                        [] => Some(None),
+                        rest => match color_arg(rest, 4) {
+                            Some((color, [])) => Some(Some(color)),
+                            _ => None,
+                        },
+                    };
+                    if let (Some(on), Some(color)) = (on, color) {
+                        self.command_queue.push(OscCommand::GridTag {
+                            name: name.clone(),
+                            tag: tag.clone(),
+                            on,
+                            color,
+                        });
                     }
-                    "/grid/create" => {
-                        if let [osc::Type::String(name), osc::Type::String(show), osc::Type::Float(x), osc::Type::Float(y), osc::Type::Float(rot)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridCreate {
-                                name: name.clone(),
-                                show: show.clone(),
-                                position: (*x, *y),
-                                rotation: *rot,
-                            });
-                        }
+                }
+            }
+            "/grid/tags" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridTags { name: name.clone() });
+                }
+            }
+            "/grid/tilemask" => {
+                if let [osc::Type::String(name), osc::Type::Int(x), osc::Type::Int(y), osc::Type::Int(visible)] =
+                    &message.args[..]
+                {
+                    if let Some(tile) = tile_coordinate(*x, *y) {
+                        self.command_queue.push(OscCommand::GridTileMask {
+                            name: name.clone(),
+                            tile,
+                            visible: *visible != 0,
+                        });
                     }
-                    "/grid/move" => {
-                        if let [osc::Type::String(name), osc::Type::Float(x), osc::Type::Float(y), osc::Type::Float(duration)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridMove {
-                                name: name.clone(),
-                                x: *x,
-                                y: *y,
-                                duration: *duration,
-                            });
-                        }
+                }
+            }
+            "/grid/tilemaskrect" => {
+                if let [osc::Type::String(name), osc::Type::Int(x1), osc::Type::Int(y1), osc::Type::Int(x2), osc::Type::Int(y2), osc::Type::Int(visible)] =
+                    &message.args[..]
+                {
+                    if let (Some(from), Some(to)) =
+                        (tile_coordinate(*x1, *y1), tile_coordinate(*x2, *y2))
+                    {
+                        self.command_queue.push(OscCommand::GridTileMaskRect {
+                            name: name.clone(),
+                            from,
+                            to,
+                            visible: *visible != 0,
+                        });
                     }
-                    "/grid/rotate" => {
-                        if let [osc::Type::String(name), osc::Type::Float(angle)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridRotate {
-                                name: name.clone(),
-                                angle: *angle,
-                            });
+                }
+            }
+            "/grid/tilemaskclear" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridTileMaskClear { name: name.clone() });
+                }
+            }
+            "/preset/reload" => {
+                self.command_queue.push(OscCommand::PresetReload {});
+            }
+            "/graph/rebuild" => {
+                self.command_queue.push(OscCommand::GraphRebuild {});
+            }
+            "/debug/graph" => {
+                if let [osc::Type::String(name), osc::Type::Int(enabled), options @ ..] =
+                    &message.args[..]
+                {
+                    let labels = matches!(options, [osc::Type::Int(labels), ..] if *labels != 0);
+                    self.command_queue.push(OscCommand::DebugGraph {
+                        name: name.clone(),
+                        enabled: *enabled != 0,
+                        labels,
+                    });
+                }
+            }
+            "/grid/backbonestrobe" => {
+                if let [osc::Type::String(name), osc::Type::Float(r), osc::Type::Float(g), osc::Type::Float(b), osc::Type::Float(hz), osc::Type::Float(duty), osc::Type::Float(duration)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridBackboneStrobe {
+                        name: name.clone(),
+                        r: *r,
+                        g: *g,
+                        b: *b,
+                        hz: *hz,
+                        duty: *duty,
+                        duration: *duration,
+                    });
+                }
+            }
+            "/grid/backbonepulse" => {
+                if let [osc::Type::String(name), osc::Type::Float(period), osc::Type::Float(depth)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridBackbonePulse {
+                        name: name.clone(),
+                        period: *period,
+                        depth: *depth,
+                    });
+                }
+            }
+            "/grid/backbonepulse_stop" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridBackbonePulseStop { name: name.clone() });
+                }
+            }
+            "/grid/backbone_stroke" => {
+                if let [osc::Type::String(name), osc::Type::Float(stroke_weight)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridBackboneStroke {
+                        name: name.clone(),
+                        stroke_weight: *stroke_weight,
+                    });
+                }
+            }
+            "/grid/backbonevisible" => {
+                if let [osc::Type::String(name), osc::Type::Int(visible), options @ ..] =
+                    &message.args[..]
+                {
+                    let duration = match options {
+                        [osc::Type::Float(duration), ..] => *duration,
+                        _ => 0.0,
+                    };
+                    self.command_queue.push(OscCommand::GridBackboneVisible {
+                        name: name.clone(),
+                        visible: *visible != 0,
+                        duration,
+                    });
+                }
+            }
+            "/grid/typeweight" => {
+                if let [osc::Type::String(name), osc::Type::String(segment_type), osc::Type::Float(multiplier)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridTypeWeight {
+                        name: name.clone(),
+                        segment_type: segment_type.clone(),
+                        multiplier: *multiplier,
+                    });
+                }
+            }
+            "/grid/create" => {
+                if let [osc::Type::String(name), osc::Type::String(show), osc::Type::Float(x), osc::Type::Float(y), osc::Type::Float(rot)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridCreate {
+                        name: name.clone(),
+                        show: show.clone(),
+                        position: (*x, *y),
+                        rotation: *rot,
+                    });
+                }
+            }
+            "/grid/array" => {
+                if let [osc::Type::String(base_name), osc::Type::String(show), osc::Type::Int(cols), osc::Type::Int(rows), osc::Type::Float(spacing_x), osc::Type::Float(spacing_y), osc::Type::Float(origin_x), osc::Type::Float(origin_y)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridArray {
+                        base_name: base_name.clone(),
+                        show: show.clone(),
+                        cols: *cols,
+                        rows: *rows,
+                        spacing: (*spacing_x, *spacing_y),
+                        origin: (*origin_x, *origin_y),
+                    });
+                }
+            }
+            "/grid/destroy" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridDestroy { name: name.clone() });
+                }
+            }
+            "/grid/rename" => {
+                if let [osc::Type::String(name), osc::Type::String(new_name)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridRename {
+                        name: name.clone(),
+                        new_name: new_name.clone(),
+                    });
+                }
+            }
+            "/grid/move" => {
+                if let [osc::Type::String(name), osc::Type::Float(x), osc::Type::Float(y), osc::Type::Float(duration), options @ ..] =
+                    &message.args[..]
+                {
+                    // optional easing name, then optional clear-queue flag
+                    let (easing, clear_queue) = match options {
+                        [] => (None, false),
+                        [osc::Type::String(easing)] => (Some(easing.clone()), false),
+                        [osc::Type::Int(clear)] => (None, *clear != 0),
+                        [osc::Type::String(easing), osc::Type::Int(clear)] => {
+                            (Some(easing.clone()), *clear != 0)
                         }
-                    }
-                    "/grid/scale" => {
-                        if let [osc::Type::String(name), osc::Type::Float(scale)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridScale {
-                                name: name.clone(),
-                                scale: *scale,
-                            });
+                        _ => {
+                            println!("/grid/move options are [easing] [clear_queue]");
+                            (None, false)
                         }
-                    }
-                    "/grid/slide" => {
-                        if let [osc::Type::String(name), osc::Type::String(axis), osc::Type::Int(number), osc::Type::Float(position)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridSlide {
+                    };
+                    self.command_queue.push(OscCommand::GridMove {
+                        name: name.clone(),
+                        x: *x,
+                        y: *y,
+                        duration: *duration,
+                        easing,
+                        clear_queue,
+                    });
+                }
+            }
+            "/grid/movequeue" => {
+                if let [osc::Type::String(name), legs @ ..] = &message.args[..] {
+                    let parsed: Option<Vec<_>> = legs
+                                .chunks(4)
+                                .map(|leg| match leg {
+                                    [osc::Type::Float(x), osc::Type::Float(y), osc::Type::Float(duration), osc::Type::String(easing)] => {
+                                        Some((*x, *y, *duration, Some(easing.clone())))
+                                    }
+                                    _ => None,
+                                })
+                                .collect();
+
+                    match parsed {
+                        Some(legs) if !legs.is_empty() => {
+                            self.command_queue.push(OscCommand::GridMoveQueue {
                                 name: name.clone(),
-                                axis: axis.clone(),
-                                number: *number,
-                                position: *position,
-                            });
-                        }
-                    }
-                    "/background/flash" => {
-                        if let [osc::Type::Float(r), osc::Type::Float(g), osc::Type::Float(b), osc::Type::Float(duration)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::BackgroundFlash {
-                                r: *r,
-                                g: *g,
-                                b: *b,
-                                duration: *duration,
+                                legs,
                             });
                         }
+                        _ => println!(
+                            "/grid/movequeue expects repeated (x, y, duration, easing) legs"
+                        ),
                     }
-                    "/background/color_fade" => {
-                        if let [osc::Type::Float(r), osc::Type::Float(g), osc::Type::Float(b), osc::Type::Float(duration)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::BackgroundColorFade {
-                                r: *r,
-                                g: *g,
-                                b: *b,
-                                duration: *duration,
-                            });
+                }
+            }
+            "/grid/keyframes" => {
+                if let [osc::Type::String(name), values @ ..] = &message.args[..] {
+                    let parsed: Option<Vec<_>> = values
+                                .chunks(6)
+                                .map(|keyframe| match keyframe {
+                                    [osc::Type::Float(time), osc::Type::Float(x), osc::Type::Float(y), osc::Type::Float(rotation), osc::Type::Float(scale), osc::Type::String(easing)] => {
+                                        Some((*time, *x, *y, *rotation, *scale, easing.clone()))
+                                    }
+                                    _ => None,
+                                })
+                                .collect();
+
+                    match parsed {
+                                Some(keyframes) if !keyframes.is_empty() => {
+                                    self.command_queue.push(OscCommand::GridKeyframes {
+                                        name: name.clone(),
+                                        keyframes,
+                                    });
+                                }
+                                _ => println!(
+                                    "/grid/keyframes expects repeated (time, x, y, rotation, scale, easing) keyframes"
+                                ),
+                            }
+                }
+            }
+            "/grid/orbit" => {
+                if let [osc::Type::String(name), osc::Type::Float(cx), osc::Type::Float(cy), osc::Type::Float(degrees_per_second), osc::Type::Float(duration), options @ ..] =
+                    &message.args[..]
+                {
+                    // optional face-path flag, then optional radius
+                    let (face_path, radius) = match options {
+                        [] => (false, None),
+                        [osc::Type::Int(face_path)] => (*face_path != 0, None),
+                        [osc::Type::Int(face_path), osc::Type::Float(radius)] => {
+                            (*face_path != 0, Some(*radius))
                         }
-                    }
-                    "/grid/glyph" => {
-                        if let [osc::Type::String(name), osc::Type::Int(index), osc::Type::Int(animation_type)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridGlyph {
-                                grid_name: name.clone(),
-                                glyph_index: *index as usize,
-                                animation_type_msg: *animation_type,
-                            });
+                        _ => {
+                            println!("/grid/orbit options are [face_path] [radius]");
+                            (false, None)
                         }
+                    };
+                    self.command_queue.push(OscCommand::GridOrbit {
+                        name: name.clone(),
+                        center: (*cx, *cy),
+                        degrees_per_second: *degrees_per_second,
+                        duration: *duration,
+                        face_path,
+                        radius,
+                    });
+                }
+            }
+            "/grid/orbitstop" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridOrbitStop { name: name.clone() });
+                }
+            }
+            "/grid/movepath" => {
+                if let [osc::Type::String(name), osc::Type::Float(duration), coords @ ..] =
+                    &message.args[..]
+                {
+                    let values: Vec<f32> = coords
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            osc::Type::Float(v) => Some(*v),
+                            _ => None,
+                        })
+                        .collect();
+
+                    if values.is_empty()
+                        || !values.len().is_multiple_of(2)
+                        || values.len() != coords.len()
+                    {
+                        println!("/grid/movepath expects pairs of float coordinates");
+                    } else {
+                        self.command_queue.push(OscCommand::GridMovePath {
+                            name: name.clone(),
+                            duration: *duration,
+                            waypoints: values.chunks(2).map(|p| (p[0], p[1])).collect(),
+                        });
                     }
-                    "/grid/instantglyphcolor" => {
-                        if let [osc::Type::String(name), osc::Type::Float(r), osc::Type::Float(g), osc::Type::Float(b), osc::Type::Float(a)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridInstantGlyphColor {
-                                grid_name: name.clone(),
-                                r: *r,
-                                g: *g,
-                                b: *b,
-                                a: *a,
-                            });
-                        }
+                }
+            }
+            "/grid/jitter" => {
+                if let [osc::Type::String(name), osc::Type::Float(amplitude), osc::Type::Float(frequency), osc::Type::Float(duration)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridJitter {
+                        name: name.clone(),
+                        amplitude: *amplitude,
+                        frequency: *frequency,
+                        duration: *duration,
+                    });
+                }
+            }
+            "/grid/flicker" => {
+                if let [osc::Type::String(name), osc::Type::Float(rate), osc::Type::Float(depth), osc::Type::Float(duration)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridFlicker {
+                        name: name.clone(),
+                        rate: *rate,
+                        depth: *depth,
+                        duration: *duration,
+                    });
+                }
+            }
+            "/grid/envelope" => {
+                if let [osc::Type::String(name), osc::Type::Float(attack), osc::Type::Float(decay), osc::Type::Float(sustain_level), osc::Type::Float(release), options @ ..] =
+                    &message.args[..]
+                {
+                    let hold = match options {
+                        [osc::Type::Float(hold), ..] => *hold,
+                        _ => 0.0,
+                    };
+                    self.command_queue.push(OscCommand::GridEnvelope {
+                        name: name.clone(),
+                        attack: *attack,
+                        decay: *decay,
+                        sustain_level: *sustain_level,
+                        release: *release,
+                        hold,
+                    });
+                }
+            }
+            "/grid/noisecolor" => {
+                if let [osc::Type::String(name), osc::Type::Float(scale), osc::Type::Float(speed), osc::Type::Float(range)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridNoiseColor {
+                        name: name.clone(),
+                        scale: *scale,
+                        speed: *speed,
+                        range: *range,
+                    });
+                }
+            }
+            "/grid/dimmer" => {
+                if let [osc::Type::String(name), osc::Type::Float(level)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridDimmer {
+                        name: name.clone(),
+                        level: *level,
+                    });
+                }
+            }
+            "/master/brightness" => {
+                if let [osc::Type::Float(level)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::MasterBrightness { level: *level });
+                }
+            }
+            "/master/blackout" => {
+                if let [osc::Type::Int(enabled)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::MasterBlackout {
+                        enabled: *enabled != 0,
+                    });
+                }
+            }
+            "/grid/glow" => {
+                if let [osc::Type::String(name), osc::Type::Int(layers), osc::Type::Float(spread), osc::Type::Float(alpha)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridGlow {
+                        name: name.clone(),
+                        layers: *layers,
+                        spread: *spread,
+                        alpha: *alpha,
+                    });
+                }
+            }
+            "/grid/glyphcolorfade" => {
+                if let [osc::Type::String(name), rest @ ..] = &message.args[..] {
+                    if let Some((color, [osc::Type::Float(duration)])) = color_arg(rest, 4) {
+                        self.command_queue.push(OscCommand::GridGlyphColorFade {
+                            name: name.clone(),
+                            color,
+                            duration: *duration,
+                        });
                     }
-                    "/grid/nextglyph" => {
-                        if let [osc::Type::String(name), osc::Type::Int(animation_type)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridNextGlyph {
-                                grid_name: name.clone(),
-                                animation_type_msg: *animation_type,
-                            });
+                }
+            }
+            "/grid/gradient" => {
+                if let [osc::Type::String(name), osc::Type::Float(r1), osc::Type::Float(g1), osc::Type::Float(b1), osc::Type::Float(a1), osc::Type::Float(r2), osc::Type::Float(g2), osc::Type::Float(b2), osc::Type::Float(a2), osc::Type::String(axis)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridGradient {
+                        name: name.clone(),
+                        color_a: (*r1, *g1, *b1, *a1),
+                        color_b: (*r2, *g2, *b2, *a2),
+                        axis: axis.clone(),
+                    });
+                }
+            }
+            "/grid/gradientoff" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridGradientOff { name: name.clone() });
+                }
+            }
+            "/grid/outline" => {
+                if let [osc::Type::String(name), osc::Type::Float(r), osc::Type::Float(g), osc::Type::Float(b), osc::Type::Float(a), osc::Type::Float(extra_weight)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridOutline {
+                        name: name.clone(),
+                        color: (*r, *g, *b, *a),
+                        extra_weight: *extra_weight,
+                    });
+                }
+            }
+            "/grid/outlineoff" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridOutlineOff { name: name.clone() });
+                }
+            }
+            "/grid/bands" => {
+                if let [osc::Type::String(name), osc::Type::String(axis), osc::Type::Int(width), osc::Type::Float(r1), osc::Type::Float(g1), osc::Type::Float(b1), osc::Type::Float(r2), osc::Type::Float(g2), osc::Type::Float(b2)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridBands {
+                        name: name.clone(),
+                        axis: axis.clone(),
+                        width: *width,
+                        color_a: (*r1, *g1, *b1),
+                        color_b: (*r2, *g2, *b2),
+                    });
+                }
+            }
+            "/grid/bandsoff" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridBandsOff { name: name.clone() });
+                }
+            }
+            "/grid/rainbow" => {
+                if let [osc::Type::String(name), osc::Type::Float(rate), osc::Type::Float(spread)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridRainbow {
+                        name: name.clone(),
+                        rate: *rate,
+                        spread: *spread,
+                    });
+                }
+            }
+            "/grid/pulse" => {
+                if let [osc::Type::String(name), osc::Type::Float(period), osc::Type::Float(depth)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridPulse {
+                        name: name.clone(),
+                        period: *period,
+                        depth: *depth,
+                    });
+                }
+            }
+            "/grid/rotate" => {
+                if let [osc::Type::String(name), osc::Type::Float(angle)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridRotate {
+                        name: name.clone(),
+                        angle: *angle,
+                    });
+                }
+            }
+            "/grid/scale" => {
+                if let [osc::Type::String(name), osc::Type::Float(scale)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridScale {
+                        name: name.clone(),
+                        scale: *scale,
+                    });
+                }
+            }
+            "/grid/scalexy" => {
+                if let [osc::Type::String(name), osc::Type::Float(sx), osc::Type::Float(sy)] =
+                    &message.args[..]
+                {
+                    self.command_queue.push(OscCommand::GridScaleXY {
+                        name: name.clone(),
+                        scale: (*sx, *sy),
+                    });
+                }
+            }
+            "/grid/slide" => {
+                if let [osc::Type::String(name), osc::Type::String(axis), osc::Type::Int(number), osc::Type::Float(position), options @ ..] =
+                    &message.args[..]
+                {
+                    // optional duration, then optional easing name, then optional wrap flag
+                    let (options, wrap) = match options {
+                        [rest @ .., osc::Type::Int(wrap)] => (rest, *wrap != 0),
+                        _ => (options, false),
+                    };
+                    let (duration, easing) = match options {
+                        [] => (None, None),
+                        [osc::Type::Float(duration)] => (Some(*duration), None),
+                        [osc::Type::Float(duration), osc::Type::String(easing)] => {
+                            (Some(*duration), Some(easing.clone()))
                         }
-                    }
-                    "/grid/nextglyphcolor" => {
-                        if let [osc::Type::String(name), osc::Type::Float(r), osc::Type::Float(g), osc::Type::Float(b), osc::Type::Float(a)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridNextGlyphColor {
-                                grid_name: name.clone(),
-                                r: *r,
-                                g: *g,
-                                b: *b,
-                                a: *a,
-                            });
+                        _ => {
+                            println!("/grid/slide options are [duration] [easing] [wrap]");
+                            (None, None)
                         }
-                    }
-                    "/grid/noglyph" => {
-                        if let [osc::Type::String(name), osc::Type::Int(animation_type)] =
-                            &message.args[..]
-                        {
-                            self.command_queue.push(OscCommand::GridNoGlyph {
-                                grid_name: name.clone(),
-                                animation_type_msg: *animation_type,
-                            });
+                    };
+                    self.command_queue.push(OscCommand::GridSlide {
+                        name: name.clone(),
+                        axis: axis.clone(),
+                        number: *number,
+                        position: *position,
+                        duration,
+                        easing,
+                        wrap,
+                    });
+                }
+            }
+            "/grid/slideclamp" => {
+                if let [osc::Type::String(name), osc::Type::Int(enabled)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridSlideClamp {
+                        name: name.clone(),
+                        enabled: *enabled != 0,
+                    });
+                }
+            }
+            "/grid/slidereset" => {
+                if let [osc::Type::String(name), options @ ..] = &message.args[..] {
+                    // optional duration, then optional easing name
+                    let (duration, easing) = match options {
+                        [] => (1.0, None),
+                        [osc::Type::Float(duration)] => (*duration, None),
+                        [osc::Type::Float(duration), osc::Type::String(easing)] => {
+                            (*duration, Some(easing.clone()))
                         }
-                    }
-                    "/grid/overwrite" => {
-                        if let [osc::Type::String(name)] = &message.args[..] {
-                            self.command_queue.push(OscCommand::GridOverwrite {
-                                grid_name: name.clone(),
-                            });
+                        _ => {
+                            println!("/grid/slidereset options are [duration] [easing]");
+                            (1.0, None)
                         }
-                    }
-                    "/grid/transitiontrigger" => {
-                        if let [osc::Type::String(name)] = &message.args[..] {
-                            self.command_queue.push(OscCommand::GridTransitionTrigger {
-                                grid_name: name.clone(),
-                            });
+                    };
+                    self.command_queue.push(OscCommand::GridSlideReset {
+                        name: name.clone(),
+                        duration,
+                        easing,
+                    });
+                }
+            }
+            "/grid/slidezero" => {
+                // /grid/slidereset with a linear ease
+                if let [osc::Type::String(name), osc::Type::Float(duration)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridSlideReset {
+                        name: name.clone(),
+                        duration: *duration,
+                        easing: None,
+                    });
+                }
+            }
+            "/grid/slidestate" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridSlideState { name: name.clone() });
+                }
+            }
+            "/grid/snapshot" => {
+                if let [osc::Type::String(name), osc::Type::String(slot)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridSnapshot {
+                        name: name.clone(),
+                        slot: slot.clone(),
+                    });
+                }
+            }
+            "/grid/restore" => {
+                if let [osc::Type::String(name), osc::Type::String(slot)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridRestore {
+                        name: name.clone(),
+                        slot: slot.clone(),
+                    });
+                }
+            }
+            "/grid/get" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridGet { name: name.clone() });
+                }
+            }
+            "/grid/center" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue
+                        .push(OscCommand::GridCenter { name: name.clone() });
+                }
+            }
+            "/grid/hittest" => {
+                if let [osc::Type::String(name), osc::Type::Float(x), osc::Type::Float(y), options @ ..] =
+                    &message.args[..]
+                {
+                    let tolerance = match options {
+                        [] => None,
+                        [osc::Type::Float(tolerance)] => Some(*tolerance),
+                        _ => {
+                            println!("/grid/hittest options are [tolerance]");
+                            None
                         }
+                    };
+                    self.command_queue.push(OscCommand::GridHitTest {
+                        name: name.clone(),
+                        point: (*x, *y),
+                        tolerance,
+                    });
+                }
+            }
+            "/background/flash" => {
+                if let Some((color, [osc::Type::Float(duration)])) = color_arg(&message.args, 3) {
+                    self.command_queue.push(OscCommand::BackgroundFlash {
+                        color,
+                        duration: *duration,
+                    });
+                }
+            }
+            "/background/color_fade" => {
+                if let Some((color, [osc::Type::Float(duration)])) = color_arg(&message.args, 3) {
+                    self.command_queue.push(OscCommand::BackgroundColorFade {
+                        color,
+                        duration: *duration,
+                    });
+                }
+            }
+            "/palette/list" => {
+                if message.args.is_empty() {
+                    self.command_queue.push(OscCommand::PaletteList {});
+                }
+            }
+            "/background/persistence" => {
+                if let [osc::Type::Float(persistence)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::BackgroundPersistence {
+                        persistence: *persistence,
+                    });
+                }
+            }
+            "/background/image" => {
+                if let [osc::Type::String(path), options @ ..] = &message.args[..] {
+                    let opacity = match options {
+                        [osc::Type::Float(opacity), ..] => Some(*opacity),
+                        _ => None,
+                    };
+                    self.command_queue.push(OscCommand::BackgroundImage {
+                        path: path.clone(),
+                        opacity,
+                    });
+                }
+            }
+            "/grid/glyph" => {
+                if let [osc::Type::String(name), osc::Type::Int(index), animation_type, extra @ ..] =
+                    &message.args[..]
+                {
+                    if let Some(animation_type_msg) = animation_type_arg(animation_type) {
+                        self.command_queue.push(OscCommand::GridGlyph {
+                            grid_name: name.clone(),
+                            glyph_index: *index as usize,
+                            animation_type_msg,
+                            animation_args: animation_args(animation_type_msg, extra),
+                        });
                     }
-                    "/grid/transitionauto" => {
-                        if let [osc::Type::String(name)] = &message.args[..] {
-                            self.command_queue.push(OscCommand::GridTransitionAuto {
-                                grid_name: name.clone(),
-                            });
-                        }
+                }
+            }
+            "/grid/instantglyphcolor" => {
+                if let [osc::Type::String(name), rest @ ..] = &message.args[..] {
+                    if let Some((color, [])) = color_arg(rest, 4) {
+                        self.command_queue.push(OscCommand::GridInstantGlyphColor {
+                            grid_name: name.clone(),
+                            color,
+                        });
                     }
-                    "/grid/togglevisibility" => {
-                        if let [osc::Type::String(name)] = &message.args[..] {
-                            self.command_queue.push(OscCommand::GridToggleVisibility {
-                                grid_name: name.clone(),
-                            });
-                        }
+                }
+            }
+            "/grid/nextglyph" => {
+                if let [osc::Type::String(name), animation_type, extra @ ..] = &message.args[..] {
+                    if let Some(animation_type_msg) = animation_type_arg(animation_type) {
+                        self.command_queue.push(OscCommand::GridNextGlyph {
+                            grid_name: name.clone(),
+                            animation_type_msg,
+                            animation_args: animation_args(animation_type_msg, extra),
+                        });
                     }
-                    "/grid/setvisibility" => {
-                        if let [osc::Type::String(name), osc::Type::Int(setting)] =
-                            &message.args[..]
-                        {
-                            let setting_bool = *setting != 0;
-                            self.command_queue.push(OscCommand::GridSetVisibility {
-                                grid_name: name.clone(),
-                                setting: setting_bool,
-                            });
-                        }
+                }
+            }
+            "/grid/nextglyphcolor" => {
+                if let [osc::Type::String(name), rest @ ..] = &message.args[..] {
+                    if let Some((color, [])) = color_arg(rest, 4) {
+                        self.command_queue.push(OscCommand::GridNextGlyphColor {
+                            grid_name: name.clone(),
+                            color,
+                        });
                     }
-                    "/grid/togglecolorful" => {
-                        if let [osc::Type::String(name)] = &message.args[..] {
-                            self.command_queue.push(OscCommand::GridToggleColorful {
-                                grid_name: name.clone(),
-                            });
-                        }
+                }
+            }
+            "/grid/segmentcolor" => {
+                if let [osc::Type::String(name), osc::Type::String(segment_id), rest @ ..] =
+                    &message.args[..]
+                {
+                    if let Some((color, [])) = color_arg(rest, 4) {
+                        self.command_queue.push(OscCommand::GridSegmentColor {
+                            grid_name: name.clone(),
+                            segment_id: segment_id.clone(),
+                            color,
+                        });
                     }
-                    "/grid/setcolorful" => {
-                        if let [osc::Type::String(name), osc::Type::Int(setting)] =
-                            &message.args[..]
-                        {
-                            let setting_bool = *setting != 0;
-                            self.command_queue.push(OscCommand::GridSetColorful {
-                                grid_name: name.clone(),
-                                setting: setting_bool,
-                            });
-                        }
+                }
+            }
+            "/grid/segmentcolorclear" => match &message.args[..] {
+                [osc::Type::String(name)] => {
+                    self.command_queue.push(OscCommand::GridSegmentColorClear {
+                        grid_name: name.clone(),
+                        segment_id: None,
+                    });
+                }
+                [osc::Type::String(name), osc::Type::String(segment_id)] => {
+                    self.command_queue.push(OscCommand::GridSegmentColorClear {
+                        grid_name: name.clone(),
+                        segment_id: Some(segment_id.clone()),
+                    });
+                }
+                _ => (),
+            },
+            "/grid/noglyph" => {
+                if let [osc::Type::String(name), animation_type, extra @ ..] = &message.args[..] {
+                    if let Some(animation_type_msg) = animation_type_arg(animation_type) {
+                        self.command_queue.push(OscCommand::GridNoGlyph {
+                            grid_name: name.clone(),
+                            animation_type_msg,
+                            animation_args: animation_args(animation_type_msg, extra),
+                        });
                     }
-                    "/grid/setpowereffect" => {
-                        if let [osc::Type::String(name), osc::Type::Int(setting)] =
-                            &message.args[..]
-                        {
-                            let setting_bool = *setting != 0;
-                            self.command_queue.push(OscCommand::GridSetPowerEffect {
-                                grid_name: name.clone(),
-                                setting: setting_bool,
-                            });
-                        }
+                }
+            }
+            "/grid/loop" => {
+                if let [osc::Type::String(name), osc::Type::Int(index_a), osc::Type::Int(index_b), osc::Type::Float(hold_time), animation_type, extra @ ..] =
+                    &message.args[..]
+                {
+                    if let Some(animation_type_msg) = animation_type_arg(animation_type) {
+                        self.command_queue.push(OscCommand::GridLoop {
+                            grid_name: name.clone(),
+                            index_a: *index_a as usize,
+                            index_b: *index_b as usize,
+                            hold_time: *hold_time,
+                            animation_type_msg,
+                            animation_args: animation_args(animation_type_msg, extra),
+                        });
                     }
-                    "/transition/update" => {
-                        let mut grid_name = String::new();
-                        let mut steps = None;
-                        let mut frame_duration = None;
-                        let mut wandering = None;
-                        let mut density = None;
-
-                        for (i, arg) in message.args.iter().enumerate() {
-                            match (i, arg) {
-                                (0, osc::Type::String(name)) => grid_name = name.clone(),
-                                (1, osc::Type::Int(s)) => steps = Some(*s as usize),
-                                (2, osc::Type::Float(f)) => frame_duration = Some(*f),
-                                (3, osc::Type::Float(w)) => wandering = Some(*w),
-                                (4, osc::Type::Float(d)) => density = Some(*d),
-                                _ => (),
-                            }
-                        }
+                }
+            }
+            "/grid/loopstop" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridLoopStop {
+                        grid_name: name.clone(),
+                    });
+                }
+            }
+            "/grid/overwrite" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridOverwrite {
+                        grid_name: name.clone(),
+                    });
+                }
+            }
+            "/grid/transitiontrigger" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridTransitionTrigger {
+                        grid_name: name.clone(),
+                    });
+                }
+            }
+            "/grid/transitionauto" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridTransitionAuto {
+                        grid_name: name.clone(),
+                    });
+                }
+            }
+            "/grid/togglevisibility" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridToggleVisibility {
+                        grid_name: name.clone(),
+                    });
+                }
+            }
+            "/grid/setvisibility" => {
+                if let [osc::Type::String(name), osc::Type::Int(setting)] = &message.args[..] {
+                    let setting_bool = *setting != 0;
+                    self.command_queue.push(OscCommand::GridSetVisibility {
+                        grid_name: name.clone(),
+                        setting: setting_bool,
+                    });
+                }
+            }
+            "/grid/togglecolorful" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridToggleColorful {
+                        grid_name: name.clone(),
+                    });
+                }
+            }
+            "/grid/setcolorful" => {
+                if let [osc::Type::String(name), osc::Type::Int(setting)] = &message.args[..] {
+                    let setting_bool = *setting != 0;
+                    self.command_queue.push(OscCommand::GridSetColorful {
+                        grid_name: name.clone(),
+                        setting: setting_bool,
+                    });
+                }
+            }
+            "/grid/colorfulmode" => {
+                if let [osc::Type::String(name), osc::Type::String(mode)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridColorfulMode {
+                        grid_name: name.clone(),
+                        mode: mode.clone(),
+                    });
+                }
+            }
+            "/grid/setpowereffect" => {
+                if let [osc::Type::String(name), osc::Type::Int(setting)] = &message.args[..] {
+                    let setting_bool = *setting != 0;
+                    self.command_queue.push(OscCommand::GridSetPowerEffect {
+                        grid_name: name.clone(),
+                        setting: setting_bool,
+                    });
+                }
+            }
+            "/grid/setreverseerase" => {
+                if let [osc::Type::String(name), osc::Type::Int(setting)] = &message.args[..] {
+                    let setting_bool = *setting != 0;
+                    self.command_queue.push(OscCommand::GridSetReverseErase {
+                        grid_name: name.clone(),
+                        setting: setting_bool,
+                    });
+                }
+            }
+            "/grid/transitionpause" => {
+                if let [osc::Type::String(name), osc::Type::Int(paused)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridTransitionPause {
+                        grid_name: name.clone(),
+                        paused: *paused != 0,
+                    });
+                }
+            }
+            "/grid/transitionprogress" => {
+                if let [osc::Type::String(name)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridTransitionProgress {
+                        grid_name: name.clone(),
+                    });
+                }
+            }
+            "/grid/speed" => {
+                if let [osc::Type::String(name), osc::Type::Float(speed)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridSpeed {
+                        grid_name: name.clone(),
+                        speed: *speed,
+                    });
+                }
+            }
+            "/grid/setstroketint" => {
+                if let [osc::Type::String(name), osc::Type::Int(setting)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridSetStrokeTint {
+                        grid_name: name.clone(),
+                        setting: *setting != 0,
+                    });
+                }
+            }
+            "/grid/setcrossfade" => {
+                if let [osc::Type::String(name), osc::Type::Int(setting)] = &message.args[..] {
+                    self.command_queue.push(OscCommand::GridSetCrossfade {
+                        grid_name: name.clone(),
+                        setting: *setting != 0,
+                    });
+                }
+            }
+            "/transition/update" => {
+                let mut grid_name = String::new();
+                let mut steps = None;
+                let mut frame_duration = None;
+                let mut wandering = None;
+                let mut density = None;
+                let mut stroke_gap_steps = None;
 
-                        self.command_queue.push(OscCommand::TransitionUpdate {
-                            grid_name,
-                            steps,
-                            frame_duration,
-                            wandering,
-                            density,
-                        });
+                for (i, arg) in message.args.iter().enumerate() {
+                    match (i, arg) {
+                        (0, osc::Type::String(name)) => grid_name = name.clone(),
+                        (1, osc::Type::Int(s)) => steps = Some(*s as usize),
+                        (2, osc::Type::Float(f)) => frame_duration = Some(*f),
+                        (3, osc::Type::Float(w)) => wandering = Some(*w),
+                        (4, osc::Type::Float(d)) => density = Some(*d),
+                        (5, osc::Type::Int(g)) if *g >= 0 => stroke_gap_steps = Some(*g as usize),
+                        _ => (),
                     }
-                    _ => println!("Unknown OSC address pattern: {}", message.addr),
-                };
+                }
+
+                self.command_queue.push(OscCommand::TransitionUpdate {
+                    grid_name,
+                    steps,
+                    frame_duration,
+                    wandering,
+                    density,
+                    stroke_gap_steps,
+                });
             }
-        }
+            "/transition/seed" => match &message.args[..] {
+                [] => self
+                    .command_queue
+                    .push(OscCommand::TransitionSeed { seed: None }),
+                [osc::Type::Int(seed)] if *seed >= 0 => {
+                    self.command_queue.push(OscCommand::TransitionSeed {
+                        seed: Some(*seed as u64),
+                    })
+                }
+                [osc::Type::Long(seed)] if *seed >= 0 => {
+                    self.command_queue.push(OscCommand::TransitionSeed {
+                        seed: Some(*seed as u64),
+                    })
+                }
+                _ => println!("/transition/seed takes one non-negative integer"),
+            },
+            _ => println!("Unknown OSC address pattern: {}", message.addr),
+        };
     }
 
     pub fn take_commands(&mut self) -> Vec<OscCommand> {
@@ -412,6 +1616,99 @@ impl OscController {
     }
 }
 
+// A color at the front of `args`: `channels` floats (3 means alpha is 1.0),
+// or a single "palette:NAME:INDEX" string. Returns the args that follow it.
+fn color_arg(args: &[osc::Type], channels: usize) -> Option<(ColorArg, &[osc::Type])> {
+    if let [osc::Type::String(reference), rest @ ..] = args {
+        let parts: Vec<&str> = reference.splitn(3, ':').collect();
+        return match parts[..] {
+            ["palette", palette, entry] if !palette.is_empty() && !entry.is_empty() => Some((
+                ColorArg::Palette {
+                    palette: palette.to_string(),
+                    entry: entry.to_string(),
+                },
+                rest,
+            )),
+            _ => {
+                eprintln!(
+                    "Bad color reference '{}', expected palette:NAME:INDEX",
+                    reference
+                );
+                None
+            }
+        };
+    }
+
+    if args.len() < channels {
+        return None;
+    }
+    let mut rgba = [1.0; 4];
+    for (channel, arg) in rgba.iter_mut().zip(&args[..channels]) {
+        match arg {
+            osc::Type::Float(value) => *channel = *value,
+            _ => return None,
+        }
+    }
+    Some((
+        ColorArg::Rgba(rgba[0], rgba[1], rgba[2], rgba[3]),
+        &args[channels..],
+    ))
+}
+
+// Transition animation types can be sent by number or by name
+fn animation_type_arg(arg: &osc::Type) -> Option<i32> {
+    match arg {
+        osc::Type::Int(animation_type) => Some(*animation_type),
+        osc::Type::String(name) => match TransitionAnimationType::try_from(name.as_str()) {
+            Ok(animation_type) => Some(animation_type.code()),
+            Err(err) => {
+                println!("{}", err);
+                None
+            }
+        },
+        _ => None,
+    }
+}
+
+// Optional trailing args after the animation type: x, y floats for Ripple, an
+// axis string and optional reverse int for Scanline, or a seed segment id
+// string for Flood
+fn animation_args(animation_type: i32, args: &[osc::Type]) -> AnimationArgs {
+    let is_flood = matches!(
+        TransitionAnimationType::try_from(animation_type),
+        Ok(TransitionAnimationType::Flood { .. })
+    );
+    match args {
+        [] => AnimationArgs::default(),
+        [osc::Type::String(seed)] if is_flood => AnimationArgs {
+            seed: Some(seed.clone()),
+            ..Default::default()
+        },
+        [osc::Type::Float(x), osc::Type::Float(y)] => AnimationArgs {
+            origin: Some((*x, *y)),
+            ..Default::default()
+        },
+        [osc::Type::String(axis)] => AnimationArgs {
+            axis: Some(axis.clone()),
+            ..Default::default()
+        },
+        [osc::Type::String(axis), osc::Type::Int(reverse)] => AnimationArgs {
+            axis: Some(axis.clone()),
+            reverse: *reverse != 0,
+            ..Default::default()
+        },
+        _ => {
+            println!("Unrecognized transition animation args, using defaults");
+            AnimationArgs::default()
+        }
+    }
+}
+
+// Tiles are numbered from 1, so anything less isn't a tile
+fn tile_coordinate(x: i32, y: i32) -> Option<(u32, u32)> {
+    (x >= 1 && y >= 1).then_some((x as u32, y as u32))
+}
+
 // src/osc_control.rs
 
 pub struct OscSender {
@@ -425,80 +1722,943 @@ impl OscSender {
         let target_addr = "127.0.0.1".to_string();
         let sender = osc::sender()?;
 
-        Ok(Self {
-            sender,
-            target_addr,
-            target_port,
-        })
+        Ok(Self {
+            sender,
+            target_addr,
+            target_port,
+        })
+    }
+
+    pub fn send_recorder_start(&self) {
+        let addr = "/recorder/start".to_string();
+        let args = Vec::new();
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_recorder_stop(&self) {
+        let addr = "/recorder/stop".to_string();
+        let args = Vec::new();
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_recorder_pause(&self) {
+        let addr = "/recorder/pause".to_string();
+        let args = Vec::new();
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_recorder_resume(&self) {
+        let addr = "/recorder/resume".to_string();
+        let args = Vec::new();
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_recorder_crop(&self, x: i32, y: i32, w: i32, h: i32) {
+        let addr = "/recorder/crop".to_string();
+        let args = vec![
+            osc::Type::Int(x),
+            osc::Type::Int(y),
+            osc::Type::Int(w),
+            osc::Type::Int(h),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_recorder_crop_off(&self) {
+        let addr = "/recorder/cropoff".to_string();
+        let args = Vec::new();
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_recorder_saveback(&self, seconds: f32, continue_recording: bool) {
+        let addr = "/recorder/saveback".to_string();
+        let args = vec![
+            osc::Type::Float(seconds),
+            osc::Type::Int(continue_recording as i32),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    // feedback: reply to /recorder/status, also sent with every heartbeat
+    pub fn send_recorder_status(&self, status: &RecorderStatus) {
+        let addr = "/recorder/status".to_string();
+        let args = recorder_status_args(status);
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    // feedback: a stopped take has finished writing and is safe to move
+    pub fn send_recorder_finished(&self, path: &str) {
+        let addr = "/recorder/finished".to_string();
+        let args = vec![osc::Type::String(path.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    // feedback: a recording take has "started" or "ended", or a "replay" was
+    // saved, and where it's written
+    pub fn send_recorder_take(&self, event: &str, path: &str) {
+        let addr = "/recorder/take".to_string();
+        let args = vec![
+            osc::Type::String(event.to_string()),
+            osc::Type::String(path.to_string()),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    // feedback: sent every second so controllers can tell the app is alive
+    pub fn send_heartbeat(&self, time: f32, recording: bool, paused: bool) {
+        let addr = "/heartbeat".to_string();
+        let args = vec![
+            osc::Type::Float(time),
+            osc::Type::Int(recording as i32),
+            osc::Type::Int(paused as i32),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_recorder_screenshot(&self) {
+        let addr = "/recorder/screenshot".to_string();
+        let args = Vec::new();
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_create_grid(&self, name: &str, show: &str, x: f32, y: f32, rotation: f32) {
+        let addr = "/grid/create".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::String(show.to_string()),
+            osc::Type::Float(x),
+            osc::Type::Float(y),
+            osc::Type::Float(rotation),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_array(
+        &self,
+        base_name: &str,
+        show: &str,
+        cols: i32,
+        rows: i32,
+        spacing: (f32, f32),
+        origin: (f32, f32),
+    ) {
+        let addr = "/grid/array".to_string();
+        let args = vec![
+            osc::Type::String(base_name.to_string()),
+            osc::Type::String(show.to_string()),
+            osc::Type::Int(cols),
+            osc::Type::Int(rows),
+            osc::Type::Float(spacing.0),
+            osc::Type::Float(spacing.1),
+            osc::Type::Float(origin.0),
+            osc::Type::Float(origin.1),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_destroy_grid(&self, name: &str) {
+        let addr = "/grid/destroy".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_rename_grid(&self, name: &str, new_name: &str) {
+        let addr = "/grid/rename".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::String(new_name.to_string()),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_move_grid(&self, name: &str, x: f32, y: f32, duration: f32, easing: Option<&str>) {
+        let addr = "/grid/move".to_string();
+        let mut args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(x),
+            osc::Type::Float(y),
+            osc::Type::Float(duration),
+        ];
+        if let Some(easing) = easing {
+            args.push(osc::Type::String(easing.to_string()));
+        }
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_move_grid_path(&self, name: &str, duration: f32, waypoints: &[(f32, f32)]) {
+        let addr = "/grid/movepath".to_string();
+        let mut args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(duration),
+        ];
+        for (x, y) in waypoints {
+            args.push(osc::Type::Float(*x));
+            args.push(osc::Type::Float(*y));
+        }
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_move_queue(&self, name: &str, legs: &[(f32, f32, f32, &str)]) {
+        let addr = "/grid/movequeue".to_string();
+        let mut args = vec![osc::Type::String(name.to_string())];
+        for (x, y, duration, easing) in legs {
+            args.push(osc::Type::Float(*x));
+            args.push(osc::Type::Float(*y));
+            args.push(osc::Type::Float(*duration));
+            args.push(osc::Type::String(easing.to_string()));
+        }
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_keyframes(&self, name: &str, keyframes: &[(f32, f32, f32, f32, f32, &str)]) {
+        let addr = "/grid/keyframes".to_string();
+        let mut args = vec![osc::Type::String(name.to_string())];
+        for (time, x, y, rotation, scale, easing) in keyframes {
+            args.push(osc::Type::Float(*time));
+            args.push(osc::Type::Float(*x));
+            args.push(osc::Type::Float(*y));
+            args.push(osc::Type::Float(*rotation));
+            args.push(osc::Type::Float(*scale));
+            args.push(osc::Type::String(easing.to_string()));
+        }
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_orbit(
+        &self,
+        name: &str,
+        center: (f32, f32),
+        degrees_per_second: f32,
+        duration: f32,
+        face_path: bool,
+    ) {
+        let addr = "/grid/orbit".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(center.0),
+            osc::Type::Float(center.1),
+            osc::Type::Float(degrees_per_second),
+            osc::Type::Float(duration),
+            osc::Type::Int(face_path as i32),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_orbit_stop(&self, name: &str) {
+        let addr = "/grid/orbitstop".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    // feedback: a queued movement leg has finished
+    pub fn send_move_leg_complete(&self, name: &str, leg: i32, remaining: i32) {
+        let addr = "/grid/movecomplete".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Int(leg),
+            osc::Type::Int(remaining),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_jitter(&self, name: &str, amplitude: f32, frequency: f32, duration: f32) {
+        let addr = "/grid/jitter".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(amplitude),
+            osc::Type::Float(frequency),
+            osc::Type::Float(duration),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_sparkle(&self, name: &str, rate: f32, lifetime: f32, r: f32, g: f32, b: f32) {
+        let addr = "/grid/sparkle".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(rate),
+            osc::Type::Float(lifetime),
+            osc::Type::Float(r),
+            osc::Type::Float(g),
+            osc::Type::Float(b),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_sparkle_stop(&self, name: &str) {
+        let addr = "/grid/sparklestop".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    // "random" for either end picks one at random
+    pub fn send_grid_chase(&self, name: &str, start: &str, end: &str, speed: f32, width: i32) {
+        let addr = "/grid/chase".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::String(start.to_string()),
+            osc::Type::String(end.to_string()),
+            osc::Type::Float(speed),
+            osc::Type::Int(width),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_chase_stop(&self, name: &str) {
+        let addr = "/grid/chasestop".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_heatmap(&self, name: &str, enabled: i32, decay: f32) {
+        let addr = "/grid/heatmap".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Int(enabled),
+            osc::Type::Float(decay),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_preset(&self, name: &str, preset: &str) {
+        let addr = "/grid/preset".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::String(preset.to_string()),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_component(&self, name: &str, segment_id: &str, on: bool) {
+        let addr = "/grid/component".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::String(segment_id.to_string()),
+            osc::Type::String(if on { "on" } else { "off" }.to_string()),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_tag(&self, name: &str, tag: &str, on: bool) {
+        let addr = "/grid/tag".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::String(tag.to_string()),
+            osc::Type::String(if on { "on" } else { "off" }.to_string()),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_tags(&self, name: &str) {
+        let addr = "/grid/tags".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    // feedback: reply to /grid/tags, each tag followed by how many segments
+    // carry it
+    pub fn send_grid_tag_counts(&self, name: &str, counts: &[(&str, usize)]) {
+        let addr = "/grid/tags".to_string();
+        let mut args = vec![osc::Type::String(name.to_string())];
+        for (tag, count) in counts {
+            args.push(osc::Type::String(tag.to_string()));
+            args.push(osc::Type::Int(*count as i32));
+        }
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_tile_mask(&self, name: &str, x: i32, y: i32, visible: bool) {
+        let addr = "/grid/tilemask".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Int(x),
+            osc::Type::Int(y),
+            osc::Type::Int(visible as i32),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_tile_mask_rect(
+        &self,
+        name: &str,
+        from: (i32, i32),
+        to: (i32, i32),
+        visible: bool,
+    ) {
+        let addr = "/grid/tilemaskrect".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Int(from.0),
+            osc::Type::Int(from.1),
+            osc::Type::Int(to.0),
+            osc::Type::Int(to.1),
+            osc::Type::Int(visible as i32),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_tile_mask_clear(&self, name: &str) {
+        let addr = "/grid/tilemaskclear".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_preset_reload(&self) {
+        let addr = "/preset/reload".to_string();
+        let args = Vec::new();
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_graph_rebuild(&self) {
+        let addr = "/graph/rebuild".to_string();
+        let args = Vec::new();
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_debug_graph(&self, name: &str, enabled: bool, labels: bool) {
+        let addr = "/debug/graph".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Int(enabled as i32),
+            osc::Type::Int(labels as i32),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_flicker(&self, name: &str, rate: f32, depth: f32, duration: f32) {
+        let addr = "/grid/flicker".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(rate),
+            osc::Type::Float(depth),
+            osc::Type::Float(duration),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_noise_color(&self, name: &str, scale: f32, speed: f32, range: f32) {
+        let addr = "/grid/noisecolor".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(scale),
+            osc::Type::Float(speed),
+            osc::Type::Float(range),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_dimmer(&self, name: &str, level: f32) {
+        let addr = "/grid/dimmer".to_string();
+        let args = vec![osc::Type::String(name.to_string()), osc::Type::Float(level)];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_master_brightness(&self, level: f32) {
+        let addr = "/master/brightness".to_string();
+        let args = vec![osc::Type::Float(level)];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_master_blackout(&self, enabled: i32) {
+        let addr = "/master/blackout".to_string();
+        let args = vec![osc::Type::Int(enabled)];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_glow(&self, name: &str, layers: i32, spread: f32, alpha: f32) {
+        let addr = "/grid/glow".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Int(layers),
+            osc::Type::Float(spread),
+            osc::Type::Float(alpha),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_envelope(
+        &self,
+        name: &str,
+        attack: f32,
+        decay: f32,
+        sustain_level: f32,
+        release: f32,
+        hold: f32,
+    ) {
+        let addr = "/grid/envelope".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(attack),
+            osc::Type::Float(decay),
+            osc::Type::Float(sustain_level),
+            osc::Type::Float(release),
+            osc::Type::Float(hold),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_glyph_color_fade(&self, name: &str, r: f32, g: f32, b: f32, a: f32, duration: f32) {
+        let addr = "/grid/glyphcolorfade".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(r),
+            osc::Type::Float(g),
+            osc::Type::Float(b),
+            osc::Type::Float(a),
+            osc::Type::Float(duration),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_gradient(
+        &self,
+        name: &str,
+        color_a: (f32, f32, f32, f32),
+        color_b: (f32, f32, f32, f32),
+        axis: &str,
+    ) {
+        let addr = "/grid/gradient".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(color_a.0),
+            osc::Type::Float(color_a.1),
+            osc::Type::Float(color_a.2),
+            osc::Type::Float(color_a.3),
+            osc::Type::Float(color_b.0),
+            osc::Type::Float(color_b.1),
+            osc::Type::Float(color_b.2),
+            osc::Type::Float(color_b.3),
+            osc::Type::String(axis.to_string()),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_gradient_off(&self, name: &str) {
+        let addr = "/grid/gradientoff".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_outline(&self, name: &str, color: (f32, f32, f32, f32), extra_weight: f32) {
+        let addr = "/grid/outline".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(color.0),
+            osc::Type::Float(color.1),
+            osc::Type::Float(color.2),
+            osc::Type::Float(color.3),
+            osc::Type::Float(extra_weight),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_outline_off(&self, name: &str) {
+        let addr = "/grid/outlineoff".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_bands(
+        &self,
+        name: &str,
+        axis: &str,
+        width: i32,
+        color_a: (f32, f32, f32),
+        color_b: (f32, f32, f32),
+    ) {
+        let addr = "/grid/bands".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::String(axis.to_string()),
+            osc::Type::Int(width),
+            osc::Type::Float(color_a.0),
+            osc::Type::Float(color_a.1),
+            osc::Type::Float(color_a.2),
+            osc::Type::Float(color_b.0),
+            osc::Type::Float(color_b.1),
+            osc::Type::Float(color_b.2),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_bands_off(&self, name: &str) {
+        let addr = "/grid/bandsoff".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_rainbow(&self, name: &str, rate: f32, spread: f32) {
+        let addr = "/grid/rainbow".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(rate),
+            osc::Type::Float(spread),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_pulse(&self, name: &str, period: f32, depth: f32) {
+        let addr = "/grid/pulse".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(period),
+            osc::Type::Float(depth),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_rotate_grid(&self, name: &str, angle: f32) {
+        let addr = "/grid/rotate".to_string();
+        let args = vec![osc::Type::String(name.to_string()), osc::Type::Float(angle)];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_scale_grid(&self, name: &str, scale: f32) {
+        let addr = "/grid/scale".to_string();
+        let args = vec![osc::Type::String(name.to_string()), osc::Type::Float(scale)];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_scale_grid_xy(&self, name: &str, sx: f32, sy: f32) {
+        let addr = "/grid/scalexy".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(sx),
+            osc::Type::Float(sy),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn send_grid_slide(
+        &self,
+        name: &str,
+        axis: &str,
+        number: i32,
+        position: f32,
+        duration: f32,
+        easing: &str,
+        wrap: bool,
+    ) {
+        let addr = "/grid/slide".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::String(axis.to_string()),
+            osc::Type::Int(number),
+            osc::Type::Float(position),
+            osc::Type::Float(duration),
+            osc::Type::String(easing.to_string()),
+            osc::Type::Int(wrap as i32),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_slide_clamp(&self, name: &str, enabled: bool) {
+        let addr = "/grid/slideclamp".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Int(enabled as i32),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_slide_reset(&self, name: &str, duration: f32, easing: &str) {
+        let addr = "/grid/slidereset".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(duration),
+            osc::Type::String(easing.to_string()),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_get(&self, name: &str) {
+        let addr = "/grid/get".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_slide_state_query(&self, name: &str) {
+        let addr = "/grid/slidestate".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
     }
 
-    pub fn send_recorder_start(&self) {
-        let addr = "/recorder/start".to_string();
-        let args = Vec::new();
+    pub fn send_grid_slide_zero(&self, name: &str, duration: f32) {
+        let addr = "/grid/slidezero".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(duration),
+        ];
         self.sender
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
 
-    pub fn send_recorder_stop(&self) {
-        let addr = "/recorder/stop".to_string();
-        let args = Vec::new();
+    pub fn send_grid_snapshot(&self, name: &str, slot: &str) {
+        let addr = "/grid/snapshot".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::String(slot.to_string()),
+        ];
         self.sender
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
 
-    pub fn send_create_grid(&self, name: &str, show: &str, x: f32, y: f32, rotation: f32) {
-        let addr = "/grid/create".to_string();
+    pub fn send_grid_restore(&self, name: &str, slot: &str) {
+        let addr = "/grid/restore".to_string();
         let args = vec![
             osc::Type::String(name.to_string()),
-            osc::Type::String(show.to_string()),
-            osc::Type::Float(x),
-            osc::Type::Float(y),
-            osc::Type::Float(rotation),
+            osc::Type::String(slot.to_string()),
         ];
         self.sender
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
 
-    pub fn send_move_grid(&self, name: &str, x: f32, y: f32, duration: f32) {
-        let addr = "/grid/move".to_string();
+    // feedback: reply to /grid/slidestate with every row and column that has
+    // been slid, zeros included, in the /grid/get offset layout: a count
+    // followed by (index, offset) pairs, rows first, then columns
+    pub fn send_grid_slide_state(
+        &self,
+        name: &str,
+        row_offsets: &[(i32, f32)],
+        col_offsets: &[(i32, f32)],
+    ) {
+        let addr = "/grid/slidestate".to_string();
+        let mut args = vec![osc::Type::String(name.to_string())];
+        for offsets in [row_offsets, col_offsets] {
+            args.push(osc::Type::Int(offsets.len() as i32));
+            for (index, offset) in offsets {
+                args.push(osc::Type::Int(*index));
+                args.push(osc::Type::Float(*offset));
+            }
+        }
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_center(&self, name: &str) {
+        let addr = "/grid/center".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_hittest(&self, name: &str, x: f32, y: f32) {
+        let addr = "/grid/hittest".to_string();
         let args = vec![
             osc::Type::String(name.to_string()),
             osc::Type::Float(x),
             osc::Type::Float(y),
-            osc::Type::Float(duration),
         ];
         self.sender
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
 
-    pub fn send_rotate_grid(&self, name: &str, angle: f32) {
-        let addr = "/grid/rotate".to_string();
-        let args = vec![osc::Type::String(name.to_string()), osc::Type::Float(angle)];
+    // feedback: reply to /grid/hittest with the segment hit and how far it
+    // is from the point. A miss replies with the grid name alone.
+    pub fn send_grid_hit(&self, name: &str, hit: Option<(&str, f32)>) {
+        let addr = "/grid/hittest".to_string();
+        let mut args = vec![osc::Type::String(name.to_string())];
+        if let Some((segment_id, distance)) = hit {
+            args.push(osc::Type::String(segment_id.to_string()));
+            args.push(osc::Type::Float(distance));
+        }
         self.sender
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
 
-    pub fn send_scale_grid(&self, name: &str, scale: f32) {
-        let addr = "/grid/scale".to_string();
-        let args = vec![osc::Type::String(name.to_string()), osc::Type::Float(scale)];
+    pub fn send_palette_list(&self) {
+        let addr = "/palette/list".to_string();
+        let args = Vec::new();
         self.sender
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
 
-    pub fn send_grid_slide(&self, name: &str, axis: &str, number: i32, position: f32) {
-        let addr = "/grid/slide".to_string();
-        let args = vec![
+    // feedback: reply to /palette/list, one message per palette. Each color
+    // is its name followed by r, g, b, a.
+    pub fn send_palette(&self, name: &str, colors: &[(&str, [f32; 4])]) {
+        let addr = "/palette/list".to_string();
+        let mut args = vec![osc::Type::String(name.to_string())];
+        for (color_name, rgba) in colors {
+            args.push(osc::Type::String(color_name.to_string()));
+            args.extend(rgba.iter().map(|channel| osc::Type::Float(*channel)));
+        }
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    // feedback: reply to /grid/get with the grid's transform and the rows and
+    // columns slid away from 0. Offsets are listed as a count followed by
+    // (index, offset) pairs, rows first, then columns. The world-space bounding
+    // box comes last, as left, bottom, right, top.
+    #[allow(clippy::too_many_arguments)]
+    // Scale goes out as the mean of the two axes, where the uniform scale
+    // always was, and then each axis after the bounds
+    pub fn send_grid_state(
+        &self,
+        name: &str,
+        position: (f32, f32),
+        rotation: f32,
+        scale: (f32, f32),
+        row_offsets: &[(i32, f32)],
+        col_offsets: &[(i32, f32)],
+        bounds: [f32; 4],
+    ) {
+        let addr = "/grid/get".to_string();
+        let mut args = vec![
             osc::Type::String(name.to_string()),
-            osc::Type::String(axis.to_string()),
-            osc::Type::Int(number),
-            osc::Type::Float(position),
+            osc::Type::Float(position.0),
+            osc::Type::Float(position.1),
+            osc::Type::Float(rotation),
+            osc::Type::Float((scale.0 * scale.1).sqrt()),
         ];
+        for offsets in [row_offsets, col_offsets] {
+            args.push(osc::Type::Int(offsets.len() as i32));
+            for (index, offset) in offsets {
+                args.push(osc::Type::Int(*index));
+                args.push(osc::Type::Float(*offset));
+            }
+        }
+        args.extend(bounds.map(osc::Type::Float));
+        args.push(osc::Type::Float(scale.0));
+        args.push(osc::Type::Float(scale.1));
         self.sender
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
@@ -527,6 +2687,52 @@ impl OscSender {
             .ok();
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn send_grid_backbone_strobe(
+        &self,
+        grid_name: &str,
+        r: f32,
+        g: f32,
+        b: f32,
+        hz: f32,
+        duty: f32,
+        duration: f32,
+    ) {
+        let addr = "/grid/backbonestrobe".to_string();
+        let args = vec![
+            osc::Type::String(grid_name.to_string()),
+            osc::Type::Float(r),
+            osc::Type::Float(g),
+            osc::Type::Float(b),
+            osc::Type::Float(hz),
+            osc::Type::Float(duty),
+            osc::Type::Float(duration),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_backbone_pulse(&self, name: &str, period: f32, depth: f32) {
+        let addr = "/grid/backbonepulse".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Float(period),
+            osc::Type::Float(depth),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_backbone_pulse_stop(&self, name: &str) {
+        let addr = "/grid/backbonepulse_stop".to_string();
+        let args = vec![osc::Type::String(name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
     pub fn send_grid_backbone_stroke(&self, name: &str, stroke_weight: f32) {
         let addr = "/grid/backbone_stroke".to_string();
         let args = vec![
@@ -538,6 +2744,30 @@ impl OscSender {
             .ok();
     }
 
+    pub fn send_grid_backbone_visible(&self, name: &str, visible: i32, duration: f32) {
+        let addr = "/grid/backbonevisible".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::Int(visible),
+            osc::Type::Float(duration),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_grid_typeweight(&self, name: &str, segment_type: &str, multiplier: f32) {
+        let addr = "/grid/typeweight".to_string();
+        let args = vec![
+            osc::Type::String(name.to_string()),
+            osc::Type::String(segment_type.to_string()),
+            osc::Type::Float(multiplier),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
     pub fn send_glyph(&self, grid_name: &str, index: i32, animation_type_msg: i32) {
         let addr = "/grid/glyph".to_string();
         let args = vec![
@@ -586,6 +2816,38 @@ impl OscSender {
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
+    pub fn send_segment_color(
+        &self,
+        grid_name: &str,
+        segment_id: &str,
+        r: f32,
+        g: f32,
+        b: f32,
+        a: f32,
+    ) {
+        let addr = "/grid/segmentcolor".to_string();
+        let args = vec![
+            osc::Type::String(grid_name.to_string()),
+            osc::Type::String(segment_id.to_string()),
+            osc::Type::Float(r),
+            osc::Type::Float(g),
+            osc::Type::Float(b),
+            osc::Type::Float(a),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+    pub fn send_segment_color_clear(&self, grid_name: &str, segment_id: Option<&str>) {
+        let addr = "/grid/segmentcolorclear".to_string();
+        let mut args = vec![osc::Type::String(grid_name.to_string())];
+        if let Some(id) = segment_id {
+            args.push(osc::Type::String(id.to_string()));
+        }
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
     pub fn send_no_glyph(&self, grid_name: &str, animation_type_msg: i32) {
         let addr = "/grid/noglyph".to_string();
         let args = vec![
@@ -596,6 +2858,33 @@ impl OscSender {
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
+    pub fn send_grid_loop(
+        &self,
+        grid_name: &str,
+        index_a: i32,
+        index_b: i32,
+        hold_time: f32,
+        animation_type_msg: i32,
+    ) {
+        let addr = "/grid/loop".to_string();
+        let args = vec![
+            osc::Type::String(grid_name.to_string()),
+            osc::Type::Int(index_a),
+            osc::Type::Int(index_b),
+            osc::Type::Float(hold_time),
+            osc::Type::Int(animation_type_msg),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+    pub fn send_grid_loop_stop(&self, grid_name: &str) {
+        let addr = "/grid/loopstop".to_string();
+        let args = vec![osc::Type::String(grid_name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
     pub fn send_grid_overwrite(&self, grid_name: &str) {
         let addr = "/grid/overwrite".to_string();
         let args = vec![osc::Type::String(grid_name.to_string())];
@@ -634,6 +2923,16 @@ impl OscSender {
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
+    pub fn send_colorful_mode(&self, grid_name: &str, mode: &str) {
+        let addr = "/grid/colorfulmode".to_string();
+        let args = vec![
+            osc::Type::String(grid_name.to_string()),
+            osc::Type::String(mode.to_string()),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
     pub fn send_set_power_effect(&self, grid_name: &str, setting: i32) {
         let addr = "/grid/setpowereffect".to_string();
         let args = vec![
@@ -644,6 +2943,82 @@ impl OscSender {
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
+    pub fn send_set_reverse_erase(&self, grid_name: &str, setting: i32) {
+        let addr = "/grid/setreverseerase".to_string();
+        let args = vec![
+            osc::Type::String(grid_name.to_string()),
+            osc::Type::Int(setting),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+    pub fn send_set_stroke_tint(&self, grid_name: &str, setting: i32) {
+        let addr = "/grid/setstroketint".to_string();
+        let args = vec![
+            osc::Type::String(grid_name.to_string()),
+            osc::Type::Int(setting),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+    pub fn send_set_crossfade(&self, grid_name: &str, setting: i32) {
+        let addr = "/grid/setcrossfade".to_string();
+        let args = vec![
+            osc::Type::String(grid_name.to_string()),
+            osc::Type::Int(setting),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+    pub fn send_transition_pause(&self, grid_name: &str, paused: i32) {
+        let addr = "/grid/transitionpause".to_string();
+        let args = vec![
+            osc::Type::String(grid_name.to_string()),
+            osc::Type::Int(paused),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+    pub fn send_transition_progress_query(&self, grid_name: &str) {
+        let addr = "/grid/transitionprogress".to_string();
+        let args = vec![osc::Type::String(grid_name.to_string())];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+    // feedback: reply to /grid/transitionprogress
+    pub fn send_transition_progress(
+        &self,
+        grid_name: &str,
+        current_step: i32,
+        total_steps: i32,
+        animation_type: &str,
+    ) {
+        let addr = "/grid/transitionprogress".to_string();
+        let args = vec![
+            osc::Type::String(grid_name.to_string()),
+            osc::Type::Int(current_step),
+            osc::Type::Int(total_steps),
+            osc::Type::String(animation_type.to_string()),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+    pub fn send_grid_speed(&self, grid_name: &str, speed: f32) {
+        let addr = "/grid/speed".to_string();
+        let args = vec![
+            osc::Type::String(grid_name.to_string()),
+            osc::Type::Float(speed),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
     pub fn send_background_flash(&self, r: f32, g: f32, b: f32, duration: f32) {
         let addr = "/background/flash".to_string();
         let args = vec![
@@ -668,6 +3043,26 @@ impl OscSender {
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
+
+    pub fn send_background_persistence(&self, persistence: f32) {
+        let addr = "/background/persistence".to_string();
+        let args = vec![osc::Type::Float(persistence)];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_background_image(&self, path: &str, opacity: Option<f32>) {
+        let addr = "/background/image".to_string();
+        let mut args = vec![osc::Type::String(path.to_string())];
+        if let Some(opacity) = opacity {
+            args.push(osc::Type::Float(opacity));
+        }
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
     pub fn send_update_transition_config(
         &self,
         grid_name: &str,
@@ -675,6 +3070,7 @@ impl OscSender {
         frame_duration: Option<f32>,
         wandering: Option<f32>,
         density: Option<f32>,
+        stroke_gap_steps: Option<usize>,
     ) {
         let addr = "/transition/update".to_string();
         let mut args = vec![osc::Type::String(grid_name.to_string())];
@@ -692,9 +3088,102 @@ impl OscSender {
         if let Some(d) = density {
             args.push(osc::Type::Float(d));
         }
+        if let Some(g) = stroke_gap_steps {
+            args.push(osc::Type::Int(g as i32));
+        }
+
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
 
+    // feedback: reply to /transition/update with the values the grid's
+    // transitions now use: steps, frame duration, wandering, density and
+    // stroke gap steps
+    pub fn send_transition_config(&self, grid_name: &str, config: &TransitionConfig) {
+        let addr = "/transition/update".to_string();
+        let args = vec![
+            osc::Type::String(grid_name.to_string()),
+            osc::Type::Int(config.steps as i32),
+            osc::Type::Float(config.frame_duration),
+            osc::Type::Float(config.wandering),
+            osc::Type::Float(config.density),
+            osc::Type::Int(config.stroke_gap_steps as i32),
+        ];
+        self.sender
+            .send((addr, args), (self.target_addr.as_str(), self.target_port))
+            .ok();
+    }
+
+    pub fn send_transition_seed(&self, seed: Option<i64>) {
+        let addr = "/transition/seed".to_string();
+        let args = seed.map(osc::Type::Long).into_iter().collect();
         self.sender
             .send((addr, args), (self.target_addr.as_str(), self.target_port))
             .ok();
     }
 }
+
+// recording, paused, frames captured, in queue, processed and dropped, seconds
+// recorded, the take's path (empty when there's no take), then seconds in the
+// replay buffer
+fn recorder_status_args(status: &RecorderStatus) -> Vec<osc::Type> {
+    vec![
+        osc::Type::Int(status.recording as i32),
+        osc::Type::Int(status.paused as i32),
+        osc::Type::Int(status.frames_captured as i32),
+        osc::Type::Int(status.frames_in_queue as i32),
+        osc::Type::Int(status.frames_processed as i32),
+        osc::Type::Int(status.frames_dropped as i32),
+        osc::Type::Float(status.elapsed),
+        osc::Type::String(
+            status
+                .take_path
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        ),
+        osc::Type::Float(status.replay_buffered),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_recorder_status_args() {
+        let mut status = RecorderStatus {
+            recording: true,
+            paused: false,
+            frames_captured: 90,
+            frames_in_queue: 4,
+            frames_processed: 86,
+            frames_dropped: 2,
+            elapsed: 3.0,
+            take_path: Some(PathBuf::from("output/glyphvis_20240229_123456.mp4")),
+            replay_buffered: 0.0,
+        };
+        assert_eq!(
+            recorder_status_args(&status),
+            vec![
+                osc::Type::Int(1),
+                osc::Type::Int(0),
+                osc::Type::Int(90),
+                osc::Type::Int(4),
+                osc::Type::Int(86),
+                osc::Type::Int(2),
+                osc::Type::Float(3.0),
+                osc::Type::String("output/glyphvis_20240229_123456.mp4".to_string()),
+                osc::Type::Float(0.0),
+            ]
+        );
+
+        status.take_path = None;
+        assert_eq!(
+            recorder_status_args(&status)[7],
+            osc::Type::String(String::new())
+        );
+    }
+}
@@ -0,0 +1,243 @@
+// src/effects/active_fx.rs
+// these effects are applied to the active segments of a grid (the lit glyph).
+
+use super::ActiveEffect;
+use crate::models::SegmentKey;
+use crate::views::DrawStyle;
+use nannou::noise::{NoiseFn, Seedable, SuperSimplex};
+use nannou::prelude::*;
+use rand::{rngs::StdRng, Rng};
+use std::collections::{HashMap, HashSet};
+
+// Periodic brightness pulse. At depth 1.0 the glyph dims all the way to
+// black at the bottom of each period; at depth 0.0 it does nothing.
+pub struct ActivePulseEffect {
+    pub period: f32,
+    pub depth: f32,
+    pub start_time: f32,
+}
+
+impl ActivePulseEffect {
+    pub fn new(period: f32, depth: f32, start_time: f32) -> Self {
+        Self {
+            period: period.max(0.001),
+            depth: depth.clamp(0.0, 1.0),
+            start_time,
+        }
+    }
+}
+
+impl ActiveEffect for ActivePulseEffect {
+    fn update(&self, style: &DrawStyle, time: f32) -> DrawStyle {
+        // start the cycle at full brightness
+        let phase = (time - self.start_time) / self.period * TAU;
+        let brightness = 1.0 - self.depth * (0.5 - 0.5 * phase.cos());
+
+        let color = Hsla::from(style.color);
+        DrawStyle {
+            color: Rgba::from(Hsla::new(
+                color.hue,
+                color.saturation,
+                color.lightness * brightness,
+                color.alpha,
+            )),
+            stroke_weight: style.stroke_weight,
+        }
+    }
+
+    // this is a continuous effect
+    fn is_finished(&self, _time: f32) -> bool {
+        false
+    }
+}
+
+// Moves the glyph color toward a goal over a duration. GridInstance applies
+// it to target_style itself, so the faded color sticks once it's done.
+pub struct ActiveFadeEffect {
+    pub from_color: Rgba,
+    pub to_color: Rgba,
+    pub duration: f32,
+    pub start_time: f32,
+}
+
+impl ActiveFadeEffect {
+    pub fn new(from_color: Rgba, to_color: Rgba, duration: f32, start_time: f32) -> Self {
+        Self {
+            from_color,
+            to_color,
+            duration,
+            start_time,
+        }
+    }
+}
+
+impl ActiveEffect for ActiveFadeEffect {
+    fn update(&self, style: &DrawStyle, time: f32) -> DrawStyle {
+        let t = if self.duration <= 0.0 {
+            1.0
+        } else {
+            ((time - self.start_time) / self.duration).clamp(0.0, 1.0)
+        };
+
+        let (from, to) = (self.from_color, self.to_color);
+        DrawStyle {
+            color: rgba(
+                from.red + (to.red - from.red) * t,
+                from.green + (to.green - from.green) * t,
+                from.blue + (to.blue - from.blue) * t,
+                from.alpha + (to.alpha - from.alpha) * t,
+            ),
+            stroke_weight: style.stroke_weight,
+        }
+    }
+
+    fn is_finished(&self, time: f32) -> bool {
+        time - self.start_time >= self.duration
+    }
+}
+
+// "Failing neon": each active segment drops out now and then on its own
+// schedule. Unlike the other effects this one is per segment, so GridInstance
+// asks it which segments are out each frame rather than folding it into a
+// shared style.
+pub struct FlickerEffect {
+    pub rate: f32,     // dropouts per second, per segment, on average
+    pub depth: f32,    // 1.0 drops a segment to black
+    pub duration: f32, // seconds a dropout lasts
+    schedule: HashMap<SegmentKey, FlickerTimes>,
+    rng: StdRng,
+}
+
+struct FlickerTimes {
+    next_dropout: f32,
+    recover_at: f32,
+}
+
+impl FlickerEffect {
+    pub fn new(rate: f32, depth: f32, duration: f32, rng: StdRng) -> Self {
+        Self {
+            rate: rate.max(0.0),
+            depth: depth.clamp(0.0, 1.0),
+            duration: duration.max(0.0),
+            schedule: HashMap::new(),
+            rng,
+        }
+    }
+
+    // Advances every segment's schedule to `time`. Segments that are no
+    // longer lit are forgotten.
+    pub fn update(&mut self, segments: &HashSet<SegmentKey>, time: f32) {
+        self.schedule
+            .retain(|segment_id, _| segments.contains(segment_id));
+
+        // fixed order so a seeded rng always makes the same choices
+        let mut segment_ids: Vec<&SegmentKey> = segments.iter().collect();
+        segment_ids.sort();
+
+        for segment_id in segment_ids {
+            match self.schedule.get(segment_id) {
+                None => {
+                    let next_dropout = time + self.wait();
+                    self.schedule.insert(
+                        segment_id.clone(),
+                        FlickerTimes {
+                            next_dropout,
+                            recover_at: f32::MIN,
+                        },
+                    );
+                }
+                Some(times) if time >= times.next_dropout => {
+                    let recover_at = time + self.duration;
+                    let next_dropout = recover_at + self.wait();
+                    self.schedule.insert(
+                        segment_id.clone(),
+                        FlickerTimes {
+                            next_dropout,
+                            recover_at,
+                        },
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    pub fn is_dropped(&self, segment_id: &SegmentKey, time: f32) -> bool {
+        self.schedule
+            .get(segment_id)
+            .is_some_and(|times| time < times.recover_at)
+    }
+
+    pub fn dim(&self, style: &DrawStyle) -> DrawStyle {
+        let color = Hsla::from(style.color);
+        DrawStyle {
+            color: Rgba::from(Hsla::new(
+                color.hue,
+                color.saturation,
+                color.lightness * (1.0 - self.depth),
+                color.alpha,
+            )),
+            stroke_weight: style.stroke_weight,
+        }
+    }
+
+    // Exponentially distributed, so dropouts arrive at `rate` on average
+    // with no rhythm to them
+    fn wait(&mut self) -> f32 {
+        if self.rate <= 0.0 {
+            return f32::MAX;
+        }
+        let u: f32 = self.rng.gen_range(f32::EPSILON..1.0);
+        -u.ln() / self.rate
+    }
+}
+
+// Slow organic color drift. Each segment samples noise at its grid-local
+// position, moving through the noise field over time, and shifts its hue by
+// up to hue_range degrees either way, with a gentler lightness shift.
+pub struct NoiseColorEffect {
+    pub scale: f32,      // noise features across the grid's longest side
+    pub speed: f32,      // how fast the field drifts, in noise units per second
+    pub hue_range: f32,  // degrees either side of the base hue
+    noise: SuperSimplex, // OpenSimplex in this noise version has visible seams
+}
+
+// Lightness swing, relative to the base, at a half-turn hue range
+const NOISE_LIGHTNESS_DEPTH: f32 = 0.25;
+
+// Offset into the noise field for the lightness sample, so it moves
+// independently of the hue
+const NOISE_LIGHTNESS_OFFSET: f64 = 31.7;
+
+impl NoiseColorEffect {
+    pub fn new(scale: f32, speed: f32, hue_range: f32, seed: u32) -> Self {
+        Self {
+            scale: scale.max(0.0),
+            speed,
+            hue_range: hue_range.clamp(0.0, 180.0),
+            noise: SuperSimplex::new().set_seed(seed),
+        }
+    }
+
+    // `position` is grid-local, 0.0 to 1.0 along the grid's longest side
+    pub fn shift(&self, style: &DrawStyle, position: Vec2, time: f32) -> DrawStyle {
+        let x = (position.x * self.scale) as f64;
+        let y = (position.y * self.scale) as f64;
+        let z = (time * self.speed) as f64;
+
+        let hue_noise = self.noise.get([x, y, z]) as f32;
+        let lightness_noise = self.noise.get([x + NOISE_LIGHTNESS_OFFSET, y, z]) as f32;
+
+        let color = Hsla::from(style.color);
+        let depth = NOISE_LIGHTNESS_DEPTH * self.hue_range / 180.0;
+        DrawStyle {
+            color: Rgba::from(Hsla::new(
+                color.hue.to_degrees() + hue_noise * self.hue_range,
+                color.saturation,
+                (color.lightness * (1.0 + lightness_noise * depth)).clamp(0.0, 1.0),
+                color.alpha,
+            )),
+            stroke_weight: style.stroke_weight,
+        }
+    }
+}
@@ -2,30 +2,46 @@
 // these effects are applied to sets of segments, like Glyphs and Grids.
 
 use super::BackboneEffect;
+use crate::models::SegmentKey;
 use crate::views::DrawStyle;
 use nannou::prelude::*;
+use rand::{rngs::StdRng, Rng};
+use std::collections::HashMap;
 
+// Slow "breathing" of the unlit grid. At depth 1.0 the backbone dims all
+// the way to black at the bottom of each period; at depth 0.0 it does nothing.
+// Runs until removed.
 pub struct PulseEffect {
-    pub frequency: f32,
-    pub min_brightness: f32,
-    pub max_brightness: f32,
+    pub period: f32,
+    pub depth: f32,
+    pub start_time: f32,
+}
+
+impl PulseEffect {
+    pub fn new(period: f32, depth: f32, start_time: f32) -> Self {
+        Self {
+            period: period.max(0.001),
+            depth: depth.clamp(0.0, 1.0),
+            start_time,
+        }
+    }
 }
 
 impl BackboneEffect for PulseEffect {
-    fn update(&self, start_style: &DrawStyle, time: f32) -> DrawStyle {
-        let brightness = (time * self.frequency).sin() * 0.5 + 0.5;
-        let brightness =
-            self.min_brightness + brightness * (self.max_brightness - self.min_brightness);
+    fn update(&self, style: &DrawStyle, time: f32) -> DrawStyle {
+        // start the cycle at full brightness
+        let phase = (time - self.start_time) / self.period * TAU;
+        let brightness = 1.0 - self.depth * (0.5 - 0.5 * phase.cos());
 
-        let color = start_style.color;
+        let color = Hsla::from(style.color);
         DrawStyle {
-            color: rgba(
-                color.red * brightness,
-                color.green * brightness,
-                color.blue * brightness,
+            color: Rgba::from(Hsla::new(
+                color.hue,
+                color.saturation,
+                color.lightness * brightness,
                 color.alpha,
-            ),
-            stroke_weight: start_style.stroke_weight,
+            )),
+            stroke_weight: style.stroke_weight,
         }
     }
 
@@ -33,6 +49,10 @@ impl BackboneEffect for PulseEffect {
     fn is_finished(&self, _time: f32) -> bool {
         false
     }
+
+    fn is_modulation(&self) -> bool {
+        true
+    }
 }
 
 pub struct ColorCycleEffect {
@@ -54,6 +74,10 @@ impl BackboneEffect for ColorCycleEffect {
     fn is_finished(&self, _time: f32) -> bool {
         false
     }
+
+    fn is_modulation(&self) -> bool {
+        true
+    }
 }
 
 pub struct FadeEffect {
@@ -100,3 +124,224 @@ impl BackboneEffect for FadeEffect {
         elapsed > self.duration
     }
 }
+
+// Fastest strobe allowed, kept under the range most likely to trigger
+// photosensitive seizures.
+pub const MAX_STROBE_HZ: f32 = 25.0;
+
+// Alternates the backbone between its base color and a target color. Each
+// cycle starts on the target color and holds it for `duty` of the period.
+pub struct StrobeEffect {
+    pub base_style: DrawStyle,
+    pub target_color: Rgba,
+    pub hz: f32,
+    pub duty: f32,
+    pub duration: f32,
+    pub start_time: f32,
+}
+
+impl StrobeEffect {
+    pub fn new(
+        base_style: DrawStyle,
+        target_color: Rgba,
+        hz: f32,
+        duty: f32,
+        duration: f32,
+        start_time: f32,
+    ) -> Self {
+        Self {
+            base_style,
+            target_color,
+            hz: hz.clamp(0.0, MAX_STROBE_HZ),
+            duty: duty.clamp(0.0, 1.0),
+            duration,
+            start_time,
+        }
+    }
+}
+
+impl BackboneEffect for StrobeEffect {
+    fn update(&self, current_style: &DrawStyle, time: f32) -> DrawStyle {
+        let elapsed = time - self.start_time;
+
+        // settle back on the pre-strobe color once the strobe is over
+        let color = if elapsed < 0.0 || elapsed >= self.duration {
+            self.base_style.color
+        } else if (elapsed * self.hz).fract() < self.duty {
+            self.target_color
+        } else {
+            self.base_style.color
+        };
+
+        DrawStyle {
+            color,
+            ..*current_style
+        }
+    }
+
+    fn is_finished(&self, time: f32) -> bool {
+        time - self.start_time >= self.duration
+    }
+}
+
+// Glitter on the unlit grid: random backbone segments light up briefly while
+// the glyph stays as it is. GridInstance stages the segments on and off; this
+// only decides which ones and when. Sparkles are never part of the glyph.
+pub struct SparkleEffect {
+    pub rate: f32,     // new sparkles per second, across the whole grid
+    pub lifetime: f32, // seconds each one stays lit
+    pub color: Rgba,
+    live: HashMap<SegmentKey, f32>, // segment id -> time it goes out
+    owed: f32,                      // fraction of a sparkle carried to the next frame
+    rng: StdRng,
+}
+
+impl SparkleEffect {
+    pub fn new(rate: f32, lifetime: f32, color: Rgba, rng: StdRng) -> Self {
+        Self {
+            rate: rate.max(0.0),
+            lifetime: lifetime.max(0.0),
+            color,
+            live: HashMap::new(),
+            owed: 0.0,
+            rng,
+        }
+    }
+
+    // Picks this frame's new sparkles from `candidates`, which should be
+    // sorted so a seeded rng always makes the same choices
+    pub fn spawn(&mut self, candidates: &[SegmentKey], dt: f32, time: f32) -> Vec<SegmentKey> {
+        self.owed += self.rate * dt;
+        let mut candidates: Vec<&SegmentKey> = candidates
+            .iter()
+            .filter(|segment_id| !self.live.contains_key(*segment_id))
+            .collect();
+
+        let mut started = Vec::new();
+        while self.owed >= 1.0 && !candidates.is_empty() {
+            self.owed -= 1.0;
+            let segment_id = candidates.swap_remove(self.rng.gen_range(0..candidates.len()));
+            self.live.insert(segment_id.clone(), time + self.lifetime);
+            started.push(segment_id.clone());
+        }
+        // nothing left to light, so don't save them up
+        self.owed = self.owed.min(1.0);
+        started
+    }
+
+    // Sparkles whose time is up, no longer tracked
+    pub fn expire(&mut self, time: f32) -> Vec<SegmentKey> {
+        let mut expired: Vec<SegmentKey> = self
+            .live
+            .iter()
+            .filter(|(_, out_at)| time >= **out_at)
+            .map(|(segment_id, _)| segment_id.clone())
+            .collect();
+        expired.sort();
+        for segment_id in &expired {
+            self.live.remove(segment_id);
+        }
+        expired
+    }
+
+    // Stops tracking a sparkle without it going out, e.g. when a glyph takes
+    // the segment over
+    pub fn forget(&mut self, segment_id: &SegmentKey) -> bool {
+        self.live.remove(segment_id).is_some()
+    }
+
+    pub fn is_sparkling(&self, segment_id: &SegmentKey) -> bool {
+        self.live.contains_key(segment_id)
+    }
+
+    // Every live sparkle, no longer tracked
+    pub fn clear(&mut self) -> Vec<SegmentKey> {
+        self.owed = 0.0;
+        self.live
+            .drain()
+            .map(|(segment_id, _)| segment_id)
+            .collect()
+    }
+}
+
+// A short run of lit segments travelling along a path through the segment
+// graph, start to end and round again until stopped. Like sparkles, the
+// chase is an overlay: GridInstance lights the window, not the glyph.
+pub struct ChaseEffect {
+    path: Vec<SegmentKey>,
+    pub speed: f32,   // segments per second
+    pub width: usize, // segments lit at once
+    start_time: f32,
+}
+
+impl ChaseEffect {
+    pub fn new(path: Vec<SegmentKey>, speed: f32, width: usize, time: f32) -> Self {
+        Self {
+            path,
+            speed: speed.max(0.0),
+            width: width.max(1),
+            start_time: time,
+        }
+    }
+
+    pub fn path(&self) -> &[SegmentKey] {
+        &self.path
+    }
+
+    // The segments lit at `time`. The head enters at the start and the tail
+    // leaves at the end before the next pass begins.
+    pub fn window(&self, time: f32) -> &[SegmentKey] {
+        if self.path.is_empty() {
+            return &[];
+        }
+        let cycle = self.path.len() + self.width - 1;
+        let steps = ((time - self.start_time).max(0.0) * self.speed) as usize;
+        let head = steps % cycle;
+        let end = (head + 1).min(self.path.len());
+        let start = (head + 1).saturating_sub(self.width).min(end);
+        &self.path[start..end]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chase_window_runs_off_the_end_and_repeats() {
+        let path: Vec<SegmentKey> = ["a", "b", "c", "d"].map(SegmentKey::off_grid).to_vec();
+        let chase = ChaseEffect::new(path, 1.0, 2, 0.0);
+        let window = |time| {
+            chase
+                .window(time)
+                .iter()
+                .map(|id| id.element.as_str())
+                .collect::<String>()
+        };
+
+        assert_eq!(window(0.0), "a");
+        assert_eq!(window(1.0), "ab");
+        assert_eq!(window(3.5), "cd");
+        assert_eq!(window(4.0), "d");
+        assert_eq!(window(5.0), "a");
+    }
+
+    #[test]
+    fn test_strobe_clamps_rate_and_settles() {
+        let base = DrawStyle {
+            color: rgba(0.2, 0.2, 0.2, 1.0),
+            stroke_weight: 2.0,
+        };
+        let target = rgba(1.0, 1.0, 1.0, 1.0);
+        let strobe = StrobeEffect::new(base.clone(), target, 100.0, 0.5, 1.0, 0.0);
+        assert_eq!(strobe.hz, MAX_STROBE_HZ);
+
+        // on for the first half of each 40ms period
+        assert_eq!(strobe.update(&base, 0.01).color, target);
+        assert_eq!(strobe.update(&base, 0.03).color, base.color);
+
+        assert!(strobe.is_finished(1.0));
+        let lit = strobe.update(&base, 0.01);
+        assert_eq!(strobe.update(&lit, 1.0).color, base.color);
+    }
+}
@@ -1,15 +1,31 @@
 use crate::views::DrawStyle;
 use nannou::prelude::*;
 
+pub mod active_fx;
 pub mod backbone_fx;
 pub mod background_fx;
 
-pub use backbone_fx::FadeEffect;
+pub use active_fx::{ActiveFadeEffect, ActivePulseEffect, FlickerEffect, NoiseColorEffect};
+pub use backbone_fx::{ChaseEffect, FadeEffect, PulseEffect, SparkleEffect, StrobeEffect};
 pub use background_fx::{BackgroundColorFade, BackgroundFlash};
 
 pub trait BackboneEffect {
     fn update(&self, style: &DrawStyle, time: f32) -> DrawStyle;
     fn is_finished(&self, time: f32) -> bool;
+
+    // Modulations are layered over the settled backbone style every frame
+    // instead of being written into it, so they don't compound and the
+    // backbone returns to its settled style when they're removed.
+    fn is_modulation(&self) -> bool {
+        false
+    }
+}
+
+// The foreground analogue of BackboneEffect: modulates the style of a grid's
+// active segments on top of its target_style.
+pub trait ActiveEffect {
+    fn update(&self, style: &DrawStyle, time: f32) -> DrawStyle;
+    fn is_finished(&self, time: f32) -> bool;
 }
 
 pub trait BackgroundEffect {
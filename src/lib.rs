@@ -7,3 +7,6 @@ pub mod models;
 pub mod services;
 pub mod utilities;
 pub mod views;
+
+#[cfg(test)]
+mod test_helpers;
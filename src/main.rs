@@ -1,29 +1,42 @@
 // src/main.rs
 use nannou::prelude::*;
-use rand::Rng;
+use rand::{rngs::StdRng, Rng};
+use serde::Serialize;
 use std::{
     collections::HashMap,
     io::{self, Write},
+    path::PathBuf,
     rc::Rc,
     time::Instant,
 };
 
 use glyphvis::{
     animation::{
-        EasingType, MovementEngine, TransitionAnimationType, TransitionEngine,
-        TransitionTriggerType,
+        EasingType, Keyframe, MovementEngine, MovementLeg, TransitionAnimationType,
+        TransitionEngine, TransitionTriggerType, INSTANT_SLIDE_DURATION,
     },
     config::*,
-    controllers::{OscCommand, OscController, OscSender},
-    effects::FadeEffect,
+    controllers::{
+        mouse, AnimationArgs, Automation, ColorArg, MouseEditor, OscCommand, OscController,
+        OscSender,
+    },
+    effects::{
+        FadeEffect, FlickerEffect, NoiseColorEffect, PulseEffect, SparkleEffect, StrobeEffect,
+    },
     models::{Axis, Project},
-    services::{FrameRecorder, SegmentGraph},
-    views::{BackgroundManager, CachedGrid, DrawStyle, GridInstance},
+    services::{Clock, FrameRecorder, OutputMode, SegmentGraph},
+    views::{
+        grid_array_layout, BackgroundManager, BandStyle, CachedGrid, ColorfulMode, DrawStyle,
+        Envelope, GlowParams, GradientStyle, GridInstance, GridPool, GridSnapshot, OutlineParams,
+        SegmentType,
+    },
 };
 
 struct Model {
     // Data from the Project file including all Glyph definitions
     project: Project,
+    project_path: PathBuf,
+    project_hash: String, // tells apart edits of the same project file in take metadata
 
     // Grids are the primary logical units that get rendered. A grid is a virtual segmented display for Hangeul characters.
     // By lighting up sets of segments, different characters are displayed.
@@ -36,13 +49,22 @@ struct Model {
     // The Graph is the network of connections between segments. This is shared among Grids
     // of the same type as it is read-only.
     base_graph: Rc<SegmentGraph>,
+    graph_cache_path: PathBuf, // rewritten by /graph/rebuild
+    graph_cache_key: String,
 
     // A GridInstance manages the state of an individual grid and sends commands to its internal segments to turn on or off,
     // or display different colors.
     //
     // When a GridInstance is created, a Show from the Project file is attached. The GridInstance is hidden by default until it receives a command
-    // to be shown. A GridInstance cannot be destroyed once created.
+    // to be shown. A destroyed GridInstance's grid goes to the pool, and the next one created
+    // takes it from there rather than cloning base_grid. Pooled grids are copies of base_grid,
+    // so the pool is cleared whenever that's replaced.
     grids: HashMap<String, GridInstance>, //(grid_id : GridInstance)
+    grid_pool: GridPool,
+    // Grids made together by /grid/array, by the array's base name
+    grid_groups: HashMap<String, Vec<String>>,
+    // Saved by /grid/snapshot, by (grid name, slot)
+    grid_snapshots: HashMap<(String, String), GridSnapshot>,
 
     // BackgroundManager handles Background color state
     background: BackgroundManager,
@@ -58,6 +80,11 @@ struct Model {
     // Keyboard commands (with a few exceptions) use the internal OSC sender to execute commands.
     osc_sender: OscSender,
 
+    // Replies and notifications to show controllers, when a feedback port is configured
+    osc_feedback: Option<OscSender>,
+    last_heartbeat: f32,
+    reported_take: Option<PathBuf>, // the take controllers were last told of
+
     // Rendering components:
     //
     // The full-resolution texture that is drawn every frame
@@ -70,23 +97,46 @@ struct Model {
     // The reshaper is used to resize the texture for the screen monitor display
     texture_reshaper: wgpu::TextureReshaper,
 
-    // A random number generator
-    random: rand::rngs::ThreadRng,
+    // A random number generator, seeded along with the transition engine
+    random: StdRng,
 
     // Segment default style as stored in config.toml
     // Need it here to pass into GridInstance when a Grid is created.
     default_stroke_weight: f32,
     default_backbone_stroke_weight: f32,
+    default_glow: Option<GlowParams>,
+    default_envelope: Envelope,
+    default_type_weights: HashMap<SegmentType, f32>,
+
+    // Brand palettes from config.toml, for colors sent as "palette:NAME:INDEX"
+    palettes: Palettes,
+
+    // Effect presets from the presets directory, reloaded by /preset/reload
+    presets: HashMap<String, EffectPreset>,
+    presets_dir: PathBuf,
 
     // Frame recorder service saves JPGs of full resolution textures at 30fps
     frame_recorder: FrameRecorder,
 
+    // Set when recording at a different resolution from the preview texture
+    capture_target: Option<CaptureTarget>,
+
+    // Set by /recorder/screenshot, a still is captured on the next render
+    screenshot_requested: bool,
+
     // Tracks if a Quit command has been issued, for a graceful exit that waits
     // for all queued framees to finish saving before halting the program
     exit_requested: bool,
 
+    // Time base for animations
+    clock: Clock,
+
+    // Master fader over everything drawn. Blackout holds it at zero
+    // without losing the fader's position.
+    master_brightness: f32,
+    blackout: bool,
+
     // FPS
-    last_update: Instant,
     fps: f32,
     fps_update_interval: f32,
     last_fps_display_update: f32,
@@ -95,28 +145,141 @@ struct Model {
 
     // When on, displays more verbose messages in the terminal
     debug_flag: bool,
+
+    // Clicking segments to design glyphs, toggled with the K key
+    mouse_editor: MouseEditor,
+
+    // Set by --render, None when running live
+    offline: Option<OfflineRender>,
+}
+
+// The scene drawn a second time at the recording resolution, into a Draw of
+// its own: rendering a Draw consumes its commands, so the preview's can't be
+// rendered again. The preview texture and window are left alone.
+struct CaptureTarget {
+    texture: wgpu::Texture,
+    renderer: nannou::draw::Renderer,
+    scale_factor: f32,
+    draw: Draw,
+}
+
+// The scale factor the scene is drawn to the preview texture at
+const TEXTURE_SCALE_FACTOR: f32 = 2.0;
+
+// A non-realtime render: commands come from an automation file instead of the
+// network, and each update steps the clock by exactly one recorded frame.
+struct OfflineRender {
+    automation: Automation,
+    automation_path: PathBuf,
+    frames: u32, // to render in all
+    frames_rendered: u32,
 }
 
+// How far from a segment /grid/hittest still counts as a hit, in texture
+// pixels, when the query doesn't say
+const HIT_TEST_TOLERANCE: f32 = 10.0;
+
+// Used when rendering offline with no transition seed configured, so two
+// renders of the same automation come out the same
+const OFFLINE_SEED: u64 = 0;
+
 fn main() {
     nannou::app(model).update(update).run();
 }
 
+// `glyphvis --render <automation_file> <duration>` renders offline
+fn render_args() -> Option<(PathBuf, f32)> {
+    let args: Vec<String> = std::env::args().collect();
+    let position = args.iter().position(|arg| arg == "--render")?;
+    match (args.get(position + 1), args.get(position + 2)) {
+        (Some(path), Some(duration)) => match duration.parse::<f32>() {
+            Ok(duration) if duration > 0.0 => Some((PathBuf::from(path), duration)),
+            _ => {
+                eprintln!("Render duration must be a positive number of seconds");
+                std::process::exit(2);
+            }
+        },
+        _ => {
+            eprintln!("Usage: glyphvis --render <automation_file> <duration>");
+            std::process::exit(2);
+        }
+    }
+}
+
 fn model(app: &App) -> Model {
     // Load config
     let config = Config::load().expect("Failed to load config file");
 
+    let offline = render_args().map(|(path, duration)| {
+        let automation = Automation::load(&path).unwrap_or_else(|err| {
+            eprintln!("Automation file {} failed to load: {}", path.display(), err);
+            std::process::exit(2);
+        });
+        let frames = (duration * config.frame_recorder.fps as f32).round() as u32;
+        if frames == 0 {
+            eprintln!("Render duration must be at least one frame long");
+            std::process::exit(2);
+        }
+        println!(
+            "Rendering {} frames from {} ({} messages)",
+            frames,
+            path.display(),
+            automation.len()
+        );
+        OfflineRender {
+            automation,
+            automation_path: path,
+            frames,
+            frames_rendered: 0,
+        }
+    });
+
     // Load project & config
     let project_path = config.resolve_project_path();
-    let project = Project::load(project_path).expect("Failed to load project file");
+    let project = Project::load(&project_path).expect("Failed to load project file");
+    let project_hash = std::fs::read(&project_path)
+        .map(|bytes| fnv1a_hex(&bytes))
+        .unwrap_or_default();
 
-    // Cache grid draw instructions and the segment graph
+    // Cache grid draw instructions and the segment graph. The graph is saved
+    // between runs under a hash of the tile and grid size.
     let base_grid = CachedGrid::new(&project);
-    let base_graph = Rc::new(SegmentGraph::new(&base_grid));
+    let connection_threshold = config.paths.connection_threshold;
+    let graph_cache_key = fnv1a_hex(
+        format!(
+            "{}\n{}x{}\n{}",
+            project.svg_base_tile, project.grid_x, project.grid_y, connection_threshold
+        )
+        .as_bytes(),
+    );
+    let graph_cache_path = config
+        .resolve_output_dir()
+        .join("cache")
+        .join(format!("segment_graph_{}.json", graph_cache_key));
+    let rebuild_cache = std::env::args().any(|arg| arg == "--rebuild-cache");
+    let base_graph = Rc::new(SegmentGraph::with_cache(
+        &base_grid,
+        &graph_cache_path,
+        &graph_cache_key,
+        connection_threshold,
+        rebuild_cache,
+    ));
+
+    // Load effect presets
+    let presets_dir = config.resolve_presets_dir();
+    let mut presets = HashMap::new();
+    load_presets(&presets_dir, &mut presets);
 
     // Create OSC controller
-    let osc_controller =
-        OscController::new(config.osc.rx_port).expect("Failed to create OSC Controller");
+    let osc_controller = match offline {
+        Some(_) => OscController::offline(),
+        None => OscController::new(config.osc.rx_port).expect("Failed to create OSC Controller"),
+    };
     let osc_sender = OscSender::new(config.osc.rx_port).expect("Failed to create OSC Sender");
+    let osc_feedback = config
+        .osc
+        .feedback_port
+        .map(|port| OscSender::new(port).expect("Failed to create OSC feedback sender"));
 
     // Create window
     let window_id = app
@@ -126,6 +289,10 @@ fn model(app: &App) -> Model {
         .msaa_samples(1)
         .view(view)
         .key_pressed(key_pressed)
+        .mouse_pressed(mouse_pressed)
+        .mouse_moved(mouse_moved)
+        .mouse_released(mouse_released)
+        .mouse_wheel(mouse_wheel)
         .build()
         .unwrap();
     let window = app.window(window_id).unwrap();
@@ -173,45 +340,146 @@ fn model(app: &App) -> Model {
         frame_duration: config.animation.transition.frame_duration,
         wandering: config.animation.transition.wandering,
         density: config.animation.transition.density,
+        seed: match offline {
+            Some(_) => config.animation.transition.seed.or(Some(OFFLINE_SEED)),
+            None => config.animation.transition.seed,
+        },
+        stroke_gap_steps: config.animation.transition.stroke_gap_steps,
+        stagger: config.animation.transition.stagger,
     };
 
+    let mut background = BackgroundManager::default();
+    background.set_stage_size(texture_size(&texture));
+    background.set_image_opacity(config.background.image_opacity);
+    background.set_persistence(config.background.persistence);
+    if let Some(path) = config.resolve_background_image_path() {
+        background.set_image(app, &path);
+    }
+
     let recorder_fps = config.frame_recorder.fps;
+    let output_mode = OutputMode::try_from(config.frame_recorder.output_mode.as_str())
+        .map(|mode| match mode {
+            OutputMode::Gif { .. } => OutputMode::Gif {
+                width: config.frame_recorder.gif_width,
+            },
+            mode => mode,
+        })
+        .and_then(|mode| mode.check_frame_limit(config.frame_recorder.frame_limit))
+        .unwrap_or_else(|err| {
+            println!("{}, recording to h264", err);
+            OutputMode::default()
+        });
+
+    // Recording over transparency always needs the scene drawn separately
+    let transparent = output_mode.keeps_alpha();
+    background.set_transparent(transparent);
+    let capture_target = config
+        .frame_recorder
+        .capture_resolution
+        .or(transparent.then(|| texture.size()))
+        .and_then(|resolution| create_capture_target(device, &texture, resolution, transparent));
 
     // Create the frame recorder
-    let frame_recorder = FrameRecorder::new(
+    let mut frame_recorder = FrameRecorder::new(
         device,
-        &texture,
+        capture_target
+            .as_ref()
+            .map_or(&texture, |capture| &capture.texture),
         &config.resolve_output_dir_as_str(),
+        &config.frame_recorder.file_prefix,
         config.frame_recorder.frame_limit,
         recorder_fps,
+        output_mode,
+        &config.frame_recorder.ffmpeg_path,
+        config.frame_recorder.ffmpeg_config(),
+        config.frame_recorder.quality,
     );
+    if config.frame_recorder.crop.is_some() {
+        set_recorder_crop(&mut frame_recorder, device, config.frame_recorder.crop);
+    }
+    if offline.is_none() && config.frame_recorder.replay_seconds > 0.0 {
+        frame_recorder.set_replay(
+            config.frame_recorder.replay_seconds,
+            config.frame_recorder.replay_max_mb * 1_000_000,
+        );
+    }
+    if offline.is_some() {
+        frame_recorder.set_offline(true);
+        frame_recorder.toggle_recording();
+    }
+
+    let transition_engine = TransitionEngine::new(default_transition_config);
+    let random = transition_engine.child_rng();
 
     Model {
         project,
+        project_path,
+        project_hash,
         base_grid,
         base_graph,
+        graph_cache_path,
+        graph_cache_key,
 
         grids: HashMap::new(), //grid,
-        transition_engine: TransitionEngine::new(default_transition_config),
-        background: BackgroundManager::default(),
+        grid_pool: GridPool::new(config.rendering.grid_pool_size),
+        grid_groups: HashMap::new(),
+        grid_snapshots: HashMap::new(),
+        transition_engine,
+        background,
 
         osc_controller,
         osc_sender,
+        osc_feedback,
+        last_heartbeat: 0.0,
+        reported_take: None,
 
         texture,
         draw,
         draw_renderer,
         texture_reshaper,
-        random: rand::thread_rng(),
+        random,
 
         default_stroke_weight: config.style.default_stroke_weight,
         default_backbone_stroke_weight: config.style.default_backbone_stroke_weight,
+        default_glow: (config.style.glow_layers > 0).then_some(GlowParams {
+            layers: config.style.glow_layers,
+            spread: config.style.glow_spread,
+            alpha_falloff: config.style.glow_alpha,
+        }),
+        default_envelope: config
+            .animation
+            .envelope
+            .as_ref()
+            .map(|env| {
+                Envelope::new(
+                    env.attack,
+                    env.hold,
+                    env.decay,
+                    env.sustain_level,
+                    env.release,
+                )
+            })
+            .unwrap_or_default(),
+        default_type_weights: resolve_type_weights(&config.style.type_weights),
+        palettes: config.palettes,
+        presets,
+        presets_dir,
 
         frame_recorder,
+        capture_target,
+        screenshot_requested: false,
         exit_requested: false,
 
+        // an offline render steps one recorded frame per update
+        clock: match offline {
+            Some(_) => Clock::fixed(1.0 / recorder_fps as f32),
+            None => Clock::realtime(),
+        },
+
+        master_brightness: 1.0,
+        blackout: false,
+
         // FPS
-        last_update: Instant::now(),
         fps: 0.0,
         fps_update_interval: 0.3,
         last_fps_display_update: 0.0,
@@ -219,14 +487,16 @@ fn model(app: &App) -> Model {
         frame_time_accumulator: 0.0,
 
         debug_flag: false,
+        mouse_editor: MouseEditor::default(),
+
+        offline,
     }
 }
 
 fn update(app: &App, model: &mut Model, _update: Update) {
-    let now = Instant::now();
-    let duration = now - model.last_update;
-    let dt = duration.as_secs_f32();
-    model.last_update = now;
+    model.clock.tick(app.time);
+    let time = model.clock.time();
+    let dt = model.clock.dt();
 
     // FPS calculations
     if model.debug_flag {
@@ -234,6 +504,11 @@ fn update(app: &App, model: &mut Model, _update: Update) {
     }
 
     // Process OSC messages
+    if let Some(offline) = &mut model.offline {
+        for message in offline.automation.due(time) {
+            model.osc_controller.handle_message(message);
+        }
+    }
     model.osc_controller.process_messages();
     launch_commands(app, model);
 
@@ -241,10 +516,36 @@ fn update(app: &App, model: &mut Model, _update: Update) {
     coordinate_colorful_grid_styles(app, model);
 
     // Handle the background
-    model.background.draw(&model.draw, app.time);
+    let capture_draw = capture_draw(model);
+    if let Some(draw) = &capture_draw {
+        draw.reset();
+    }
+    model
+        .background
+        .draw(&model.draw, capture_draw.as_ref(), time);
+    let master_level = output_level(model);
+    if master_level < 1.0 {
+        let size = texture_size(&model.texture);
+        // grids are dimmed themselves, so a transparent recording needs no rect
+        let capture_draw = capture_draw
+            .as_ref()
+            .filter(|_| !model.background.is_transparent());
+        for draw in std::iter::once(&model.draw).chain(capture_draw) {
+            draw.rect()
+                .w_h(size.x, size.y)
+                .color(rgba(0.0, 0.0, 0.0, 1.0 - master_level));
+        }
+    }
 
     // Clean up any completed recording threads
-    model.frame_recorder.cleanup_completed_worker();
+    if let Some(finished) = model.frame_recorder.cleanup_completed_worker() {
+        if let Some(feedback) = &model.osc_feedback {
+            feedback.send_recorder_finished(&finished.to_string_lossy());
+        }
+    }
+
+    send_heartbeat(app, model);
+    report_take(model);
 
     // Frames processing progress bar:
     if model.exit_requested {
@@ -254,7 +555,28 @@ fn update(app: &App, model: &mut Model, _update: Update) {
 
     /*********************  Main update method for grids **********************/
     for (_, grid_instance) in model.grids.iter_mut() {
-        grid_instance.update(&model.draw, &model.transition_engine, app.time, dt);
+        if grid_instance.has_glyph_loop() {
+            grid_instance.update_glyph_loop(&model.project, time);
+        }
+        grid_instance.master_level = master_level;
+        grid_instance.update(&model.draw, &model.transition_engine, time, dt);
+
+        for (leg, remaining) in grid_instance.take_completed_legs() {
+            if let Some(feedback) = &model.osc_feedback {
+                feedback.send_move_leg_complete(&grid_instance.id, leg as i32, remaining as i32);
+            }
+        }
+    }
+    for grid_instance in model.grids.values() {
+        grid_instance.draw_overlay(&model.draw);
+    }
+    if let Some(draw) = &capture_draw {
+        for grid_instance in model.grids.values() {
+            grid_instance.draw_again(draw);
+        }
+        for grid_instance in model.grids.values() {
+            grid_instance.draw_overlay(draw);
+        }
     }
 
     // Handle FPS and origin display
@@ -265,6 +587,19 @@ fn update(app: &App, model: &mut Model, _update: Update) {
     // Render to texture and handle frame recording
     render_and_capture(app, model);
 
+    if let Some(offline) = &mut model.offline {
+        offline.frames_rendered += 1;
+        if offline.frames_rendered >= offline.frames {
+            println!("Rendered {} frames", offline.frames);
+            stop_recording(model);
+            model.exit_requested = true;
+        }
+    }
+
+    if model.frame_recorder.frame_limit_reached() {
+        stop_recording(model);
+    }
+
     // For benchmarking:
     //let total_duration = start_time.elapsed();
     //println!("Total update time: {:?}", total_duration);
@@ -298,6 +633,19 @@ fn draw_fps(model: &Model) {
     draw.text(&format!("FPS: {:.1}", model.fps))
         .x_y(1100.0, 290.0)
         .color(RED);
+
+    // Transition progress per grid
+    let mut grid_names: Vec<&String> = model.grids.keys().collect();
+    grid_names.sort();
+    let progress_lines = grid_names.into_iter().filter_map(|name| {
+        let (step, total, animation_type) = model.grids[name].transition_progress()?;
+        Some(format!("{}: {} {}/{}", name, animation_type, step, total))
+    });
+    for (i, line) in progress_lines.enumerate() {
+        draw.text(&line)
+            .x_y(1100.0, 270.0 - 20.0 * i as f32)
+            .color(RED);
+    }
 }
 
 fn init_fps(app: &App, model: &mut Model) {
@@ -340,12 +688,21 @@ fn coordinate_colorful_grid_styles(_app: &App, model: &mut Model) {
 
     let color = Rgba::from(color_hsl);
 
+    let time = model.clock.time();
+
     for grid_instance in model.grids.values_mut() {
-        if grid_instance.has_target_segments() && grid_instance.colorful_flag {
+        // rainbow hue follows the clock, so it's the same every run
+        grid_instance.update_rainbow(time);
+
+        // PerSegment grids color each segment as it turns on instead
+        if grid_instance.has_target_segments()
+            && grid_instance.colorful_flag
+            && grid_instance.colorful_mode() == ColorfulMode::Uniform
+        {
             grid_instance.set_effect_target_style(DrawStyle {
                 color,
                 // account for any grid scaling
-                stroke_weight: model.default_stroke_weight * grid_instance.current_scale,
+                stroke_weight: model.default_stroke_weight * grid_instance.mean_scale(),
             });
         }
     }
@@ -353,6 +710,68 @@ fn coordinate_colorful_grid_styles(_app: &App, model: &mut Model) {
 
 // ******************************* Rendering and Capture *****************************
 
+// A texture like the preview's at `resolution`, or None if that's the preview's
+// size already or unusable. The scene keeps its scale to the shorter fit, so
+// a different aspect ratio comes out letterboxed.
+fn create_capture_target(
+    device: &wgpu::Device,
+    texture: &wgpu::Texture,
+    resolution: [u32; 2],
+    transparent: bool,
+) -> Option<CaptureTarget> {
+    let [preview_width, preview_height] = texture.size();
+    let mut resolution = resolution;
+    if resolution[0] == 0 || resolution[1] == 0 {
+        eprintln!(
+            "Capture resolution {}x{} is empty, recording at the texture's {}x{}",
+            resolution[0], resolution[1], preview_width, preview_height
+        );
+        resolution = texture.size();
+    }
+    if resolution == texture.size() && !transparent {
+        return None;
+    }
+    let [width, height] = resolution;
+
+    let fit_x = width as f32 / preview_width as f32;
+    let fit_y = height as f32 / preview_height as f32;
+    if (fit_x - fit_y).abs() > 0.001 * fit_x.max(fit_y) {
+        println!(
+            "WARNING: Capture resolution {}x{} isn't the {}x{} texture's aspect ratio, \
+             recordings will be letterboxed",
+            width, height, preview_width, preview_height
+        );
+    }
+    println!("Recording at {}x{}", width, height);
+
+    let capture_texture = wgpu::TextureBuilder::new()
+        .size(resolution)
+        .usage(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING)
+        .sample_count(texture.sample_count())
+        .format(texture.format())
+        .build(device);
+    let renderer = nannou::draw::RendererBuilder::new()
+        .build_from_texture_descriptor(device, capture_texture.descriptor());
+
+    Some(CaptureTarget {
+        texture: capture_texture,
+        renderer,
+        scale_factor: TEXTURE_SCALE_FACTOR * fit_x.min(fit_y),
+        draw: Draw::new(),
+    })
+}
+
+// The recording's Draw when it's drawn separately this frame, i.e. there's a
+// capture target and something will be captured
+fn capture_draw(model: &Model) -> Option<Draw> {
+    let capturing = model.frame_recorder.wants_frames() || model.screenshot_requested;
+    model
+        .capture_target
+        .as_ref()
+        .filter(|_| capturing)
+        .map(|capture| capture.draw.clone())
+}
+
 fn render_and_capture(app: &App, model: &mut Model) {
     let window = app.main_window();
     let device = window.device();
@@ -366,21 +785,52 @@ fn render_and_capture(app: &App, model: &mut Model) {
         device,
         &mut encoder,
         &model.draw,
-        2.0,
+        TEXTURE_SCALE_FACTOR,
         model.texture.size(),
         &texture_view,
         None,
     );
 
+    // Draw again at the recording resolution, only when something's captured
+    let capturing = model.frame_recorder.wants_frames() || model.screenshot_requested;
+    let capture_texture = match &mut model.capture_target {
+        Some(capture) => {
+            if capturing {
+                let capture_view = capture.texture.view().build();
+                capture.renderer.encode_render_pass(
+                    device,
+                    &mut encoder,
+                    &capture.draw,
+                    capture.scale_factor,
+                    capture.texture.size(),
+                    &capture_view,
+                    None,
+                );
+            }
+            &capture.texture
+        }
+        None => &model.texture,
+    };
+
     // Capture the texture for FrameRecorder
-    if model.frame_recorder.is_recording() {
+    if model.frame_recorder.wants_frames() {
+        model
+            .frame_recorder
+            .capture_frame(device, &mut encoder, capture_texture);
+    }
+
+    // Capture a single still, independent of any recording
+    if model.screenshot_requested {
         model
             .frame_recorder
-            .capture_frame(device, &mut encoder, &model.texture);
+            .capture_still(device, &mut encoder, capture_texture, None);
+        model.screenshot_requested = false;
     }
 
     window.queue().submit(Some(encoder.finish()));
     device.poll(wgpu::Maintain::Wait);
+
+    model.frame_recorder.process_pending_still(device);
 }
 
 // ******************************* Exit State Handling *******************************
@@ -415,7 +865,7 @@ fn key_pressed(app: &App, model: &mut Model, key: Key) {
         Key::Backslash => {
             // Move to original position
             for name in model.grids.keys() {
-                model.osc_sender.send_move_grid(name, 0.0, 0.0, 0.0)
+                model.osc_sender.send_move_grid(name, 0.0, 0.0, 0.0, None)
             }
         }
 
@@ -446,22 +896,30 @@ fn key_pressed(app: &App, model: &mut Model, key: Key) {
         }
         Key::Key3 => {
             for name in model.grids.keys() {
-                model.osc_sender.send_grid_slide(name, "y", 2, 50.0);
+                model
+                    .osc_sender
+                    .send_grid_slide(name, "y", 2, 50.0, 0.0, "linear", false);
             }
         }
         Key::Key4 => {
             for name in model.grids.keys() {
-                model.osc_sender.send_grid_slide(name, "y", 2, -50.0);
+                model
+                    .osc_sender
+                    .send_grid_slide(name, "y", 2, -50.0, 0.0, "linear", false);
             }
         }
         Key::Key5 => {
             for name in model.grids.keys() {
-                model.osc_sender.send_grid_slide(name, "x", 2, 50.0);
+                model
+                    .osc_sender
+                    .send_grid_slide(name, "x", 2, 50.0, 0.0, "linear", false);
             }
         }
         Key::Key6 => {
             for name in model.grids.keys() {
-                model.osc_sender.send_grid_slide(name, "x", 2, -50.0);
+                model
+                    .osc_sender
+                    .send_grid_slide(name, "x", 2, -50.0, 0.0, "linear", false);
             }
         }
         Key::Key9 => {
@@ -560,10 +1018,14 @@ fn key_pressed(app: &App, model: &mut Model, key: Key) {
                 .send_background_color_fade(0.6, 0.2, 0.5, 10.0);
         }
         Key::Right => {
-            model.osc_sender.send_move_grid("grid_3", 700.0, 0.0, 3.0);
+            model
+                .osc_sender
+                .send_move_grid("grid_3", 700.0, 0.0, 3.0, None);
         }
         Key::Left => {
-            model.osc_sender.send_move_grid("grid_1", -700.0, 0.0, 3.0);
+            model
+                .osc_sender
+                .send_move_grid("grid_1", -700.0, 0.0, 3.0, None);
         }
         Key::Up => {
             for name in model.grids.keys() {
@@ -585,6 +1047,33 @@ fn key_pressed(app: &App, model: &mut Model, key: Key) {
                 model.osc_sender.send_rotate_grid(name, -5.0);
             }
         }
+        // Graph debug overlay, with degree labels, on every grid
+        Key::O => {
+            for (name, grid_instance) in model.grids.iter() {
+                let enabled = !grid_instance.is_graph_debug();
+                model.osc_sender.send_debug_graph(name, enabled, enabled);
+            }
+        }
+        // Mouse edit mode: click segments, shift-drag grids, scroll to scale
+        Key::K => {
+            let enabled = model.mouse_editor.toggle();
+            println!("Mouse edit mode {}", if enabled { "on" } else { "off" });
+        }
+        // Print each grid's edited glyph, ready for the project file
+        Key::L => {
+            for name in model.grids.keys() {
+                let Some(glyph) = model
+                    .mouse_editor
+                    .scratch_glyph(name, &format!("{} edit", name))
+                else {
+                    continue;
+                };
+                match serde_json::to_string_pretty(&glyph) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => println!("WARNING: can't export glyph: {}", e),
+                }
+            }
+        }
         Key::Z => {
             for grid_instance in model.grids.values_mut() {
                 grid_instance.boundary_test(Axis::X);
@@ -612,6 +1101,9 @@ fn key_pressed(app: &App, model: &mut Model, key: Key) {
                 model.osc_sender.send_recorder_stop();
             }
         }
+        Key::S => {
+            model.osc_sender.send_recorder_screenshot();
+        }
         /***************** Below functions aren't implemented in OSC ****************** */
         Key::P => {
             model.debug_flag = !model.debug_flag;
@@ -619,6 +1111,7 @@ fn key_pressed(app: &App, model: &mut Model, key: Key) {
         }
         // Graceful quit that waits for frame queue to be processed
         Key::Q => {
+            stop_recording(model);
             model.frame_recorder.signal_shutdown();
             model.exit_requested = true;
             println!("\nShutdown requested.");
@@ -628,9 +1121,217 @@ fn key_pressed(app: &App, model: &mut Model, key: Key) {
     }
 }
 
+// ************************ Mouse edit mode  *************************************
+
+// A window point in the scene the grids are drawn in
+fn scene_point(app: &App, model: &Model, point: Point2) -> Point2 {
+    mouse::window_to_scene(
+        point,
+        app.main_window().rect().wh(),
+        texture_size(&model.texture),
+        TEXTURE_SCALE_FACTOR,
+    )
+}
+
+// Left-click toggles the segment under the cursor and shows the result right
+// away. Shift-click picks up the grid under the cursor instead.
+fn mouse_pressed(app: &App, model: &mut Model, button: MouseButton) {
+    if !model.mouse_editor.is_enabled() || button != MouseButton::Left {
+        return;
+    }
+    let point = scene_point(app, model, app.mouse.position());
+
+    if app.keys.mods.shift() {
+        if let Some(name) = mouse::topmost_grid(model.grids.values(), point) {
+            model.mouse_editor.start_drag(name, point);
+        }
+        return;
+    }
+
+    let Some((name, segment)) =
+        mouse::topmost_hit(model.grids.values(), point, mouse::CLICK_TOLERANCE)
+            .map(|(name, segment)| (name.to_string(), segment))
+    else {
+        return;
+    };
+    let Some(grid) = model.grids.get_mut(&name) else {
+        return;
+    };
+    let scratch = model.mouse_editor.toggle_segment(&name, &segment, || {
+        grid.target_segments
+            .as_ref()
+            .unwrap_or(&grid.current_active_segments)
+            .iter()
+            .cloned()
+            .collect()
+    });
+    grid.stop_loop();
+    grid.target_segments = Some(scratch.iter().cloned().collect());
+    grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+    if model.debug_flag {
+        println!("{}: toggled {}", name, segment);
+    }
+}
+
+fn mouse_moved(app: &App, model: &mut Model, position: Point2) {
+    let point = scene_point(app, model, position);
+    if let Some((name, offset)) = model.mouse_editor.drag_to(point) {
+        if let Some(grid) = model.grids.get_mut(name) {
+            grid.stop_transform_animations();
+            grid.move_by(offset);
+        }
+    }
+}
+
+fn mouse_released(_app: &App, model: &mut Model, button: MouseButton) {
+    if button == MouseButton::Left {
+        model.mouse_editor.end_drag();
+    }
+}
+
+fn mouse_wheel(app: &App, model: &mut Model, delta: MouseScrollDelta, _phase: TouchPhase) {
+    if !model.mouse_editor.is_enabled() {
+        return;
+    }
+    let point = scene_point(app, model, app.mouse.position());
+    let Some(name) = mouse::topmost_grid(model.grids.values(), point).map(str::to_string) else {
+        return;
+    };
+    if let Some(grid) = model.grids.get_mut(&name) {
+        grid.stop_transform_animations();
+        grid.scale_xy_in_place(grid.current_scale * mouse::scroll_scale_factor(delta));
+    }
+}
+
+const HEARTBEAT_INTERVAL: f32 = 1.0; // seconds
+
+// Lets controllers know the app is alive and what the recorder is doing
+fn send_heartbeat(app: &App, model: &mut Model) {
+    let Some(feedback) = &model.osc_feedback else {
+        return;
+    };
+    if app.time - model.last_heartbeat < HEARTBEAT_INTERVAL {
+        return;
+    }
+    model.last_heartbeat = app.time;
+    feedback.send_heartbeat(
+        app.time,
+        model.frame_recorder.is_recording(),
+        model.frame_recorder.is_paused(),
+    );
+    feedback.send_recorder_status(&model.frame_recorder.status());
+}
+
+fn set_recorder_crop(
+    frame_recorder: &mut FrameRecorder,
+    device: &wgpu::Device,
+    crop: Option<CropConfig>,
+) {
+    match frame_recorder.set_crop(device, crop) {
+        Ok(crop) => println!(
+            "Recording {}x{} at ({}, {})",
+            crop.w, crop.h, crop.x, crop.y
+        ),
+        Err(err) => println!("Recorder crop not changed: {}", err),
+    }
+}
+
+// Tells controllers where each take is written as it starts and ends,
+// however the recording was started or stopped
+fn report_take(model: &mut Model) {
+    let take = if model.frame_recorder.is_recording() {
+        model.frame_recorder.current_take()
+    } else {
+        None
+    };
+    if take == model.reported_take {
+        return;
+    }
+    if let Some(feedback) = &model.osc_feedback {
+        if let Some(ended) = &model.reported_take {
+            feedback.send_recorder_take("ended", &ended.to_string_lossy());
+        }
+        if let Some(started) = &take {
+            feedback.send_recorder_take("started", &started.to_string_lossy());
+        }
+    }
+    model.reported_take = take;
+}
+
+// ************************ Recording metadata  *************************************
+
+// Written beside each take as <take>.meta.json, so takes can be told apart
+// long after they were recorded
+#[derive(Serialize)]
+struct RecordingMetadata<'a> {
+    app_version: &'static str,
+    project: String,
+    project_hash: String,
+    grids: Vec<GridMetadata<'a>>,
+    transition: &'a TransitionConfig, // the defaults, before any per-command overrides
+    fps: u64,
+    resolution: [u32; 2],
+    encoder: Option<&'a str>,   // None for image sequences and GIFs
+    automation: Option<String>, // the command file an offline render played
+}
+
+#[derive(Serialize)]
+struct GridMetadata<'a> {
+    id: &'a str,
+    show: &'a str,
+}
+
+// Stops recording, first leaving the take's metadata beside it
+fn stop_recording(model: &mut Model) {
+    if !model.frame_recorder.is_recording() {
+        return;
+    }
+    model
+        .frame_recorder
+        .write_metadata(&recording_metadata(model));
+    model.frame_recorder.toggle_recording();
+}
+
+fn recording_metadata(model: &Model) -> RecordingMetadata<'_> {
+    let mut grids: Vec<GridMetadata> = model
+        .grids
+        .values()
+        .map(|grid| GridMetadata {
+            id: &grid.id,
+            show: grid.show(),
+        })
+        .collect();
+    grids.sort_by_key(|grid| grid.id);
+
+    let (width, height) = model.frame_recorder.capture_size();
+    RecordingMetadata {
+        app_version: env!("CARGO_PKG_VERSION"),
+        project: model.project_path.to_string_lossy().into_owned(),
+        project_hash: model.project_hash.clone(),
+        grids,
+        transition: model.transition_engine.get_default_config(),
+        fps: model.frame_recorder.fps(),
+        resolution: [width, height],
+        encoder: model.frame_recorder.encoder(),
+        automation: model
+            .offline
+            .as_ref()
+            .map(|offline| offline.automation_path.to_string_lossy().into_owned()),
+    }
+}
+
+// 64-bit FNV-1a, as hex. Stable across builds, unlike std's hasher.
+fn fnv1a_hex(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
 // ******************************* OSC Launcher *******************************
 
 fn launch_commands(app: &App, model: &mut Model) {
+    let time = model.clock.time();
     for command in model.osc_controller.take_commands() {
         match command {
             OscCommand::RecorderStart {} => {
@@ -639,40 +1340,147 @@ fn launch_commands(app: &App, model: &mut Model) {
                 }
             }
             OscCommand::RecorderStop {} => {
-                if model.frame_recorder.is_recording() {
-                    model.frame_recorder.toggle_recording();
-                }
+                stop_recording(model);
+            }
+            OscCommand::RecorderPause {} => {
+                model.frame_recorder.pause();
+            }
+            OscCommand::RecorderResume {} => {
+                model.frame_recorder.resume();
+            }
+            OscCommand::RecorderCrop { x, y, w, h } => {
+                let (Ok(x), Ok(y), Ok(w), Ok(h)) = (
+                    u32::try_from(x),
+                    u32::try_from(y),
+                    u32::try_from(w),
+                    u32::try_from(h),
+                ) else {
+                    println!("Recorder crop can't be negative");
+                    continue;
+                };
+                let window = app.main_window();
+                let crop = CropConfig { x, y, w, h };
+                set_recorder_crop(&mut model.frame_recorder, window.device(), Some(crop));
+            }
+            OscCommand::RecorderCropOff {} => {
+                let window = app.main_window();
+                set_recorder_crop(&mut model.frame_recorder, window.device(), None);
             }
-            OscCommand::BackgroundFlash { r, g, b, duration } => {
-                model.background.flash(rgb(r, g, b), duration, app.time);
+            OscCommand::RecorderScreenshot {} => {
+                model.screenshot_requested = true;
             }
-            OscCommand::BackgroundColorFade { r, g, b, duration } => {
+            OscCommand::RecorderSaveback {
+                seconds,
+                continue_recording,
+            } => match model
+                .frame_recorder
+                .save_replay(seconds, continue_recording)
+            {
+                Ok(take_path) => {
+                    if let Some(feedback) = &model.osc_feedback {
+                        feedback.send_recorder_take("replay", &take_path.to_string_lossy());
+                    }
+                }
+                Err(e) => println!("WARNING: {}", e),
+            },
+            OscCommand::RecorderStatus {} => {
+                let status = model.frame_recorder.status();
+                match &model.osc_feedback {
+                    Some(feedback) => feedback.send_recorder_status(&status),
+                    None => println!("{:?}", status),
+                }
+            }
+            OscCommand::BackgroundFlash { color, duration } => {
+                let Some(color) = resolve_color(&model.palettes, &color) else {
+                    continue;
+                };
                 model
                     .background
-                    .color_fade(rgb(r, g, b), duration, app.time);
+                    .flash(rgb(color.red, color.green, color.blue), duration, time);
+            }
+            OscCommand::BackgroundImage { path, opacity } => {
+                if let Some(opacity) = opacity {
+                    model.background.set_image_opacity(opacity);
+                }
+                if path.is_empty() {
+                    model.background.clear_image();
+                } else {
+                    model.background.set_image(app, std::path::Path::new(&path));
+                }
+            }
+            OscCommand::BackgroundPersistence { persistence } => {
+                model.background.set_persistence(persistence);
+            }
+            OscCommand::BackgroundColorFade { color, duration } => {
+                let Some(color) = resolve_color(&model.palettes, &color) else {
+                    continue;
+                };
+                model.background.color_fade(
+                    rgb(color.red, color.green, color.blue),
+                    duration,
+                    time,
+                );
             }
             OscCommand::GridBackboneFade {
                 name,
-                r,
-                g,
-                b,
-                a,
+                color,
                 duration,
             } => {
+                let Some(color) = resolve_color(&model.palettes, &color) else {
+                    continue;
+                };
                 if let Some(grid) = model.grids.get_mut(&name) {
                     let effect = FadeEffect {
                         base_style: grid.backbone_style.clone(),
                         target_style: DrawStyle {
-                            color: rgba(r, g, b, a),
+                            color,
                             stroke_weight: grid.backbone_style.stroke_weight,
                         },
                         duration,
-                        start_time: app.time,
+                        start_time: time,
                         is_active: true,
                     };
                     grid.add_backbone_effect("backbone", Box::new(effect));
                 }
             }
+            OscCommand::GridBackboneStrobe {
+                name,
+                r,
+                g,
+                b,
+                hz,
+                duty,
+                duration,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    let target_color = rgba(r, g, b, grid.backbone_style.color.alpha);
+                    let effect = StrobeEffect::new(
+                        grid.backbone_style.clone(),
+                        target_color,
+                        hz,
+                        duty,
+                        duration,
+                        time,
+                    );
+                    // on top of any fade in progress
+                    grid.add_backbone_effect_with_priority("strobe", 1, Box::new(effect));
+                }
+            }
+            OscCommand::GridBackbonePulse {
+                name,
+                period,
+                depth,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    let effect = PulseEffect::new(period, depth, time);
+                    grid.add_backbone_effect("pulse", Box::new(effect));
+                }
+            }
+            OscCommand::GridBackbonePulseStop { name } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.remove_backbone_effect("pulse");
+                }
+            }
             OscCommand::GridBackboneStroke {
                 name,
                 stroke_weight,
@@ -681,125 +1489,632 @@ fn launch_commands(app: &App, model: &mut Model) {
                     grid.set_backbone_stroke_weight(stroke_weight);
                 }
             }
+            OscCommand::GridBackboneVisible {
+                name,
+                visible,
+                duration,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.set_backbone_visible(visible, duration, time);
+                }
+            }
+            OscCommand::GridTypeWeight {
+                name,
+                segment_type,
+                multiplier,
+            } => {
+                let types = match SegmentType::named(&segment_type) {
+                    Ok(types) => types,
+                    Err(e) => {
+                        println!("{}", e);
+                        continue;
+                    }
+                };
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    for &segment_type in types {
+                        grid.set_type_weight(segment_type, multiplier);
+                    }
+                }
+            }
             OscCommand::GridCreate {
                 name,
                 show,
                 position,
                 rotation,
             } => {
-                let grid = GridInstance::new(
-                    name.clone(),
-                    &model.project,
-                    &show,
-                    &model.base_grid,
-                    Rc::clone(&model.base_graph),
-                    pt2(position.0, position.1),
-                    rotation,
-                    model.default_stroke_weight,
-                    model.default_backbone_stroke_weight,
-                );
-                model.grids.insert(name, grid);
+                create_grid(model, name, &show, pt2(position.0, position.1), rotation);
+            }
+            OscCommand::GridArray {
+                base_name,
+                show,
+                cols,
+                rows,
+                spacing,
+                origin,
+            } => {
+                let grids = match grid_array_layout(
+                    &base_name,
+                    cols,
+                    rows,
+                    spacing,
+                    origin,
+                    model.base_grid.bounding_box().wh(),
+                    &model.grid_groups,
+                    &model.grids,
+                ) {
+                    Ok(grids) => grids,
+                    Err(err) => {
+                        println!("{}", err);
+                        continue;
+                    }
+                };
+                for (name, position) in &grids {
+                    create_grid(model, name.clone(), &show, *position, 0.0);
+                }
+                let names = grids.into_iter().map(|(name, _)| name).collect();
+                model.grid_groups.insert(base_name, names);
             }
+            OscCommand::GridDestroy { name } => match model.grids.remove(&name) {
+                Some(grid) => {
+                    model.grid_pool.release(grid);
+                    for members in model.grid_groups.values_mut() {
+                        members.retain(|member| *member != name);
+                    }
+                    model.grid_groups.retain(|_, members| !members.is_empty());
+                    model.grid_snapshots.retain(|(grid, _), _| *grid != name);
+                }
+                None => println!("No grid named {} to destroy", name),
+            },
+            OscCommand::GridRename { name, new_name } => rename_grid(model, name, new_name),
 
             OscCommand::GridMove {
                 name,
                 x,
                 y,
                 duration,
+                easing,
+                clear_queue,
             } => {
                 if let Some(grid) = model.grids.get_mut(&name) {
-                    let movement_config = MovementConfig {
-                        duration,
-                        easing: EasingType::Linear,
-                    };
+                    let easing = parse_easing(easing.as_deref());
+                    let movement_config = MovementConfig { duration, easing };
                     let movement_engine = MovementEngine::new(movement_config);
+                    if clear_queue {
+                        grid.clear_movement_queue();
+                    }
+                    grid.stop_transform_animations();
                     grid.active_movement = None;
-                    grid.stage_movement(x, y, duration, &movement_engine, app.time);
+                    grid.stage_movement(x, y, duration, &movement_engine, time);
                 }
             }
-            OscCommand::GridRotate { name, angle } => {
+            OscCommand::GridMoveQueue { name, legs } => {
                 if let Some(grid) = model.grids.get_mut(&name) {
-                    grid.rotate_in_place(angle);
+                    let legs = legs
+                        .into_iter()
+                        .map(|(x, y, duration, easing)| MovementLeg {
+                            target: pt2(x, y),
+                            duration,
+                            easing: parse_easing(easing.as_deref()),
+                        })
+                        .collect();
+                    grid.stop_transform_animations();
+                    grid.queue_movements(legs);
                 }
             }
-            OscCommand::GridScale { name, scale } => {
+            OscCommand::GridKeyframes { name, keyframes } => {
                 if let Some(grid) = model.grids.get_mut(&name) {
-                    grid.scale_in_place(scale);
+                    let keyframes = keyframes
+                        .into_iter()
+                        .map(|(time, x, y, rotation, scale, easing)| Keyframe {
+                            time,
+                            position: pt2(x, y),
+                            rotation,
+                            scale,
+                            easing: parse_easing(Some(&easing)),
+                        })
+                        .collect();
+                    grid.stage_keyframes(keyframes, time);
                 }
             }
-            OscCommand::GridSlide {
+            OscCommand::GridOrbit {
                 name,
-                axis,
-                number,
-                position,
+                center,
+                degrees_per_second,
+                duration,
+                face_path,
+                radius,
             } => {
                 if let Some(grid) = model.grids.get_mut(&name) {
-                    let axis_validated = match Axis::try_from(axis.as_str()) {
-                        Ok(axis) => axis,
-                        Err(err) => {
-                            println!("{}", err);
-                            return;
-                        }
-                    };
-
-                    grid.slide(axis_validated, number, position, app.time);
+                    grid.stage_orbit(
+                        pt2(center.0, center.1),
+                        radius,
+                        degrees_per_second,
+                        duration,
+                        face_path,
+                        time,
+                    );
                 }
             }
-            OscCommand::GridGlyph {
-                grid_name,
-                glyph_index,
-                animation_type_msg,
-            } => {
-                if let Some(grid) = model.grids.get_mut(&grid_name) {
-                    grid.stage_glyph_by_index(&model.project, glyph_index);
-                    grid.transition_next_animation_type =
-                        transition_next_animation_type(animation_type_msg);
+            OscCommand::GridOrbitStop { name } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.stop_orbit();
                 }
             }
-            OscCommand::GridInstantGlyphColor {
-                grid_name,
-                r,
-                g,
-                b,
-                a,
+            OscCommand::GridMovePath {
+                name,
+                duration,
+                waypoints,
             } => {
-                if let Some(grid) = model.grids.get_mut(&grid_name) {
-                    grid.instant_color_change(rgba(r, g, b, a));
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    let waypoints: Vec<Point2> =
+                        waypoints.iter().map(|(x, y)| pt2(*x, *y)).collect();
+                    grid.stop_transform_animations();
+                    grid.stage_path_movement(&waypoints, duration, time);
                 }
             }
-            OscCommand::GridNextGlyph {
+            OscCommand::GridJitter {
+                name,
+                amplitude,
+                frequency,
+                duration,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.stage_jitter(
+                        amplitude,
+                        frequency,
+                        duration,
+                        time,
+                        model.transition_engine.child_rng(),
+                    );
+                }
+            }
+            OscCommand::GridSparkle {
+                name,
+                rate,
+                lifetime,
+                color,
+            } => {
+                let Some(color) = resolve_color(&model.palettes, &color) else {
+                    continue;
+                };
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    let rng = model.transition_engine.child_rng();
+                    let effect = SparkleEffect::new(rate, lifetime, color, rng);
+                    grid.set_sparkle(Some(effect));
+                }
+            }
+            OscCommand::GridSparkleStop { name } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.set_sparkle(None);
+                }
+            }
+            OscCommand::GridFlicker {
+                name,
+                rate,
+                depth,
+                duration,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    if rate <= 0.0 || depth <= 0.0 {
+                        grid.set_flicker(None);
+                    } else {
+                        let rng = model.transition_engine.child_rng();
+                        let effect = FlickerEffect::new(rate, depth, duration, rng);
+                        grid.set_flicker(Some(effect));
+                    }
+                }
+            }
+            OscCommand::GridNoiseColor {
+                name,
+                scale,
+                speed,
+                range,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    if range <= 0.0 {
+                        grid.set_noise_color(None);
+                    } else {
+                        // seeded from the transition engine so renders repeat
+                        let seed = model.transition_engine.child_rng().gen();
+                        let effect = NoiseColorEffect::new(scale, speed, range, seed);
+                        grid.set_noise_color(Some(effect));
+                    }
+                }
+            }
+            OscCommand::GridEnvelope {
+                name,
+                attack,
+                decay,
+                sustain_level,
+                release,
+                hold,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.envelope = Envelope::new(attack, hold, decay, sustain_level, release);
+                }
+            }
+            OscCommand::GridDimmer { name, level } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.dimmer = level.clamp(0.0, 1.0);
+                }
+            }
+            OscCommand::MasterBrightness { level } => {
+                model.master_brightness = level.clamp(0.0, 1.0);
+            }
+            OscCommand::MasterBlackout { enabled } => {
+                model.blackout = enabled;
+            }
+            OscCommand::GridGlow {
+                name,
+                layers,
+                spread,
+                alpha,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.glow = (layers > 0).then_some(GlowParams {
+                        layers: layers as u32,
+                        spread,
+                        alpha_falloff: alpha,
+                    });
+                }
+            }
+            OscCommand::GridGlyphColorFade {
+                name,
+                color,
+                duration,
+            } => {
+                let Some(color) = resolve_color(&model.palettes, &color) else {
+                    continue;
+                };
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.glyph_color_fade(color, duration, time);
+                }
+            }
+            OscCommand::GridGradient {
+                name,
+                color_a,
+                color_b,
+                axis,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    let axis = match Axis::try_from(axis.as_str()) {
+                        Ok(axis) => axis,
+                        Err(err) => {
+                            println!("{}", err);
+                            continue;
+                        }
+                    };
+                    grid.set_gradient(GradientStyle {
+                        color_a: rgba(color_a.0, color_a.1, color_a.2, color_a.3),
+                        color_b: rgba(color_b.0, color_b.1, color_b.2, color_b.3),
+                        axis,
+                    });
+                }
+            }
+            OscCommand::GridGradientOff { name } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.clear_gradient();
+                }
+            }
+            OscCommand::GridOutline {
+                name,
+                color,
+                extra_weight,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.outline = Some(OutlineParams {
+                        color: rgba(color.0, color.1, color.2, color.3),
+                        extra_weight: extra_weight.max(0.0),
+                    });
+                }
+            }
+            OscCommand::GridOutlineOff { name } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.outline = None;
+                }
+            }
+            OscCommand::GridBands {
+                name,
+                axis,
+                width,
+                color_a,
+                color_b,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    let axis = match Axis::try_from(axis.as_str()) {
+                        Ok(axis) => axis,
+                        Err(err) => {
+                            println!("{}", err);
+                            continue;
+                        }
+                    };
+                    grid.set_bands(BandStyle {
+                        axis,
+                        width: width.max(1) as u32,
+                        color_a: rgba(color_a.0, color_a.1, color_a.2, 1.0),
+                        color_b: rgba(color_b.0, color_b.1, color_b.2, 1.0),
+                    });
+                }
+            }
+            OscCommand::GridBandsOff { name } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.clear_bands();
+                }
+            }
+            OscCommand::GridRainbow { name, rate, spread } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.set_rainbow(rate, spread);
+                }
+            }
+            OscCommand::GridPulse {
+                name,
+                period,
+                depth,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.set_pulse(period, depth, time);
+                }
+            }
+            OscCommand::GridRotate { name, angle } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.stop_transform_animations();
+                    grid.rotate_in_place(angle);
+                }
+            }
+            OscCommand::GridScale { name, scale } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.stop_transform_animations();
+                    grid.scale_in_place(scale);
+                }
+            }
+            OscCommand::GridScaleXY { name, scale } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.stop_transform_animations();
+                    grid.scale_xy_in_place(vec2(scale.0, scale.1));
+                }
+            }
+            OscCommand::GridSlide {
+                name,
+                axis,
+                number,
+                position,
+                duration,
+                easing,
+                wrap,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    let axis_validated = match Axis::try_from(axis.as_str()) {
+                        Ok(axis) => axis,
+                        Err(err) => {
+                            println!("{}", err);
+                            return;
+                        }
+                    };
+
+                    // without a duration the slide snaps as before
+                    let duration = duration.unwrap_or(INSTANT_SLIDE_DURATION);
+                    let easing = parse_easing(easing.as_deref());
+                    grid.slide(
+                        axis_validated,
+                        number,
+                        position,
+                        duration,
+                        easing,
+                        wrap,
+                        time,
+                    );
+                }
+            }
+            OscCommand::GridSlideClamp { name, enabled } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.set_slide_clamp(enabled.then_some(texture_size(&model.texture)));
+                }
+            }
+            OscCommand::GridSlideReset {
+                name,
+                duration,
+                easing,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    let easing = parse_easing(easing.as_deref());
+                    grid.slide_reset(duration, easing, time);
+                }
+            }
+            OscCommand::GridSlideState { name } => {
+                if let Some(grid) = model.grids.get(&name) {
+                    let mut row_offsets = Vec::new();
+                    let mut col_offsets = Vec::new();
+                    for (axis, index, offset) in grid.slide_offsets() {
+                        match axis {
+                            Axis::X => row_offsets.push((index, offset)),
+                            Axis::Y => col_offsets.push((index, offset)),
+                        }
+                    }
+                    match &model.osc_feedback {
+                        Some(feedback) => {
+                            feedback.send_grid_slide_state(&name, &row_offsets, &col_offsets)
+                        }
+                        None => println!(
+                            "{} slides: rows {:?}, columns {:?}",
+                            name, row_offsets, col_offsets
+                        ),
+                    }
+                }
+            }
+            OscCommand::GridSnapshot { name, slot } => {
+                if let Some(grid) = model.grids.get(&name) {
+                    model.grid_snapshots.insert((name, slot), grid.snapshot());
+                }
+            }
+            OscCommand::GridRestore { name, slot } => {
+                let key = (name, slot);
+                match (model.grids.get_mut(&key.0), model.grid_snapshots.get(&key)) {
+                    (Some(grid), Some(snapshot)) => grid.restore(snapshot, &model.project),
+                    (Some(_), None) => println!("No snapshot {} for grid {}", key.1, key.0),
+                    (None, _) => {}
+                }
+            }
+            OscCommand::GridGet { name } => {
+                if let Some(grid) = model.grids.get(&name) {
+                    let mut row_offsets = Vec::new();
+                    let mut col_offsets = Vec::new();
+                    for (axis, index, offset) in grid.slide_offsets() {
+                        match axis {
+                            Axis::X if offset != 0.0 => row_offsets.push((index, offset)),
+                            Axis::Y if offset != 0.0 => col_offsets.push((index, offset)),
+                            _ => (),
+                        }
+                    }
+                    let position = (grid.current_position.x, grid.current_position.y);
+                    let bounds = grid.bounding_box();
+                    match &model.osc_feedback {
+                        Some(feedback) => feedback.send_grid_state(
+                            &name,
+                            position,
+                            grid.current_rotation,
+                            (grid.current_scale.x, grid.current_scale.y),
+                            &row_offsets,
+                            &col_offsets,
+                            [bounds.left(), bounds.bottom(), bounds.right(), bounds.top()],
+                        ),
+                        None => println!(
+                            "{}: position {:?}, rotation {}, scale {}, rows {:?}, columns {:?}, bounds {:?}",
+                            name,
+                            position,
+                            grid.current_rotation,
+                            grid.current_scale,
+                            row_offsets,
+                            col_offsets,
+                            bounds
+                        ),
+                    }
+                }
+            }
+            OscCommand::GridHitTest {
+                name,
+                point,
+                tolerance,
+            } => {
+                if let Some(grid) = model.grids.get(&name) {
+                    let hit = grid
+                        .hit_test(
+                            pt2(point.0, point.1),
+                            tolerance.unwrap_or(HIT_TEST_TOLERANCE),
+                        )
+                        .map(|(id, distance)| (id.to_string(), distance));
+                    match &model.osc_feedback {
+                        Some(feedback) => feedback.send_grid_hit(
+                            &name,
+                            hit.as_ref().map(|(id, distance)| (id.as_str(), *distance)),
+                        ),
+                        None => println!("{} hit test at {:?}: {:?}", name, point, hit),
+                    }
+                }
+            }
+            OscCommand::GridCenter { name } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.stop_transform_animations();
+                    // the texture is drawn around the world origin
+                    grid.center_on(pt2(0.0, 0.0));
+                }
+            }
+            OscCommand::GridGlyph {
                 grid_name,
+                glyph_index,
                 animation_type_msg,
+                animation_args,
             } => {
                 if let Some(grid) = model.grids.get_mut(&grid_name) {
-                    grid.stage_next_glyph(&model.project);
+                    grid.stop_loop();
+                    grid.stage_glyph_by_index(&model.project, glyph_index);
                     grid.transition_next_animation_type =
-                        transition_next_animation_type(animation_type_msg);
+                        transition_next_animation_type(animation_type_msg, &animation_args);
                 }
             }
-            OscCommand::GridNextGlyphColor {
+            OscCommand::GridInstantGlyphColor { grid_name, color } => {
+                let Some(color) = resolve_color(&model.palettes, &color) else {
+                    continue;
+                };
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.instant_color_change(color);
+                }
+            }
+            OscCommand::GridNextGlyph {
                 grid_name,
-                r,
-                g,
-                b,
-                a,
+                animation_type_msg,
+                animation_args,
             } => {
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.stop_loop();
+                    grid.stage_next_glyph(&model.project);
+                    grid.transition_next_animation_type =
+                        transition_next_animation_type(animation_type_msg, &animation_args);
+                }
+            }
+            OscCommand::GridNextGlyphColor { grid_name, color } => {
+                let Some(color) = resolve_color(&model.palettes, &color) else {
+                    continue;
+                };
                 if let Some(grid) = model.grids.get_mut(&grid_name) {
                     let style = DrawStyle {
-                        color: rgba(r, g, b, a),
-                        stroke_weight: model.default_stroke_weight * grid.current_scale,
+                        color,
+                        stroke_weight: model.default_stroke_weight * grid.mean_scale(),
                     };
                     grid.set_effect_target_style(style);
                 }
             }
+            OscCommand::GridSegmentColor {
+                grid_name,
+                segment_id,
+                color,
+            } => {
+                let Some(color) = resolve_color(&model.palettes, &color) else {
+                    continue;
+                };
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.set_segment_color_override(&segment_id, color);
+                }
+            }
+            OscCommand::GridSegmentColorClear {
+                grid_name,
+                segment_id,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.clear_segment_color_overrides(segment_id.as_deref());
+                }
+            }
             OscCommand::GridNoGlyph {
                 grid_name,
                 animation_type_msg,
+                animation_args,
             } => {
                 if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.stop_loop();
                     grid.stage_empty_glyph();
                     grid.transition_next_animation_type =
-                        transition_next_animation_type(animation_type_msg);
+                        transition_next_animation_type(animation_type_msg, &animation_args);
+                }
+            }
+            OscCommand::GridLoop {
+                grid_name,
+                index_a,
+                index_b,
+                hold_time,
+                animation_type_msg,
+                animation_args,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.set_loop(
+                        index_a,
+                        index_b,
+                        hold_time,
+                        transition_next_animation_type(animation_type_msg, &animation_args),
+                    );
+                }
+            }
+            OscCommand::GridLoopStop { grid_name } => {
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.stop_loop();
                 }
             }
             OscCommand::GridOverwrite { grid_name } => {
@@ -840,17 +2155,74 @@ fn launch_commands(app: &App, model: &mut Model) {
                     grid.colorful_flag = setting;
                 }
             }
+            OscCommand::GridColorfulMode { grid_name, mode } => {
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    let mode = match ColorfulMode::try_from(mode.as_str()) {
+                        Ok(mode) => mode,
+                        Err(err) => {
+                            println!("{}", err);
+                            continue;
+                        }
+                    };
+                    grid.set_colorful_mode(mode, model.transition_engine.child_rng());
+                }
+            }
             OscCommand::GridSetPowerEffect { grid_name, setting } => {
                 if let Some(grid) = model.grids.get_mut(&grid_name) {
                     grid.use_power_on_effect = setting;
                 }
             }
+            OscCommand::GridSetReverseErase { grid_name, setting } => {
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.transition_use_reverse_erase = setting;
+                }
+            }
+            OscCommand::GridTransitionPause { grid_name, paused } => {
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.set_transition_paused(paused);
+                }
+            }
+            OscCommand::GridTransitionProgress { grid_name } => {
+                if let Some(grid) = model.grids.get(&grid_name) {
+                    // an idle grid reports no steps
+                    let (step, total, animation_type) =
+                        grid.transition_progress().unwrap_or((0, 0, "none"));
+                    match &model.osc_feedback {
+                        Some(feedback) => feedback.send_transition_progress(
+                            &grid_name,
+                            step as i32,
+                            total as i32,
+                            animation_type,
+                        ),
+                        None => println!(
+                            "{} transition progress: {}/{} ({})",
+                            grid_name, step, total, animation_type
+                        ),
+                    }
+                }
+            }
+            OscCommand::GridSpeed { grid_name, speed } => {
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.set_transition_speed(speed);
+                }
+            }
+            OscCommand::GridSetStrokeTint { grid_name, setting } => {
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.transition_use_stroke_tint = setting;
+                }
+            }
+            OscCommand::GridSetCrossfade { grid_name, setting } => {
+                if let Some(grid) = model.grids.get_mut(&grid_name) {
+                    grid.transition_use_crossfade = setting;
+                }
+            }
             OscCommand::TransitionUpdate {
                 grid_name,
                 steps,
                 frame_duration,
                 wandering,
                 density,
+                stroke_gap_steps,
             } => {
                 if let Some(grid) = model.grids.get_mut(&grid_name) {
                     grid.update_transition_config(
@@ -858,20 +2230,476 @@ fn launch_commands(app: &App, model: &mut Model) {
                         frame_duration,
                         wandering,
                         density,
+                        stroke_gap_steps,
                         model.transition_engine.get_default_config(),
                     );
+                    let config = model.transition_engine.config_for(grid);
+                    match &model.osc_feedback {
+                        Some(feedback) => feedback.send_transition_config(&grid_name, config),
+                        None => println!("{} transitions now use {:?}", grid_name, config),
+                    }
+                }
+            }
+            OscCommand::PaletteList {} => {
+                for (name, colors) in model.palettes.sorted() {
+                    let colors: Vec<(&str, [f32; 4])> = colors
+                        .iter()
+                        .map(|color| (color.name.as_str(), color.rgba))
+                        .collect();
+                    match &model.osc_feedback {
+                        Some(feedback) => feedback.send_palette(name, &colors),
+                        None => println!("palette {}: {:?}", name, colors),
+                    }
+                }
+            }
+            OscCommand::GridChase {
+                name,
+                start,
+                end,
+                speed,
+                width,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    let rng = model.transition_engine.child_rng();
+                    if let Err(err) = grid.start_chase(
+                        start.as_deref(),
+                        end.as_deref(),
+                        speed,
+                        width.max(1) as usize,
+                        time,
+                        rng,
+                    ) {
+                        println!("{}", err);
+                    }
+                }
+            }
+            OscCommand::GridChaseStop { name } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.stop_chase();
+                }
+            }
+            OscCommand::GridHeatmap {
+                name,
+                enabled,
+                decay,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.set_heatmap(enabled, decay);
+                }
+            }
+            OscCommand::GridComponent {
+                name,
+                segment_id,
+                on,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.stage_component(&segment_id, on);
+                }
+            }
+            OscCommand::GridTag {
+                name,
+                tag,
+                on,
+                color,
+            } => {
+                let color = match color {
+                    Some(color) => match resolve_color(&model.palettes, &color) {
+                        Some(color) => Some(color),
+                        None => continue,
+                    },
+                    None => None,
+                };
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    if on {
+                        grid.stage_tag_on(&tag, color);
+                    } else {
+                        grid.stage_tag_off(&tag);
+                    }
+                }
+            }
+            OscCommand::GridTags { name } => {
+                if let Some(grid) = model.grids.get(&name) {
+                    let counts = grid.grid.tag_counts();
+                    match &model.osc_feedback {
+                        Some(feedback) => {
+                            let counts: Vec<(&str, usize)> = counts
+                                .iter()
+                                .map(|(tag, count)| (tag.as_str(), *count))
+                                .collect();
+                            feedback.send_grid_tag_counts(&name, &counts);
+                        }
+                        None => println!("{} tags: {:?}", name, counts),
+                    }
+                }
+            }
+            OscCommand::GridTileMask {
+                name,
+                tile,
+                visible,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.set_tile_visible(tile.0, tile.1, visible);
+                }
+            }
+            OscCommand::GridTileMaskRect {
+                name,
+                from,
+                to,
+                visible,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.set_tile_rect_visible(from, to, visible);
+                }
+            }
+            OscCommand::GridTileMaskClear { name } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.clear_tile_mask();
+                }
+            }
+            OscCommand::GridPreset { name, preset } => {
+                let Some(preset) = model.presets.get(&preset) else {
+                    eprintln!("Unknown preset: {}", preset);
+                    continue;
+                };
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    apply_preset(grid, preset, &model.transition_engine, time);
+                }
+            }
+            OscCommand::PresetReload {} => {
+                load_presets(&model.presets_dir, &mut model.presets);
+            }
+            OscCommand::GraphRebuild {} => {
+                // grids already out keep the graph they were made with
+                model.base_graph = Rc::new(SegmentGraph::with_cache(
+                    &model.base_grid,
+                    &model.graph_cache_path,
+                    &model.graph_cache_key,
+                    model.base_graph.threshold(),
+                    true,
+                ));
+            }
+            OscCommand::DebugGraph {
+                name,
+                enabled,
+                labels,
+            } => {
+                if let Some(grid) = model.grids.get_mut(&name) {
+                    grid.set_graph_debug(enabled, labels);
+                }
+            }
+            OscCommand::TransitionSeed { seed } => {
+                model.transition_engine.set_seed(seed);
+            }
+        }
+    }
+}
+
+// Makes a grid with the app's default style, from the pool when it has one
+fn create_grid(model: &mut Model, name: String, show: &str, position: Point2, rotation: f32) {
+    let started = Instant::now();
+    let pooled = model.grid_pool.take();
+    let source = if pooled.is_some() {
+        "pool"
+    } else {
+        "base grid"
+    };
+    let mut grid = GridInstance::with_grid(
+        name.clone(),
+        &model.project,
+        show,
+        pooled.unwrap_or_else(|| model.base_grid.clone()),
+        Rc::clone(&model.base_graph),
+        position,
+        rotation,
+        model.default_stroke_weight,
+        model.default_backbone_stroke_weight,
+    );
+    grid.glow = model.default_glow;
+    grid.envelope = model.default_envelope;
+    grid.set_colorful_rng(model.transition_engine.child_rng());
+    for (&segment_type, &multiplier) in &model.default_type_weights {
+        grid.set_type_weight(segment_type, multiplier);
+    }
+    println!(
+        "Grid {} created from the {} in {:.2} ms",
+        name,
+        source,
+        started.elapsed().as_secs_f64() * 1000.0
+    );
+    // a grid created again under the same name replaces the old one
+    if let Some(old) = model.grids.insert(name, grid) {
+        model.grid_pool.release(old);
+    }
+}
+
+// Moves a grid to a new name, along with its group memberships, snapshots
+// and mouse edits. Refuses a name that's taken rather than replacing that grid.
+fn rename_grid(model: &mut Model, name: String, new_name: String) {
+    if model.grids.contains_key(&new_name) {
+        println!(
+            "Can't rename grid {} to {}, a grid by that name already exists",
+            name, new_name
+        );
+        return;
+    }
+    let Some(mut grid) = model.grids.remove(&name) else {
+        println!("No grid named {} to rename", name);
+        return;
+    };
+    grid.id = new_name.clone();
+    model.grids.insert(new_name.clone(), grid);
+
+    for members in model.grid_groups.values_mut() {
+        for member in members.iter_mut().filter(|member| **member == name) {
+            *member = new_name.clone();
+        }
+    }
+    let slots: Vec<String> = model
+        .grid_snapshots
+        .keys()
+        .filter(|(grid, _)| *grid == name)
+        .map(|(_, slot)| slot.clone())
+        .collect();
+    for slot in slots {
+        if let Some(snapshot) = model.grid_snapshots.remove(&(name.clone(), slot.clone())) {
+            model
+                .grid_snapshots
+                .insert((new_name.clone(), slot), snapshot);
+        }
+    }
+    model.mouse_editor.rename_grid(&name, &new_name);
+
+    // the file may rename the grid itself, so its messages are left as written
+    if let Some(offline) = &model.offline {
+        let pending = offline.automation.pending_for(&name);
+        if pending > 0 {
+            println!(
+                "Warning: {} automation messages still to come are for grid {}, now named {}",
+                pending, name, new_name
+            );
+        }
+    }
+    println!("Grid {} renamed to {}", name, new_name);
+}
+
+// Colors sent by palette reference are looked up here, when the command runs.
+// A bad reference drops the command instead of falling back to black.
+fn resolve_color(palettes: &Palettes, color: &ColorArg) -> Option<Rgba> {
+    match color {
+        ColorArg::Rgba(r, g, b, a) => Some(rgba(*r, *g, *b, *a)),
+        ColorArg::Palette { palette, entry } => match palettes.resolve(palette, entry) {
+            Ok(color) => Some(color),
+            Err(err) => {
+                eprintln!("{}, command ignored", err);
+                None
+            }
+        },
+    }
+}
+
+// Applies every effect in a preset the way its own OSC command would.
+// Presets are validated whole when loaded, so this can't stop partway.
+fn apply_preset(
+    grid: &mut GridInstance,
+    preset: &EffectPreset,
+    transition_engine: &TransitionEngine,
+    time: f32,
+) {
+    for effect in &preset.effects {
+        match *effect {
+            EffectDescriptor::BackboneFade { color, duration } => {
+                let effect = FadeEffect {
+                    base_style: grid.backbone_style.clone(),
+                    target_style: DrawStyle {
+                        color: preset_rgba(color),
+                        stroke_weight: grid.backbone_style.stroke_weight,
+                    },
+                    duration,
+                    start_time: time,
+                    is_active: true,
+                };
+                grid.add_backbone_effect("backbone", Box::new(effect));
+            }
+            EffectDescriptor::BackbonePulse { period, depth } => {
+                let effect = PulseEffect::new(period, depth, time);
+                grid.add_backbone_effect("pulse", Box::new(effect));
+            }
+            EffectDescriptor::BackboneStrobe {
+                color,
+                hz,
+                duty,
+                duration,
+            } => {
+                let effect = StrobeEffect::new(
+                    grid.backbone_style.clone(),
+                    preset_rgba(color),
+                    hz,
+                    duty,
+                    duration,
+                    time,
+                );
+                grid.add_backbone_effect_with_priority("strobe", 1, Box::new(effect));
+            }
+            EffectDescriptor::BackboneStroke { stroke_weight } => {
+                grid.set_backbone_stroke_weight(stroke_weight);
+            }
+            EffectDescriptor::GlyphColor { color, duration } => {
+                if duration > 0.0 {
+                    grid.glyph_color_fade(preset_rgba(color), duration, time);
+                } else {
+                    grid.instant_color_change(preset_rgba(color));
+                }
+            }
+            EffectDescriptor::GlyphPulse { period, depth } => {
+                grid.set_pulse(period, depth, time);
+            }
+            EffectDescriptor::Colorful { enabled } => {
+                grid.colorful_flag = enabled;
+            }
+            EffectDescriptor::PowerEffect { enabled } => {
+                grid.use_power_on_effect = enabled;
+            }
+            EffectDescriptor::Rainbow { rate, spread } => {
+                grid.set_rainbow(rate, spread);
+            }
+            EffectDescriptor::Flicker {
+                rate,
+                depth,
+                duration,
+            } => {
+                if rate <= 0.0 || depth <= 0.0 {
+                    grid.set_flicker(None);
+                } else {
+                    let rng = transition_engine.child_rng();
+                    let effect = FlickerEffect::new(rate, depth, duration, rng);
+                    grid.set_flicker(Some(effect));
+                }
+            }
+            EffectDescriptor::NoiseColor {
+                scale,
+                speed,
+                range,
+            } => {
+                if range <= 0.0 {
+                    grid.set_noise_color(None);
+                } else {
+                    let seed = transition_engine.child_rng().gen();
+                    let effect = NoiseColorEffect::new(scale, speed, range, seed);
+                    grid.set_noise_color(Some(effect));
                 }
             }
+            EffectDescriptor::Sparkle {
+                rate,
+                lifetime,
+                color,
+            } => {
+                let rng = transition_engine.child_rng();
+                let effect = SparkleEffect::new(rate, lifetime, preset_rgba(color), rng);
+                grid.set_sparkle(Some(effect));
+            }
+            EffectDescriptor::Glow {
+                layers,
+                spread,
+                alpha,
+            } => {
+                grid.glow = (layers > 0).then_some(GlowParams {
+                    layers,
+                    spread,
+                    alpha_falloff: alpha,
+                });
+            }
+            EffectDescriptor::Envelope {
+                attack,
+                hold,
+                decay,
+                sustain_level,
+                release,
+            } => {
+                grid.envelope = Envelope::new(attack, hold, decay, sustain_level, release);
+            }
+            EffectDescriptor::Dimmer { level } => {
+                grid.dimmer = level.clamp(0.0, 1.0);
+            }
+        }
+    }
+}
+
+fn preset_rgba(color: PresetColor) -> Rgba {
+    let [r, g, b, a] = color.0;
+    rgba(r, g, b, a)
+}
+
+// The master fader as it applies this frame
+fn output_level(model: &Model) -> f32 {
+    if model.blackout {
+        0.0
+    } else {
+        model.master_brightness
+    }
+}
+
+fn texture_size(texture: &wgpu::Texture) -> Vec2 {
+    let [width, height] = texture.size();
+    vec2(width as f32, height as f32)
+}
+
+// Single types win over "arc" and "diagonal". Unknown type names are
+// reported and left out.
+fn resolve_type_weights(weights: &HashMap<String, f32>) -> HashMap<SegmentType, f32> {
+    let mut named: Vec<(&[SegmentType], f32)> = Vec::new();
+    for (name, &multiplier) in weights {
+        match SegmentType::named(name) {
+            Ok(types) => named.push((types, multiplier)),
+            Err(e) => println!("{} in [style.type_weights]", e),
+        }
+    }
+    named.sort_by_key(|(types, _)| std::cmp::Reverse(types.len()));
+
+    named
+        .into_iter()
+        .flat_map(|(types, multiplier)| types.iter().map(move |&t| (t, multiplier)))
+        .collect()
+}
+
+fn parse_easing(easing: Option<&str>) -> EasingType {
+    match easing.map(EasingType::try_from) {
+        Some(Ok(easing)) => easing,
+        Some(Err(err)) => {
+            println!("{}, using linear", err);
+            EasingType::Linear
         }
+        None => EasingType::Linear,
     }
 }
 
-fn transition_next_animation_type(msg: i32) -> TransitionAnimationType {
-    match msg {
-        0 => TransitionAnimationType::Random,
-        1 => TransitionAnimationType::Immediate,
-        2 => TransitionAnimationType::Writing,
-        3 => TransitionAnimationType::Overwrite,
-        _ => TransitionAnimationType::Immediate,
+fn transition_next_animation_type(msg: i32, args: &AnimationArgs) -> TransitionAnimationType {
+    match TransitionAnimationType::try_from(msg) {
+        Ok(TransitionAnimationType::Scanline { .. }) => {
+            let axis = match args.axis.as_deref().map(Axis::try_from) {
+                Some(Ok(axis)) => axis,
+                Some(Err(e)) => {
+                    println!("{}, using x", e);
+                    Axis::X
+                }
+                None => Axis::X,
+            };
+            TransitionAnimationType::Scanline {
+                axis,
+                reverse: args.reverse,
+            }
+        }
+        Ok(TransitionAnimationType::Ripple { .. }) => {
+            let (x, y) = args.origin.unwrap_or((0.0, 0.0));
+            TransitionAnimationType::Ripple { origin: pt2(x, y) }
+        }
+        Ok(TransitionAnimationType::Flood { .. }) => TransitionAnimationType::Flood {
+            // an empty seed is reported and replaced when the flood starts
+            seed: args.seed.clone().unwrap_or_default(),
+        },
+        Ok(animation_type) => animation_type,
+        Err(err) => {
+            println!("{}, using immediate", err);
+            TransitionAnimationType::Immediate
+        }
     }
 }
@@ -1,5 +1,7 @@
 pub mod data_model;
 pub mod geometry;
+pub mod segment_key;
 
 pub use data_model::Project;
 pub use geometry::{Axis, EdgeType, PathElement, ViewBox};
+pub use segment_key::SegmentKey;
@@ -0,0 +1,154 @@
+// src/models/segment_key.rs
+//
+// SegmentKey: names a segment of a grid by its tile and SVG element id.
+// Project files and OSC messages use the string form "x,y : element", which
+// Display and FromStr convert to and from. Stretch segments don't belong to
+// a tile. They're on tile (0, 0) and written as the element alone.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use smol_str::SmolStr;
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SegmentKey {
+    pub tile: (u32, u32), // 1-based, (0, 0) for segments off the grid
+    pub element: SmolStr,
+}
+
+impl SegmentKey {
+    pub fn new(tile: (u32, u32), element: impl Into<SmolStr>) -> Self {
+        Self {
+            tile,
+            element: element.into(),
+        }
+    }
+
+    // A segment that isn't on any tile, like a stretch segment
+    pub fn off_grid(element: impl Into<SmolStr>) -> Self {
+        Self::new((0, 0), element)
+    }
+
+    pub fn is_off_grid(&self) -> bool {
+        self.tile == (0, 0)
+    }
+}
+
+impl fmt::Display for SegmentKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_off_grid() {
+            write!(f, "{}", self.element)
+        } else {
+            write!(f, "{},{} : {}", self.tile.0, self.tile.1, self.element)
+        }
+    }
+}
+
+// Spaces around the numbers are allowed, as older project files have them
+impl FromStr for SegmentKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid segment id: '{}'. Expected 'x,y : element'", s);
+        let Some((tile, element)) = s.split_once(" : ") else {
+            // a tile with a mistyped separator isn't an off-grid element
+            if starts_with_tile(s) {
+                return Err(invalid());
+            }
+            return match s.trim() {
+                "" => Err("Empty segment id".to_string()),
+                element => Ok(Self::off_grid(element)),
+            };
+        };
+        let (x, y) = tile.split_once(',').ok_or_else(invalid)?;
+        let x = x.trim().parse().map_err(|_| invalid())?;
+        let y = y.trim().parse().map_err(|_| invalid())?;
+        let element = element.trim();
+        if element.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self::new((x, y), element))
+    }
+}
+
+// "x,y" at the start, with x a number and y starting with a digit
+fn starts_with_tile(s: &str) -> bool {
+    s.split_once(',').is_some_and(|(x, rest)| {
+        x.trim().parse::<u32>().is_ok()
+            && rest.trim_start().starts_with(|c: char| c.is_ascii_digit())
+    })
+}
+
+// Compares with the string form, as ids in project files are written
+impl PartialEq<str> for SegmentKey {
+    fn eq(&self, other: &str) -> bool {
+        other
+            .parse::<SegmentKey>()
+            .is_ok_and(|other| *self == other)
+    }
+}
+
+impl PartialEq<&str> for SegmentKey {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+// Kept in the string form, so caches written before keys existed still load
+impl Serialize for SegmentKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SegmentKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_round_trip_through_strings() {
+        let key: SegmentKey = "3,2 : arc-1".parse().unwrap();
+        assert_eq!(key, SegmentKey::new((3, 2), "arc-1"));
+        assert_eq!(key.to_string(), "3,2 : arc-1");
+
+        // older spacing still reads
+        assert_eq!("3, 2 : arc-1".parse::<SegmentKey>().unwrap(), key);
+
+        let stretch = SegmentKey::off_grid("stretch-x-1");
+        assert_eq!(stretch.to_string(), "stretch-x-1");
+        assert_eq!("stretch-x-1".parse::<SegmentKey>().unwrap(), stretch);
+
+        assert_eq!(key, "3,2 : arc-1");
+        assert_ne!(key, "3,2 : arc-2");
+
+        assert!("x,2 : arc-1".parse::<SegmentKey>().is_err());
+        assert!("3,2 : ".parse::<SegmentKey>().is_err());
+        assert!("".parse::<SegmentKey>().is_err());
+    }
+
+    #[test]
+    fn test_tiles_without_the_separator_are_refused() {
+        for id in ["3,2: arc-1", "3,2 :arc-1", "3,2:arc-1", "3, 2 arc-1", "3,2"] {
+            assert!(id.parse::<SegmentKey>().is_err(), "{} parsed", id);
+        }
+        // off-grid element ids may still hold commas
+        assert_eq!(
+            "stretch,x-1".parse::<SegmentKey>().unwrap(),
+            SegmentKey::off_grid("stretch,x-1")
+        );
+    }
+
+    #[test]
+    fn test_keys_serialize_as_strings() {
+        let key = SegmentKey::new((1, 4), "hor-1-1");
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json, "\"1,4 : hor-1-1\"");
+        assert_eq!(serde_json::from_str::<SegmentKey>(&json).unwrap(), key);
+    }
+}
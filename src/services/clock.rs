@@ -0,0 +1,73 @@
+// src/services/clock.rs
+//
+// The time base for a frame. Everything animated reads time and dt from
+// here instead of the system clock, so the same code runs live, paused
+// or rendered offline at a fixed frame rate.
+
+use std::time::Instant;
+
+pub struct Clock {
+    time: f32,               // seconds, as passed to animations
+    dt: f32,                 // seconds since the previous tick
+    fixed_step: Option<f32>, // when set, each tick advances exactly this much
+    last_tick: Instant,
+}
+
+impl Clock {
+    // Follows the app clock and measures dt from the wall clock
+    pub fn realtime() -> Self {
+        Self {
+            time: 0.0,
+            dt: 0.0,
+            fixed_step: None,
+            last_tick: Instant::now(),
+        }
+    }
+
+    // Advances by the same step every tick, however long the frame took
+    pub fn fixed(step: f32) -> Self {
+        Self {
+            fixed_step: Some(step),
+            ..Self::realtime()
+        }
+    }
+
+    // Call once at the start of each frame. `app_time` is only used in
+    // realtime mode.
+    pub fn tick(&mut self, app_time: f32) {
+        let now = Instant::now();
+        match self.fixed_step {
+            Some(step) => {
+                self.time += step;
+                self.dt = step;
+            }
+            None => {
+                self.time = app_time;
+                self.dt = (now - self.last_tick).as_secs_f32();
+            }
+        }
+        self.last_tick = now;
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock_ignores_app_time() {
+        let mut clock = Clock::fixed(0.5);
+        clock.tick(100.0);
+        clock.tick(100.0);
+        assert_eq!(clock.time(), 1.0);
+        assert_eq!(clock.dt(), 0.5);
+    }
+}
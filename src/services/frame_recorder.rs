@@ -1,16 +1,23 @@
 // src/services/frame_recorder.rs
 // FrameRecorder is a service for capturing frames from a wgpu::Texture and encoding them to video.
 // It gets its own thread to avoid blocking the main thread.
-// Encoding is done by piping frames directly to ffmpeg for h264 encoding.
+// Encoding is done by piping frames directly to ffmpeg for h264 encoding, by
+// writing numbered image files in parallel batches for the sequence modes, or
+// by streaming scaled-down frames into an animated GIF.
 
+use crate::config::{CropConfig, FfmpegConfig, RecordingQuality};
 use nannou::{image::RgbaImage, wgpu};
+use rayon::prelude::*;
+use serde::Serialize;
 use std::{
-    io::Write,
-    path::Path,
+    collections::VecDeque,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
     process::{Child, Command, Stdio},
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
-        mpsc::{channel, Receiver, Sender},
+        mpsc::{channel, sync_channel, Receiver, SyncSender, TrySendError},
         Arc, Mutex,
     },
     thread::{self, JoinHandle},
@@ -19,53 +26,396 @@ use std::{
 const BATCH_SIZE: usize = 10;
 const RESOLVED_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
 const VERBOSE: bool = false; // true to show debug msgs
+const JPEG_QUALITY: u8 = 95;
+const DEFAULT_GIF_WIDTH: u32 = 480;
+const GIF_QUANTIZE_SPEED: i32 = 10; // 1 (best colors) to 30 (fastest)
+
+// Frames captured but not yet written out are held in the worker channel and
+// each is a full RGBA frame, so these set how far the writer may fall behind
+// before frames are dropped. Masters get more room for ProRes's data rate.
+const PREVIEW_CHANNEL_CAPACITY: usize = 20;
+const MASTER_CHANNEL_CAPACITY: usize = 60;
+const PREVIEW_STAGING_BUFFERS: usize = 3;
+const MASTER_STAGING_BUFFERS: usize = 6;
+
+// ProRes 422 HQ runs about 220 Mb/s at 1080p30
+const PRORES_HQ_BITS_PER_PIXEL: f64 = 3.5;
+
+// Hardware encoders tried in order for encoder = "auto", before libx264
+const AUTO_ENCODERS: [&str; 2] = ["h264_nvenc", "h264_videotoolbox"];
+// Hardware encoders don't take a CRF, so they get a bitrate unless the
+// profile sets one
+const HARDWARE_BITRATE: &str = "50M";
+
+// Type alias for the frame data tuple: (frame number, rgba data, width, height)
+type FrameData = (u32, Vec<u8>, u32, u32);
+
+// How recorded frames are written out. Each take is named for the time it
+// started: video goes to <prefix>_<YYYYMMDD_HHMMSS>.mp4, and sequences to
+// numbered frames in a <prefix>_<YYYYMMDD_HHMMSS> directory.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputMode {
+    #[default]
+    H264Pipe,
+    PngSequence,
+    JpegSequence,
+    Gif {
+        width: u32,
+    }, // scaled down to this width, never up
+    // The alpha modes record over transparency instead of the background, for
+    // compositing: video to a .mov, or RGBA PNG frames
+    AlphaPipe {
+        codec: AlphaCodec,
+    },
+    PngAlphaSequence,
+}
+
+// Video codecs that carry an alpha channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaCodec {
+    ProRes4444,
+    Qtrle, // lossless, several times the size
+}
+
+impl AlphaCodec {
+    fn ffmpeg_config(self) -> FfmpegConfig {
+        match self {
+            AlphaCodec::ProRes4444 => FfmpegConfig::prores_4444(),
+            AlphaCodec::Qtrle => FfmpegConfig::qtrle(),
+        }
+    }
+}
+
+impl TryFrom<&str> for OutputMode {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "h264" | "mp4" => Ok(OutputMode::H264Pipe),
+            "png" => Ok(OutputMode::PngSequence),
+            "jpg" | "jpeg" => Ok(OutputMode::JpegSequence),
+            "gif" => Ok(OutputMode::Gif {
+                width: DEFAULT_GIF_WIDTH,
+            }),
+            "prores4444" | "prores_4444" => Ok(OutputMode::AlphaPipe {
+                codec: AlphaCodec::ProRes4444,
+            }),
+            "qtrle" => Ok(OutputMode::AlphaPipe {
+                codec: AlphaCodec::Qtrle,
+            }),
+            "png_alpha" => Ok(OutputMode::PngAlphaSequence),
+            _ => Err(format!(
+                "Invalid output mode: '{}'. Expected 'h264', 'png', 'jpg', 'gif', \
+                 'prores4444', 'qtrle' or 'png_alpha'",
+                value
+            )),
+        }
+    }
+}
+
+impl OutputMode {
+    // File extension of each sequence frame, None when recording video
+    fn frame_extension(&self) -> Option<&'static str> {
+        match self {
+            OutputMode::H264Pipe | OutputMode::Gif { .. } | OutputMode::AlphaPipe { .. } => None,
+            OutputMode::PngSequence | OutputMode::PngAlphaSequence => Some("png"),
+            OutputMode::JpegSequence => Some("jpg"),
+        }
+    }
+
+    // Encoded by ffmpeg rather than written out here
+    fn is_video(&self) -> bool {
+        matches!(self, OutputMode::H264Pipe | OutputMode::AlphaPipe { .. })
+    }
+
+    pub fn keeps_alpha(&self) -> bool {
+        matches!(
+            self,
+            OutputMode::AlphaPipe { .. } | OutputMode::PngAlphaSequence
+        )
+    }
+
+    // A GIF holds the whole animation in one file, so it has to have an end
+    pub fn check_frame_limit(self, frame_limit: u32) -> Result<Self, String> {
+        match self {
+            OutputMode::Gif { .. } if frame_limit == 0 => {
+                Err("GIF recording needs a frame_limit above 0".to_string())
+            }
+            mode => Ok(mode),
+        }
+    }
+}
+
+// A snapshot of the recorder for status replies
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecorderStatus {
+    pub recording: bool,
+    pub paused: bool,
+    pub frames_captured: u32,
+    pub frames_in_queue: usize,
+    pub frames_processed: usize,
+    pub frames_dropped: usize,
+    pub elapsed: f32, // seconds of video recorded so far
+    pub take_path: Option<PathBuf>,
+    pub replay_buffered: f32, // seconds held for /recorder/saveback, 0 with replay off
+}
+
+// Why a frame is missing from a take
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum DropCause {
+    Schedule, // the app fell behind the capture schedule
+    Busy,     // the previous capture was still being read back
+    Queue,    // the writer couldn't keep up
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FrameDrop {
+    video_ns: u64, // where in the take the frames are missing
+    frames: u64,
+    gap_ns: u64, // how far behind schedule capture was
+    cause: DropCause,
+}
+
+// Frames lost from the current take, counted as they happen and reported when
+// it stops
+#[derive(Debug, Default)]
+struct DropLog {
+    drops: Vec<FrameDrop>,
+    frames_dropped: u64,
+    late_captures: u64, // captured, but more than half a frame off schedule
+    worst_gap_ns: u64,  // furthest behind schedule a capture came
+}
+
+impl DropLog {
+    fn record_capture(&mut self, late_ns: u64, frame_time: u64) {
+        if late_ns > frame_time / 2 {
+            self.late_captures += 1;
+        }
+        self.worst_gap_ns = self.worst_gap_ns.max(late_ns);
+    }
+
+    fn record_drop(&mut self, video_ns: u64, frames: u64, gap_ns: u64, cause: DropCause) {
+        self.drops.push(FrameDrop {
+            video_ns,
+            frames,
+            gap_ns,
+            cause,
+        });
+        self.frames_dropped += frames;
+        self.worst_gap_ns = self.worst_gap_ns.max(gap_ns);
+    }
+
+    fn summary(&self) -> String {
+        if self.frames_dropped == 0 && self.late_captures == 0 {
+            return "No frames dropped".to_string();
+        }
+        format!(
+            "Dropped {} frames in {} gaps, {} late captures, worst gap {:.1}ms",
+            self.frames_dropped,
+            self.drops.len(),
+            self.late_captures,
+            self.worst_gap_ns as f64 / 1_000_000.0
+        )
+    }
+
+    fn report(&self, take_path: &Path, fps: u64, frames_captured: u32) -> DropReport {
+        DropReport {
+            take: take_path.to_string_lossy().into_owned(),
+            fps,
+            frames_captured,
+            frames_dropped: self.frames_dropped,
+            late_captures: self.late_captures,
+            worst_gap_ms: self.worst_gap_ns as f64 / 1_000_000.0,
+            drops: self
+                .drops
+                .iter()
+                .map(|drop| DropEntry {
+                    time: drop.video_ns as f64 / 1_000_000_000.0,
+                    frames: drop.frames,
+                    behind_ms: drop.gap_ns as f64 / 1_000_000.0,
+                    cause: drop.cause,
+                })
+                .collect(),
+        }
+    }
+}
+
+// The <take>.drops.json sidecar
+#[derive(Debug, Serialize)]
+struct DropReport {
+    take: String,
+    fps: u64,
+    frames_captured: u32,
+    frames_dropped: u64,
+    late_captures: u64,
+    worst_gap_ms: f64,
+    drops: Vec<DropEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct DropEntry {
+    time: f64, // seconds into the take
+    frames: u64,
+    behind_ms: f64,
+    cause: DropCause,
+}
+
+// The last few seconds of frames, kept as JPEGs while not recording so a
+// moment can be saved after it happened. The oldest frames go first when
+// either the length or the memory budget runs out.
+struct ReplayBuffer {
+    frames: VecDeque<Vec<u8>>,
+    bytes: usize,
+    max_frames: usize,
+    max_bytes: usize,
+}
+
+impl ReplayBuffer {
+    fn new(max_frames: usize, max_bytes: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(max_frames),
+            bytes: 0,
+            max_frames,
+            max_bytes,
+        }
+    }
+
+    fn push(&mut self, jpeg: Vec<u8>) {
+        self.bytes += jpeg.len();
+        self.frames.push_back(jpeg);
+        while self.frames.len() > self.max_frames || self.bytes > self.max_bytes {
+            match self.frames.pop_front() {
+                Some(oldest) => self.bytes -= oldest.len(),
+                None => break,
+            }
+        }
+    }
+
+    // Empties the buffer, handing back up to `count` of the newest frames
+    fn take_latest(&mut self, count: usize) -> Vec<Vec<u8>> {
+        let skip = self.frames.len().saturating_sub(count);
+        let frames = self.frames.drain(..).skip(skip).collect();
+        self.bytes = 0;
+        frames
+    }
+
+    fn clear(&mut self) {
+        self.frames.clear();
+        self.bytes = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.frames.len()
+    }
+}
 
-// Type alias for the frame data tuple
-type FrameData = (Vec<u8>, u32, u32);
+// A still capture that has been encoded but not yet read back from the GPU
+struct PendingStill {
+    path: PathBuf,
+    width: u32,
+    height: u32,
+}
 
 struct WorkerThread {
     thread_handle: JoinHandle<()>,
-    frame_sender: Sender<FrameData>,
+    frame_sender: SyncSender<FrameData>,
     shutdown_requested: Arc<AtomicBool>,
     thread_completed: Arc<AtomicBool>,
-    frames_in_queue: Arc<AtomicUsize>,
+    frames_in_queue: Arc<AtomicUsize>, // captured, not yet written out
+    frames_processed: Arc<AtomicUsize>, // written to ffmpeg or to disk
 
-    // FFmpeg process info
+    // FFmpeg process info, None for the sequence modes
     ffmpeg_process: Arc<Mutex<Option<Child>>>,
+
+    // The video file or sequence directory this take is written to
+    take_path: PathBuf,
 }
 
 pub struct FrameRecorder {
     worker_thread: Arc<Mutex<Option<WorkerThread>>>,
 
+    // Frames go here instead while not recording, when replay is on
+    replay_worker: Mutex<Option<WorkerThread>>,
+    replay: Option<Arc<Mutex<ReplayBuffer>>>,
+    // A take to carry on into, and its frame count so far, for /recorder/saveback
+    continue_take: Mutex<Option<(PathBuf, u32)>>,
+
     is_recording: Arc<Mutex<bool>>,
+    is_paused: AtomicBool, // recording, but not capturing frames
     frame_limit: u32,
+    limit_reached: AtomicBool, // capture stops here until the app stops the take
     frame_number: Arc<Mutex<u32>>,
+    drop_log: Arc<Mutex<DropLog>>, // frames skipped on schedule or by a full writer queue
     capture_in_progress: Arc<AtomicBool>,
     frame_time: u64,
     output_dir: String,
+    file_prefix: String,
     fps: u64,
+    output_mode: OutputMode,
+    ffmpeg_path: String,
+    ffmpeg: FfmpegConfig,
+    channel_capacity: usize,
+    offline: bool, // capture every frame and never drop one
 
     // capture pipeline
     texture_reshaper: wgpu::TextureReshaper,
-    resolved_texture: wgpu::Texture, // for MSAA resolution
-    staging_buffers: Vec<Arc<wgpu::Buffer>>,
+    resolved_texture: wgpu::Texture,         // for MSAA resolution
+    staging_buffers: Vec<Arc<wgpu::Buffer>>, // sized for the crop
     current_buffer_index: Arc<AtomicUsize>,
+    crop: CropConfig, // the whole texture unless set_crop() says otherwise
 
     // Synchronization
     next_scheduled_capture: Arc<Mutex<u64>>,
+
+    // still capture pipeline, kept apart from the recording staging buffers
+    still_staging_buffer: wgpu::Buffer,
+    pending_still: Mutex<Option<PendingStill>>,
 }
 
 impl FrameRecorder {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         device: &wgpu::Device,
         render_texture: &wgpu::Texture,
         output_dir: &str,
+        file_prefix: &str,
         frame_limit: u32,
         fps: u64,
+        output_mode: OutputMode,
+        ffmpeg_path: &str,
+        ffmpeg: FfmpegConfig,
+        quality: RecordingQuality,
     ) -> Self {
         // Ensure output directory exists
         std::fs::create_dir_all(output_dir).expect("Failed to create output directory");
 
+        let mut output_mode = output_mode;
+        if output_mode.is_video() && !ffmpeg_available(ffmpeg_path) {
+            output_mode = if output_mode.keeps_alpha() {
+                OutputMode::PngAlphaSequence
+            } else {
+                sequence_mode_for(quality)
+            };
+            println!();
+            println!("WARNING: ffmpeg wasn't found at '{}'", ffmpeg_path);
+            println!(
+                "WARNING: Recording {} frames instead of video. Install ffmpeg or set",
+                output_mode.frame_extension().unwrap_or_default()
+            );
+            println!("WARNING: ffmpeg_path under [frame_recorder] in config.toml.");
+            println!();
+        }
+        let ffmpeg = match output_mode {
+            OutputMode::H264Pipe => select_encoder(ffmpeg_path, ffmpeg),
+            OutputMode::AlphaPipe { codec } => codec.ffmpeg_config(),
+            _ => ffmpeg,
+        };
+        let (num_buffers, channel_capacity) = match quality {
+            RecordingQuality::Preview => (PREVIEW_STAGING_BUFFERS, PREVIEW_CHANNEL_CAPACITY),
+            RecordingQuality::Master => (MASTER_STAGING_BUFFERS, MASTER_CHANNEL_CAPACITY),
+        };
+
         // Create a texture for resolving MSAA
         let resolved_texture = wgpu::TextureBuilder::new()
             .size([render_texture.width(), render_texture.height()])
@@ -90,86 +440,403 @@ impl FrameRecorder {
         );
 
         // Create n staging buffers for GPU->CPU transfer
-        const NUM_BUFFERS: usize = 3;
+        let staging_buffers = create_staging_buffers(
+            device,
+            render_texture.width(),
+            render_texture.height(),
+            num_buffers,
+        );
+
+        // Stills are always the whole texture
         let pixel_size = format_bytes_per_pixel(RESOLVED_TEXTURE_FORMAT);
         let bytes_per_row = wgpu::util::align_to(render_texture.width() * pixel_size, 256);
         let buffer_size = (bytes_per_row * render_texture.height()) as u64;
-
-        let mut staging_buffers = Vec::with_capacity(NUM_BUFFERS);
-        for i in 0..NUM_BUFFERS {
-            let staging_buffer = Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
-                label: Some(&format!("Frame Capture Staging Buffer {}", i)),
-                size: buffer_size,
-                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-                mapped_at_creation: false,
-            }));
-            staging_buffers.push(staging_buffer);
-        }
+        let still_staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Still Capture Staging Buffer"),
+            size: buffer_size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
 
         Self {
             worker_thread: Arc::new(Mutex::new(None)),
+            replay_worker: Mutex::new(None),
+            replay: None,
+            continue_take: Mutex::new(None),
             is_recording: Arc::new(Mutex::new(false)),
+            is_paused: AtomicBool::new(false),
             frame_limit,
+            limit_reached: AtomicBool::new(false),
             frame_number: Arc::new(Mutex::new(0)),
+            drop_log: Arc::new(Mutex::new(DropLog::default())),
             capture_in_progress: Arc::new(AtomicBool::new(false)),
             frame_time: 1_000_000_000 / fps,
             output_dir: output_dir.to_string(),
+            file_prefix: file_prefix.to_string(),
             fps,
+            output_mode,
+            ffmpeg_path: ffmpeg_path.to_string(),
+            ffmpeg,
+            channel_capacity,
+            offline: false,
 
             texture_reshaper,
             resolved_texture,
             staging_buffers,
             current_buffer_index: Arc::new(AtomicUsize::new(0)),
+            crop: CropConfig {
+                x: 0,
+                y: 0,
+                w: render_texture.width(),
+                h: render_texture.height(),
+            },
 
             next_scheduled_capture: Arc::new(Mutex::new(0)),
+
+            still_staging_buffer,
+            pending_still: Mutex::new(None),
         }
     }
 
-    fn create_worker_thread(&self, width: u32, height: u32) -> WorkerThread {
+    fn create_worker_thread(
+        &self,
+        width: u32,
+        height: u32,
+        continued: Option<PathBuf>,
+    ) -> Result<WorkerThread, String> {
+        let extension = match self.output_mode {
+            OutputMode::H264Pipe | OutputMode::AlphaPipe { .. } => {
+                Some(self.ffmpeg.extension.as_str())
+            }
+            OutputMode::Gif { .. } => Some("gif"),
+            _ => None,
+        };
+        let take_path = continued.unwrap_or_else(|| {
+            next_take_path(
+                Path::new(&self.output_dir),
+                &self.file_prefix,
+                extension,
+                unix_time(),
+            )
+        });
+
+        Ok(match self.output_mode {
+            OutputMode::H264Pipe | OutputMode::AlphaPipe { .. } => {
+                self.create_pipe_worker(width, height, take_path)?
+            }
+            OutputMode::Gif { width: gif_width } => {
+                Self::create_gif_worker(take_path, gif_width, self.fps, self.channel_capacity)
+            }
+            mode => Self::create_sequence_worker(take_path, mode, self.channel_capacity),
+        })
+    }
+
+    fn create_pipe_worker(
+        &self,
+        width: u32,
+        height: u32,
+        take_path: PathBuf,
+    ) -> Result<WorkerThread, String> {
         let frames_in_queue = Arc::new(AtomicUsize::new(0));
+        let frames_processed = Arc::new(AtomicUsize::new(0));
         let ffmpeg_process = Arc::new(Mutex::new(None));
         let shutdown_requested = Arc::new(AtomicBool::new(false));
         let thread_completed = Arc::new(AtomicBool::new(false));
 
-        let (sender, receiver) = channel();
-
-        let thread_output_dir = self.output_dir.clone();
-        let thread_fps = self.fps;
-
-        // Pre-initialize FFmpeg before spawning the thread
-        let (process, stdin) = start_ffmpeg_process(&thread_output_dir, width, height, thread_fps);
+        let (sender, receiver) = sync_channel(self.channel_capacity);
+
+        println!("Encoding with {}", self.ffmpeg.codec);
+        let alpha = self.output_mode.keeps_alpha();
+        // Start FFmpeg before spawning the thread, so a failure stops the
+        // recording before it claims to have started
+        let (process, stdin) = start_ffmpeg_process(
+            &self.ffmpeg_path,
+            &take_path,
+            width,
+            height,
+            self.fps,
+            &self.ffmpeg,
+            alpha,
+        )?;
         *ffmpeg_process.lock().unwrap() = Some(process);
 
         let frames_in_queue_clone = frames_in_queue.clone();
+        let frames_processed_clone = frames_processed.clone();
         let ffmpeg_process_clone = ffmpeg_process.clone();
         let shutdown_requested_clone = shutdown_requested.clone();
         let thread_completed_clone = thread_completed.clone();
+        let is_recording = self.is_recording.clone();
 
-        // Pass the stdin to the thread
-        let ffmpeg_stdin = Arc::new(Mutex::new(Some(stdin)));
-
-        // Spawn worker thread with pre-initialized FFmpeg process
+        // Spawn worker thread with the running FFmpeg process
         let thread_handle = thread::spawn(move || {
             Self::worker_thread_function(
                 receiver,
-                thread_output_dir,
-                thread_fps,
+                stdin,
                 frames_in_queue_clone,
+                frames_processed_clone,
                 ffmpeg_process_clone,
                 shutdown_requested_clone,
                 thread_completed_clone,
-                ffmpeg_stdin, // Pass the pre-initialized stdin
+                is_recording,
+                alpha,
             );
         });
 
-        WorkerThread {
+        Ok(WorkerThread {
             thread_handle,
             frame_sender: sender,
             shutdown_requested,
             frames_in_queue,
+            frames_processed,
             thread_completed,
             ffmpeg_process,
+            take_path,
+        })
+    }
+
+    // Sequences go to a fresh directory per take, named like the videos
+    fn create_sequence_worker(
+        take_path: PathBuf,
+        mode: OutputMode,
+        channel_capacity: usize,
+    ) -> WorkerThread {
+        let frames_in_queue = Arc::new(AtomicUsize::new(0));
+        let frames_processed = Arc::new(AtomicUsize::new(0));
+        let shutdown_requested = Arc::new(AtomicBool::new(false));
+        let thread_completed = Arc::new(AtomicBool::new(false));
+
+        let (sender, receiver) = sync_channel(channel_capacity);
+
+        let sequence_dir = take_path.clone();
+        if let Err(e) = std::fs::create_dir_all(&sequence_dir) {
+            eprintln!("Failed to create {}: {}", sequence_dir.display(), e);
+        }
+
+        let frames_in_queue_clone = frames_in_queue.clone();
+        let frames_processed_clone = frames_processed.clone();
+        let shutdown_requested_clone = shutdown_requested.clone();
+        let thread_completed_clone = thread_completed.clone();
+
+        let thread_handle = thread::spawn(move || {
+            Self::sequence_worker_function(
+                receiver,
+                sequence_dir,
+                mode,
+                frames_in_queue_clone,
+                frames_processed_clone,
+                shutdown_requested_clone,
+                thread_completed_clone,
+            );
+        });
+
+        WorkerThread {
+            thread_handle,
+            frame_sender: sender,
+            shutdown_requested,
+            frames_in_queue,
+            frames_processed,
+            thread_completed,
+            ffmpeg_process: Arc::new(Mutex::new(None)),
+            take_path,
+        }
+    }
+
+    // Frames are scaled and encoded one at a time as they arrive, so memory
+    // stays at what the channel holds however long the animation runs
+    fn create_gif_worker(
+        take_path: PathBuf,
+        gif_width: u32,
+        fps: u64,
+        channel_capacity: usize,
+    ) -> WorkerThread {
+        let frames_in_queue = Arc::new(AtomicUsize::new(0));
+        let frames_processed = Arc::new(AtomicUsize::new(0));
+        let shutdown_requested = Arc::new(AtomicBool::new(false));
+        let thread_completed = Arc::new(AtomicBool::new(false));
+
+        let (sender, receiver) = sync_channel(channel_capacity);
+
+        let thread_take_path = take_path.clone();
+        let frames_in_queue_clone = frames_in_queue.clone();
+        let frames_processed_clone = frames_processed.clone();
+        let shutdown_requested_clone = shutdown_requested.clone();
+        let thread_completed_clone = thread_completed.clone();
+
+        let thread_handle = thread::spawn(move || {
+            Self::gif_worker_function(
+                receiver,
+                thread_take_path,
+                gif_width,
+                fps,
+                frames_in_queue_clone,
+                frames_processed_clone,
+                shutdown_requested_clone,
+                thread_completed_clone,
+            );
+        });
+
+        WorkerThread {
+            thread_handle,
+            frame_sender: sender,
+            shutdown_requested,
+            frames_in_queue,
+            frames_processed,
+            thread_completed,
+            ffmpeg_process: Arc::new(Mutex::new(None)),
+            take_path,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn gif_worker_function(
+        receiver: Receiver<FrameData>,
+        take_path: PathBuf,
+        gif_width: u32,
+        fps: u64,
+        frames_in_queue: Arc<AtomicUsize>,
+        frames_processed: Arc<AtomicUsize>,
+        shutdown_requested: Arc<AtomicBool>,
+        thread_completed: Arc<AtomicBool>,
+    ) {
+        // Created with the first frame, once its size is known
+        let mut encoder: Option<GifWriter> = None;
+
+        loop {
+            match receiver.recv_timeout(std::time::Duration::from_millis(50)) {
+                Ok((frame_number, frame_data, width, height)) => {
+                    if encoder.is_none() {
+                        match GifWriter::create(&take_path, width, height, gif_width, fps) {
+                            Ok(writer) => encoder = Some(writer),
+                            Err(e) => eprintln!("Failed to start {}: {}", take_path.display(), e),
+                        }
+                    }
+                    if let Some(writer) = encoder.as_mut() {
+                        match writer.write_frame(frame_data, width, height) {
+                            Ok(()) => {
+                                frames_processed.fetch_add(1, Ordering::SeqCst);
+                            }
+                            Err(e) => eprintln!("Failed to add frame {}: {}", frame_number, e),
+                        }
+                    }
+                    frames_in_queue.fetch_sub(1, Ordering::SeqCst);
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                    if shutdown_requested.load(Ordering::SeqCst) {
+                        break;
+                    }
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        // Dropping the encoder writes the GIF trailer
+        drop(encoder);
+        thread_completed.store(true, Ordering::SeqCst);
+        println!("GIF worker thread finished");
+    }
+
+    // Replay frames are encoded in parallel batches like the sequences, but
+    // kept in memory
+    fn create_replay_worker(replay: Arc<Mutex<ReplayBuffer>>) -> WorkerThread {
+        let frames_in_queue = Arc::new(AtomicUsize::new(0));
+        let frames_processed = Arc::new(AtomicUsize::new(0));
+        let shutdown_requested = Arc::new(AtomicBool::new(false));
+        let thread_completed = Arc::new(AtomicBool::new(false));
+
+        let (sender, receiver) = sync_channel(PREVIEW_CHANNEL_CAPACITY);
+
+        let frames_in_queue_clone = frames_in_queue.clone();
+        let shutdown_requested_clone = shutdown_requested.clone();
+        let thread_completed_clone = thread_completed.clone();
+
+        let thread_handle = thread::spawn(move || {
+            let mut frame_batch = Vec::with_capacity(BATCH_SIZE);
+            loop {
+                match receiver.recv_timeout(std::time::Duration::from_millis(50)) {
+                    Ok(frame) => {
+                        frame_batch.push(frame);
+                        if frame_batch.len() >= BATCH_SIZE {
+                            buffer_replay_batch(&mut frame_batch, &replay, &frames_in_queue_clone);
+                        }
+                    }
+                    Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                        buffer_replay_batch(&mut frame_batch, &replay, &frames_in_queue_clone);
+                        if shutdown_requested_clone.load(Ordering::SeqCst) {
+                            break;
+                        }
+                    }
+                    Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            thread_completed_clone.store(true, Ordering::SeqCst);
+        });
+
+        WorkerThread {
+            thread_handle,
+            frame_sender: sender,
+            shutdown_requested,
+            frames_in_queue,
+            frames_processed,
+            thread_completed,
+            ffmpeg_process: Arc::new(Mutex::new(None)),
+            take_path: PathBuf::new(),
+        }
+    }
+
+    fn sequence_worker_function(
+        receiver: Receiver<FrameData>,
+        sequence_dir: PathBuf,
+        mode: OutputMode,
+        frames_in_queue: Arc<AtomicUsize>,
+        frames_processed: Arc<AtomicUsize>,
+        shutdown_requested: Arc<AtomicBool>,
+        thread_completed: Arc<AtomicBool>,
+    ) {
+        let mut frame_batch = Vec::with_capacity(BATCH_SIZE);
+
+        loop {
+            // Use recv_timeout to allow checking for shutdown
+            match receiver.recv_timeout(std::time::Duration::from_millis(50)) {
+                Ok(frame) => {
+                    frame_batch.push(frame);
+                    if frame_batch.len() >= BATCH_SIZE {
+                        write_frame_batch(
+                            &mut frame_batch,
+                            &sequence_dir,
+                            mode,
+                            &frames_in_queue,
+                            &frames_processed,
+                        );
+                    }
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                    // Write any partial batch while waiting for more frames
+                    write_frame_batch(
+                        &mut frame_batch,
+                        &sequence_dir,
+                        mode,
+                        &frames_in_queue,
+                        &frames_processed,
+                    );
+                    if shutdown_requested.load(Ordering::SeqCst) {
+                        break;
+                    }
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                    write_frame_batch(
+                        &mut frame_batch,
+                        &sequence_dir,
+                        mode,
+                        &frames_in_queue,
+                        &frames_processed,
+                    );
+                    break;
+                }
+            }
         }
+
+        thread_completed.store(true, Ordering::SeqCst);
+        println!("Sequence worker thread finished");
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -177,34 +844,56 @@ impl FrameRecorder {
     // of args.
     fn worker_thread_function(
         receiver: Receiver<FrameData>,
-        output_dir: String,
-        fps: u64,
+        mut ffmpeg_stdin: std::process::ChildStdin,
         frames_in_queue: Arc<AtomicUsize>,
+        frames_processed: Arc<AtomicUsize>,
         ffmpeg_process: Arc<Mutex<Option<Child>>>,
         shutdown_requested: Arc<AtomicBool>,
         thread_completed: Arc<AtomicBool>,
-        ffmpeg_stdin: Arc<Mutex<Option<std::process::ChildStdin>>>,
+        is_recording: Arc<Mutex<bool>>,
+        alpha: bool, // pass RGBA through rather than convert to RGB
     ) {
         // Add batch handling
         let mut frame_batch = Vec::new();
         let mut batch_count = 0;
 
+        // Hands the batch to FFmpeg. If FFmpeg has gone away nothing more can
+        // be written, so the recording is marked stopped rather than claim to
+        // go on.
+        let mut write_batch = |frame_batch: &mut Vec<u8>, batch_count: &mut usize| -> bool {
+            if *batch_count == 0 {
+                return true;
+            }
+            let written = ffmpeg_stdin.write_all(frame_batch);
+            frame_batch.clear();
+            let count = std::mem::take(batch_count);
+            match written {
+                Ok(()) => {
+                    frames_in_queue.fetch_sub(count, Ordering::SeqCst);
+                    frames_processed.fetch_add(count, Ordering::SeqCst);
+                    true
+                }
+                Err(e) => {
+                    frames_in_queue.fetch_sub(count, Ordering::SeqCst);
+                    eprintln!("Failed to write frames to FFmpeg, recording stopped: {}", e);
+                    *is_recording.lock().unwrap() = false;
+                    false
+                }
+            }
+        };
+
         loop {
             // Use recv_timeout to allow checking for shutdown
             match receiver.recv_timeout(std::time::Duration::from_millis(50)) {
-                Ok((frame_data, width, height)) => {
-                    // Initialize FFmpeg if needed
-                    if batch_count == 0 {
-                        let mut stdin_guard = ffmpeg_stdin.lock().unwrap();
-                        if stdin_guard.is_none() {
-                            // Initialize FFmpeg on first frame
-                            let (process, stdin) =
-                                start_ffmpeg_process(&output_dir, width, height, fps);
-                            *ffmpeg_process.lock().unwrap() = Some(process);
-                            *stdin_guard = Some(stdin);
-                        }
+                Ok((_, frame_data, _, _)) if alpha => {
+                    frame_batch.extend_from_slice(&frame_data);
+                    batch_count += 1;
+                    if batch_count >= BATCH_SIZE && !write_batch(&mut frame_batch, &mut batch_count)
+                    {
+                        break;
                     }
-
+                }
+                Ok((_, frame_data, width, height)) => {
                     // Convert RGBA to RGB and add to batch
                     if let Some(image_buffer) = RgbaImage::from_raw(width, height, frame_data) {
                         let rgb_buffer =
@@ -215,58 +904,31 @@ impl FrameRecorder {
                         batch_count += 1;
 
                         // Process batch if full
-                        if batch_count >= BATCH_SIZE {
-                            // Write batch to FFmpeg
-                            let mut stdin_guard = ffmpeg_stdin.lock().unwrap();
-                            if let Some(stdin) = stdin_guard.as_mut() {
-                                if let Err(e) = stdin.write_all(&frame_batch) {
-                                    eprintln!("Failed to write frames to FFmpeg: {}", e);
-                                } else {
-                                    frames_in_queue.fetch_sub(batch_count, Ordering::SeqCst);
-                                }
-                            }
-                            frame_batch.clear();
-                            batch_count = 0;
+                        if batch_count >= BATCH_SIZE
+                            && !write_batch(&mut frame_batch, &mut batch_count)
+                        {
+                            break;
                         }
                     }
                 }
                 Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
                     // Check if shutdown requested and handle any partial batch
                     if shutdown_requested.load(Ordering::SeqCst) {
-                        // Write any remaining frames in the batch
-                        if batch_count > 0 {
-                            let mut stdin_guard = ffmpeg_stdin.lock().unwrap();
-                            if let Some(stdin) = stdin_guard.as_mut() {
-                                if let Err(e) = stdin.write_all(&frame_batch) {
-                                    eprintln!("Failed to write remaining frames to FFmpeg: {}", e);
-                                } else {
-                                    frames_in_queue.fetch_sub(batch_count, Ordering::SeqCst);
-                                }
-                            }
-                        }
-
-                        // Close the FFmpeg stdin stream to signal end of input
-                        drop(ffmpeg_stdin.lock().unwrap().take());
+                        write_batch(&mut frame_batch, &mut batch_count);
                         break; // Exit the loop
                     }
                 }
                 Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
                     // Channel closed, handle any remaining frames
-                    if batch_count > 0 {
-                        let mut stdin_guard = ffmpeg_stdin.lock().unwrap();
-                        if let Some(stdin) = stdin_guard.as_mut() {
-                            if let Err(e) = stdin.write_all(&frame_batch) {
-                                eprintln!("Failed to write remaining frames to FFmpeg: {}", e);
-                            } else {
-                                frames_in_queue.fetch_sub(batch_count, Ordering::SeqCst);
-                            }
-                        }
-                    }
+                    write_batch(&mut frame_batch, &mut batch_count);
                     break;
                 }
             }
         }
 
+        // Close the FFmpeg stdin stream to signal end of input
+        drop(ffmpeg_stdin);
+
         // Wait for FFmpeg to finish after exiting the loop
         if let Some(mut process) = ffmpeg_process.lock().unwrap().take() {
             match process.wait() {
@@ -287,11 +949,21 @@ impl FrameRecorder {
     pub fn toggle_recording(&self) {
         let mut is_recording = self.is_recording.lock().unwrap();
         *is_recording = !*is_recording;
+        self.is_paused.store(false, Ordering::SeqCst);
 
         if *is_recording {
             // Starting a new recording - clean up any completed worker first
             self.cleanup_completed_worker();
 
+            // The replay buffer picks up again from when this take stops
+            if let Some(replay) = &self.replay {
+                replay.lock().unwrap().clear();
+            }
+            let (continued, first_frame) = match self.continue_take.lock().unwrap().take() {
+                Some((take_path, frames)) => (Some(take_path), frames),
+                None => (None, 0),
+            };
+
             let mut worker_thread_guard = self.worker_thread.lock().unwrap();
 
             // If there's an existing worker thread, just signal it to shut down
@@ -300,23 +972,52 @@ impl FrameRecorder {
                 Self::request_worker_shutdown(worker);
             }
 
-            let width = self.resolved_texture.width();
-            let height = self.resolved_texture.height();
+            let width = self.crop.w;
+            let height = self.crop.h;
 
             // Create new worker thread
-            *worker_thread_guard = Some(self.create_worker_thread(width, height));
+            let worker = match self.create_worker_thread(width, height, continued) {
+                Ok(worker) => worker,
+                Err(e) => {
+                    eprintln!("Recording failed to start: {}", e);
+                    *is_recording = false;
+                    return;
+                }
+            };
+            println!("Recording started: {}", worker.take_path.display());
+            *worker_thread_guard = Some(worker);
 
             // Reset recording state
-            *self.frame_number.lock().unwrap() = 0;
+            *self.frame_number.lock().unwrap() = first_frame;
+            self.limit_reached.store(false, Ordering::SeqCst);
+            *self.drop_log.lock().unwrap() = DropLog::default();
             *self.next_scheduled_capture.lock().unwrap() = 0;
-            println!("Recording started");
+            if let Some(note) = self.throughput_note(width, height) {
+                println!("{}", note);
+            }
         } else {
             // Stopping recording - just signal the worker to shut down
-            println!("Recording stopped");
+            match self.current_take() {
+                Some(take_path) => {
+                    println!("Recording stopped: {}", take_path.display());
+                    self.write_drop_report(&take_path);
+                }
+                None => println!("Recording stopped"),
+            }
             self.signal_shutdown();
         }
     }
 
+    // Prints the take's dropped frames and saves them beside it
+    fn write_drop_report(&self, take_path: &Path) {
+        let drop_log = self.drop_log.lock().unwrap();
+        println!("{}", drop_log.summary());
+
+        let frames_captured = *self.frame_number.lock().unwrap();
+        let report = drop_log.report(take_path, self.fps, frames_captured);
+        write_sidecar(take_path, "drops.json", &report);
+    }
+
     fn request_worker_shutdown(worker: &WorkerThread) {
         worker.shutdown_requested.store(true, Ordering::SeqCst);
     }
@@ -349,7 +1050,8 @@ impl FrameRecorder {
         }
     }
 
-    pub fn cleanup_completed_worker(&self) {
+    // Returns the take that finished writing, if any, so it can be announced
+    pub fn cleanup_completed_worker(&self) -> Option<PathBuf> {
         let mut worker_thread_guard = self.worker_thread.lock().unwrap();
 
         if let Some(worker) = worker_thread_guard.as_ref() {
@@ -364,94 +1066,241 @@ impl FrameRecorder {
                         eprintln!("Error joining completed worker thread: {:?}", e);
                     } else {
                         println!("Worker thread cleanup complete.\n");
+                        return Some(worker.take_path);
                     }
                 }
             }
         }
+        None
     }
 
     pub fn is_recording(&self) -> bool {
         *self.is_recording.lock().unwrap()
     }
 
-    pub fn capture_frame(
-        &self,
-        device: &wgpu::Device,
-        encoder: &mut wgpu::CommandEncoder,
-        render_texture: &wgpu::Texture,
-    ) {
+    // True while frames should be handed to capture_frame(): during a take,
+    // or all the time when the replay buffer is on
+    pub fn wants_frames(&self) -> bool {
+        self.is_recording() || self.replay.is_some()
+    }
+
+    // Stops capturing without finishing the recording. The ffmpeg pipe stays
+    // open and frames are only appended while unpaused, so the video plays
+    // straight through the pause.
+    pub fn pause(&self) {
         if !self.is_recording() {
+            println!("WARNING: Not recording, nothing to pause");
             return;
         }
+        if !self.is_paused.swap(true, Ordering::SeqCst) {
+            println!("Recording paused");
+        }
+    }
 
-        // Get the worker thread
-        let worker_thread_guard = self.worker_thread.lock().unwrap();
-        let worker_thread = match worker_thread_guard.as_ref() {
-            Some(worker) => worker,
-            None => return, // No worker thread available
+    pub fn resume(&self) {
+        if !self.is_recording() {
+            println!("WARNING: Not recording, nothing to resume");
+            return;
+        }
+        if self.is_paused.swap(false, Ordering::SeqCst) {
+            // restart the frame clock, or the pause would read as frames to skip
+            *self.next_scheduled_capture.lock().unwrap() = 0;
+            println!("Recording resumed");
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused.load(Ordering::SeqCst)
+    }
+
+    // Keeps the last `seconds` captured while not recording, in at most
+    // `max_bytes` of JPEGs. Capturing all the time costs GPU readback and
+    // encoding every frame, so this is off unless configured.
+    pub fn set_replay(&mut self, seconds: f32, max_bytes: usize) {
+        let max_frames = (seconds * self.fps as f32).round() as usize;
+        if max_frames == 0 {
+            return;
+        }
+        let replay = Arc::new(Mutex::new(ReplayBuffer::new(max_frames, max_bytes)));
+        *self.replay_worker.lock().unwrap() = Some(Self::create_replay_worker(replay.clone()));
+        self.replay = Some(replay);
+        println!(
+            "Replay buffer holds the last {:.1}s, up to {} MB",
+            seconds,
+            max_bytes / 1_000_000
+        );
+    }
+
+    // Writes the last `seconds` of the replay buffer to a new JPEG sequence,
+    // named for when its first frame was captured, and returns its directory.
+    // With `continue_recording`, recording starts straight after: into the
+    // same sequence when recording JPEGs, or as a take of its own otherwise.
+    pub fn save_replay(&self, seconds: f32, continue_recording: bool) -> Result<PathBuf, String> {
+        let Some(replay) = &self.replay else {
+            return Err("Replay isn't on, set replay_seconds in config.toml".to_string());
         };
+        if self.is_recording() {
+            return Err("Already recording, nothing buffered".to_string());
+        }
 
-        // Check if enough time has passed since last capture
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64;
+        let count = (seconds * self.fps as f32).round() as usize;
+        let frames = replay.lock().unwrap().take_latest(count);
+        if frames.is_empty() {
+            return Err("Nothing buffered yet".to_string());
+        }
 
-        // Get our next scheduled capture time
-        let mut next_scheduled = self.next_scheduled_capture.lock().unwrap();
-        // If this is the first frame after starting recording, initialize the schedule
-        if *next_scheduled == 0 {
-            *next_scheduled = now;
+        let buffered_secs = frames.len() as u64 / self.fps;
+        let take_path = next_take_path(
+            Path::new(&self.output_dir),
+            &self.file_prefix,
+            None,
+            unix_time().saturating_sub(buffered_secs),
+        );
+        std::fs::create_dir_all(&take_path)
+            .map_err(|e| format!("Failed to create {}: {}", take_path.display(), e))?;
+
+        let frame_count = frames.len() as u32;
+        let sequence_dir = take_path.clone();
+        thread::spawn(move || {
+            for (index, jpeg) in frames.iter().enumerate() {
+                let path = sequence_dir.join(format!("frame{:05}.jpg", index + 1));
+                if let Err(e) = std::fs::write(&path, jpeg) {
+                    eprintln!("Failed to save replay frame {}: {}", path.display(), e);
+                }
+            }
+            println!(
+                "Saved {} replay frames to {}",
+                frames.len(),
+                sequence_dir.display()
+            );
+        });
+
+        if continue_recording {
+            if self.output_mode == OutputMode::JpegSequence {
+                *self.continue_take.lock().unwrap() = Some((take_path.clone(), frame_count));
+            }
+            self.toggle_recording();
         }
-        // Check if it's time for the next frame yet
-        if now < *next_scheduled {
-            // Too early, wait until exactly the scheduled time
-            return;
+        Ok(take_path)
+    }
+
+    // For renders that don't run in real time: every frame is captured, and a
+    // full writer queue holds up the render instead of dropping frames
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    // Records only `crop` of the texture from the next take, or the whole
+    // texture with None. Only the cropped pixels are copied off the GPU, into
+    // staging buffers sized to match. Returns the crop as applied.
+    pub fn set_crop(
+        &mut self,
+        device: &wgpu::Device,
+        crop: Option<CropConfig>,
+    ) -> Result<CropConfig, String> {
+        if self.is_recording() {
+            return Err("Can't change the crop while recording".to_string());
         }
 
-        // If we're more than a frame behind, skip to the next appropriate frame time
-        // This prevents frame accumulation if we fall behind
-        if now > *next_scheduled + self.frame_time {
-            // Calculate how many frames we're behind
-            let frames_behind = (now - *next_scheduled) / self.frame_time;
+        let width = self.resolved_texture.width();
+        let height = self.resolved_texture.height();
+        let crop = match crop {
+            Some(crop) => validate_crop(crop, width, height, self.output_mode.is_video())?,
+            None => CropConfig {
+                x: 0,
+                y: 0,
+                w: width,
+                h: height,
+            },
+        };
 
-            // Calculate time difference in milliseconds
-            let time_diff_ms = (now - *next_scheduled) / 1_000_000;
+        self.staging_buffers =
+            create_staging_buffers(device, crop.w, crop.h, self.staging_buffers.len());
+        self.current_buffer_index.store(0, Ordering::SeqCst);
+        self.crop = crop;
+        Ok(crop)
+    }
 
-            // Calculate video timestamp (time since recording started)
-            // We use frame_number to calculate the position in the video timeline
-            let frame_num = *self.frame_number.lock().unwrap();
-            let video_time_ns = frame_num as u64 * self.frame_time;
-            let video_time_s = video_time_ns / 1_000_000_000;
-            let video_time_ms = (video_time_ns % 1_000_000_000) / 1_000_000;
+    // True once a take has all the frames it's allowed, until it's stopped
+    pub fn frame_limit_reached(&self) -> bool {
+        self.limit_reached.load(Ordering::SeqCst)
+    }
 
-            let video_timestamp = format!(
-                "{:02}:{:02}:{:02}.{:03}",
-                (video_time_s / 3600),    // hours
-                (video_time_s / 60) % 60, // minutes
-                video_time_s % 60,        // seconds
-                video_time_ms             // milliseconds
-            );
+    pub fn fps(&self) -> u64 {
+        self.fps
+    }
 
-            // Skip to the next valid frame time, dropping any missed frames
-            *next_scheduled += (frames_behind + 1) * self.frame_time;
+    // Size of the recorded frames, after any crop
+    pub fn capture_size(&self) -> (u32, u32) {
+        (self.crop.w, self.crop.h)
+    }
 
-            println!(
-                "WARNING: Skipped {} frames, {}ms behind schedule, video time: {}",
-                frames_behind, time_diff_ms, video_timestamp
-            );
+    // Saves `metadata` as <take>.meta.json beside the current take. Like the
+    // drops report, failing to write it only warns.
+    pub fn write_metadata(&self, metadata: &impl Serialize) {
+        if let Some(take_path) = self.current_take() {
+            write_sidecar(&take_path, "meta.json", metadata);
+        }
+    }
+
+    // The ffmpeg encoder recordings use, None for the image modes
+    pub fn encoder(&self) -> Option<&str> {
+        match self.output_mode {
+            OutputMode::H264Pipe | OutputMode::AlphaPipe { .. } => Some(&self.ffmpeg.codec),
+            _ => None,
+        }
+    }
+
+    // Where the current take is being written, until it's finished writing
+    pub fn current_take(&self) -> Option<PathBuf> {
+        self.worker_thread
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|worker| worker.take_path.clone())
+    }
 
-            return; // Skip this frame and catch up on the next one
+    pub fn capture_frame(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        _render_texture: &wgpu::Texture,
+    ) {
+        if self.is_paused() {
+            return;
         }
+        // Between takes, frames go to the replay buffer if there is one
+        let recording = self.is_recording();
+        let worker_slot = match (recording, &self.replay) {
+            (true, _) => &*self.worker_thread,
+            (false, Some(_)) => &self.replay_worker,
+            (false, None) => return,
+        };
+
+        // Get the worker thread
+        let worker_thread_guard = worker_slot.lock().unwrap();
+        let worker_thread = match worker_thread_guard.as_ref() {
+            Some(worker) => worker,
+            None => return, // No worker thread available
+        };
 
-        // Schedule the next frame at exactly frame_time nanoseconds from the current scheduled time
-        *next_scheduled += self.frame_time;
+        // Offline renders capture every frame, however long it took
+        if !self.offline && !self.capture_due(recording) {
+            return;
+        }
 
         // Check if we're still processing the previous frame
         if self.capture_in_progress.load(Ordering::SeqCst) {
-            println!(
-                "WARNING: Previous capture still in progress, skipping frame at scheduled time {}",
-                *next_scheduled - self.frame_time
+            if !recording {
+                return;
+            }
+            println!("WARNING: Previous capture still in progress, skipping frame");
+            let video_ns = *self.frame_number.lock().unwrap() as u64 * self.frame_time;
+            self.drop_log.lock().unwrap().record_drop(
+                video_ns,
+                1,
+                self.frame_time,
+                DropCause::Busy,
             );
             return;
         }
@@ -461,15 +1310,24 @@ impl FrameRecorder {
         //*last_capture = now;
         let frame_start = std::time::Instant::now();
 
-        // Check if we've reached the frame limit
-        let mut frame_number = self.frame_number.lock().unwrap();
-        if *frame_number >= self.frame_limit {
-            self.toggle_recording();
-            return;
-        }
+        // Check if we've reached the frame limit. The app stops the take, so it
+        // can write the take's metadata first. Replay frames aren't numbered.
+        let frame_num = if recording {
+            let mut frame_number = self.frame_number.lock().unwrap();
+            if *frame_number >= self.frame_limit {
+                if !self.limit_reached.swap(true, Ordering::SeqCst) {
+                    println!("Frame limit of {} reached", self.frame_limit);
+                }
+                self.capture_in_progress.store(false, Ordering::SeqCst);
+                return;
+            }
 
-        // Increment frame number
-        *frame_number += 1;
+            // Increment frame number
+            *frame_number += 1;
+            *frame_number
+        } else {
+            0
+        };
 
         // Get the next staging buffer
         let buffer_index = {
@@ -489,24 +1347,31 @@ impl FrameRecorder {
             println!("MSAA resolve took: {:?}", msaa_start.elapsed());
         }
 
-        // Step 2: Copy from resolved texture to staging buffer
+        // Step 2: Copy the crop from resolved texture to staging buffer
         // Calculate minimum bytes per row required by wgpu
+        let crop = self.crop;
         let pixel_size = format_bytes_per_pixel(RESOLVED_TEXTURE_FORMAT);
-        let bytes_per_row = wgpu::util::align_to(self.resolved_texture.width() * pixel_size, 256);
+        let bytes_per_row = wgpu::util::align_to(crop.w * pixel_size, 256);
+        let mut source = self.resolved_texture.as_image_copy();
+        source.origin = wgpu::Origin3d {
+            x: crop.x,
+            y: crop.y,
+            z: 0,
+        };
         let copy_start = std::time::Instant::now();
         encoder.copy_texture_to_buffer(
-            self.resolved_texture.as_image_copy(),
+            source,
             wgpu::ImageCopyBuffer {
                 buffer: &staging_buffer,
                 layout: wgpu::ImageDataLayout {
                     offset: 0,
                     bytes_per_row: Some(bytes_per_row),
-                    rows_per_image: Some(render_texture.height()),
+                    rows_per_image: Some(crop.h),
                 },
             },
             wgpu::Extent3d {
-                width: render_texture.width(),
-                height: render_texture.height(),
+                width: crop.w,
+                height: crop.h,
                 depth_or_array_layers: 1,
             },
         );
@@ -519,9 +1384,12 @@ impl FrameRecorder {
         let sender = worker_thread.frame_sender.clone();
         let frames_in_queue = worker_thread.frames_in_queue.clone();
         let capture_in_progress_outer = self.capture_in_progress.clone();
+        let drop_log = self.drop_log.clone();
+        let frame_time = self.frame_time;
+        let offline = self.offline;
 
-        let width = render_texture.width();
-        let height = render_texture.height();
+        let width = crop.w;
+        let height = crop.h;
 
         // Submit the encoder (prevents buffer mapping deadlock)
         device.poll(wgpu::Maintain::Poll);
@@ -569,9 +1437,37 @@ impl FrameRecorder {
 
                         // Send the frame data
                         frames_in_queue.fetch_add(1, Ordering::SeqCst);
-                        if let Err(e) = sender.send((unpadded_data, width, height)) {
-                            frames_in_queue.fetch_sub(1, Ordering::SeqCst);
-                            eprintln!("Failed to send frame: {}", e);
+                        let frame = (frame_num, unpadded_data, width, height);
+                        let sent = if offline {
+                            sender
+                                .send(frame)
+                                .map_err(|e| TrySendError::Disconnected(e.0))
+                        } else {
+                            sender.try_send(frame)
+                        };
+                        match sent {
+                            Ok(()) => (),
+                            Err(TrySendError::Full(_)) if !recording => {
+                                // the replay just has a gap
+                                frames_in_queue.fetch_sub(1, Ordering::SeqCst);
+                            }
+                            Err(TrySendError::Full(_)) => {
+                                frames_in_queue.fetch_sub(1, Ordering::SeqCst);
+                                drop_log.lock().unwrap().record_drop(
+                                    (frame_num as u64 - 1) * frame_time,
+                                    1,
+                                    0,
+                                    DropCause::Queue,
+                                );
+                                println!(
+                                    "WARNING: Writer can't keep up, dropped frame {}",
+                                    frame_num
+                                );
+                            }
+                            Err(e) => {
+                                frames_in_queue.fetch_sub(1, Ordering::SeqCst);
+                                eprintln!("Failed to send frame: {}", e);
+                            }
                         }
                     }
                     Err(e) => {
@@ -611,24 +1507,210 @@ impl FrameRecorder {
         self.capture_in_progress.store(false, Ordering::SeqCst);
     }
 
+    // Steps the wall-clock schedule, true when this frame should be captured.
+    // Only a take's drops are logged, the replay buffer just has gaps.
+    fn capture_due(&self, recording: bool) -> bool {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
+
+        let mut next_scheduled = self.next_scheduled_capture.lock().unwrap();
+        if !recording {
+            let mut gaps = DropLog::default();
+            return check_schedule(&mut next_scheduled, &mut gaps, now, self.frame_time, 0);
+        }
+
+        let frames_captured = *self.frame_number.lock().unwrap();
+        let mut drop_log = self.drop_log.lock().unwrap();
+        let drops_before = drop_log.drops.len();
+        let due = check_schedule(
+            &mut next_scheduled,
+            &mut drop_log,
+            now,
+            self.frame_time,
+            frames_captured,
+        );
+        if let Some(drop) = drop_log.drops.get(drops_before) {
+            let video_s = drop.video_ns / 1_000_000_000;
+            let video_timestamp = format!(
+                "{:02}:{:02}:{:02}.{:03}",
+                (video_s / 3600),    // hours
+                (video_s / 60) % 60, // minutes
+                video_s % 60,        // seconds
+                (drop.video_ns % 1_000_000_000) / 1_000_000
+            );
+            println!(
+                "WARNING: Skipped {} frames, {}ms behind schedule, video time: {}",
+                drop.frames,
+                drop.gap_ns / 1_000_000,
+                video_timestamp
+            );
+        }
+        due
+    }
+
+    // Encode a single full-resolution still into the encoder. This doesn't touch the
+    // recording state, so it's safe to use while a recording is in progress.
+    // The PNG is read back and written in process_pending_still() once the encoder
+    // has been submitted. With no path, the still is named with a timestamp and
+    // saved in the output dir.
+    pub fn capture_still(
+        &self,
+        _device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        render_texture: &wgpu::Texture,
+        path: Option<&Path>,
+    ) {
+        let mut pending_still = self.pending_still.lock().unwrap();
+        if pending_still.is_some() {
+            println!("Still capture already pending, ignoring request");
+            return;
+        }
+
+        // Step 1: Use the reshaper to resolve MSAA
+        self.texture_reshaper
+            .encode_render_pass(&self.resolved_texture.view().build(), encoder);
+
+        // Step 2: Copy from resolved texture to the still staging buffer
+        let pixel_size = format_bytes_per_pixel(RESOLVED_TEXTURE_FORMAT);
+        let bytes_per_row = wgpu::util::align_to(self.resolved_texture.width() * pixel_size, 256);
+        encoder.copy_texture_to_buffer(
+            self.resolved_texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.still_staging_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(render_texture.height()),
+                },
+            },
+            wgpu::Extent3d {
+                width: render_texture.width(),
+                height: render_texture.height(),
+                depth_or_array_layers: 1,
+            },
+        );
+
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => Path::new(&self.output_dir).join(still_filename()),
+        };
+
+        *pending_still = Some(PendingStill {
+            path,
+            width: render_texture.width(),
+            height: render_texture.height(),
+        });
+    }
+
+    // Read back a still encoded by capture_still(). Must be called after the
+    // encoder has been submitted to the queue.
+    pub fn process_pending_still(&self, device: &wgpu::Device) {
+        let still = match self.pending_still.lock().unwrap().take() {
+            Some(still) => still,
+            None => return,
+        };
+
+        let (sender, receiver) = channel();
+        let slice = self.still_staging_buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            sender.send(result).ok();
+        });
+        device.poll(wgpu::Maintain::Wait);
+
+        match receiver.recv() {
+            Ok(Ok(())) => {
+                let pixel_size = format_bytes_per_pixel(RESOLVED_TEXTURE_FORMAT);
+                let bytes_per_row = wgpu::util::align_to(still.width * pixel_size, 256) as usize;
+                let actual_row_bytes = (still.width * pixel_size) as usize;
+
+                let unpadded_data = {
+                    let mapped_memory = slice.get_mapped_range();
+                    let mut unpadded_data =
+                        Vec::with_capacity(actual_row_bytes * still.height as usize);
+                    for row in 0..still.height as usize {
+                        let src_start = row * bytes_per_row;
+                        unpadded_data.extend_from_slice(
+                            &mapped_memory[src_start..src_start + actual_row_bytes],
+                        );
+                    }
+                    unpadded_data
+                };
+                self.still_staging_buffer.unmap();
+
+                // Encoding a full-resolution PNG is slow, keep it off the main thread
+                thread::spawn(move || {
+                    match RgbaImage::from_raw(still.width, still.height, unpadded_data) {
+                        Some(image) => match image.save(&still.path) {
+                            Ok(()) => println!("Saved still to {}", still.path.display()),
+                            Err(e) => eprintln!("Failed to save still: {}", e),
+                        },
+                        None => eprintln!("Failed to build still image from frame data"),
+                    }
+                });
+            }
+            Ok(Err(e)) => {
+                eprintln!("Still buffer mapping error: {}", e);
+                self.still_staging_buffer.unmap();
+            }
+            Err(e) => eprintln!("Still buffer mapping never completed: {}", e),
+        }
+    }
+
+    // What a recording asks of the disk, for the status output
+    fn throughput_note(&self, width: u32, height: u32) -> Option<String> {
+        let pixels_per_second = width as f64 * height as f64 * self.fps as f64;
+        let bytes_per_second = match self.output_mode {
+            OutputMode::H264Pipe if self.ffmpeg.codec == "prores_ks" => {
+                pixels_per_second * PRORES_HQ_BITS_PER_PIXEL / 8.0
+            }
+            // h264's rate depends on the picture, but it's far below the disk's
+            OutputMode::H264Pipe => return None,
+            // uncompressed, sequences land somewhere below this
+            OutputMode::PngSequence | OutputMode::JpegSequence => pixels_per_second * 3.0,
+            // the same with alpha, though ProRes 4444 comes in well under it
+            OutputMode::PngAlphaSequence | OutputMode::AlphaPipe { .. } => pixels_per_second * 4.0,
+            // scaled down and palettized, GIFs are small
+            OutputMode::Gif { .. } => return None,
+        };
+        let megabytes_per_second = bytes_per_second / 1_000_000.0;
+        Some(format!(
+            "Expect up to {:.0} MB/s ({:.1} GB per minute) written to disk",
+            megabytes_per_second,
+            megabytes_per_second * 60.0 / 1000.0
+        ))
+    }
+
+    pub fn status(&self) -> RecorderStatus {
+        let frames_captured = *self.frame_number.lock().unwrap();
+        let (frames_processed, total) = self.get_queue_status();
+        RecorderStatus {
+            recording: self.is_recording(),
+            paused: self.is_paused(),
+            frames_captured,
+            frames_in_queue: total - frames_processed,
+            frames_processed,
+            frames_dropped: self.drop_log.lock().unwrap().frames_dropped as usize,
+            elapsed: frames_captured as f32 / self.fps as f32,
+            take_path: self.current_take(),
+            replay_buffered: self.replay.as_ref().map_or(0.0, |replay| {
+                replay.lock().unwrap().len() as f32 / self.fps as f32
+            }),
+        }
+    }
+
+    // (frames written out, frames captured) for the current recording,
+    // whichever output mode it uses
     pub fn get_queue_status(&self) -> (usize, usize) {
         // Get the worker thread
         let worker_thread_guard = self.worker_thread.lock().unwrap();
 
         match worker_thread_guard.as_ref() {
             Some(worker) => {
-                let total = worker.frames_in_queue.load(Ordering::SeqCst);
-
-                // Check if FFmpeg process is still running
-                let is_running = worker.ffmpeg_process.lock().unwrap().is_some();
-
-                if is_running {
-                    // FFmpeg still running - show 0 processed
-                    (0, total)
-                } else {
-                    // FFmpeg finished - all frames processed
-                    (total, total)
-                }
+                let processed = worker.frames_processed.load(Ordering::SeqCst);
+                let queued = worker.frames_in_queue.load(Ordering::SeqCst);
+                (processed, processed + queued)
             }
             None => (0, 0),
         }
@@ -648,90 +1730,612 @@ impl FrameRecorder {
     }
 }
 
-fn start_ffmpeg_process(
-    output_dir: &str,
+// Encodes a batch of frames to JPEG in parallel and adds them to the replay
+// buffer in capture order
+fn buffer_replay_batch(
+    frame_batch: &mut Vec<FrameData>,
+    replay: &Mutex<ReplayBuffer>,
+    frames_in_queue: &AtomicUsize,
+) {
+    if frame_batch.is_empty() {
+        return;
+    }
+    let count = frame_batch.len();
+    let encoded: Vec<Result<Vec<u8>, String>> = frame_batch
+        .par_drain(..)
+        .map(|(_, frame_data, width, height)| encode_jpeg(frame_data, width, height))
+        .collect();
+    let mut replay = replay.lock().unwrap();
+    for jpeg in encoded {
+        match jpeg {
+            Ok(jpeg) => replay.push(jpeg),
+            Err(e) => eprintln!("Failed to encode replay frame: {}", e),
+        }
+    }
+    frames_in_queue.fetch_sub(count, Ordering::SeqCst);
+}
+
+fn encode_jpeg(frame_data: Vec<u8>, width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let image_buffer = RgbaImage::from_raw(width, height, frame_data)
+        .ok_or("frame data doesn't match its dimensions")?;
+    let rgb_buffer = nannou::image::DynamicImage::ImageRgba8(image_buffer).to_rgb8();
+    let mut jpeg = Vec::new();
+    nannou::image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY)
+        .encode(
+            rgb_buffer.as_raw(),
+            width,
+            height,
+            nannou::image::ColorType::Rgb8,
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(jpeg)
+}
+
+// Compares a capture at `now` against the ideal schedule, where frame k of the
+// take is due k frame times after the first. Captures that are late are
+// counted, and when the app has fallen more than a frame behind the missed
+// frames are logged as dropped and the schedule skips ahead. Returns true
+// when this frame should be captured.
+fn check_schedule(
+    next_scheduled: &mut u64,
+    drop_log: &mut DropLog,
+    now: u64,
+    frame_time: u64,
+    frames_captured: u32,
+) -> bool {
+    // If this is the first frame after starting recording, initialize the schedule
+    if *next_scheduled == 0 {
+        *next_scheduled = now;
+    }
+    // Too early, wait until exactly the scheduled time
+    if now < *next_scheduled {
+        return false;
+    }
+
+    let behind = now - *next_scheduled;
+    // If we're more than a frame behind, skip to the next appropriate frame time
+    // This prevents frame accumulation if we fall behind
+    if behind > frame_time {
+        let frames_behind = behind / frame_time;
+
+        // Position in the video timeline, where the frames go missing
+        let video_ns = frames_captured as u64 * frame_time;
+
+        // Skip to the next valid frame time, dropping any missed frames
+        *next_scheduled += (frames_behind + 1) * frame_time;
+        drop_log.record_drop(video_ns, frames_behind + 1, behind, DropCause::Schedule);
+
+        return false; // Skip this frame and catch up on the next one
+    }
+
+    drop_log.record_capture(behind, frame_time);
+    // Schedule the next frame at exactly frame_time nanoseconds from the current scheduled time
+    *next_scheduled += frame_time;
+    true
+}
+
+// A file that belongs with a take, e.g. take.mp4 -> take.mp4.drops.json
+fn sidecar_path(take_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = take_path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+// Sidecars are only notes about a take, so a failed write is reported and
+// otherwise ignored
+fn write_sidecar(take_path: &Path, suffix: &str, contents: &impl Serialize) {
+    let path = sidecar_path(take_path, suffix);
+    let written = serde_json::to_string_pretty(contents)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        eprintln!("Failed to write {}: {}", path.display(), e);
+    }
+}
+
+// Encodes a batch of frames in parallel and writes each to its numbered file
+fn write_frame_batch(
+    frame_batch: &mut Vec<FrameData>,
+    sequence_dir: &Path,
+    mode: OutputMode,
+    frames_in_queue: &AtomicUsize,
+    frames_processed: &AtomicUsize,
+) {
+    frame_batch
+        .par_drain(..)
+        .for_each(|(frame_number, frame_data, width, height)| {
+            let encode_start = std::time::Instant::now();
+            match write_sequence_frame(sequence_dir, mode, frame_number, frame_data, width, height)
+            {
+                Ok(()) => {
+                    frames_processed.fetch_add(1, Ordering::SeqCst);
+                }
+                Err(e) => eprintln!("Failed to save frame {}: {}", frame_number, e),
+            }
+            frames_in_queue.fetch_sub(1, Ordering::SeqCst);
+            if VERBOSE {
+                println!(
+                    "Frame {} encoding took: {:?}",
+                    frame_number,
+                    encode_start.elapsed()
+                );
+            }
+        });
+}
+
+fn write_sequence_frame(
+    sequence_dir: &Path,
+    mode: OutputMode,
+    frame_number: u32,
+    frame_data: Vec<u8>,
+    width: u32,
+    height: u32,
+) -> Result<(), String> {
+    let extension = mode.frame_extension().ok_or("not an image sequence mode")?;
+    let path = sequence_dir.join(format!("frame{:05}.{}", frame_number, extension));
+
+    let image_buffer = RgbaImage::from_raw(width, height, frame_data)
+        .ok_or("frame data doesn't match its dimensions")?;
+
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+    let mut buf_writer = BufWriter::new(file);
+    if mode == OutputMode::PngAlphaSequence {
+        return nannou::image::codecs::png::PngEncoder::new(&mut buf_writer)
+            .encode(
+                image_buffer.as_raw(),
+                width,
+                height,
+                nannou::image::ColorType::Rgba8,
+            )
+            .map_err(|e| e.to_string());
+    }
+    let rgb_buffer = nannou::image::DynamicImage::ImageRgba8(image_buffer).to_rgb8();
+    match mode {
+        OutputMode::JpegSequence => nannou::image::codecs::jpeg::JpegEncoder::new_with_quality(
+            &mut buf_writer,
+            JPEG_QUALITY,
+        )
+        .encode(
+            rgb_buffer.as_raw(),
+            width,
+            height,
+            nannou::image::ColorType::Rgb8,
+        )
+        .map_err(|e| e.to_string()),
+        _ => nannou::image::codecs::png::PngEncoder::new(&mut buf_writer)
+            .encode(
+                rgb_buffer.as_raw(),
+                width,
+                height,
+                nannou::image::ColorType::Rgb8,
+            )
+            .map_err(|e| e.to_string()),
+    }
+}
+
+// Streams frames into an animated GIF, scaling each one down and quantizing
+// it to its own palette
+struct GifWriter {
+    encoder: gif::Encoder<BufWriter<File>>,
     width: u32,
     height: u32,
     fps: u64,
-) -> (Child, std::process::ChildStdin) {
-    // Find the next available output file name
-    let output_file = find_next_output_filename(output_dir);
-    let output_path = format!("{}/{}", output_dir, output_file);
+    frames_written: u64,
+}
+
+impl GifWriter {
+    fn create(
+        path: &Path,
+        frame_width: u32,
+        frame_height: u32,
+        gif_width: u32,
+        fps: u64,
+    ) -> Result<Self, String> {
+        let (width, height) = gif_size(frame_width, frame_height, gif_width);
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        let mut encoder = gif::Encoder::new(BufWriter::new(file), width as u16, height as u16, &[])
+            .map_err(|e| e.to_string())?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(|e| e.to_string())?;
+        println!(
+            "Writing {}x{} GIF to {}",
+            width,
+            height,
+            path.to_string_lossy()
+        );
+
+        Ok(Self {
+            encoder,
+            width,
+            height,
+            fps,
+            frames_written: 0,
+        })
+    }
+
+    fn write_frame(&mut self, frame_data: Vec<u8>, width: u32, height: u32) -> Result<(), String> {
+        let image = RgbaImage::from_raw(width, height, frame_data)
+            .ok_or("frame data doesn't match its dimensions")?;
+        let mut pixels = if (width, height) == (self.width, self.height) {
+            image.into_raw()
+        } else {
+            nannou::image::imageops::resize(
+                &image,
+                self.width,
+                self.height,
+                nannou::image::imageops::FilterType::Triangle,
+            )
+            .into_raw()
+        };
 
-    println!("Starting FFmpeg process to encode to {}", output_path);
+        let mut frame = gif::Frame::from_rgba_speed(
+            self.width as u16,
+            self.height as u16,
+            &mut pixels,
+            GIF_QUANTIZE_SPEED,
+        );
+        frame.delay = gif_frame_delay(self.frames_written, self.fps);
+        self.encoder
+            .write_frame(&frame)
+            .map_err(|e| e.to_string())?;
+        self.frames_written += 1;
+        Ok(())
+    }
+}
+
+// The GIF's size for a frame: no wider than gif_width, same aspect ratio, and
+// within the 16-bit sizes GIF allows
+fn gif_size(frame_width: u32, frame_height: u32, gif_width: u32) -> (u32, u32) {
+    let width = frame_width.min(gif_width).min(u16::MAX as u32).max(1);
+    let height = (frame_height as u64 * width as u64 / frame_width.max(1) as u64) as u32;
+    (width, height.clamp(1, u16::MAX as u32))
+}
+
+// GIF delays are in hundredths of a second, so at most frame rates no single
+// delay is exact. Rounding each frame's end time instead of its length keeps
+// the animation as a whole at the recorder's fps.
+fn gif_frame_delay(frame_index: u64, fps: u64) -> u16 {
+    let fps = fps.max(1);
+    let end = ((frame_index + 1) * 100 + fps / 2) / fps;
+    let start = (frame_index * 100 + fps / 2) / fps;
+    (end - start) as u16
+}
 
-    // Set up FFmpeg command with appropriate parameters
-    let mut command = Command::new("ffmpeg");
+fn start_ffmpeg_process(
+    ffmpeg_path: &str,
+    output_path: &Path,
+    width: u32,
+    height: u32,
+    fps: u64,
+    ffmpeg: &FfmpegConfig,
+    alpha: bool,
+) -> Result<(Child, std::process::ChildStdin), String> {
+    let args = ffmpeg_args(
+        ffmpeg,
+        width,
+        height,
+        fps,
+        alpha,
+        &output_path.to_string_lossy(),
+    );
+    println!("Starting FFmpeg: {} {}", ffmpeg_path, args.join(" "));
+
+    // Errors always reach the console, so a bad codec/pixel format combination
+    // shows up at recording start rather than as a broken file afterwards
+    let mut command = Command::new(ffmpeg_path);
     command
-        .args([
-            "-f",
-            "rawvideo", // Input format is raw video data
-            "-pixel_format",
-            "rgb24", // Input pixel format (matching our RGB8 conversion)
-            "-video_size",
-            &format!("{}x{}", width, height), // Video dimensions
-            "-framerate",
-            &fps.to_string(), // Frame rate
-            "-i",
-            "-", // Read from stdin
-            "-vsync",
-            "cfr", // constant frame rate
-            "-r",
-            &fps.to_string(), // force output frame rate
-            "-c:v",
-            "libx264", // Use H.264 codec
-            "-preset",
-            "medium", // Encoding speed/quality tradeoff
-            "-crf",
-            "10", // Quality level (lower is better quality, 23 is default)
-            "-pix_fmt",
-            "yuv420p",    // Output pixel format
-            "-y",         // Overwrite output file if it exists
-            &output_path, // Output file path
-        ])
+        .args(&args)
         .stdin(Stdio::piped()) // Capture stdin
         .stdout(Stdio::null()) // Discard stdout
-        .stderr(if VERBOSE {
-            Stdio::inherit()
-        } else {
-            Stdio::null()
-        }); // Show or hide stderr
+        .stderr(Stdio::inherit());
 
     // Start the FFmpeg process
-    let mut process = command.spawn().expect("Failed to start FFmpeg process");
+    let mut process = command
+        .spawn()
+        .map_err(|e| format!("couldn't run {}: {}", ffmpeg_path, e))?;
 
     // Get the stdin handle that we'll write frames to
     let stdin = process
         .stdin
         .take()
-        .expect("Failed to open stdin for FFmpeg process");
+        .ok_or("couldn't open stdin for FFmpeg")?;
+
+    Ok((process, stdin))
+}
+
+// True if `ffmpeg_path` runs. It may be a bare name to look up on PATH.
+fn ffmpeg_available(ffmpeg_path: &str) -> bool {
+    Command::new(ffmpeg_path)
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+// Where frames go when there's no ffmpeg to make video. Masters keep every
+// pixel as PNG, previews go for the smaller JPEGs.
+fn sequence_mode_for(quality: RecordingQuality) -> OutputMode {
+    match quality {
+        RecordingQuality::Preview => OutputMode::JpegSequence,
+        RecordingQuality::Master => OutputMode::PngSequence,
+    }
+}
 
-    (process, stdin)
+fn create_staging_buffers(
+    device: &wgpu::Device,
+    width: u32,
+    height: u32,
+    count: usize,
+) -> Vec<Arc<wgpu::Buffer>> {
+    let pixel_size = format_bytes_per_pixel(RESOLVED_TEXTURE_FORMAT);
+    let bytes_per_row = wgpu::util::align_to(width * pixel_size, 256);
+    let buffer_size = (bytes_per_row * height) as u64;
+
+    (0..count)
+        .map(|i| {
+            Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&format!("Frame Capture Staging Buffer {}", i)),
+                size: buffer_size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }))
+        })
+        .collect()
+}
+
+// Checks a crop fits in the texture. Video encoders want even dimensions, so
+// with `even` the width and height are rounded down to even numbers.
+fn validate_crop(
+    crop: CropConfig,
+    texture_width: u32,
+    texture_height: u32,
+    even: bool,
+) -> Result<CropConfig, String> {
+    let mut crop = crop;
+    if even {
+        crop.w -= crop.w % 2;
+        crop.h -= crop.h % 2;
+    }
+    if crop.w == 0 || crop.h == 0 {
+        return Err(format!("Crop {}x{} is empty", crop.w, crop.h));
+    }
+    if crop.x + crop.w > texture_width || crop.y + crop.h > texture_height {
+        return Err(format!(
+            "Crop {}x{} at ({}, {}) runs outside the {}x{} texture",
+            crop.w, crop.h, crop.x, crop.y, texture_width, texture_height
+        ));
+    }
+    Ok(crop)
+}
+
+// Settles the encoder before recording. "auto" takes the first hardware
+// encoder that works here, and an encoder ffmpeg can't use falls back to
+// libx264. If ffmpeg can't be asked, the config is kept and any error shows at
+// recording.
+fn select_encoder(ffmpeg_path: &str, mut ffmpeg: FfmpegConfig) -> FfmpegConfig {
+    let output = match Command::new(ffmpeg_path)
+        .args(["-hide_banner", "-encoders"])
+        .stderr(Stdio::null())
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("Couldn't run ffmpeg to check encoders: {}", e);
+            if ffmpeg.codec == "auto" {
+                ffmpeg.codec = FfmpegConfig::default().codec;
+            }
+            return ffmpeg;
+        }
+    };
+    let encoders = String::from_utf8_lossy(&output.stdout);
+    let usable = |codec: &str| {
+        // ffmpeg lists hardware encoders it was built with even when there's
+        // no hardware for them, so those have to be tried
+        has_encoder(&encoders, codec)
+            && (!is_hardware_encoder(codec) || encoder_runs(ffmpeg_path, codec))
+    };
+
+    match pick_encoder(&ffmpeg.codec, usable) {
+        Some(codec) => {
+            ffmpeg.codec = codec;
+            ffmpeg
+        }
+        None => {
+            println!(
+                "WARNING: ffmpeg can't use the {} encoder, recording h264 instead",
+                ffmpeg.codec
+            );
+            FfmpegConfig::default()
+        }
+    }
+}
+
+// The encoder to record with for `codec`, or None to fall back to libx264
+fn pick_encoder(codec: &str, usable: impl Fn(&str) -> bool) -> Option<String> {
+    if codec == "auto" {
+        let codec = AUTO_ENCODERS
+            .into_iter()
+            .find(|encoder| usable(encoder))
+            .unwrap_or("libx264");
+        return Some(codec.to_string());
+    }
+    usable(codec).then(|| codec.to_string())
 }
 
-fn find_next_output_filename(output_dir: &str) -> String {
-    // Try output.mp4 first
-    let base_name = "output";
-    let extension = "mp4";
-    let mut index = 0;
+fn is_hardware_encoder(codec: &str) -> bool {
+    codec.ends_with("_nvenc") || codec.ends_with("_videotoolbox")
+}
+
+// Encodes a few blank frames to see whether the hardware is really there
+fn encoder_runs(ffmpeg_path: &str, codec: &str) -> bool {
+    Command::new(ffmpeg_path)
+        .args([
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-f",
+            "lavfi",
+            "-i",
+            "color=black:size=256x256:rate=30",
+            "-frames:v",
+            "3",
+            "-c:v",
+            codec,
+            "-f",
+            "null",
+            "-",
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+// Looks for `codec` in the listing from `ffmpeg -encoders`, where each
+// encoder's line is its capability flags, name and description
+fn has_encoder(encoders: &str, codec: &str) -> bool {
+    encoders
+        .lines()
+        .any(|line| line.split_whitespace().nth(1) == Some(codec))
+}
+
+// The ffmpeg command line for piping raw frames into `output_path`: rgb24, or
+// rgba with `alpha`
+fn ffmpeg_args(
+    ffmpeg: &FfmpegConfig,
+    width: u32,
+    height: u32,
+    fps: u64,
+    alpha: bool,
+    output_path: &str,
+) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "-hide_banner",
+        "-loglevel",
+        if VERBOSE { "info" } else { "error" },
+        "-f",
+        "rawvideo", // Input format is raw video data
+        "-pixel_format",
+        if alpha { "rgba" } else { "rgb24" }, // Input pixel format (matching what the worker sends)
+        "-video_size",
+        &format!("{}x{}", width, height), // Video dimensions
+        "-framerate",
+        &fps.to_string(), // Frame rate
+        "-i",
+        "-", // Read from stdin
+        "-vsync",
+        "cfr", // constant frame rate
+        "-r",
+        &fps.to_string(), // force output frame rate
+        "-c:v",
+        &ffmpeg.codec,
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+
+    if is_hardware_encoder(&ffmpeg.codec) {
+        // x264's CRF and presets mean nothing to these, they run to a bitrate
+        let bitrate = ffmpeg.bitrate.as_deref().unwrap_or(HARDWARE_BITRATE);
+        if ffmpeg.codec.ends_with("_nvenc") {
+            args.extend(["-rc".to_string(), "vbr".to_string()]);
+        }
+        args.extend(["-b:v".to_string(), bitrate.to_string()]);
+        if ffmpeg.codec.starts_with("hevc") {
+            // so QuickTime will play it
+            args.extend(["-tag:v".to_string(), "hvc1".to_string()]);
+        }
+    } else {
+        if let Some(preset) = &ffmpeg.preset {
+            args.extend(["-preset".to_string(), preset.clone()]);
+        }
+        if let Some(crf) = ffmpeg.crf {
+            args.extend(["-crf".to_string(), crf.to_string()]);
+        }
+        if let Some(bitrate) = &ffmpeg.bitrate {
+            args.extend(["-b:v".to_string(), bitrate.clone()]);
+        }
+    }
+    args.extend(["-pix_fmt".to_string(), ffmpeg.pix_fmt.clone()]);
+    args.extend(ffmpeg.extra_args.iter().cloned());
+    args.extend(["-y".to_string(), output_path.to_string()]); // Overwrite output file if it exists
+    args
+}
+
+fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+// Names a take <prefix>_<YYYYMMDD_HHMMSS>, in UTC, from `now` in seconds since
+// the epoch. A counter is added rather than overwrite an earlier take. With no
+// extension, this names a directory for an image sequence.
+fn next_take_path(output_dir: &Path, prefix: &str, extension: Option<&str>, now: u64) -> PathBuf {
+    let stem = format!("{}_{}", prefix, utc_timestamp(now));
+    let mut index = 1;
 
     loop {
-        let file_name = if index == 0 {
-            format!("{}.{}", base_name, extension)
+        let stem = if index == 1 {
+            stem.clone()
         } else {
-            format!("{}{}.{}", base_name, index, extension)
+            format!("{}_{}", stem, index)
+        };
+        let file_name = match extension {
+            Some(extension) => format!("{}.{}", stem, extension),
+            None => stem,
         };
 
-        let path = Path::new(output_dir).join(&file_name);
-
+        let path = output_dir.join(&file_name);
         if !path.exists() {
-            return file_name;
+            return path;
         }
 
         index += 1;
     }
 }
 
+// YYYYMMDD_HHMMSS for a time in seconds since the epoch
+fn utc_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let secs_of_day = secs % 86_400;
+
+    // civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}_{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+fn still_filename() -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap();
+    format!("still_{}_{:03}.png", now.as_secs(), now.subsec_millis())
+}
+
 fn format_bytes_per_pixel(format: wgpu::TextureFormat) -> u32 {
     match format {
         wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => 4,
@@ -745,3 +2349,385 @@ fn format_bytes_per_pixel(format: wgpu::TextureFormat) -> u32 {
         _ => panic!("Unsupported texture format: {:?}", format),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{Duration, Instant};
+
+    // A fresh directory under the temp dir. Tests remove it when they're done.
+    fn test_dir(prefix: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "test_frames_{}_{}_{}",
+            prefix,
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_png_sequence_writes_numbered_decodable_frames() {
+        let test_dir = test_dir("png");
+
+        let (width, height) = (8, 4);
+        let worker = FrameRecorder::create_sequence_worker(
+            test_dir.join("take"),
+            OutputMode::PngSequence,
+            PREVIEW_CHANNEL_CAPACITY,
+        );
+        for frame_number in 1..=3u32 {
+            let shade = frame_number as u8 * 60;
+            let frame_data = [shade, 0, 255 - shade, 255].repeat((width * height) as usize);
+            worker.frames_in_queue.fetch_add(1, Ordering::SeqCst);
+            worker
+                .frame_sender
+                .send((frame_number, frame_data, width, height))
+                .unwrap();
+        }
+        FrameRecorder::request_worker_shutdown(&worker);
+
+        let start = Instant::now();
+        while !worker.thread_completed.load(Ordering::SeqCst) {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "worker never finished"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(worker.frames_processed.load(Ordering::SeqCst), 3);
+        assert_eq!(worker.frames_in_queue.load(Ordering::SeqCst), 0);
+
+        for frame_number in 1..=3u32 {
+            let path = test_dir.join(format!("take/frame{:05}.png", frame_number));
+            let image = nannou::image::open(&path)
+                .unwrap_or_else(|e| panic!("{} should decode: {}", path.display(), e))
+                .to_rgb8();
+            assert_eq!(image.dimensions(), (width, height));
+            let shade = frame_number as u8 * 60;
+            assert_eq!(image.get_pixel(3, 2).0, [shade, 0, 255 - shade]);
+        }
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_alpha_modes_keep_transparency() {
+        let mode = OutputMode::try_from("qtrle").unwrap();
+        assert!(mode.keeps_alpha() && mode.is_video());
+        assert!(!OutputMode::PngSequence.keeps_alpha());
+        let args = ffmpeg_args(&FfmpegConfig::qtrle(), 8, 4, 30, true, "take.mov");
+        assert!(args
+            .windows(2)
+            .any(|pair| pair == ["-pixel_format", "rgba"]));
+
+        let test_dir = test_dir("alpha");
+        let (width, height) = (8, 4);
+        let frame_data = [200, 100, 0, 64].repeat((width * height) as usize);
+        write_sequence_frame(
+            &test_dir,
+            OutputMode::PngAlphaSequence,
+            1,
+            frame_data,
+            width,
+            height,
+        )
+        .unwrap();
+        let image = nannou::image::open(test_dir.join("frame00001.png"))
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(image.get_pixel(5, 1).0, [200, 100, 0, 64]);
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_gif_streams_scaled_frames_at_recorder_fps() {
+        let test_dir = test_dir("gif");
+        let path = test_dir.join("take.gif");
+
+        let (width, height) = (16, 8);
+        let worker =
+            FrameRecorder::create_gif_worker(path.clone(), 8, 30, PREVIEW_CHANNEL_CAPACITY);
+        for frame_number in 1..=3u32 {
+            let shade = frame_number as u8 * 60;
+            let frame_data = [shade, 0, 255 - shade, 255].repeat((width * height) as usize);
+            worker.frames_in_queue.fetch_add(1, Ordering::SeqCst);
+            worker
+                .frame_sender
+                .send((frame_number, frame_data, width, height))
+                .unwrap();
+        }
+        FrameRecorder::request_worker_shutdown(&worker);
+
+        let start = Instant::now();
+        while !worker.thread_completed.load(Ordering::SeqCst) {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "worker never finished"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(worker.frames_processed.load(Ordering::SeqCst), 3);
+        assert_eq!(worker.frames_in_queue.load(Ordering::SeqCst), 0);
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(File::open(&path).unwrap()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (8, 4));
+        let mut delays = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+        // 30fps can't be exact in hundredths, but three frames make 1/10s
+        assert_eq!(delays, vec![3, 4, 3]);
+
+        let _ = fs::remove_dir_all(&test_dir);
+
+        let gif = OutputMode::Gif { width: 480 };
+        assert!(gif.check_frame_limit(0).is_err());
+        assert_eq!(gif.check_frame_limit(300), Ok(gif));
+        assert!(OutputMode::H264Pipe.check_frame_limit(0).is_ok());
+    }
+
+    #[test]
+    fn test_schedule_logs_late_and_dropped_frames() {
+        let frame_time = 1_000_000; // 1ms frames keep the numbers readable
+        let start = 5_000_000_000;
+        let mut next_scheduled = 0;
+        let mut drop_log = DropLog::default();
+        let mut captured = 0;
+        let mut capture_at = |now: u64, captured: &mut u32, log: &mut DropLog| {
+            let due = check_schedule(&mut next_scheduled, log, now, frame_time, *captured);
+            if due {
+                *captured += 1;
+            }
+            due
+        };
+
+        assert!(capture_at(start, &mut captured, &mut drop_log));
+        assert!(!capture_at(start + 500_000, &mut captured, &mut drop_log)); // early
+        assert!(capture_at(start + 1_000_000, &mut captured, &mut drop_log));
+        assert!(capture_at(start + 2_700_000, &mut captured, &mut drop_log)); // late
+        assert_eq!(drop_log.late_captures, 1);
+        assert_eq!(drop_log.frames_dropped, 0);
+
+        // 3.5 frames behind: this one and the three before it are lost
+        assert!(!capture_at(start + 6_500_000, &mut captured, &mut drop_log));
+        assert!(capture_at(start + 7_000_000, &mut captured, &mut drop_log));
+        assert_eq!(captured, 4);
+        assert_eq!(drop_log.frames_dropped, 4);
+        assert_eq!(
+            drop_log.drops,
+            vec![FrameDrop {
+                video_ns: 3 * frame_time,
+                frames: 4,
+                gap_ns: 3_500_000,
+                cause: DropCause::Schedule,
+            }]
+        );
+        assert_eq!(drop_log.worst_gap_ns, 3_500_000);
+
+        let report = drop_log.report(Path::new("take.mp4"), 1000, captured);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["drops"][0]["time"], 0.003);
+        assert_eq!(json["drops"][0]["cause"], "schedule");
+        assert_eq!(
+            sidecar_path(Path::new("frames/take.mp4"), "drops.json"),
+            PathBuf::from("frames/take.mp4.drops.json")
+        );
+    }
+
+    #[test]
+    fn test_replay_buffer_drops_oldest_within_budget() {
+        let mut replay = ReplayBuffer::new(3, 10);
+        for frame in 1..=4u8 {
+            replay.push(vec![frame; 2]);
+        }
+        // four frames, room for three
+        assert_eq!(replay.len(), 3);
+        assert_eq!(replay.bytes, 6);
+        assert_eq!(replay.frames[0], vec![2, 2]);
+
+        // one big frame squeezes out the rest of the 10 bytes
+        replay.push(vec![9; 7]);
+        assert_eq!(replay.len(), 2);
+        assert_eq!(replay.bytes, 9);
+
+        assert_eq!(replay.take_latest(1), vec![vec![9; 7]]);
+        assert_eq!((replay.len(), replay.bytes), (0, 0));
+
+        let jpeg = encode_jpeg([200, 20, 20, 255].repeat(16), 4, 4).unwrap();
+        let decoded = nannou::image::load_from_memory(&jpeg).unwrap().to_rgb8();
+        assert_eq!(decoded.dimensions(), (4, 4));
+    }
+
+    #[test]
+    fn test_ffmpeg_args_follow_config() {
+        let ffmpeg = FfmpegConfig {
+            codec: "libx265".to_string(),
+            crf: None,
+            bitrate: Some("20M".to_string()),
+            preset: None,
+            pix_fmt: "yuv444p".to_string(),
+            extra_args: vec!["-tag:v".to_string(), "hvc1".to_string()],
+            extension: "mp4".to_string(),
+        };
+        let args = ffmpeg_args(&ffmpeg, 640, 480, 30, false, "out/output.mp4");
+        let tail: Vec<&str> = args
+            .iter()
+            .map(String::as_str)
+            .skip_while(|a| *a != "-c:v")
+            .collect();
+        assert_eq!(
+            tail,
+            [
+                "-c:v",
+                "libx265",
+                "-b:v",
+                "20M",
+                "-pix_fmt",
+                "yuv444p",
+                "-tag:v",
+                "hvc1",
+                "-y",
+                "out/output.mp4"
+            ]
+        );
+        assert!(args.windows(2).any(|w| w == ["-video_size", "640x480"]));
+
+        // hardware encoders swap the profile's CRF and preset for a bitrate
+        let nvenc = FfmpegConfig {
+            codec: "hevc_nvenc".to_string(),
+            ..FfmpegConfig::default()
+        };
+        let args = ffmpeg_args(&nvenc, 640, 480, 30, false, "out/output.mp4");
+        let tail: Vec<&str> = args
+            .iter()
+            .map(String::as_str)
+            .skip_while(|a| *a != "-c:v")
+            .take(8)
+            .collect();
+        assert_eq!(
+            tail,
+            [
+                "-c:v",
+                "hevc_nvenc",
+                "-rc",
+                "vbr",
+                "-b:v",
+                "50M",
+                "-tag:v",
+                "hvc1"
+            ]
+        );
+        assert!(!args.iter().any(|a| a == "-crf" || a == "-preset"));
+    }
+
+    #[test]
+    fn test_pick_encoder_prefers_working_hardware() {
+        let on_mac = |codec: &str| codec == "libx264" || codec == "h264_videotoolbox";
+        assert_eq!(
+            pick_encoder("auto", on_mac),
+            Some("h264_videotoolbox".to_string())
+        );
+        assert_eq!(
+            pick_encoder("auto", |c| c == "libx264"),
+            Some("libx264".to_string())
+        );
+        assert_eq!(pick_encoder("h264_nvenc", on_mac), None);
+        assert_eq!(pick_encoder("libx264", on_mac), Some("libx264".to_string()));
+    }
+
+    #[test]
+    fn test_has_encoder_reads_ffmpeg_listing() {
+        let listing = "Encoders:
+ V..... = Video
+ ------
+ V....D libx264              libx264 H.264 / AVC (codec h264)
+ VF..D. prores_ks            Apple ProRes (iCodec Pro) (codec prores)
+";
+        assert!(has_encoder(listing, "prores_ks"));
+        assert!(has_encoder(listing, "libx264"));
+        assert!(!has_encoder(listing, "prores"));
+        assert!(!has_encoder(listing, "Video"));
+    }
+
+    #[test]
+    fn test_missing_ffmpeg_falls_back_to_sequences() {
+        assert!(!ffmpeg_available("/nonexistent/bin/ffmpeg"));
+        assert!(!ffmpeg_available("glyphvis-no-such-ffmpeg"));
+        assert_eq!(
+            sequence_mode_for(RecordingQuality::Preview),
+            OutputMode::JpegSequence
+        );
+        assert_eq!(
+            sequence_mode_for(RecordingQuality::Master),
+            OutputMode::PngSequence
+        );
+
+        let err = start_ffmpeg_process(
+            "/nonexistent/bin/ffmpeg",
+            Path::new("take.mp4"),
+            8,
+            8,
+            30,
+            &FfmpegConfig::default(),
+            false,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("/nonexistent/bin/ffmpeg"), "{}", err);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ffmpeg_probe_runs_the_configured_binary() {
+        // stand-ins for an ffmpeg that works and one that's broken
+        assert!(ffmpeg_available("true"));
+        assert!(!ffmpeg_available("false"));
+    }
+
+    #[test]
+    fn test_take_names_are_timestamped_and_never_reused() {
+        assert_eq!(utc_timestamp(0), "19700101_000000");
+        assert_eq!(utc_timestamp(1_709_210_096), "20240229_123456");
+
+        let test_dir = test_dir("takes");
+        let dir = test_dir.as_path();
+
+        let first = next_take_path(dir, "show", Some("mp4"), 1_709_210_096);
+        assert_eq!(first, dir.join("show_20240229_123456.mp4"));
+        fs::write(&first, b"").unwrap();
+        let second = next_take_path(dir, "show", Some("mp4"), 1_709_210_096);
+        assert_eq!(second, dir.join("show_20240229_123456_2.mp4"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_validate_crop_checks_bounds_and_evens_video() {
+        let crop = CropConfig {
+            x: 100,
+            y: 50,
+            w: 1921,
+            h: 1081,
+        };
+        assert_eq!(
+            validate_crop(crop, 4742, 1200, true),
+            Ok(CropConfig {
+                w: 1920,
+                h: 1080,
+                ..crop
+            })
+        );
+        assert_eq!(validate_crop(crop, 4742, 1200, false), Ok(crop));
+        assert!(validate_crop(crop, 1920, 1200, true).is_err());
+        assert!(validate_crop(CropConfig { w: 1, ..crop }, 4742, 1200, true).is_err());
+    }
+}
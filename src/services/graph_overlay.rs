@@ -0,0 +1,392 @@
+// src/services/graph_overlay.rs
+//
+// The SegmentGraph is built once and shared by every grid instance, but an
+// instance can add segments of its own, like the stretch segments. Those go
+// in the instance's GraphDelta, and GraphView answers graph queries from the
+// shared graph with the delta laid over it. With an empty delta a view reads
+// straight through to the shared graph.
+
+use crate::models::SegmentKey;
+use crate::services::SegmentGraph;
+use nannou::prelude::*;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+// One grid instance's changes to the shared graph
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GraphDelta {
+    added: BTreeSet<SegmentKey>,   // segments the shared graph doesn't have
+    removed: BTreeSet<SegmentKey>, // shared segments taken out
+    connections: BTreeMap<SegmentKey, Vec<(SegmentKey, Point2)>>, // to and from added segments
+}
+
+impl GraphDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    // Adds a segment joined to each of `connections` at the given point
+    pub fn add_segment(&mut self, id: &SegmentKey, connections: &[(SegmentKey, Point2)]) {
+        self.removed.remove(id);
+        self.added.insert(id.clone());
+        for (other, point) in connections {
+            self.connect(id, other, *point);
+            self.connect(other, id, *point);
+        }
+    }
+
+    // Takes out an added segment and its connections, or hides a shared one
+    pub fn remove_segment(&mut self, id: &SegmentKey, base: &SegmentGraph) {
+        if self.added.remove(id) {
+            for (other, _) in self.connections.remove(id).unwrap_or_default() {
+                if let Some(connections) = self.connections.get_mut(&other) {
+                    connections.retain(|(conn, _)| conn != id);
+                    if connections.is_empty() {
+                        self.connections.remove(&other);
+                    }
+                }
+            }
+        } else if base.node(id).is_some() {
+            self.removed.insert(id.clone());
+        }
+    }
+
+    fn connect(&mut self, from: &SegmentKey, to: &SegmentKey, point: Point2) {
+        let connections = self.connections.entry(from.clone()).or_default();
+        if !connections.iter().any(|(id, _)| id == to) {
+            connections.push((to.clone(), point));
+            connections.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+    }
+}
+
+// The shared graph as one grid instance sees it
+#[derive(Debug, Clone, Copy)]
+pub struct GraphView<'a> {
+    base: &'a SegmentGraph,
+    delta: Option<&'a GraphDelta>,
+}
+
+impl<'a> GraphView<'a> {
+    pub fn new(base: &'a SegmentGraph, delta: &'a GraphDelta) -> Self {
+        Self {
+            base,
+            delta: Some(delta).filter(|delta| !delta.is_empty()),
+        }
+    }
+
+    // Reads the shared graph alone
+    pub fn base(base: &'a SegmentGraph) -> Self {
+        Self { base, delta: None }
+    }
+
+    pub fn contains(&self, id: &SegmentKey) -> bool {
+        match self.delta {
+            Some(delta) if delta.added.contains(id) => true,
+            Some(delta) if delta.removed.contains(id) => false,
+            _ => self.base.node(id).is_some(),
+        }
+    }
+
+    // The segments connected to `id`, sorted by id and each listed once.
+    // Empty for a segment that isn't in the graph.
+    pub fn neighbors(&self, id: &SegmentKey) -> Cow<'a, [SegmentKey]> {
+        let Some(delta) = self.delta else {
+            return Cow::Borrowed(self.base.neighbors(id));
+        };
+        if !self.contains(id) {
+            return Cow::Borrowed(&[]);
+        }
+        let added = delta.connections.get(id);
+        if added.is_none() && delta.removed.is_empty() {
+            return Cow::Borrowed(self.base.neighbors(id));
+        }
+
+        let mut neighbors: Vec<SegmentKey> = self
+            .base
+            .neighbors(id)
+            .iter()
+            .filter(|neighbor| !delta.removed.contains(*neighbor))
+            .cloned()
+            .chain(added.into_iter().flatten().map(|(other, _)| other.clone()))
+            .collect();
+        neighbors.sort();
+        neighbors.dedup();
+        Cow::Owned(neighbors)
+    }
+
+    pub fn degree(&self, id: &SegmentKey) -> usize {
+        self.neighbors(id).len()
+    }
+
+    // Where two segments meet, if they're connected
+    pub fn connection_point(&self, first: &SegmentKey, second: &SegmentKey) -> Option<Point2> {
+        if !self.contains(first) || !self.contains(second) {
+            return None;
+        }
+        let added = self
+            .delta
+            .and_then(|delta| delta.connections.get(first))
+            .and_then(|connections| connections.iter().find(|(id, _)| id == second))
+            .map(|(_, point)| *point);
+        added.or_else(|| self.base.connection_point(first, second))
+    }
+
+    pub fn find_path(&self, start: &SegmentKey, end: &SegmentKey) -> Option<Vec<SegmentKey>> {
+        // Simple BFS to find path
+        let mut queue = VecDeque::new();
+        let mut visited = HashSet::new();
+        let mut came_from: HashMap<SegmentKey, SegmentKey> = HashMap::new();
+
+        queue.push_back(start.clone());
+        visited.insert(start.clone());
+
+        while let Some(current) = queue.pop_front() {
+            if current == *end {
+                // Reconstruct path
+                let mut path = Vec::new();
+                let mut current = current;
+                while current != *start {
+                    path.push(current.clone());
+                    current = came_from.get(&current)?.clone();
+                }
+                path.push(start.clone());
+                path.reverse();
+                return Some(path);
+            }
+
+            // Add unvisited neighbors to queue
+            for neighbor in self.neighbors(&current).iter() {
+                if visited.insert(neighbor.clone()) {
+                    queue.push_back(neighbor.clone());
+                    came_from.insert(neighbor.clone(), current.clone());
+                }
+            }
+        }
+
+        None // No path found
+    }
+
+    // Shortest path from start to whichever of the goal segments is closest
+    // in hops. The path includes both ends.
+    pub fn find_path_to_nearest(
+        &self,
+        start: &SegmentKey,
+        goals: &HashSet<SegmentKey>,
+    ) -> Option<Vec<SegmentKey>> {
+        let mut queue = VecDeque::new();
+        let mut visited = HashSet::new();
+        let mut came_from: HashMap<SegmentKey, SegmentKey> = HashMap::new();
+
+        queue.push_back(start.clone());
+        visited.insert(start.clone());
+
+        while let Some(current) = queue.pop_front() {
+            if current != *start && goals.contains(&current) {
+                let mut path = vec![current.clone()];
+                let mut current = current;
+                while let Some(previous) = came_from.get(&current) {
+                    path.push(previous.clone());
+                    current = previous.clone();
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for neighbor in self.neighbors(&current).iter() {
+                if visited.insert(neighbor.clone()) {
+                    came_from.insert(neighbor.clone(), current.clone());
+                    queue.push_back(neighbor.clone());
+                }
+            }
+        }
+
+        None
+    }
+
+    // How many connections each reachable segment is from `start`, which is 0
+    pub fn hop_distances(&self, start: &SegmentKey) -> HashMap<SegmentKey, usize> {
+        let mut distances = HashMap::new();
+        if !self.contains(start) {
+            return distances;
+        }
+
+        let mut queue = VecDeque::from([start.clone()]);
+        distances.insert(start.clone(), 0);
+        while let Some(current) = queue.pop_front() {
+            let distance = distances[&current] + 1;
+            for neighbor in self.neighbors(&current).iter() {
+                if !distances.contains_key(neighbor) {
+                    distances.insert(neighbor.clone(), distance);
+                    queue.push_back(neighbor.clone());
+                }
+            }
+        }
+        distances
+    }
+
+    // Segments whose removal would split `segments` into more pieces, going
+    // by the connections among them alone
+    pub fn articulation_points(&self, segments: &HashSet<SegmentKey>) -> BTreeSet<SegmentKey> {
+        self.cut_structure(segments).0
+    }
+
+    // Connections whose removal would split `segments` into more pieces, as
+    // the lower id and the higher id
+    pub fn bridges(&self, segments: &HashSet<SegmentKey>) -> BTreeSet<(SegmentKey, SegmentKey)> {
+        self.cut_structure(segments).1
+    }
+
+    // Tarjan's depth first search, with an explicit stack so large grids
+    // can't overflow it. A segment's low point is the earliest discovered
+    // segment its subtree reaches without going back through its parent.
+    fn cut_structure(
+        &self,
+        segments: &HashSet<SegmentKey>,
+    ) -> (BTreeSet<SegmentKey>, BTreeSet<(SegmentKey, SegmentKey)>) {
+        let mut ids: Vec<&SegmentKey> = segments.iter().filter(|id| self.contains(id)).collect();
+        ids.sort();
+        let index_of: HashMap<&SegmentKey, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (*id, index))
+            .collect();
+        let adjacency: Vec<Vec<usize>> = ids
+            .iter()
+            .map(|id| {
+                self.neighbors(id)
+                    .iter()
+                    .filter_map(|neighbor| index_of.get(neighbor).copied())
+                    .collect()
+            })
+            .collect();
+
+        const UNSEEN: usize = usize::MAX;
+        let mut discovered = vec![UNSEEN; ids.len()];
+        let mut low = vec![0; ids.len()];
+        let mut time = 0;
+        let mut points = BTreeSet::new();
+        let mut bridges = BTreeSet::new();
+
+        for root in 0..ids.len() {
+            if discovered[root] != UNSEEN {
+                continue;
+            }
+            discovered[root] = time;
+            low[root] = time;
+            time += 1;
+            let mut root_children = 0;
+
+            // (segment, parent, next neighbor to look at)
+            let mut stack = vec![(root, UNSEEN, 0)];
+            while let Some(top) = stack.last_mut() {
+                let (node, parent) = (top.0, top.1);
+                if let Some(&child) = adjacency[node].get(top.2) {
+                    top.2 += 1;
+                    if discovered[child] == UNSEEN {
+                        discovered[child] = time;
+                        low[child] = time;
+                        time += 1;
+                        if node == root {
+                            root_children += 1;
+                        }
+                        stack.push((child, node, 0));
+                    } else if child != parent {
+                        low[node] = low[node].min(discovered[child]);
+                    }
+                    continue;
+                }
+
+                stack.pop();
+                if parent == UNSEEN {
+                    continue;
+                }
+                low[parent] = low[parent].min(low[node]);
+                if low[node] > discovered[parent] {
+                    let (first, second) = (ids[parent].clone(), ids[node].clone());
+                    bridges.insert(if first < second {
+                        (first, second)
+                    } else {
+                        (second, first)
+                    });
+                }
+                if parent != root && low[node] >= discovered[parent] {
+                    points.insert(ids[parent].clone());
+                }
+            }
+            if root_children > 1 {
+                points.insert(ids[root].clone());
+            }
+        }
+        (points, bridges)
+    }
+
+    // Every segment joined to `id`, including itself. Uses the shared
+    // graph's cached components when there's no delta.
+    pub fn component_of(&self, id: &SegmentKey) -> Option<HashSet<SegmentKey>> {
+        match self.delta {
+            None => self.base.component_of(id).cloned(),
+            Some(_) if self.contains(id) => Some(self.hop_distances(id).into_keys().collect()),
+            Some(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Project;
+    use crate::views::CachedGrid;
+
+    // Every segment's neighbors and where it meets each of them
+    fn snapshot(
+        view: &GraphView,
+        ids: &[SegmentKey],
+    ) -> Vec<(SegmentKey, Vec<(SegmentKey, Point2)>)> {
+        ids.iter()
+            .map(|id| {
+                let neighbors = view
+                    .neighbors(id)
+                    .iter()
+                    .map(|n| (n.clone(), view.connection_point(id, n).unwrap()))
+                    .collect();
+                (id.clone(), neighbors)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_added_segments_join_and_leave_the_graph() {
+        let grid = CachedGrid::new(&Project::load("projects/debug.json").unwrap());
+        let graph = SegmentGraph::new(&grid);
+        let mut ids: Vec<SegmentKey> = grid.segments.keys().cloned().collect();
+        ids.sort();
+        let before = snapshot(&GraphView::base(&graph), &ids);
+
+        let (node, junction) = graph.nodes().next().unwrap();
+        let at_node: Vec<(SegmentKey, Point2)> = graph
+            .segments_at_node(node)
+            .iter()
+            .map(|id| (id.clone(), junction.point))
+            .collect();
+        let stretch = SegmentKey::off_grid("stretch");
+        let mut delta = GraphDelta::default();
+        delta.add_segment(&stretch, &at_node);
+
+        let view = GraphView::new(&graph, &delta);
+        assert!(view.contains(&stretch));
+        assert_eq!(view.degree(&stretch), at_node.len());
+        for (id, point) in &at_node {
+            assert!(view.neighbors(id).contains(&stretch));
+            assert_eq!(view.connection_point(id, &stretch), Some(*point));
+        }
+        let (first, _) = &at_node[0];
+        assert!(view.component_of(first).unwrap().contains(&stretch));
+
+        delta.remove_segment(&stretch, &graph);
+        assert_eq!(delta, GraphDelta::default());
+        let view = GraphView::new(&graph, &delta);
+        assert!(!view.contains(&stretch));
+        assert_eq!(snapshot(&view, &ids), before);
+    }
+}
@@ -1,6 +1,9 @@
+pub mod clock;
 pub mod frame_recorder;
-pub mod frame_recorder_jpg;
+pub mod graph_overlay;
 pub mod segment_graph;
 
-pub use frame_recorder::FrameRecorder;
-pub use segment_graph::SegmentGraph;
+pub use clock::Clock;
+pub use frame_recorder::{FrameRecorder, OutputMode, RecorderStatus};
+pub use graph_overlay::{GraphDelta, GraphView};
+pub use segment_graph::{Junction, NodeId, SegmentGraph};
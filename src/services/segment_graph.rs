@@ -1,29 +1,56 @@
 // src/views/grid/segment_graph.rs
 //
 // SegmentGraph holds all the relationships between segment endpoints in a Grid.
+// Each point where segments meet is also kept as a node of its own, so the
+// segments at a junction can be looked up without comparing endpoints again.
+//
+// Finding the connections compares endpoints pairwise, which is slow for large
+// grids, so they can be saved to a cache file and read back on the next run.
 
+use crate::models::SegmentKey;
+use crate::services::GraphView;
 use crate::views::{CachedGrid, DrawCommand};
 use nannou::prelude::*;
-use std::collections::HashMap;
-
-const CONNECTION_THRESHOLD: f32 = 0.001; // Small threshold for floating point comparison
+use serde::{Deserialize, Serialize};
+use std::cell::OnceCell;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+// How close two endpoints must be to connect, in the grid's local units.
+// Small, as it's only there for floating point error.
+pub const DEFAULT_CONNECTION_THRESHOLD: f32 = 0.001;
 const VERBOSE: bool = false;
 
 #[derive(Debug, Clone)]
 pub struct SegmentConnection {
-    segment_id: String,
+    segment_id: SegmentKey,
     connection_point: Point2,
 }
 
 #[derive(Debug)]
 pub struct SegmentNode {
-    id: String,
+    id: SegmentKey,
     tile_pos: (u32, u32),
     commands: Vec<DrawCommand>,
     connections: Vec<SegmentConnection>,
+    neighbors: Vec<SegmentKey>, // ids in `connections`, each once
+    junctions: Vec<NodeId>,     // where it meets other segments
 }
 
 impl SegmentNode {
+    // Sorted so that graph searches visit neighbors in the same order every run
+    fn set_connections(&mut self, mut connections: Vec<SegmentConnection>) {
+        connections.sort_by(|a, b| a.segment_id.cmp(&b.segment_id));
+        self.neighbors = connections
+            .iter()
+            .map(|conn| conn.segment_id.clone())
+            .collect();
+        self.neighbors.dedup();
+        self.connections = connections;
+    }
+
     fn endpoints(&self) -> Vec<Point2> {
         let mut points = Vec::new();
 
@@ -50,13 +77,88 @@ impl SegmentNode {
     }
 }
 
+// Index of a junction in the graph, stable for as long as the graph is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub usize);
+
+// A point where two or more segments meet
+#[derive(Debug)]
+pub struct Junction {
+    pub point: Point2,
+    pub segments: Vec<SegmentKey>, // sorted
+}
+
 #[derive(Debug)]
 pub struct SegmentGraph {
-    nodes: HashMap<String, SegmentNode>,
+    nodes: HashMap<SegmentKey, SegmentNode>,
+    junctions: Vec<Junction>, // indexed by NodeId, ordered by their segments
+    threshold: f32,           // the connection threshold it was built with
+    components: OnceCell<Vec<HashSet<SegmentKey>>>, // worked out on first use
+    aliases: Rc<HashMap<SegmentKey, SegmentKey>>, // the grid's purged segment ids
+}
+
+// What's saved of a graph: each segment's connections. The draw commands come
+// from the grid either way.
+#[derive(Debug, Serialize, Deserialize)]
+struct GraphCache {
+    key: String,
+    connections: HashMap<SegmentKey, Vec<CachedConnection>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedConnection {
+    segment_id: SegmentKey,
+    point: [f32; 2],
 }
 
 impl SegmentGraph {
     pub fn new(grid: &CachedGrid) -> Self {
+        Self::with_threshold(grid, DEFAULT_CONNECTION_THRESHOLD)
+    }
+
+    // Connects endpoints up to `threshold` apart, in the grid's own units.
+    // A grid that has been scaled needs the threshold scaled with it.
+    pub fn with_threshold(grid: &CachedGrid, threshold: f32) -> Self {
+        let mut graph = Self::unconnected(grid, threshold);
+        graph.build_connections();
+        graph
+    }
+
+    // Like new(), but reuses the connections saved in `cache_path` when they
+    // were saved under the same `key`, which should change whenever the tile
+    // or grid size does. Otherwise they're worked out again and saved there
+    // for next time. The key should cover the threshold too. `rebuild`
+    // ignores anything saved.
+    pub fn with_cache(
+        grid: &CachedGrid,
+        cache_path: &Path,
+        key: &str,
+        threshold: f32,
+        rebuild: bool,
+    ) -> Self {
+        let mut graph = Self::unconnected(grid, threshold);
+        if !rebuild {
+            if let Some(cache) = read_cache(cache_path, key) {
+                if graph.apply_cache(cache) {
+                    println!("Loaded the segment graph from {}", cache_path.display());
+                    return graph;
+                }
+            }
+        }
+
+        graph.build_connections();
+        match graph.save_cache(cache_path, key) {
+            Ok(()) => println!("Saved the segment graph to {}", cache_path.display()),
+            Err(err) => println!(
+                "Warning: couldn't save the segment graph to {}: {}",
+                cache_path.display(),
+                err
+            ),
+        }
+        graph
+    }
+
+    fn unconnected(grid: &CachedGrid, threshold: f32) -> Self {
         let mut nodes = HashMap::new();
 
         // First create nodes for each segment
@@ -68,19 +170,83 @@ impl SegmentGraph {
                     tile_pos: segment.tile_coordinate,
                     commands: segment.draw_commands.clone(),
                     connections: Vec::new(),
+                    neighbors: Vec::new(),
+                    junctions: Vec::new(),
                 },
             );
         }
 
-        // Then find connections between segments
-        let mut graph = Self { nodes };
-        graph.build_connections();
-        graph
+        Self {
+            nodes,
+            junctions: Vec::new(),
+            threshold,
+            components: OnceCell::new(),
+            aliases: Rc::clone(&grid.aliases),
+        }
+    }
+
+    fn save_cache(&self, cache_path: &Path, key: &str) -> Result<(), String> {
+        let connections = self
+            .nodes
+            .iter()
+            .map(|(id, node)| {
+                let connections = node
+                    .connections
+                    .iter()
+                    .map(|conn| CachedConnection {
+                        segment_id: conn.segment_id.clone(),
+                        point: conn.connection_point.to_array(),
+                    })
+                    .collect();
+                (id.clone(), connections)
+            })
+            .collect();
+        let cache = GraphCache {
+            key: key.to_string(),
+            connections,
+        };
+
+        if let Some(dir) = cache_path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string(&cache).map_err(|e| e.to_string())?;
+        fs::write(cache_path, json).map_err(|e| e.to_string())
+    }
+
+    // Takes the saved connections if they cover exactly this graph's segments.
+    // Anything else means the cache is stale, and nothing is changed.
+    fn apply_cache(&mut self, cache: GraphCache) -> bool {
+        let matches = cache.connections.len() == self.nodes.len()
+            && cache.connections.iter().all(|(id, connections)| {
+                self.nodes.contains_key(id)
+                    && connections
+                        .iter()
+                        .all(|conn| self.nodes.contains_key(&conn.segment_id))
+            });
+        if !matches {
+            return false;
+        }
+
+        for (id, connections) in cache.connections {
+            if let Some(node) = self.nodes.get_mut(&id) {
+                node.set_connections(
+                    connections
+                        .into_iter()
+                        .map(|conn| SegmentConnection {
+                            segment_id: conn.segment_id,
+                            connection_point: Point2::from(conn.point),
+                        })
+                        .collect(),
+                );
+            }
+        }
+        self.build_junctions();
+        true
     }
 
     fn build_connections(&mut self) {
         // Collect all SegmentNodes by tile position
-        let mut nodes_by_pos: HashMap<(u32, u32), Vec<String>> = HashMap::new();
+        let mut nodes_by_pos: HashMap<(u32, u32), Vec<SegmentKey>> = HashMap::new();
         for (id, node) in &self.nodes {
             nodes_by_pos
                 .entry(node.tile_pos)
@@ -88,7 +254,7 @@ impl SegmentGraph {
                 .push(id.clone());
         }
 
-        let mut new_connections: HashMap<String, Vec<SegmentConnection>> = HashMap::new();
+        let mut new_connections: HashMap<SegmentKey, Vec<SegmentConnection>> = HashMap::new();
 
         // For each segment
         for (id1, segment1) in &self.nodes {
@@ -118,7 +284,7 @@ impl SegmentGraph {
                             for p1 in &endpoints1 {
                                 for p2 in &endpoints2 {
                                     let distance = p1.distance(*p2);
-                                    if distance <= CONNECTION_THRESHOLD {
+                                    if distance <= self.threshold {
                                         // Found a connection - add it to both segments
                                         let connection_point = (*p1 + *p2) / 2.0;
 
@@ -140,8 +306,9 @@ impl SegmentGraph {
 
         // Replace all connections at once
         for node in self.nodes.values_mut() {
-            node.connections = new_connections.remove(&node.id).unwrap_or_default();
+            node.set_connections(new_connections.remove(&node.id).unwrap_or_default());
         }
+        self.build_junctions();
 
         // Print final connections
         if VERBOSE {
@@ -149,68 +316,214 @@ impl SegmentGraph {
         }
     }
 
-    pub fn find_path(&self, start: &str, end: &str) -> Option<Vec<String>> {
-        use std::collections::{HashSet, VecDeque};
-
-        // Simple BFS to find path
-        let mut queue = VecDeque::new();
-        let mut visited = HashSet::new();
-        let mut came_from: HashMap<String, String> = HashMap::new();
-
-        queue.push_back(start.to_string());
-        visited.insert(start.to_string());
+    // Gathers the connection points into junctions. Each point is the midpoint
+    // of two endpoints within the threshold, so points of the same junction
+    // can be up to twice that apart. Points are bucketed by that tolerance,
+    // so only the neighboring buckets need checking.
+    fn build_junctions(&mut self) {
+        let tolerance = self.threshold * 2.0;
+        let bucket = |point: Point2| {
+            (
+                (point.x / tolerance).floor() as i64,
+                (point.y / tolerance).floor() as i64,
+            )
+        };
+        let mut junctions: Vec<Junction> = Vec::new();
+        let mut buckets: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+
+        // sorted so junctions are numbered the same every run
+        let mut ids: Vec<&SegmentKey> = self.nodes.keys().collect();
+        ids.sort();
+        for id in ids {
+            for conn in &self.nodes[id].connections {
+                let point = conn.connection_point;
+                let (bx, by) = bucket(point);
+                let existing = (bx - 1..=bx + 1)
+                    .flat_map(|x| (by - 1..=by + 1).map(move |y| (x, y)))
+                    .filter_map(|key| buckets.get(&key))
+                    .flatten()
+                    .copied()
+                    .find(|&index| junctions[index].point.distance(point) <= tolerance);
+
+                let index = existing.unwrap_or_else(|| {
+                    junctions.push(Junction {
+                        point,
+                        segments: Vec::new(),
+                    });
+                    buckets
+                        .entry((bx, by))
+                        .or_default()
+                        .push(junctions.len() - 1);
+                    junctions.len() - 1
+                });
+                let segments = &mut junctions[index].segments;
+                for segment in [id, &conn.segment_id] {
+                    if !segments.contains(segment) {
+                        segments.push(segment.clone());
+                    }
+                }
+            }
+        }
 
-        while let Some(current) = queue.pop_front() {
-            if current == end {
-                // Reconstruct path
-                let mut path = Vec::new();
-                let mut current = current;
-                while current != start {
-                    path.push(current.clone());
-                    current = came_from.get(&current)?.clone();
+        for node in self.nodes.values_mut() {
+            node.junctions.clear();
+        }
+        for (index, junction) in junctions.iter_mut().enumerate() {
+            junction.segments.sort();
+            for segment in &junction.segments {
+                if let Some(node) = self.nodes.get_mut(segment) {
+                    node.junctions.push(NodeId(index));
                 }
-                path.push(start.to_string());
-                path.reverse();
-                return Some(path);
             }
+        }
+        self.junctions = junctions;
+    }
+
+    pub fn find_path(&self, start: &SegmentKey, end: &SegmentKey) -> Option<Vec<SegmentKey>> {
+        GraphView::base(self).find_path(start, end)
+    }
+
+    pub fn find_path_to_nearest(
+        &self,
+        start: &SegmentKey,
+        goals: &HashSet<SegmentKey>,
+    ) -> Option<Vec<SegmentKey>> {
+        GraphView::base(self).find_path_to_nearest(start, goals)
+    }
 
-            // Add unvisited neighbors to queue
-            if let Some(node) = self.nodes.get(&current) {
-                for connection in &node.connections {
-                    if !visited.contains(&connection.segment_id) {
-                        queue.push_back(connection.segment_id.clone());
-                        visited.insert(connection.segment_id.clone());
-                        came_from.insert(connection.segment_id.clone(), current.clone());
+    // Groups of segments joined to each other and to nothing else, like the
+    // separate figures on a grid. Ordered by their first segment id.
+    pub fn connected_components(&self) -> &[HashSet<SegmentKey>] {
+        self.components.get_or_init(|| {
+            let mut ids: Vec<&SegmentKey> = self.nodes.keys().collect();
+            ids.sort();
+
+            let mut components = Vec::new();
+            let mut seen = HashSet::new();
+            for id in ids {
+                if seen.contains(id) {
+                    continue;
+                }
+                let mut component = HashSet::new();
+                let mut queue = VecDeque::from([id.clone()]);
+                seen.insert(id.clone());
+                while let Some(current) = queue.pop_front() {
+                    for neighbor in self.neighbors(&current) {
+                        if seen.insert(neighbor.clone()) {
+                            queue.push_back(neighbor.clone());
+                        }
                     }
+                    component.insert(current);
                 }
+                components.push(component);
             }
-        }
+            components
+        })
+    }
 
-        None // No path found
+    pub fn hop_distances(&self, start: &SegmentKey) -> HashMap<SegmentKey, usize> {
+        GraphView::base(self).hop_distances(start)
     }
 
-    pub fn node(&self, id: &str) -> Option<&SegmentNode> {
-        self.nodes.get(id)
+    // Segments whose removal would split the graph into more pieces
+    pub fn articulation_points(&self) -> BTreeSet<SegmentKey> {
+        GraphView::base(self).articulation_points(&self.nodes.keys().cloned().collect())
     }
 
-    pub fn neighbors_of(&self, id: &str) -> Vec<String> {
-        self.nodes
-            .get(id)
-            .map(|node| {
-                node.connections
-                    .iter()
-                    .map(|conn| conn.segment_id.clone())
-                    .collect()
-            })
-            .unwrap_or_default()
+    // Connections whose removal would split the graph into more pieces, as
+    // the lower id and the higher id
+    pub fn bridges(&self) -> BTreeSet<(SegmentKey, SegmentKey)> {
+        GraphView::base(self).bridges(&self.nodes.keys().cloned().collect())
+    }
+
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    pub fn component_of(&self, id: &SegmentKey) -> Option<&HashSet<SegmentKey>> {
+        let id = self.resolve_id(id);
+        self.connected_components()
+            .iter()
+            .find(|component| component.contains(id))
+    }
+
+    // The id a segment is kept under in the graph. A segment purged from the
+    // grid as a duplicate is looked up as the one kept in its place.
+    pub fn resolve_id<'a>(&'a self, id: &'a SegmentKey) -> &'a SegmentKey {
+        self.aliases.get(id).unwrap_or(id)
     }
 
-    pub fn get_connection_point(&self, first: &str, second: &str) -> Option<&Point2> {
-        self.node(first)?
-            .connections
+    pub fn node(&self, id: &SegmentKey) -> Option<&SegmentNode> {
+        self.nodes.get(self.resolve_id(id))
+    }
+
+    // The segments connected to `id`, sorted by id and each listed once.
+    // Empty for a segment that isn't in the graph.
+    pub fn neighbors(&self, id: &SegmentKey) -> &[SegmentKey] {
+        self.node(id).map_or(&[], |node| &node.neighbors)
+    }
+
+    pub fn degree(&self, id: &SegmentKey) -> usize {
+        self.neighbors(id).len()
+    }
+
+    // Where two segments meet, if they're connected
+    pub fn connection_point(&self, first: &SegmentKey, second: &SegmentKey) -> Option<Point2> {
+        let second = self.resolve_id(second);
+        self.nodes_of_segment(first)
             .iter()
-            .find(|c| c.segment_id == second)
-            .map(|c| &c.connection_point)
+            .map(|&node| &self.junctions[node.0])
+            .find(|junction| junction.segments.binary_search(second).is_ok())
+            .map(|junction| junction.point)
+    }
+
+    // Every junction with its id, in id order
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &Junction)> + '_ {
+        self.junctions
+            .iter()
+            .enumerate()
+            .map(|(index, junction)| (NodeId(index), junction))
+    }
+
+    // The junction closest to `point`, if one is within `tolerance`
+    pub fn node_at(&self, point: Point2, tolerance: f32) -> Option<NodeId> {
+        self.nodes()
+            .map(|(node, junction)| (node, junction.point.distance(point)))
+            .filter(|(_, distance)| *distance <= tolerance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(node, _)| node)
+    }
+
+    pub fn node_point(&self, node: NodeId) -> Option<Point2> {
+        self.junctions.get(node.0).map(|junction| junction.point)
+    }
+
+    // The segments meeting at a junction, sorted by id
+    pub fn segments_at_node(&self, node: NodeId) -> &[SegmentKey] {
+        self.junctions
+            .get(node.0)
+            .map_or(&[], |junction| &junction.segments)
+    }
+
+    // The junctions along a segment, in id order
+    pub fn nodes_of_segment(&self, id: &SegmentKey) -> &[NodeId] {
+        self.node(id).map_or(&[], |node| &node.junctions)
+    }
+
+    // Every connection once, as the lower id, the higher id and where they
+    // meet, sorted by id
+    pub fn edges(&self) -> impl Iterator<Item = (&SegmentKey, &SegmentKey, Point2)> + '_ {
+        let mut nodes: Vec<&SegmentNode> = self.nodes.values().collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        nodes.into_iter().flat_map(move |node| {
+            node.neighbors
+                .iter()
+                .filter(|neighbor| node.id < **neighbor)
+                .filter_map(move |neighbor| {
+                    self.connection_point(&node.id, neighbor)
+                        .map(|point| (&node.id, neighbor, point))
+                })
+        })
     }
 
     // Debug helper
@@ -225,10 +538,31 @@ impl SegmentGraph {
     }
 }
 
+// The cache at `cache_path`, if there is one saved under `key`
+fn read_cache(cache_path: &Path, key: &str) -> Option<GraphCache> {
+    let json = fs::read_to_string(cache_path).ok()?;
+    match serde_json::from_str::<GraphCache>(&json) {
+        Ok(cache) if cache.key == key => Some(cache),
+        Ok(_) => None,
+        Err(err) => {
+            println!(
+                "Warning: ignoring unreadable segment graph cache {}: {}",
+                cache_path.display(),
+                err
+            );
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn key(id: &str) -> SegmentKey {
+        id.parse().unwrap()
+    }
+
     // Helper to create test graphs
     fn create_test_graph() -> SegmentGraph {
         let mut nodes = HashMap::new();
@@ -250,37 +584,49 @@ mod tests {
         }];
 
         nodes.insert(
-            "A".to_string(),
+            key("A"),
             SegmentNode {
-                id: "A".to_string(),
+                id: key("A"),
                 tile_pos: (1, 1),
                 commands: commands_a,
                 connections: Vec::new(),
+                neighbors: Vec::new(),
+                junctions: Vec::new(),
             },
         );
 
         nodes.insert(
-            "B".to_string(),
+            key("B"),
             SegmentNode {
-                id: "B".to_string(),
+                id: key("B"),
                 tile_pos: (1, 1),
                 commands: commands_b,
                 connections: Vec::new(),
+                neighbors: Vec::new(),
+                junctions: Vec::new(),
             },
         );
 
         nodes.insert(
-            "C".to_string(),
+            key("C"),
             SegmentNode {
-                id: "C".to_string(),
+                id: key("C"),
                 tile_pos: (1, 1),
 
                 commands: commands_c,
                 connections: Vec::new(),
+                neighbors: Vec::new(),
+                junctions: Vec::new(),
             },
         );
 
-        let mut graph = SegmentGraph { nodes };
+        let mut graph = SegmentGraph {
+            nodes,
+            junctions: Vec::new(),
+            threshold: DEFAULT_CONNECTION_THRESHOLD,
+            components: OnceCell::new(),
+            aliases: Rc::default(),
+        };
         graph.build_connections();
         graph
     }
@@ -326,50 +672,64 @@ mod tests {
 
         // Insert all nodes
         nodes.insert(
-            "H1".to_string(),
+            key("H1"),
             SegmentNode {
-                id: "H1".to_string(),
+                id: key("H1"),
                 tile_pos: (1, 1),
 
                 commands: commands_h1,
                 connections: Vec::new(),
+                neighbors: Vec::new(),
+                junctions: Vec::new(),
             },
         );
 
         nodes.insert(
-            "H2".to_string(),
+            key("H2"),
             SegmentNode {
-                id: "H2".to_string(),
+                id: key("H2"),
                 tile_pos: (1, 1),
 
                 commands: commands_h2,
                 connections: Vec::new(),
+                neighbors: Vec::new(),
+                junctions: Vec::new(),
             },
         );
 
         nodes.insert(
-            "V".to_string(),
+            key("V"),
             SegmentNode {
-                id: "V".to_string(),
+                id: key("V"),
                 tile_pos: (1, 1),
 
                 commands: commands_v,
                 connections: Vec::new(),
+                neighbors: Vec::new(),
+                junctions: Vec::new(),
             },
         );
 
         nodes.insert(
-            "A1".to_string(),
+            key("A1"),
             SegmentNode {
-                id: "A1".to_string(),
+                id: key("A1"),
                 tile_pos: (1, 1),
 
                 commands: commands_a1,
                 connections: Vec::new(),
+                neighbors: Vec::new(),
+                junctions: Vec::new(),
             },
         );
 
-        let mut graph = SegmentGraph { nodes };
+        let mut graph = SegmentGraph {
+            nodes,
+            junctions: Vec::new(),
+            threshold: DEFAULT_CONNECTION_THRESHOLD,
+            components: OnceCell::new(),
+            aliases: Rc::default(),
+        };
         graph.build_connections();
         graph
     }
@@ -379,62 +739,252 @@ mod tests {
         let graph = create_test_graph();
 
         // Check if A connects to B
-        let node_a = graph.node("A").unwrap();
+        let node_a = graph.node(&key("A")).unwrap();
         assert_eq!(node_a.connections.len(), 1);
         assert_eq!(node_a.connections[0].segment_id, "B");
 
         // Check if B connects to both A and C
-        let node_b = graph.node("B").unwrap();
+        let node_b = graph.node(&key("B")).unwrap();
         assert_eq!(node_b.connections.len(), 2);
+        assert_eq!(graph.neighbors(&key("B")), ["A", "C"]);
+        assert_eq!(graph.degree(&key("B")), 2);
+        assert_eq!(graph.degree(&key("missing")), 0);
+
+        let edges: Vec<(&str, &str, Point2)> = graph
+            .edges()
+            .map(|(a, b, point)| (a.element.as_str(), b.element.as_str(), point))
+            .collect();
+        assert_eq!(
+            edges,
+            [("A", "B", pt2(28.0, 50.0)), ("B", "C", pt2(68.0, 50.0))]
+        );
 
         // Check if C connects to B
-        let node_c = graph.node("C").unwrap();
+        let node_c = graph.node(&key("C")).unwrap();
         assert_eq!(node_c.connections.len(), 1);
         assert_eq!(node_c.connections[0].segment_id, "B");
     }
 
+    #[test]
+    fn test_junctions_list_the_segments_meeting_there() {
+        let graph = create_test_graph();
+
+        let points: Vec<Point2> = graph.nodes().map(|(_, j)| j.point).collect();
+        assert_eq!(points, [pt2(28.0, 50.0), pt2(68.0, 50.0)]);
+
+        let node = graph.node_at(pt2(68.5, 50.0), 1.0).unwrap();
+        assert_eq!(graph.segments_at_node(node), ["B", "C"]);
+        assert!(graph.node_at(pt2(50.0, 50.0), 1.0).is_none());
+
+        assert_eq!(graph.nodes_of_segment(&key("A")), [NodeId(0)]);
+        assert_eq!(graph.nodes_of_segment(&key("B")), [NodeId(0), NodeId(1)]);
+        assert_eq!(
+            graph.connection_point(&key("C"), &key("B")),
+            Some(pt2(68.0, 50.0))
+        );
+        assert_eq!(graph.connection_point(&key("A"), &key("C")), None);
+    }
+
+    #[test]
+    fn test_scaled_grid_keeps_its_connections() {
+        use crate::{models::Project, views::Transform2D};
+
+        let grid = CachedGrid::new(&Project::load("projects/debug.json").unwrap());
+        let mut scaled = grid.clone();
+        scaled.apply_transform(&Transform2D {
+            scale: Vec2::splat(0.2),
+            ..Default::default()
+        });
+
+        let graph = SegmentGraph::new(&grid);
+        let small = SegmentGraph::with_threshold(&scaled, DEFAULT_CONNECTION_THRESHOLD * 0.2);
+        assert_eq!(small.threshold(), DEFAULT_CONNECTION_THRESHOLD * 0.2);
+        assert!(graph.edges().count() > 0);
+        for id in grid.segments.keys() {
+            assert_eq!(small.neighbors(id), graph.neighbors(id), "{}", id);
+        }
+        assert_eq!(small.nodes().count(), graph.nodes().count());
+    }
+
+    #[test]
+    fn test_articulation_points_and_bridges() {
+        // A - B - C in a line
+        let graph = create_test_graph();
+        assert_eq!(graph.articulation_points(), BTreeSet::from([key("B")]));
+        let bridges = graph.bridges();
+        let bridges: Vec<(&str, &str)> = bridges
+            .iter()
+            .map(|(a, b)| (a.element.as_str(), b.element.as_str()))
+            .collect();
+        assert_eq!(bridges, [("A", "B"), ("B", "C")]);
+
+        // a square has no single point of failure
+        let mut nodes = HashMap::new();
+        let corners = [
+            pt2(0.0, 0.0),
+            pt2(10.0, 0.0),
+            pt2(10.0, 10.0),
+            pt2(0.0, 10.0),
+        ];
+        for (i, name) in ["S1", "S2", "S3", "S4"].iter().enumerate() {
+            nodes.insert(
+                key(name),
+                SegmentNode {
+                    id: key(name),
+                    tile_pos: (1, 1),
+                    commands: vec![DrawCommand::Line {
+                        start: corners[i],
+                        end: corners[(i + 1) % 4],
+                    }],
+                    connections: Vec::new(),
+                    neighbors: Vec::new(),
+                    junctions: Vec::new(),
+                },
+            );
+        }
+        let mut square = SegmentGraph {
+            nodes,
+            junctions: Vec::new(),
+            threshold: DEFAULT_CONNECTION_THRESHOLD,
+            components: OnceCell::new(),
+            aliases: Rc::default(),
+        };
+        square.build_connections();
+        assert!(square.articulation_points().is_empty());
+        assert!(square.bridges().is_empty());
+
+        // within part of the line, the middle still holds it together
+        let part: HashSet<SegmentKey> = ["A", "B"].map(key).into();
+        assert!(GraphView::base(&graph)
+            .articulation_points(&part)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_purged_segments_are_found_through_their_duplicate() {
+        let grid = CachedGrid::new(&crate::models::Project::load("projects/ulsan.json").unwrap());
+        let graph = SegmentGraph::new(&grid);
+        assert_eq!(graph.nodes.len(), grid.segments.len());
+
+        // the edge kept from the tile above joins the tile below as before
+        let kept = &key("1,2 : hor-3-2");
+        let purged = &key("1,3 : hor-1-2");
+        assert!(graph.neighbors(kept).contains(&key("1,3 : ver-2-1")));
+        assert!(graph.neighbors(kept).contains(&key("1,2 : ver-2-2")));
+        assert_eq!(graph.neighbors(purged), graph.neighbors(kept));
+        assert_eq!(graph.resolve_id(purged), kept);
+        assert_eq!(
+            graph.connection_point(&key("1,3 : ver-2-1"), purged),
+            graph.connection_point(&key("1,3 : ver-2-1"), kept)
+        );
+        assert!(graph.component_of(purged).unwrap().contains(kept));
+    }
+
     #[test]
     fn test_simple_path_finding() {
         let graph = create_test_graph();
 
         // Test path from A to C
-        let path = graph.find_path("A", "C").unwrap();
+        let path = graph.find_path(&key("A"), &key("C")).unwrap();
         assert_eq!(path, vec!["A", "B", "C"]);
 
         // Test path from C to A
-        let path = graph.find_path("C", "A").unwrap();
+        let path = graph.find_path(&key("C"), &key("A")).unwrap();
         assert_eq!(path, vec!["C", "B", "A"]);
     }
 
+    #[test]
+    fn test_path_to_nearest() {
+        let graph = create_test_graph();
+
+        let goals = ["A", "B"].map(key).into();
+        assert_eq!(
+            graph.find_path_to_nearest(&key("C"), &goals).unwrap(),
+            vec!["C", "B"]
+        );
+
+        let goals = [key("A")].into();
+        assert_eq!(
+            graph.find_path_to_nearest(&key("C"), &goals).unwrap(),
+            vec!["C", "B", "A"]
+        );
+        assert!(graph
+            .find_path_to_nearest(&key("C"), &HashSet::new())
+            .is_none());
+    }
+
     #[test]
     fn test_complex_connections() {
         let graph = create_complex_test_graph();
 
         // Test T-junction connections
-        let node_h1 = graph.node("H1").unwrap();
+        let node_h1 = graph.node(&key("H1")).unwrap();
         assert_eq!(node_h1.connections.len(), 0); // Connects to nothing
 
-        let node_h2 = graph.node("H2").unwrap();
+        let node_h2 = graph.node(&key("H2")).unwrap();
         assert_eq!(node_h2.connections.len(), 1); // Connects to V
 
-        let node_v = graph.node("V").unwrap();
+        let node_v = graph.node(&key("V")).unwrap();
         assert_eq!(node_v.connections.len(), 2); // Connects to H2 and A1
 
         // Test arc connections
-        let node_a1 = graph.node("A1").unwrap();
+        let node_a1 = graph.node(&key("A1")).unwrap();
         assert_eq!(node_a1.connections.len(), 1); // Connects to V
     }
 
+    #[test]
+    fn test_cached_graph_round_trips() {
+        let graph = create_complex_test_graph();
+        let cache_path =
+            std::env::temp_dir().join(format!("glyphvis_graph_cache_{}.json", std::process::id()));
+        graph.save_cache(&cache_path, "key").unwrap();
+        assert!(read_cache(&cache_path, "other key").is_none());
+
+        let mut loaded = SegmentGraph {
+            junctions: Vec::new(),
+            threshold: graph.threshold,
+            components: OnceCell::new(),
+            aliases: Rc::default(),
+            nodes: graph
+                .nodes
+                .values()
+                .map(|node| {
+                    let node = SegmentNode {
+                        id: node.id.clone(),
+                        tile_pos: node.tile_pos,
+                        commands: node.commands.clone(),
+                        connections: Vec::new(),
+                        neighbors: Vec::new(),
+                        junctions: Vec::new(),
+                    };
+                    (node.id.clone(), node)
+                })
+                .collect(),
+        };
+        assert!(loaded.apply_cache(read_cache(&cache_path, "key").unwrap()));
+        let _ = fs::remove_file(&cache_path);
+
+        for id in graph.nodes.keys() {
+            assert_eq!(loaded.neighbors(id), graph.neighbors(id), "{}", id);
+            for neighbor in graph.neighbors(id) {
+                assert_eq!(
+                    loaded.connection_point(id, neighbor),
+                    graph.connection_point(id, neighbor)
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_complex_path_finding() {
         let graph = create_complex_test_graph();
 
         // Test path through T-junction
-        let path = graph.find_path("H2", "A1").unwrap();
+        let path = graph.find_path(&key("H2"), &key("A1")).unwrap();
         assert_eq!(path.len(), 2); // Should find path H2 -> V -> A1
 
         // Test path using arc
-        let path = graph.find_path("A1", "V").unwrap();
+        let path = graph.find_path(&key("A1"), &key("V")).unwrap();
         assert!(path.len() <= 3); // Should find either H1 -> H2 -> V or H1 -> A1 -> V
     }
 }
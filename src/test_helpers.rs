@@ -0,0 +1,50 @@
+// src/test_helpers.rs
+//
+// Fixtures shared by the unit tests
+
+use nannou::prelude::*;
+use std::rc::Rc;
+
+use crate::{
+    animation::TransitionEngine,
+    config::TransitionConfig,
+    models::Project,
+    services::SegmentGraph,
+    views::{CachedGrid, GridInstance},
+};
+
+// A grid of the debug project at the origin, unturned
+pub fn test_grid_instance() -> GridInstance {
+    let project = Project::load("projects/debug.json").unwrap();
+    let grid = CachedGrid::new(&project);
+    let graph = Rc::new(SegmentGraph::new(&grid));
+    GridInstance::new(
+        "test".to_string(),
+        &project,
+        "",
+        &grid,
+        graph,
+        pt2(0.0, 0.0),
+        0.0,
+        10.0,
+        5.0,
+    )
+}
+
+// Seeded, with no gaps between strokes and no stagger. Tests that need
+// something else override fields on this with struct update syntax.
+pub fn test_config(steps: usize, frame_duration: f32) -> TransitionConfig {
+    TransitionConfig {
+        steps,
+        frame_duration,
+        wandering: 0.5,
+        density: 0.1,
+        seed: Some(1),
+        stroke_gap_steps: 0,
+        stagger: 0.0,
+    }
+}
+
+pub fn test_engine(steps: usize, frame_duration: f32) -> TransitionEngine {
+    TransitionEngine::new(test_config(steps, frame_duration))
+}
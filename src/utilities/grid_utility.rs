@@ -400,6 +400,7 @@ pub fn get_neighbor_direction(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::SegmentKey;
 
     // Helper to create a test viewbox
     fn create_test_viewbox() -> ViewBox {
@@ -462,7 +463,7 @@ mod tests {
     fn test_check_segment_alignment() {
         // Test basic edge alignments
         let segment1 = CachedSegment::new(
-            "test1".to_string(),
+            SegmentKey::off_grid("test1"),
             (1, 1),
             &PathElement::Line {
                 x1: 0.0,
@@ -476,7 +477,7 @@ mod tests {
         );
 
         let segment2 = CachedSegment::new(
-            "test2".to_string(),
+            SegmentKey::off_grid("test2"),
             (1, 2),
             &PathElement::Line {
                 x1: 0.0,
@@ -498,7 +499,7 @@ mod tests {
 
         // Test non-matching lines on same edge
         let line1 = CachedSegment::new(
-            "line1".to_string(),
+            SegmentKey::off_grid("line1"),
             (1, 1),
             &PathElement::Line {
                 x1: 0.0,
@@ -512,7 +513,7 @@ mod tests {
         );
 
         let line2 = CachedSegment::new(
-            "line2".to_string(),
+            SegmentKey::off_grid("line2"),
             (1, 2),
             &PathElement::Line {
                 x1: 25.0,
@@ -530,7 +531,7 @@ mod tests {
 
         // Test offset circles on same edge
         let circle1 = CachedSegment::new(
-            "circle1".to_string(),
+            SegmentKey::off_grid("circle1"),
             (1, 1),
             &PathElement::Circle {
                 cx: 0.0,
@@ -543,7 +544,7 @@ mod tests {
         );
 
         let circle2 = CachedSegment::new(
-            "circle2".to_string(),
+            SegmentKey::off_grid("circle2"),
             (1, 2),
             &PathElement::Circle {
                 cx: 10.0, // Offset by 10 units
@@ -560,7 +561,7 @@ mod tests {
 
         // Test vertical line misalignment
         let vert_line1 = CachedSegment::new(
-            "vert1".to_string(),
+            SegmentKey::off_grid("vert1"),
             (1, 1),
             &PathElement::Line {
                 x1: 100.0,
@@ -574,7 +575,7 @@ mod tests {
         );
 
         let vert_line2 = CachedSegment::new(
-            "vert2".to_string(),
+            SegmentKey::off_grid("vert2"),
             (2, 1),
             &PathElement::Line {
                 x1: 0.0,
@@ -1,4 +1,5 @@
 pub mod easing;
 pub mod grid_utility;
+pub mod segment_analysis;
 pub mod segment_utility;
 pub mod svg;
@@ -0,0 +1,221 @@
+// src/utilities/segment_analysis.rs
+//
+// Geometric queries on the segments of a CachedGrid, shared by the
+// transition generators.
+
+use crate::models::SegmentKey;
+use crate::services::SegmentGraph;
+use crate::views::{CachedGrid, DrawCommand, SegmentType};
+use nannou::prelude::*;
+
+// Get position for a segment (using the starting point)
+pub fn get_segment_position(segment_id: &SegmentKey, grid: &CachedGrid) -> Point2 {
+    if let Some(segment) = grid.segments.get(segment_id) {
+        // Use the appropriate point based on segment type
+        match segment.segment_type {
+            SegmentType::Horizontal => find_leftmost_point(&segment.draw_commands),
+            SegmentType::Vertical => find_topmost_point(&segment.draw_commands),
+            SegmentType::ArcTopLeft => find_topmost_point(&segment.draw_commands),
+            SegmentType::ArcTopRight => find_topmost_point(&segment.draw_commands),
+            SegmentType::ArcBottomLeft => find_leftmost_point(&segment.draw_commands),
+            SegmentType::ArcBottomRight => find_rightmost_point(&segment.draw_commands),
+            // diagonals start from their top end
+            SegmentType::DiagonalDown => find_leftmost_point(&segment.draw_commands),
+            SegmentType::DiagonalUp => find_rightmost_point(&segment.draw_commands),
+            SegmentType::Unknown => find_average_point(&segment.draw_commands),
+        }
+    } else {
+        Point2::new(0.0, 0.0)
+    }
+}
+
+// Get the center of a segment, averaged over its draw command points
+pub fn get_segment_center(segment_id: &SegmentKey, grid: &CachedGrid) -> Point2 {
+    grid.segments
+        .get(segment_id)
+        .map(|segment| find_average_point(&segment.draw_commands))
+        .unwrap_or(Point2::new(0.0, 0.0))
+}
+
+// Helper functions to find specific points in draw commands
+pub fn find_leftmost_point(commands: &[DrawCommand]) -> Point2 {
+    let mut leftmost = Point2::new(f32::MAX, 0.0);
+
+    for cmd in commands {
+        match cmd {
+            DrawCommand::Line { start, end } => {
+                if start.x < leftmost.x {
+                    leftmost = *start;
+                }
+                if end.x < leftmost.x {
+                    leftmost = *end;
+                }
+            }
+            DrawCommand::Arc { points } => {
+                for point in points {
+                    if point.x < leftmost.x {
+                        leftmost = *point;
+                    }
+                }
+            }
+            DrawCommand::Circle { center, .. } => {
+                if center.x < leftmost.x {
+                    leftmost = *center;
+                }
+            }
+        }
+    }
+
+    leftmost
+}
+
+pub fn find_topmost_point(commands: &[DrawCommand]) -> Point2 {
+    let mut topmost = Point2::new(0.0, f32::MAX);
+
+    for cmd in commands {
+        match cmd {
+            DrawCommand::Line { start, end } => {
+                // Note: Lower y value is higher in screen coordinates
+                if start.y < topmost.y {
+                    topmost = *start;
+                }
+                if end.y < topmost.y {
+                    topmost = *end;
+                }
+            }
+            DrawCommand::Arc { points } => {
+                for point in points {
+                    if point.y < topmost.y {
+                        topmost = *point;
+                    }
+                }
+            }
+            DrawCommand::Circle { center, .. } => {
+                if center.y < topmost.y {
+                    topmost = *center;
+                }
+            }
+        }
+    }
+
+    topmost
+}
+
+pub fn find_rightmost_point(commands: &[DrawCommand]) -> Point2 {
+    let mut rightmost = Point2::new(f32::MIN, 0.0);
+
+    for cmd in commands {
+        match cmd {
+            DrawCommand::Line { start, end } => {
+                if start.x > rightmost.x {
+                    rightmost = *start;
+                }
+                if end.x > rightmost.x {
+                    rightmost = *end;
+                }
+            }
+            DrawCommand::Arc { points } => {
+                for point in points {
+                    if point.x > rightmost.x {
+                        rightmost = *point;
+                    }
+                }
+            }
+            DrawCommand::Circle { center, .. } => {
+                if center.x > rightmost.x {
+                    rightmost = *center;
+                }
+            }
+        }
+    }
+
+    rightmost
+}
+
+pub fn find_average_point(commands: &[DrawCommand]) -> Point2 {
+    let mut sum = Point2::new(0.0, 0.0);
+    let mut count = 0;
+
+    for cmd in commands {
+        match cmd {
+            DrawCommand::Line { start, end } => {
+                sum += *start;
+                sum += *end;
+                count += 2;
+            }
+            DrawCommand::Arc { points } => {
+                for point in points {
+                    sum += *point;
+                    count += 1;
+                }
+            }
+            DrawCommand::Circle { center, .. } => {
+                sum += *center;
+                count += 1;
+            }
+        }
+    }
+
+    if count > 0 {
+        sum / count as f32
+    } else {
+        Point2::new(0.0, 0.0)
+    }
+}
+
+// Shortest distance from `point` to anything the commands draw. Circles count
+// as filled.
+pub fn distance_to_point(commands: &[DrawCommand], point: Point2) -> f32 {
+    commands
+        .iter()
+        .map(|cmd| match cmd {
+            DrawCommand::Line { start, end } => distance_to_line(point, *start, *end),
+            DrawCommand::Arc { points } => points
+                .windows(2)
+                .map(|pair| distance_to_line(point, pair[0], pair[1]))
+                .fold(f32::MAX, f32::min),
+            DrawCommand::Circle { center, radius } => (center.distance(point) - radius).max(0.0),
+        })
+        .fold(f32::MAX, f32::min)
+}
+
+// The bounding box of everything the commands draw, None if they draw nothing
+pub fn command_bounds(commands: &[DrawCommand]) -> Option<Rect> {
+    let mut corners = commands.iter().flat_map(|cmd| match cmd {
+        DrawCommand::Line { start, end } => vec![*start, *end],
+        DrawCommand::Arc { points } => points.clone(),
+        DrawCommand::Circle { center, radius } => {
+            vec![
+                *center - vec2(*radius, *radius),
+                *center + vec2(*radius, *radius),
+            ]
+        }
+    });
+    let first = corners.next()?;
+    let (min, max) = corners.fold((first, first), |(min, max), point| {
+        (min.min(point), max.max(point))
+    });
+    Some(Rect::from_corners(min, max))
+}
+
+// The segments meeting at the junction nearest `point`, within `tolerance`.
+// The point is in the grid's local space, before any instance transform.
+pub fn find_connected_segments_at_point(
+    graph: &SegmentGraph,
+    point: Point2,
+    tolerance: f32,
+) -> Vec<SegmentKey> {
+    graph
+        .node_at(point, tolerance)
+        .map_or_else(Vec::new, |node| graph.segments_at_node(node).to_vec())
+}
+
+fn distance_to_line(point: Point2, start: Point2, end: Point2) -> f32 {
+    let line = end - start;
+    let length_squared = line.length_squared();
+    if length_squared == 0.0 {
+        return point.distance(start);
+    }
+    let t = ((point - start).dot(line) / length_squared).clamp(0.0, 1.0);
+    point.distance(start + line * t)
+}
@@ -10,10 +10,28 @@ use crate::{
 };
 use nannou::prelude::*;
 
+// How far a piece of a tessellated arc may stray from the true curve, in
+// scene units. Half a pixel at the preview texture's 2x scale factor.
+const MAX_CHORD_ERROR: f32 = 0.25;
+
+// The point counts arcs are tessellated at, around ARC_RESOLUTION. Arcs only
+// move between these, so small scale changes don't re-tessellate them.
+const ARC_RESOLUTIONS: [usize; 6] = [6, 12, ARC_RESOLUTION, 50, 100, 200];
+
 pub fn generate_draw_commands(
     path: &PathElement,
     viewbox: &ViewBox,
     transform: &Transform2D,
+) -> Vec<DrawCommand> {
+    generate_draw_commands_at(path, viewbox, transform, ARC_RESOLUTION)
+}
+
+// Like generate_draw_commands, with arcs split into `resolution` pieces
+pub fn generate_draw_commands_at(
+    path: &PathElement,
+    viewbox: &ViewBox,
+    transform: &Transform2D,
+    resolution: usize,
 ) -> Vec<DrawCommand> {
     match path {
         PathElement::Line { x1, y1, x2, y2 } => {
@@ -50,12 +68,12 @@ pub fn generate_draw_commands(
             // Calculate all points, scale radii
             let points = grid_utility::generate_arc_points(
                 center,
-                *rx * transform.scale,
-                *ry * transform.scale,
+                *rx * transform.scale.x,
+                *ry * transform.scale.y,
                 start_angle,
                 sweep_angle,
                 *x_axis_rotation,
-                ARC_RESOLUTION,
+                resolution,
             );
 
             vec![DrawCommand::Arc { points }]
@@ -63,12 +81,58 @@ pub fn generate_draw_commands(
         PathElement::Circle { cx, cy, r } => {
             vec![DrawCommand::Circle {
                 center: initial_transform(*cx, *cy, viewbox, transform),
-                radius: *r * transform.scale,
+                radius: *r * transform.mean_scale(),
             }]
         }
     }
 }
 
+// How far an arc turns, in radians, and its larger radius, as drawn at
+// scale 1. None for paths that aren't arcs.
+pub fn arc_extent(path: &PathElement) -> Option<(f32, f32)> {
+    let PathElement::Arc {
+        start_x,
+        start_y,
+        rx,
+        ry,
+        x_axis_rotation,
+        large_arc,
+        sweep,
+        end_x,
+        end_y,
+    } = path
+    else {
+        return None;
+    };
+    // flipping y to nannou's coordinates doesn't change how far it turns
+    let (_, _, sweep_angle) = grid_utility::calculate_arc_center(
+        pt2(*start_x, *start_y),
+        pt2(*end_x, *end_y),
+        *rx,
+        *ry,
+        *x_axis_rotation,
+        *large_arc,
+        *sweep,
+    );
+    Some((sweep_angle.abs(), rx.max(*ry)))
+}
+
+// The fewest of ARC_RESOLUTIONS pieces that keep an arc of `radius` turning
+// through `sweep` radians within MAX_CHORD_ERROR of the curve. A piece
+// turning through angle a strays radius * (1 - cos(a / 2)) at its middle.
+pub fn arc_resolution(radius: f32, sweep: f32) -> usize {
+    let needed = if radius <= MAX_CHORD_ERROR {
+        1.0
+    } else {
+        let max_piece_angle = 2.0 * (1.0 - MAX_CHORD_ERROR / radius).acos();
+        (sweep / max_piece_angle).ceil()
+    };
+    ARC_RESOLUTIONS
+        .into_iter()
+        .find(|resolution| *resolution as f32 >= needed)
+        .unwrap_or(ARC_RESOLUTIONS[ARC_RESOLUTIONS.len() - 1])
+}
+
 // Translates a point to the correct Tile position
 pub fn calculate_tile_transform(
     viewbox: &ViewBox,
@@ -88,7 +152,7 @@ pub fn calculate_tile_transform(
 
     Transform2D {
         translation: pt2(tile_center_x, tile_center_y),
-        scale: 1.0,
+        scale: Vec2::ONE,
         rotation: 0.0,
     }
 }
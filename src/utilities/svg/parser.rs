@@ -7,6 +7,7 @@ use std::str::FromStr;
 pub struct SVGElement {
     pub id: String,
     pub path: PathElement,
+    pub tags: Vec<String>, // the element's classes
 }
 
 pub fn parse_svg(svg_content: &str) -> Vec<SVGElement> {
@@ -16,7 +17,8 @@ pub fn parse_svg(svg_content: &str) -> Vec<SVGElement> {
         .filter_map(|line| {
             if let Some(id) = parse_id(line) {
                 if let Some(path) = parse_element(line) {
-                    return Some(SVGElement { id, path });
+                    let tags = parse_tags(line);
+                    return Some(SVGElement { id, path, tags });
                 }
             }
             None
@@ -33,6 +35,15 @@ fn parse_id(element: &str) -> Option<String> {
     None
 }
 
+// The class attribute's names, in order
+fn parse_tags(element: &str) -> Vec<String> {
+    element
+        .split_once(" class=\"")
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .map(|(classes, _)| classes.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
 // supported SVG elements: path & circle
 fn parse_element(element: &str) -> Option<PathElement> {
     if element.contains("<circle") {
@@ -190,4 +201,18 @@ mod tests {
         let elements = parse_svg(svg_data);
         assert_eq!(elements.len(), 0);
     }
+
+    #[test]
+    fn test_parse_tags() {
+        let svg_data = r#"
+            <path id="ring" class="outer  inner-ring" d="M 0,0 L 100,100"/>
+            <circle class="crossbar" id="dot" cx="50" cy="50" r="25"/>
+            <path id="plain" d="M 0,0 L 100,0"/>
+        "#;
+        let elements = parse_svg(svg_data);
+        assert_eq!(elements[0].tags, ["outer", "inner-ring"]);
+        assert_eq!(elements[1].id, "dot");
+        assert_eq!(elements[1].tags, ["crossbar"]);
+        assert!(elements[2].tags.is_empty());
+    }
 }
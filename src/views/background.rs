@@ -2,15 +2,44 @@
 //
 // A simple module to manage background state
 // Needs improvement: pattern after backbone_fx
+//
+// With an image loaded, the background color is added over the image rather
+// than replacing it, so black leaves the image as is and flashes and fades
+// wash over it.
+//
+// With persistence, the render texture isn't cleared. A translucent rect
+// fades the previous frame out instead, leaving trails behind moving grids.
+//
+// In transparent mode the recording is cleared to transparent rather than
+// drawn with the background, for compositing later. Flashes and fades have
+// nothing to show there, so they're ignored. The preview keeps the background
+// so the operator still sees a sensible image.
 
 use crate::effects::*;
 use nannou::prelude::*;
+use std::path::Path;
 
-#[derive(Default)]
 pub struct BackgroundManager {
     current_color: Rgb,
     flasher: BackgroundFlash,
     color_fader: BackgroundColorFade,
+
+    image: Option<wgpu::Texture>,
+    image_opacity: f32,
+    stage_size: Vec2, // size of the render texture
+
+    persistence: f32,    // how much of the previous frame remains, 0.0 clears fully
+    last_clear: LinSrgb, // background color left on screen by the previous frame
+
+    transparent: bool,
+}
+
+pub const MAX_PERSISTENCE: f32 = 0.97;
+
+impl Default for BackgroundManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BackgroundManager {
@@ -19,10 +48,62 @@ impl BackgroundManager {
             current_color: rgb(0.0, 0.0, 0.0),
             flasher: BackgroundFlash::default(),
             color_fader: BackgroundColorFade::default(),
+            image: None,
+            image_opacity: 1.0,
+            stage_size: Vec2::ZERO,
+            persistence: 0.0,
+            last_clear: lin_srgb(0.0, 0.0, 0.0),
+            transparent: false,
         }
     }
 
+    pub fn set_stage_size(&mut self, stage_size: Vec2) {
+        self.stage_size = stage_size;
+    }
+
+    // Falls back to the solid color if the image can't be loaded
+    pub fn set_image(&mut self, app: &App, path: &Path) {
+        match wgpu::Texture::from_path(app, path) {
+            Ok(texture) => {
+                self.image = Some(texture);
+            }
+            Err(err) => {
+                println!(
+                    "Warning: couldn't load background image {}: {}. Using the solid color.",
+                    path.display(),
+                    err
+                );
+                self.image = None;
+            }
+        }
+    }
+
+    pub fn clear_image(&mut self) {
+        self.image = None;
+    }
+
+    pub fn set_image_opacity(&mut self, opacity: f32) {
+        self.image_opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    // Trails only apply to the solid color background. With an image loaded
+    // each frame is still cleared.
+    pub fn set_persistence(&mut self, persistence: f32) {
+        self.persistence = persistence.clamp(0.0, MAX_PERSISTENCE);
+    }
+
+    pub fn set_transparent(&mut self, transparent: bool) {
+        self.transparent = transparent;
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.transparent
+    }
+
     pub fn flash(&mut self, flash_color: Rgb, duration: f32, current_time: f32) {
+        if self.transparent {
+            return;
+        }
         if !self.flasher.is_active() {
             self.flasher
                 .start(flash_color, self.current_color, duration, current_time);
@@ -34,6 +115,9 @@ impl BackgroundManager {
     }
 
     pub fn color_fade(&mut self, target_color: Rgb, duration: f32, current_time: f32) {
+        if self.transparent {
+            return;
+        }
         self.color_fader
             .start(self.current_color, target_color, duration, current_time);
     }
@@ -51,9 +135,71 @@ impl BackgroundManager {
         }
     }
 
-    pub fn draw(&mut self, draw: &Draw, current_time: f32) {
+    // Draws the background to the preview, and to the recording when that's
+    // drawn separately at its own resolution
+    pub fn draw(&mut self, draw: &Draw, recording: Option<&Draw>, current_time: f32) {
         self.update_color(current_time);
-        draw.background().color(self.current_color);
+
+        self.paint(draw);
+        match recording {
+            Some(recording) if self.transparent => {
+                recording.background().color(rgba(0.0, 0.0, 0.0, 0.0));
+            }
+            Some(recording) => self.paint(recording),
+            None => {}
+        }
+        if self.image.is_none() {
+            self.last_clear = self.current_color.into_linear();
+        }
+    }
+
+    fn paint(&self, draw: &Draw) {
+        let Some(image) = &self.image else {
+            if self.persistence > 0.0 {
+                self.draw_persistent_clear(draw);
+            } else {
+                draw.background().color(self.current_color);
+            }
+            return;
+        };
+
+        let (w, h) = (self.stage_size.x, self.stage_size.y);
+        draw.background().color(BLACK);
+        draw.texture(image).w_h(w, h);
+        if self.image_opacity < 1.0 {
+            draw.rect()
+                .w_h(w, h)
+                .color(rgba(0.0, 0.0, 0.0, 1.0 - self.image_opacity));
+        }
+        draw.color_blend(BLEND_ADD)
+            .rect()
+            .w_h(w, h)
+            .color(self.current_color);
+    }
+
+    // Blends the previous frame toward the background color. The rect's color
+    // is solved so that bare background lands exactly on the current color,
+    // however far it moved this frame, while anything drawn over it keeps
+    // `persistence` of its difference from the background. That way flashes
+    // and fades still reach their colors. Worked in linear space, where the
+    // blending happens, and may go outside 0..1 on the float render texture.
+    fn draw_persistent_clear(&self, draw: &Draw) {
+        // the draw would otherwise keep clearing with last frame's background
+        draw.reset();
+
+        let alpha = 1.0 - self.persistence;
+        let target: LinSrgb = self.current_color.into_linear();
+        let last = self.last_clear;
+        let solve = |target: f32, last: f32| (target - self.persistence * last) / alpha;
+
+        draw.rect()
+            .w_h(self.stage_size.x, self.stage_size.y)
+            .color(lin_srgba(
+                solve(target.red, last.red),
+                solve(target.green, last.green),
+                solve(target.blue, last.blue),
+                alpha,
+            ));
     }
 
     pub fn get_current_color(&self) -> Rgb {
@@ -0,0 +1,149 @@
+// src/views/grid/grid_array.rs
+//
+// Names and positions for /grid/array, which lays out a lattice of grids in
+// one command and registers them as a group.
+
+use nannou::prelude::*;
+use std::collections::HashMap;
+
+// cols x rows grids named <base_name>_<row>_<col>, counted from 1 with row 1
+// at the top, centered on origin. Spacing is in grid widths and heights, so
+// 1.0 puts neighbours edge to edge. An array is never laid over an existing
+// group of the same name or over a grid that's already there.
+#[allow(clippy::too_many_arguments)]
+pub fn grid_array_layout<G>(
+    base_name: &str,
+    cols: i32,
+    rows: i32,
+    spacing: (f32, f32),
+    origin: (f32, f32),
+    grid_size: Vec2,
+    groups: &HashMap<String, Vec<String>>,
+    grids: &HashMap<String, G>,
+) -> Result<Vec<(String, Point2)>, String> {
+    if cols < 1 || rows < 1 {
+        return Err("/grid/array needs at least one column and row".to_string());
+    }
+    if groups.contains_key(base_name) {
+        return Err(format!("Grid array {} already exists", base_name));
+    }
+
+    let step = vec2(spacing.0, spacing.1) * grid_size;
+    let layout: Vec<(String, Point2)> = (1..=rows)
+        .flat_map(|row| (1..=cols).map(move |col| (row, col)))
+        .map(|(row, col)| {
+            let offset = vec2(
+                (col - 1) as f32 - (cols - 1) as f32 / 2.0,
+                (rows - 1) as f32 / 2.0 - (row - 1) as f32,
+            );
+            let position = pt2(origin.0, origin.1) + offset * step;
+            (format!("{}_{}_{}", base_name, row, col), position)
+        })
+        .collect();
+
+    if let Some((taken, _)) = layout.iter().find(|(name, _)| grids.contains_key(name)) {
+        return Err(format!(
+            "Grid array {} would replace grid {}",
+            base_name, taken
+        ));
+    }
+    Ok(layout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Project;
+    use crate::views::CachedGrid;
+
+    fn register(
+        layout: &[(String, Point2)],
+        base_name: &str,
+        groups: &mut HashMap<String, Vec<String>>,
+        grids: &mut HashMap<String, ()>,
+    ) {
+        let names: Vec<String> = layout.iter().map(|(name, _)| name.clone()).collect();
+        for name in &names {
+            grids.insert(name.clone(), ());
+        }
+        groups.insert(base_name.to_string(), names);
+    }
+
+    #[test]
+    fn test_arrays_are_never_created_twice() {
+        let mut groups = HashMap::new();
+        let mut grids = HashMap::new();
+        let size = vec2(100.0, 50.0);
+
+        let layout =
+            grid_array_layout("wall", 3, 3, (1.0, 1.0), (0.0, 0.0), size, &groups, &grids).unwrap();
+        assert_eq!(layout.len(), 9);
+        register(&layout, "wall", &mut groups, &mut grids);
+
+        // the same base name again
+        assert!(
+            grid_array_layout("wall", 3, 3, (1.0, 1.0), (0.0, 0.0), size, &groups, &grids).is_err()
+        );
+
+        // a different array over a grid created on its own
+        let mut grids = HashMap::new();
+        grids.insert("row_1_2".to_string(), ());
+        assert!(grid_array_layout(
+            "row",
+            3,
+            1,
+            (1.0, 1.0),
+            (0.0, 0.0),
+            size,
+            &HashMap::new(),
+            &grids
+        )
+        .is_err());
+
+        assert!(
+            grid_array_layout("empty", 0, 2, (1.0, 1.0), (0.0, 0.0), size, &groups, &grids)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_unit_spacing_puts_grids_edge_to_edge() {
+        let project = Project::load("projects/debug.json").unwrap();
+        let size = CachedGrid::new(&project).bounding_box().wh();
+        let layout = grid_array_layout::<()>(
+            "wall",
+            3,
+            2,
+            (1.0, 1.0),
+            (10.0, -20.0),
+            size,
+            &HashMap::new(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        let position: HashMap<&str, Point2> = layout
+            .iter()
+            .map(|(name, position)| (name.as_str(), *position))
+            .collect();
+
+        // along a row, and down a column with row 1 on top
+        assert_eq!(
+            position["wall_1_2"] - position["wall_1_1"],
+            vec2(size.x, 0.0)
+        );
+        assert_eq!(
+            position["wall_1_3"] - position["wall_1_2"],
+            vec2(size.x, 0.0)
+        );
+        assert_eq!(
+            position["wall_1_1"] - position["wall_2_1"],
+            vec2(0.0, size.y)
+        );
+
+        // centered on the origin
+        assert_eq!(
+            (position["wall_1_1"] + position["wall_2_3"]) / 2.0,
+            pt2(10.0, -20.0)
+        );
+    }
+}
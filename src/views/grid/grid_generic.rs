@@ -14,16 +14,17 @@
 // for updating its style and drawing itself.
 
 use nannou::prelude::*;
-use std::collections::HashMap;
-use std::time::Instant;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::rc::Rc;
 
 use crate::{
-    models::{EdgeType, PathElement, Project, ViewBox},
+    models::{EdgeType, PathElement, Project, SegmentKey, ViewBox},
     utilities::{
-        easing, grid_utility, segment_utility,
+        easing, grid_utility, segment_analysis, segment_utility,
         svg::{edge_detection, parser},
     },
-    views::Transform2D,
+    views::{SpatialIndex, Transform2D},
 };
 
 // TODO: USE ANIMATION DURATION CONFIG INSTEAD OF THESE CONSTANTS
@@ -39,6 +40,20 @@ pub struct DrawStyle {
     pub stroke_weight: f32,
 }
 
+impl DrawStyle {
+    // Scales the color toward black, leaving alpha alone
+    pub fn dimmed(&self, brightness: f32) -> DrawStyle {
+        let mut color = self.color;
+        color.red *= brightness;
+        color.green *= brightness;
+        color.blue *= brightness;
+        DrawStyle {
+            color,
+            stroke_weight: self.stroke_weight,
+        }
+    }
+}
+
 impl Default for DrawStyle {
     fn default() -> Self {
         Self {
@@ -49,12 +64,150 @@ impl Default for DrawStyle {
     }
 }
 
+// The shape of a segment powering on and off, like a synth's ADSR envelope.
+// Levels run from off (0.0) up to the red flash (1.0), and the glyph color
+// sits at sustain_level. At a sustain_level of 1.0 there's no flash, so a
+// slow attack swells straight into the glyph color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope {
+    pub attack: f32,        // seconds from off up to the peak
+    pub hold: f32,          // seconds held at the peak
+    pub decay: f32,         // seconds from the peak down to the glyph color
+    pub sustain_level: f32, // where the glyph color sits between off and the flash
+    pub release: f32,       // seconds to fade back to the backbone when turned off
+}
+
+impl Envelope {
+    // The original look: an instant flash, held, then eased into the glyph color
+    pub const FLASH: Envelope = Envelope {
+        attack: 0.0,
+        hold: FLASH_DURATION,
+        decay: FLASH_FADE_DURATION,
+        sustain_level: 0.5,
+        release: FADE_DURATION,
+    };
+
+    // Negative times count as zero
+    pub fn new(attack: f32, hold: f32, decay: f32, sustain_level: f32, release: f32) -> Self {
+        Self {
+            attack: attack.max(0.0),
+            hold: hold.max(0.0),
+            decay: decay.max(0.0),
+            sustain_level: sustain_level.clamp(0.0, 1.0),
+            release: release.max(0.0),
+        }
+    }
+
+    pub fn power_on_duration(&self) -> f32 {
+        self.attack + self.hold + self.decay
+    }
+
+    fn peak_color(&self, target: Rgba<f32>) -> Rgba<f32> {
+        if self.sustain_level >= 1.0 {
+            target
+        } else {
+            rgba(1.0, 0.0, 0.0, 1.0)
+        }
+    }
+
+    // Color at `level` on the way up: from `from` to the glyph color, then
+    // on to the flash
+    fn attack_color(&self, level: f32, from: Rgba<f32>, target: Rgba<f32>) -> Rgba<f32> {
+        let sustain = self.sustain_level.clamp(0.0, 1.0);
+        if level <= sustain && sustain > 0.0 {
+            lerp_color(from, target, level / sustain)
+        } else {
+            let peak = self.peak_color(target);
+            lerp_color(target, peak, (level - sustain) / (1.0 - sustain))
+        }
+    }
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self::FLASH
+    }
+}
+
+fn lerp_color(a: Rgba<f32>, b: Rgba<f32>, t: f32) -> Rgba<f32> {
+    rgba(
+        a.red + (b.red - a.red) * t,
+        a.green + (b.green - a.green) * t,
+        a.blue + (b.blue - a.blue) * t,
+        a.alpha + (b.alpha - a.alpha) * t,
+    )
+}
+
+// A cheap bloom for lit segments: each command is drawn again underneath,
+// wider and fainter, `layers` times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlowParams {
+    pub layers: u32,
+    pub spread: f32, // extra width per layer, as a multiple of the stroke weight
+    pub alpha_falloff: f32, // alpha of each layer relative to the one inside it
+}
+
+impl GlowParams {
+    // The halo styles for `style`, outermost first
+    fn layer_styles(&self, style: &DrawStyle) -> impl Iterator<Item = DrawStyle> + '_ {
+        let style = style.clone();
+        (1..=self.layers).rev().map(move |layer| {
+            let mut color = style.color;
+            color.alpha *= self.alpha_falloff.powi(layer as i32);
+            DrawStyle {
+                color,
+                stroke_weight: style.stroke_weight * (1.0 + self.spread * layer as f32),
+            }
+        })
+    }
+}
+
+// A contrasting edge around lit segments so thin strokes hold up when
+// projected: each command is drawn wider in the outline color underneath.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutlineParams {
+    pub color: Rgba<f32>,
+    pub extra_weight: f32, // added to the stroke weight, split across both edges
+}
+
+impl OutlineParams {
+    pub fn style(&self, style: &DrawStyle) -> DrawStyle {
+        DrawStyle {
+            color: self.color,
+            stroke_weight: style.stroke_weight + self.extra_weight,
+        }
+    }
+}
+
 // Which screen layer does the segment need to be drawn to?
-#[derive(Debug, Clone, PartialEq)]
+// Overlay is drawn after every grid's other layers, for things that have to
+// stay on top like chase pulses and markers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Layer {
     Background,
     Middle,
     Foreground,
+    Overlay,
+}
+
+impl Layer {
+    // Drawing order, bottom first
+    pub const ALL: [Layer; 4] = [
+        Layer::Background,
+        Layer::Middle,
+        Layer::Foreground,
+        Layer::Overlay,
+    ];
+
+    // Higher layers are drawn over lower ones
+    pub fn order(self) -> u8 {
+        match self {
+            Layer::Background => 0,
+            Layer::Middle => 1,
+            Layer::Foreground => 2,
+            Layer::Overlay => 3,
+        }
+    }
 }
 
 // These messages tell the segment what to do on the next frame
@@ -72,6 +225,9 @@ pub enum SegmentAction {
 pub struct StyleUpdateMsg {
     pub action: Option<SegmentAction>, // when None, the segment just redraws as the previous frame state
     pub target_style: Option<DrawStyle>,
+    pub delay: Option<f32>, // seconds to wait before turning on takes effect
+    pub envelope: Option<Envelope>, // shape of an On or Off, Envelope::FLASH when None
+    pub layer: Option<Layer>, // where the new state draws, the state's usual layer when None
 }
 
 impl StyleUpdateMsg {
@@ -79,6 +235,9 @@ impl StyleUpdateMsg {
         Self {
             action: Some(action),
             target_style: Some(target_style),
+            delay: None,
+            envelope: None,
+            layer: None,
         }
     }
 }
@@ -102,26 +261,68 @@ pub enum SegmentType {
     ArcTopRight,    // arc-2
     ArcBottomLeft,  // arc-3
     ArcBottomRight, // arc-4
+    DiagonalDown,   // top-left to bottom-right
+    DiagonalUp,     // bottom-left to top-right
     Unknown,
 }
 
+impl SegmentType {
+    pub const ARCS: [SegmentType; 4] = [
+        SegmentType::ArcTopLeft,
+        SegmentType::ArcTopRight,
+        SegmentType::ArcBottomLeft,
+        SegmentType::ArcBottomRight,
+    ];
+    pub const DIAGONALS: [SegmentType; 2] = [SegmentType::DiagonalDown, SegmentType::DiagonalUp];
+
+    // The types a name stands for: one type, or "arc" and "diagonal" for all
+    // of their kind. Arcs go by their SVG ids.
+    pub fn named(name: &str) -> Result<&'static [SegmentType], String> {
+        match name.to_lowercase().as_str() {
+            "horizontal" => Ok(&[SegmentType::Horizontal]),
+            "vertical" => Ok(&[SegmentType::Vertical]),
+            "arc" => Ok(&Self::ARCS),
+            "arc-1" => Ok(&[SegmentType::ArcTopLeft]),
+            "arc-2" => Ok(&[SegmentType::ArcTopRight]),
+            "arc-3" => Ok(&[SegmentType::ArcBottomLeft]),
+            "arc-4" => Ok(&[SegmentType::ArcBottomRight]),
+            "diagonal" => Ok(&Self::DIAGONALS),
+            "diagonal-down" => Ok(&[SegmentType::DiagonalDown]),
+            "diagonal-up" => Ok(&[SegmentType::DiagonalUp]),
+            "unknown" => Ok(&[SegmentType::Unknown]),
+            _ => Err(format!(
+                "Invalid segment type: '{}'. Expected horizontal, vertical, arc, arc-1 to arc-4, \
+                 diagonal, diagonal-down, diagonal-up or unknown",
+                name
+            )),
+        }
+    }
+}
+
+// How far |dx| and |dy| can differ, as a fraction of the longer one, for a
+// line to count as diagonal
+const DIAGONAL_TOLERANCE: f32 = 0.1;
+
 // A CachedSegment is the basic element of a Grid.
 // Acts like a virtual light fixture, and is reponsible for its own drawing.
 // Receives messages from the Grid that dictate its behavior for the next frame.
 pub struct CachedSegment {
     // metadata
-    pub id: String,
+    pub id: SegmentKey,
     pub tile_coordinate: (u32, u32), // which tile in the grid
     pub segment_type: SegmentType,
 
     // state
     pub current_style: DrawStyle, // current display style, here for quick access
     state: Box<dyn SegmentState>, // manages update behavior
+    dirty: bool, // state needs updating next frame: a message arrived or it's animating
 
     // draw instructions cache
-    pub draw_commands: Vec<DrawCommand>, // Nannou draw command
+    pub draw_commands: Vec<DrawCommand>, // in grid-local space, placed by the grid's transform
+    pub offset: Vec2,                    // world-space shift from slides, added after the transform
     pub original_path: PathElement,      // SVG path
     pub edge_type: EdgeType,             // type of edge in the base tile
+    pub tags: Vec<String>,               // the SVG element's classes
 }
 
 impl Clone for CachedSegment {
@@ -132,16 +333,19 @@ impl Clone for CachedSegment {
             segment_type: self.segment_type,
             current_style: self.current_style.clone(),
             state: self.state.clone_box(),
+            dirty: self.dirty,
             draw_commands: self.draw_commands.clone(),
+            offset: self.offset,
             original_path: self.original_path.clone(),
             edge_type: self.edge_type,
+            tags: self.tags.clone(),
         }
     }
 }
 
 impl CachedSegment {
     pub fn new(
-        element_id: String,
+        id: SegmentKey,
         tile_coordinate: (u32, u32),
         path: &PathElement,
         edge_type: EdgeType,
@@ -160,7 +364,15 @@ impl CachedSegment {
             PathElement::Line { x1, y1, x2, y2 } => {
                 let dx = (x2 - x1).abs();
                 let dy = (y2 - y1).abs();
-                if dx > dy {
+                if dx > 0.0 && (dx - dy).abs() <= DIAGONAL_TOLERANCE * dx.max(dy) {
+                    // SVG y grows downward, so x and y growing together is
+                    // a line heading down to the right
+                    if (x2 - x1).signum() == (y2 - y1).signum() {
+                        SegmentType::DiagonalDown
+                    } else {
+                        SegmentType::DiagonalUp
+                    }
+                } else if dx > dy {
                     SegmentType::Horizontal
                 } else {
                     SegmentType::Vertical
@@ -179,7 +391,7 @@ impl CachedSegment {
         };
 
         Self {
-            id: element_id,
+            id,
             tile_coordinate,
             segment_type,
 
@@ -188,36 +400,43 @@ impl CachedSegment {
                 style: DrawStyle::default(),
             }),
             current_style: DrawStyle::default(),
+            dirty: true,
 
             draw_commands,
+            offset: Vec2::ZERO,
             original_path: path.clone(),
             edge_type,
+            tags: Vec::new(),
         }
     }
 
     /**************************  State management *************************************** */
 
     // set up the segment state according to the StyleUpdateMessage in this frame's update batch
-    fn update_segment_state(&mut self, msg: &StyleUpdateMsg) {
+    fn update_segment_state(&mut self, msg: &StyleUpdateMsg, now: f32) {
         match (&msg.action, &msg.target_style) {
             (Some(action), Some(target_style)) => {
                 match action {
                     SegmentAction::On => {
+                        let delay = msg.delay.unwrap_or(0.0).max(0.0);
+
                         // Update the style for active segments
                         let new_state = Box::new(PoweringOnState {
-                            start_time: Instant::now(),
+                            start_time: now + delay,
+                            from_style: self.current_style.clone(),
                             target_style: target_style.clone(),
-                            flash_duration: FLASH_DURATION,
-                            fade_duration: FLASH_FADE_DURATION,
+                            envelope: msg.envelope.unwrap_or_default(),
+                            layer: msg.layer.unwrap_or(Layer::Foreground),
                         });
-                        self.transition_to(new_state);
+                        self.transition_after_delay(new_state, now, delay);
                     }
                     SegmentAction::Off => {
                         let new_state = Box::new(PoweringOffState {
-                            start_time: Instant::now(),
+                            start_time: now,
                             from_style: self.current_style.clone(),
                             target_style: target_style.clone(),
-                            duration: FADE_DURATION,
+                            duration: msg.envelope.unwrap_or_default().release,
+                            layer: msg.layer.unwrap_or(Layer::Middle),
                         });
                         self.transition_to(new_state);
                     }
@@ -231,8 +450,10 @@ impl CachedSegment {
                         // instantly change to target style without animations or effects
                         let new_state = Box::new(ActiveState {
                             style: target_style.clone(),
+                            layer: msg.layer.unwrap_or(Layer::Foreground),
                         });
-                        self.transition_to(new_state);
+                        let delay = msg.delay.unwrap_or(0.0).max(0.0);
+                        self.transition_after_delay(new_state, now, delay);
                     }
                 }
             }
@@ -240,6 +461,7 @@ impl CachedSegment {
                 // Direct style update without action
                 let new_state = Box::new(ActiveState {
                     style: target_style.clone(),
+                    layer: msg.layer.unwrap_or(Layer::Foreground),
                 });
                 self.state = new_state;
             }
@@ -247,20 +469,56 @@ impl CachedSegment {
         }
     }
 
-    fn update_segment_style(&mut self) {
+    fn update_segment_style(&mut self, now: f32) {
         // let the state perform its update for this frame
-        if let Some(new_state) = self.state.update() {
+        if let Some(new_state) = self.state.update(now) {
             self.transition_to(new_state);
         }
 
         // update the current style
-        self.current_style = self.state.calculate_style();
+        self.current_style = self.state.calculate_style(now);
+
+        // idle and active styles hold still until the next message
+        self.dirty = !matches!(
+            self.state.state_type(),
+            SegmentStateType::Idle | SegmentStateType::Active
+        );
+    }
+
+    // Back to how CachedSegment::new left it, idle and where it started
+    fn reset(&mut self) {
+        self.state = Box::new(IdleState {
+            style: DrawStyle::default(),
+        });
+        self.current_style = DrawStyle::default();
+        self.dirty = true;
+        self.offset = Vec2::ZERO;
     }
 
     fn transition_to(&mut self, new_state: Box<dyn SegmentState>) {
         self.state = new_state;
     }
 
+    // Keeps doing whatever the segment was doing until the delay is up
+    fn transition_after_delay(&mut self, new_state: Box<dyn SegmentState>, now: f32, delay: f32) {
+        if delay <= 0.0 {
+            self.transition_to(new_state);
+            return;
+        }
+
+        let current = std::mem::replace(
+            &mut self.state,
+            Box::new(IdleState {
+                style: DrawStyle::default(),
+            }),
+        );
+        self.transition_to(Box::new(DelayedState {
+            current,
+            next: new_state,
+            start_time: now + delay,
+        }));
+    }
+
     /**************************  Transform functions *************************************** */
 
     pub fn apply_transform(&mut self, transform: &Transform2D) {
@@ -269,6 +527,78 @@ impl CachedSegment {
         }
     }
 
+    // Re-tessellates an arc with the points it needs when drawn at
+    // `draw_scale`. The new points are laid out from the original path and
+    // carried over by the map that takes the original points onto the
+    // current ones, so any transform baked into them is kept. Returns
+    // whether the points changed.
+    pub fn fit_arc_resolution(
+        &mut self,
+        viewbox: &ViewBox,
+        grid_dims: (u32, u32),
+        draw_scale: f32,
+    ) -> bool {
+        let Some((sweep, radius)) = segment_utility::arc_extent(&self.original_path) else {
+            return false;
+        };
+        let [DrawCommand::Arc { points }] = self.draw_commands.as_slice() else {
+            return false;
+        };
+        if points.len() < 3 {
+            return false;
+        }
+        let resolution = points.len() - 1;
+
+        // the same points before any transform
+        let tile_transform =
+            segment_utility::calculate_tile_transform(viewbox, self.tile_coordinate, grid_dims);
+        let original = segment_utility::generate_draw_commands_at(
+            &self.original_path,
+            viewbox,
+            &tile_transform,
+            resolution,
+        );
+        let [DrawCommand::Arc { points: original }] = original.as_slice() else {
+            return false;
+        };
+        if original.len() != points.len() {
+            return false;
+        }
+
+        // the start, middle and end of the arc pin down the map
+        let mid = resolution / 2;
+        let from = Mat2::from_cols(
+            original[mid] - original[0],
+            original[resolution] - original[0],
+        );
+        let to = Mat2::from_cols(points[mid] - points[0], points[resolution] - points[0]);
+        if from.determinant().abs() < f32::EPSILON {
+            return false;
+        }
+        let map = to * from.inverse();
+
+        let scale = map.determinant().abs().sqrt();
+        let new_resolution = segment_utility::arc_resolution(radius * scale * draw_scale, sweep);
+        if new_resolution == resolution {
+            return false;
+        }
+
+        let (original_start, start) = (original[0], points[0]);
+        let mut commands = segment_utility::generate_draw_commands_at(
+            &self.original_path,
+            viewbox,
+            &tile_transform,
+            new_resolution,
+        );
+        if let [DrawCommand::Arc { points }] = commands.as_mut_slice() {
+            for point in points.iter_mut() {
+                *point = start + map * (*point - original_start);
+            }
+        }
+        self.draw_commands = commands;
+        true
+    }
+
     fn scale_stroke_weight(&mut self, scale_factor: f32) {
         self.current_style.stroke_weight *= scale_factor;
         self.state.scale_stroke_weight(scale_factor);
@@ -276,6 +606,17 @@ impl CachedSegment {
 
     /************************ Utility Methods ****************************/
 
+    // The smallest rect holding every point of the segment in grid-local
+    // space; CachedGrid::segment_bounds gives where it's drawn. Circles count
+    // their full radius; stroke weight isn't included.
+    pub fn bounding_box(&self) -> Option<Rect> {
+        segment_analysis::command_bounds(&self.draw_commands)
+    }
+
+    pub fn layer(&self) -> Layer {
+        self.state.layer()
+    }
+
     pub fn is_background(&self) -> bool {
         matches!(self.state.layer(), Layer::Background)
     }
@@ -283,17 +624,44 @@ impl CachedSegment {
     pub fn is_idle(&self) -> bool {
         matches!(self.state.state_type(), SegmentStateType::Idle)
     }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.state.state_type(), SegmentStateType::Active)
+    }
+
+    // On screen as part of a glyph, whether still powering on or not
+    pub fn is_lit(&self) -> bool {
+        matches!(
+            self.state.state_type(),
+            SegmentStateType::Active | SegmentStateType::PoweringOn
+        )
+    }
 }
 
 // CachedGrid stores the pre-processed drawing commands for an entire grid
 #[derive(Clone)]
 pub struct CachedGrid {
     pub dimensions: (u32, u32), // number of tiles in x and y
-    pub segments: HashMap<String, CachedSegment>,
+    pub segments: HashMap<SegmentKey, CachedSegment>,
     pub viewbox: ViewBox,
 
     // temporary segments for the stretch effect
-    pub stretch_segments: HashMap<String, CachedSegment>,
+    pub stretch_segments: HashMap<SegmentKey, CachedSegment>,
+
+    // where segments sit in local space, shared by every copy of the grid
+    pub spatial_index: Rc<SpatialIndex>,
+
+    // purged edge segment id -> the id of the duplicate drawn in its place
+    pub aliases: Rc<HashMap<SegmentKey, SegmentKey>>,
+
+    // Places the local segments in the world when they're drawn or
+    // measured, so their points never pick up rounding from moves and turns
+    transform: Transform2D,
+    to_world: Affine2,
+
+    // bounding_box() of the segments as they were last measured. Cleared
+    // whenever they may have moved.
+    bounds: Cell<Option<Rect>>,
 }
 
 impl CachedGrid {
@@ -312,92 +680,266 @@ impl CachedGrid {
             for x in 1..=project.grid_x {
                 for element in &elements {
                     let edge_type = edge_detection::detect_edge_type(&element.path, &viewbox);
-                    let element_id = format!("{},{} : {}", x, y, element.id);
-                    let segment = CachedSegment::new(
-                        element_id.clone(),
+                    let mut segment = CachedSegment::new(
+                        SegmentKey::new((x, y), element.id.as_str()),
                         (x, y),
                         &element.path,
                         edge_type,
                         &viewbox,
                         grid_dims,
                     );
+                    segment.tags = element.tags.clone();
 
                     segments.insert(segment.id.clone(), segment);
                 }
             }
         }
 
-        // Remove overlapping segments
-        // this doesn't work, and slide effects look better without it
-        // so shelving for now
-        //segments = purge_overlapping_segments(segments, project.grid_x, project.grid_y);
+        // Neighboring tiles both have a segment along their shared edge. Only
+        // one is kept, and the other's id becomes an alias for it.
+        let (mut segments, aliases) =
+            purge_overlapping_segments(segments, project.grid_x, project.grid_y);
+        for segment in segments.values_mut() {
+            segment.fit_arc_resolution(&viewbox, grid_dims, 1.0);
+        }
+
+        let spatial_index =
+            SpatialIndex::new(&segments, vec2(viewbox.width, viewbox.height), grid_dims);
 
         Self {
             dimensions: (project.grid_x, project.grid_y),
             segments,
             viewbox,
             stretch_segments: HashMap::new(),
+            spatial_index: Rc::new(spatial_index),
+            aliases: Rc::new(aliases),
+            transform: Transform2D::default(),
+            to_world: Affine2::IDENTITY,
+            bounds: Cell::new(None),
         }
     }
 
     /************************ Rendering ****************************/
 
-    // Draws the grid's current frame state
-    pub fn draw(&self, draw: &Draw) {
-        let mut foreground_segments = Vec::new();
-        let mut middle_segments = Vec::new();
+    // Draws the grid's current frame state, overlay last. Glow only applies
+    // to the foreground and overlay, where lit segments are.
+    // Every segment is drawn through here, so `brightness` (the grid's dimmer
+    // times the master fader) reaches every style whatever set it.
+    // Segments in `hidden_tiles` are skipped, though their states carry on.
+    // The backbone is always drawn here.
+    pub fn draw(
+        &self,
+        draw: &Draw,
+        glow: Option<&GlowParams>,
+        outline: Option<&OutlineParams>,
+        brightness: f32,
+        hidden_tiles: &HashSet<(u32, u32)>,
+    ) {
+        self.draw_below_overlay(draw, glow, outline, brightness, hidden_tiles, true);
+        self.draw_overlay(draw, glow, outline, brightness, hidden_tiles);
+    }
 
-        for segment in self.segments.values() {
-            // draw background layer first, or prepare other layers
+    // Every layer but the overlay, so several grids can be drawn before any
+    // of their overlays. Without `backbone`, idle segments on the background
+    // layer are left out; segments powering off still draw.
+    pub fn draw_below_overlay(
+        &self,
+        draw: &Draw,
+        glow: Option<&GlowParams>,
+        outline: Option<&OutlineParams>,
+        brightness: f32,
+        hidden_tiles: &HashSet<(u32, u32)>,
+        backbone: bool,
+    ) {
+        let brightness = brightness.clamp(0.0, 1.0);
+        if brightness == 0.0 {
+            // blacked out; segment states carry on underneath
+            return;
+        }
 
-            match segment.state.layer() {
-                Layer::Background => {
-                    for command in &segment.draw_commands {
-                        command.draw(draw, &segment.current_style);
-                    }
-                }
-                Layer::Middle => {
-                    middle_segments.push(segment);
-                }
-                Layer::Foreground => {
-                    foreground_segments.push(segment);
-                }
+        let background = backbone
+            .then(|| self.in_layer(Layer::Background, hidden_tiles))
+            .into_iter()
+            .flatten();
+        for segment in background.chain(self.in_layer(Layer::Middle, hidden_tiles)) {
+            let style = segment.current_style.dimmed(brightness);
+            let to_world = self.segment_to_world(segment);
+            for command in &segment.draw_commands {
+                command.draw(draw, &style, &to_world);
             }
         }
 
-        for segment in middle_segments {
-            for command in &segment.draw_commands {
-                command.draw(draw, &segment.current_style);
+        self.draw_lit_layer(
+            draw,
+            Layer::Foreground,
+            glow,
+            outline,
+            brightness,
+            hidden_tiles,
+        );
+    }
+
+    pub fn draw_overlay(
+        &self,
+        draw: &Draw,
+        glow: Option<&GlowParams>,
+        outline: Option<&OutlineParams>,
+        brightness: f32,
+        hidden_tiles: &HashSet<(u32, u32)>,
+    ) {
+        let brightness = brightness.clamp(0.0, 1.0);
+        if brightness > 0.0 {
+            self.draw_lit_layer(
+                draw,
+                Layer::Overlay,
+                glow,
+                outline,
+                brightness,
+                hidden_tiles,
+            );
+        }
+    }
+
+    // One pass over the segments per layer, so nothing is collected along
+    // the way. The map doesn't change while drawing, so every pass visits
+    // the segments in the same order.
+    fn in_layer<'a>(
+        &'a self,
+        layer: Layer,
+        hidden_tiles: &'a HashSet<(u32, u32)>,
+    ) -> impl Iterator<Item = &'a CachedSegment> + 'a {
+        self.segments
+            .values()
+            .filter(move |segment| segment.state.layer() == layer)
+            .filter(|segment| !hidden_tiles.contains(&segment.tile_coordinate))
+    }
+
+    fn draw_lit_layer(
+        &self,
+        draw: &Draw,
+        layer: Layer,
+        glow: Option<&GlowParams>,
+        outline: Option<&OutlineParams>,
+        brightness: f32,
+        hidden_tiles: &HashSet<(u32, u32)>,
+    ) {
+        // every outline goes down before any core stroke, so none can cover one
+        if let Some(outline) = outline {
+            for segment in self.in_layer(layer, hidden_tiles) {
+                let style = outline.style(&segment.current_style).dimmed(brightness);
+                let to_world = self.segment_to_world(segment);
+                for command in &segment.draw_commands {
+                    command.draw(draw, &style, &to_world);
+                }
             }
         }
 
-        for segment in foreground_segments {
+        for segment in self.in_layer(layer, hidden_tiles) {
+            let style = segment.current_style.dimmed(brightness);
+            let to_world = self.segment_to_world(segment);
+            if let Some(glow) = glow {
+                for glow_style in glow.layer_styles(&style) {
+                    for command in &segment.draw_commands {
+                        command.draw(draw, &glow_style, &to_world);
+                    }
+                }
+            }
             for command in &segment.draw_commands {
-                command.draw(draw, &segment.current_style);
+                command.draw(draw, &style, &to_world);
             }
         }
     }
 
-    pub fn apply_updates(&mut self, update_batch: &HashMap<String, StyleUpdateMsg>) {
+    // `now` is the owning grid's segment clock, which stops while its
+    // transitions are paused so that state animations freeze in place.
+    // Segments with no message that have settled are left as they are.
+    // Staged stroke weights are multiplied by their segment type's entry in
+    // `type_weights`, if it has one.
+    pub fn apply_updates(
+        &mut self,
+        update_batch: &HashMap<SegmentKey, StyleUpdateMsg>,
+        now: f32,
+        type_weights: &HashMap<SegmentType, f32>,
+    ) {
         for segment in self.segments.values_mut() {
             // process update message
             if let Some(msg) = update_batch.get(&segment.id) {
-                segment.update_segment_state(msg);
+                match type_weights.get(&segment.segment_type) {
+                    Some(&weight) if weight != 1.0 && msg.target_style.is_some() => {
+                        let mut msg = msg.clone();
+                        if let Some(style) = &mut msg.target_style {
+                            style.stroke_weight *= weight;
+                        }
+                        segment.update_segment_state(&msg, now);
+                    }
+                    _ => segment.update_segment_state(msg, now),
+                }
+                segment.dirty = true;
             }
 
             // update segment style
-            segment.update_segment_style();
+            if segment.dirty {
+                segment.update_segment_style(now);
+            }
         }
     }
 
     /************************ Transform Methods **************************/
 
+    // Where the grid is drawn. The segments' points stay as they are; this
+    // is applied to them on the way out.
+    pub fn set_transform(&mut self, transform: Transform2D) {
+        self.to_world = transform.to_affine();
+        self.transform = transform;
+        self.bounds.set(None);
+    }
+
+    pub fn transform(&self) -> &Transform2D {
+        &self.transform
+    }
+
+    // Takes a segment's points to where they're drawn: the grid's transform,
+    // then the segment's own offset
+    pub fn segment_to_world(&self, segment: &CachedSegment) -> Affine2 {
+        Affine2::from_translation(segment.offset) * self.to_world
+    }
+
+    // A segment's draw commands where they're drawn
+    pub fn world_commands(&self, segment: &CachedSegment) -> Vec<DrawCommand> {
+        let to_world = self.segment_to_world(segment);
+        segment
+            .draw_commands
+            .iter()
+            .map(|command| command.transformed(&to_world))
+            .collect()
+    }
+
+    // World-space bounds of one segment, as CachedSegment::bounding_box is
+    // locally
+    pub fn segment_bounds(&self, segment: &CachedSegment) -> Option<Rect> {
+        segment_analysis::command_bounds(&self.world_commands(segment))
+    }
+
+    // Bakes `transform` into the segments' points, e.g. to build a graph at
+    // another size. Instances move with set_transform instead.
     pub fn apply_transform(&mut self, transform: &Transform2D) {
+        self.bounds.set(None);
         for segment in self.segments.values_mut() {
             segment.apply_transform(transform);
         }
     }
 
+    // Re-tessellates arcs whose size has moved them to another resolution,
+    // e.g. after scaling
+    pub fn fit_arc_resolution(&mut self) {
+        let draw_scale = self.transform.mean_scale();
+        for segment in self.segments.values_mut() {
+            if segment.fit_arc_resolution(&self.viewbox, self.dimensions, draw_scale) {
+                self.bounds.set(None);
+            }
+        }
+    }
+
     pub fn scale_stroke_weights(&mut self, scale_factor: f32) {
         for segment in self.segments.values_mut() {
             segment.scale_stroke_weight(scale_factor);
@@ -413,9 +955,56 @@ impl CachedGrid {
             .filter(move |segment| segment.tile_coordinate == (x, y))
     }
 
-    // returns a segment reference by ID
-    pub fn segment(&self, id: &str) -> Option<&CachedSegment> {
-        self.segments.get(id)
+    // returns a segment reference by ID, stretch segments and aliases included
+    pub fn segment(&self, id: &SegmentKey) -> Option<&CachedSegment> {
+        self.segments
+            .get(self.resolve_id(id))
+            .or_else(|| self.stretch_segments.get(id))
+    }
+
+    // The id a segment is kept under. Only differs for an edge segment that
+    // was purged as a duplicate of its neighbor's.
+    pub fn resolve_id<'a>(&'a self, id: &'a SegmentKey) -> &'a SegmentKey {
+        self.aliases.get(id).unwrap_or(id)
+    }
+
+    // The key for a segment id in the string form projects and OSC use,
+    // aliases resolved. None for ids that don't parse.
+    pub fn key(&self, id: &str) -> Option<SegmentKey> {
+        let key: SegmentKey = id.parse().ok()?;
+        Some(self.resolve_id(&key).clone())
+    }
+
+    // Every segment with `tag` among its SVG classes
+    pub fn tagged<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a CachedSegment> + 'a {
+        self.segments
+            .values()
+            .filter(move |segment| segment.tags.iter().any(|t| t == tag))
+    }
+
+    // How many segments carry each tag, by tag name
+    pub fn tag_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for tag in self.segments.values().flat_map(|segment| &segment.tags) {
+            *counts.entry(tag.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    // World-space bounds of every segment, not counting stretch segments.
+    // A grid with no segments gives an empty rect at the origin.
+    pub fn bounding_box(&self) -> Rect {
+        if let Some(bounds) = self.bounds.get() {
+            return bounds;
+        }
+        let bounds = self
+            .segments
+            .values()
+            .filter_map(|segment| self.segment_bounds(segment))
+            .reduce(Rect::max)
+            .unwrap_or_else(|| Rect::from_w_h(0.0, 0.0));
+        self.bounds.set(Some(bounds));
+        bounds
     }
 
     // returns the segments of a given row
@@ -425,6 +1014,8 @@ impl CachedGrid {
             return Vec::new();
         }
         let index = number as u32;
+        // the caller is free to move them
+        self.bounds.set(None);
 
         self.segments
             .values_mut()
@@ -439,6 +1030,7 @@ impl CachedGrid {
             return Vec::new();
         }
         let index = number as u32;
+        self.bounds.set(None);
 
         self.segments
             .values_mut()
@@ -451,10 +1043,27 @@ impl CachedGrid {
         self.stretch_segments.insert(segment.id.clone(), segment);
     }
 
-    pub fn remove_stretch_segment(&mut self, id: &str) {
+    pub fn remove_stretch_segment(&mut self, id: &SegmentKey) {
         self.stretch_segments.remove(id);
     }
 
+    /************************ Reuse ****************************/
+
+    // Puts every segment back as the base grid has it, so a grid a
+    // GridInstance is done with can be handed to another
+    pub fn reset(&mut self) {
+        for segment in self.segments.values_mut() {
+            segment.reset();
+        }
+        self.stretch_segments.clear();
+        // arcs only need re-tessellating if they were drawn at another size
+        let rescaled = self.transform.mean_scale() != 1.0;
+        self.set_transform(Transform2D::default());
+        if rescaled {
+            self.fit_arc_resolution();
+        }
+    }
+
     /************************ Validation ****************************/
 
     pub fn validate_segment_points(&self) -> bool {
@@ -516,37 +1125,59 @@ impl DrawCommand {
             }
             DrawCommand::Circle { center, radius, .. } => {
                 *center = transform.apply_to_point(*center);
-                *radius *= transform.scale;
+                *radius *= transform.mean_scale();
             }
         }
     }
 
-    fn draw(&self, draw: &Draw, style: &DrawStyle) {
+    // The command with `to_world` applied. Circles scale their radius by
+    // the map's mean scale.
+    pub fn transformed(&self, to_world: &Affine2) -> DrawCommand {
+        match self {
+            DrawCommand::Line { start, end } => DrawCommand::Line {
+                start: to_world.transform_point2(*start),
+                end: to_world.transform_point2(*end),
+            },
+            DrawCommand::Arc { points } => DrawCommand::Arc {
+                points: points
+                    .iter()
+                    .map(|point| to_world.transform_point2(*point))
+                    .collect(),
+            },
+            DrawCommand::Circle { center, radius } => DrawCommand::Circle {
+                center: to_world.transform_point2(*center),
+                radius: radius * to_world.matrix2.determinant().abs().sqrt(),
+            },
+        }
+    }
+
+    // Draws the command placed by `to_world`, transforming points on the way
+    // rather than collecting them
+    fn draw(&self, draw: &Draw, style: &DrawStyle, to_world: &Affine2) {
         match self {
             DrawCommand::Line { start, end, .. } => {
                 draw.line()
-                    .start(*start)
-                    .end(*end)
+                    .start(to_world.transform_point2(*start))
+                    .end(to_world.transform_point2(*end))
                     .stroke_weight(style.stroke_weight)
                     .color(style.color)
                     .caps_round();
             }
             DrawCommand::Arc { points, .. } => {
-                for window in points.windows(2) {
-                    if let [p1, p2] = window {
-                        draw.line()
-                            .start(*p1)
-                            .end(*p2)
-                            .stroke_weight(style.stroke_weight)
-                            .color(style.color)
-                            .caps_round();
-                    }
-                }
+                // one stroked path, so the pieces meet in round joins rather
+                // than overlapping caps
+                draw.polyline()
+                    .weight(style.stroke_weight)
+                    .join_round()
+                    .caps_round()
+                    .color(style.color)
+                    .points(points.iter().map(|point| to_world.transform_point2(*point)));
             }
             DrawCommand::Circle { center, radius, .. } => {
+                let center = to_world.transform_point2(*center);
                 draw.ellipse()
                     .x_y(center.x, center.y)
-                    .radius(*radius)
+                    .radius(radius * to_world.matrix2.determinant().abs().sqrt())
                     .stroke(style.color)
                     .stroke_weight(style.stroke_weight)
                     .color(style.color)
@@ -557,12 +1188,12 @@ impl DrawCommand {
 }
 
 // SegmentState manages the current and future styles of a segment based on what it's
-// supposed to be doing at any given time
+// supposed to be doing at any given time. `now` is the grid's segment clock in seconds.
 pub trait SegmentState {
     fn state_type(&self) -> SegmentStateType;
-    fn update(&self) -> Option<Box<dyn SegmentState>>;
+    fn update(&self, now: f32) -> Option<Box<dyn SegmentState>>;
     fn layer(&self) -> Layer;
-    fn calculate_style(&self) -> DrawStyle;
+    fn calculate_style(&self, now: f32) -> DrawStyle;
     fn scale_stroke_weight(&mut self, scale_factor: f32);
     fn clone_box(&self) -> Box<dyn SegmentState>;
 }
@@ -577,7 +1208,7 @@ impl SegmentState for IdleState {
         SegmentStateType::Idle
     }
 
-    fn update(&self) -> Option<Box<dyn SegmentState>> {
+    fn update(&self, _now: f32) -> Option<Box<dyn SegmentState>> {
         // An idle segment doesn't need to be updated
         None
     }
@@ -586,7 +1217,7 @@ impl SegmentState for IdleState {
         Layer::Background
     }
 
-    fn calculate_style(&self) -> DrawStyle {
+    fn calculate_style(&self, _now: f32) -> DrawStyle {
         // An idle segment doesn't need to update its style
         self.style.clone()
     }
@@ -603,6 +1234,7 @@ impl SegmentState for IdleState {
 #[derive(Debug, Clone)]
 pub struct ActiveState {
     style: DrawStyle,
+    layer: Layer,
 }
 
 impl SegmentState for ActiveState {
@@ -610,16 +1242,16 @@ impl SegmentState for ActiveState {
         SegmentStateType::Active
     }
 
-    fn update(&self) -> Option<Box<dyn SegmentState>> {
+    fn update(&self, _now: f32) -> Option<Box<dyn SegmentState>> {
         // An idle segment doesn't need to be updated
         None
     }
 
     fn layer(&self) -> Layer {
-        Layer::Foreground
+        self.layer
     }
 
-    fn calculate_style(&self) -> DrawStyle {
+    fn calculate_style(&self, _now: f32) -> DrawStyle {
         self.style.clone()
     }
 
@@ -634,10 +1266,11 @@ impl SegmentState for ActiveState {
 
 #[derive(Debug, Clone)]
 pub struct PoweringOnState {
+    from_style: DrawStyle,
     target_style: DrawStyle,
-    start_time: Instant,
-    flash_duration: f32,
-    fade_duration: f32,
+    start_time: f32,
+    envelope: Envelope,
+    layer: Layer,
 }
 
 impl SegmentState for PoweringOnState {
@@ -645,12 +1278,13 @@ impl SegmentState for PoweringOnState {
         SegmentStateType::PoweringOn
     }
 
-    fn update(&self) -> Option<Box<dyn SegmentState>> {
-        let elapsed = self.start_time.elapsed().as_secs_f32();
-        if elapsed >= self.flash_duration + self.fade_duration {
+    fn update(&self, now: f32) -> Option<Box<dyn SegmentState>> {
+        let elapsed = now - self.start_time;
+        if elapsed >= self.envelope.power_on_duration() {
             // Change to active state
             Some(Box::new(ActiveState {
                 style: self.target_style.clone(),
+                layer: self.layer,
             }))
         } else {
             None
@@ -658,35 +1292,41 @@ impl SegmentState for PoweringOnState {
     }
 
     fn layer(&self) -> Layer {
-        Layer::Foreground
+        self.layer
     }
 
-    fn calculate_style(&self) -> DrawStyle {
-        let elapsed = self.start_time.elapsed().as_secs_f32();
-        if elapsed <= self.flash_duration {
-            // Flash phase
-            DrawStyle {
-                color: rgba(1.0, 0.0, 0.0, 1.0),
-                stroke_weight: self.target_style.stroke_weight,
-            }
+    fn calculate_style(&self, now: f32) -> DrawStyle {
+        let elapsed = now - self.start_time;
+        let envelope = &self.envelope;
+        let target = self.target_style.color;
+        let peak = envelope.peak_color(target);
+
+        let color = if elapsed < envelope.attack {
+            // Attack phase
+            let level = (elapsed / envelope.attack).max(0.0);
+            envelope.attack_color(level, self.from_style.color, target)
+        } else if elapsed <= envelope.attack + envelope.hold {
+            // Hold phase, the flash unless sustain_level is 1.0
+            peak
         } else {
-            // Fade phase
-            let fade_progress = (elapsed - self.flash_duration) / self.fade_duration;
-            let flash_color = rgba(1.0, 0.0, 0.0, 1.0);
+            // Decay phase
+            let decay_elapsed = elapsed - envelope.attack - envelope.hold;
+            let fade_progress = if envelope.decay > 0.0 {
+                (decay_elapsed / envelope.decay).min(1.0)
+            } else {
+                1.0
+            };
+            easing::color_exp_ease(peak, target, fade_progress, 6.0)
+        };
 
-            DrawStyle {
-                color: easing::color_exp_ease(
-                    flash_color,
-                    self.target_style.color,
-                    fade_progress,
-                    6.0,
-                ),
-                stroke_weight: self.target_style.stroke_weight,
-            }
+        DrawStyle {
+            color,
+            stroke_weight: self.target_style.stroke_weight,
         }
     }
 
     fn scale_stroke_weight(&mut self, scale_factor: f32) {
+        self.from_style.stroke_weight *= scale_factor;
         self.target_style.stroke_weight *= scale_factor;
     }
 
@@ -695,12 +1335,63 @@ impl SegmentState for PoweringOnState {
     }
 }
 
+// Holds a segment in its current state until the next one is due to start.
+// Counts as powering on, so nothing else claims the segment in the meantime.
+pub struct DelayedState {
+    current: Box<dyn SegmentState>,
+    next: Box<dyn SegmentState>,
+    start_time: f32,
+}
+
+impl SegmentState for DelayedState {
+    fn state_type(&self) -> SegmentStateType {
+        SegmentStateType::PoweringOn
+    }
+
+    fn update(&self, now: f32) -> Option<Box<dyn SegmentState>> {
+        if now >= self.start_time {
+            return Some(self.next.clone_box());
+        }
+
+        // the current state keeps running, e.g. a power off fade
+        self.current.update(now).map(|current| {
+            Box::new(DelayedState {
+                current,
+                next: self.next.clone_box(),
+                start_time: self.start_time,
+            }) as Box<dyn SegmentState>
+        })
+    }
+
+    fn layer(&self) -> Layer {
+        self.current.layer()
+    }
+
+    fn calculate_style(&self, now: f32) -> DrawStyle {
+        self.current.calculate_style(now)
+    }
+
+    fn scale_stroke_weight(&mut self, scale_factor: f32) {
+        self.current.scale_stroke_weight(scale_factor);
+        self.next.scale_stroke_weight(scale_factor);
+    }
+
+    fn clone_box(&self) -> Box<dyn SegmentState> {
+        Box::new(DelayedState {
+            current: self.current.clone_box(),
+            next: self.next.clone_box(),
+            start_time: self.start_time,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PoweringOffState {
     target_style: DrawStyle,
     from_style: DrawStyle,
-    start_time: Instant,
+    start_time: f32,
     duration: f32,
+    layer: Layer,
 }
 
 impl SegmentState for PoweringOffState {
@@ -708,8 +1399,8 @@ impl SegmentState for PoweringOffState {
         SegmentStateType::PoweringOff
     }
 
-    fn update(&self) -> Option<Box<dyn SegmentState>> {
-        let elapsed = self.start_time.elapsed().as_secs_f32();
+    fn update(&self, now: f32) -> Option<Box<dyn SegmentState>> {
+        let elapsed = now - self.start_time;
         if elapsed >= self.duration {
             // Change to idle state
             Some(Box::new(IdleState {
@@ -721,12 +1412,12 @@ impl SegmentState for PoweringOffState {
     }
 
     fn layer(&self) -> Layer {
-        Layer::Middle
+        self.layer
     }
 
-    fn calculate_style(&self) -> DrawStyle {
-        let elapsed = self.start_time.elapsed().as_secs_f32();
-        if elapsed <= self.duration {
+    fn calculate_style(&self, now: f32) -> DrawStyle {
+        let elapsed = now - self.start_time;
+        if elapsed < self.duration {
             // Fade phase
             let fade_progress = elapsed / self.duration;
 
@@ -759,14 +1450,18 @@ impl SegmentState for PoweringOffState {
 // Unlike Glyphmaker, where we draw all elements and then handle selection logic,
 // in Glyphvis we decide on whether to draw an element at the beginning.
 //
-// This function doesn't work! Run grid.slide() to see the problem.
-// But we decided not to use it because grid.slide looks better without purging.
-fn _purge_overlapping_segments(
-    segments: HashMap<String, CachedSegment>,
+// Of two edge segments that overlap, the one in the tile further left, or
+// further up in the same column, is kept. Returns the kept segments and, for
+// each one purged, the id of the segment kept in its place.
+fn purge_overlapping_segments(
+    segments: HashMap<SegmentKey, CachedSegment>,
     grid_width: u32,
     grid_height: u32,
-) -> HashMap<String, CachedSegment> {
-    let mut final_segments = HashMap::new();
+) -> (
+    HashMap<SegmentKey, CachedSegment>,
+    HashMap<SegmentKey, SegmentKey>,
+) {
+    let mut aliases = HashMap::new();
 
     // Group segments by position for easier overlap checking
     let mut segments_by_pos: HashMap<(u32, u32), Vec<&CachedSegment>> = HashMap::new();
@@ -777,73 +1472,44 @@ fn _purge_overlapping_segments(
             .push(segment);
     }
 
-    // Check each segment against its potential neighbors
+    // Check each edge segment against its neighbor tile, if that has priority
     for segment in segments.values() {
-        // Skip if it's not an edge
-        if segment.edge_type == EdgeType::None {
-            final_segments.insert(
-                segment.id.clone(),
-                segments.get(&segment.id).unwrap().clone(),
-            );
-            continue;
-        }
-
-        // Get potential neighbors based on edge type
-        if let Some((neighbor_x, neighbor_y)) = grid_utility::get_neighbor_coords(
+        let Some((neighbor_x, neighbor_y)) = grid_utility::get_neighbor_coords(
             segment.tile_coordinate.0,
             segment.tile_coordinate.1,
             segment.edge_type,
             grid_width,
             grid_height,
-        ) {
-            // check if neighbor has priority
-            let neighbor_has_priority = neighbor_x < segment.tile_coordinate.0
-                || (neighbor_x == segment.tile_coordinate.1
-                    && neighbor_y < segment.tile_coordinate.1);
-
-            if neighbor_has_priority {
-                // Look for matching segments at neighbor position
-                if let Some(neighbor_segments) = segments_by_pos.get(&(neighbor_x, neighbor_y)) {
-                    let mut should_keep = true;
-
-                    for neighbor in neighbor_segments {
-                        let direction = grid_utility::get_neighbor_direction(
-                            segment.tile_coordinate.0,
-                            segment.tile_coordinate.1,
-                            neighbor_x,
-                            neighbor_y,
-                        );
-
-                        if grid_utility::check_segment_alignment(segment, neighbor, direction) {
-                            should_keep = false;
-                            break;
-                        }
-                    }
+        ) else {
+            continue;
+        };
+        let neighbor_has_priority = neighbor_x < segment.tile_coordinate.0
+            || (neighbor_x == segment.tile_coordinate.0 && neighbor_y < segment.tile_coordinate.1);
+        if !neighbor_has_priority {
+            continue;
+        }
 
-                    if should_keep {
-                        final_segments.insert(
-                            segment.id.clone(),
-                            segments.get(&segment.id).unwrap().clone(),
-                        );
-                    }
-                }
-            } else {
-                // This segment has priority, keep it
-                final_segments.insert(
-                    segment.id.clone(),
-                    segments.get(&segment.id).unwrap().clone(),
-                );
-            }
-        } else {
-            // No valid neighbor position, keep the segment
-            final_segments.insert(
-                segment.id.clone(),
-                segments.get(&segment.id).unwrap().clone(),
-            );
+        let direction = grid_utility::get_neighbor_direction(
+            segment.tile_coordinate.0,
+            segment.tile_coordinate.1,
+            neighbor_x,
+            neighbor_y,
+        );
+        let duplicate = segments_by_pos
+            .get(&(neighbor_x, neighbor_y))
+            .into_iter()
+            .flatten()
+            .find(|neighbor| grid_utility::check_segment_alignment(segment, neighbor, direction));
+        if let Some(duplicate) = duplicate {
+            aliases.insert(segment.id.clone(), duplicate.id.clone());
         }
     }
-    // return:
-    final_segments
+
+    let final_segments = segments
+        .into_iter()
+        .filter(|(id, _)| !aliases.contains_key(id))
+        .collect();
+    (final_segments, aliases)
 }
 
 #[cfg(test)]
@@ -869,7 +1535,7 @@ mod tests {
         fn test_draw_command_transform() {
             let transform = Transform2D {
                 translation: Vec2::new(10.0, 10.0),
-                scale: 2.0,
+                scale: Vec2::splat(2.0),
                 rotation: 0.0,
             };
 
@@ -917,7 +1583,7 @@ mod tests {
             };
 
             let segment = CachedSegment::new(
-                "test".to_string(),
+                SegmentKey::off_grid("test"),
                 (1, 1),
                 &path,
                 EdgeType::None,
@@ -931,6 +1597,43 @@ mod tests {
             assert!(!segment.draw_commands.is_empty());
         }
 
+        #[test]
+        fn test_line_types() {
+            let viewbox = create_test_viewbox();
+            let segment_type = |x1, y1, x2, y2| {
+                CachedSegment::new(
+                    SegmentKey::off_grid("test"),
+                    (1, 1),
+                    &PathElement::Line { x1, y1, x2, y2 },
+                    EdgeType::None,
+                    &viewbox,
+                    TEST_GRID_DIMS,
+                )
+                .segment_type
+            };
+
+            assert_eq!(segment_type(0.0, 0.0, 50.0, 0.0), SegmentType::Horizontal);
+            assert_eq!(segment_type(0.0, 0.0, 0.0, 50.0), SegmentType::Vertical);
+            // y grows downward in the SVG, whichever end the path starts at
+            assert_eq!(
+                segment_type(0.0, 0.0, 50.0, 50.0),
+                SegmentType::DiagonalDown
+            );
+            assert_eq!(
+                segment_type(50.0, 50.0, 0.0, 0.0),
+                SegmentType::DiagonalDown
+            );
+            assert_eq!(segment_type(0.0, 50.0, 50.0, 0.0), SegmentType::DiagonalUp);
+            assert_eq!(segment_type(50.0, 0.0, 0.0, 50.0), SegmentType::DiagonalUp);
+            // close enough to 45 degrees, but not a steep or shallow line
+            assert_eq!(
+                segment_type(0.0, 0.0, 100.0, 95.0),
+                SegmentType::DiagonalDown
+            );
+            assert_eq!(segment_type(0.0, 0.0, 100.0, 80.0), SegmentType::Horizontal);
+            assert_eq!(segment_type(0.0, 0.0, 80.0, 100.0), SegmentType::Vertical);
+        }
+
         #[test]
         fn test_coordinate_transformation() {
             let viewbox = create_test_viewbox();
@@ -943,7 +1646,7 @@ mod tests {
             };
 
             let segment = CachedSegment::new(
-                "center_test".to_string(),
+                SegmentKey::off_grid("center_test"),
                 (1, 1),
                 &path,
                 EdgeType::None,
@@ -961,6 +1664,199 @@ mod tests {
                 _ => panic!("Expected Circle"),
             }
         }
+
+        #[test]
+        fn test_power_on_at_simulated_times() {
+            let viewbox = create_test_viewbox();
+            let path = PathElement::Line {
+                x1: 0.0,
+                y1: 0.0,
+                x2: 10.0,
+                y2: 10.0,
+            };
+            let mut segment = CachedSegment::new(
+                SegmentKey::off_grid("test"),
+                (1, 1),
+                &path,
+                EdgeType::None,
+                &viewbox,
+                TEST_GRID_DIMS,
+            );
+
+            let mut clock = crate::services::Clock::fixed(0.1);
+            clock.tick(0.0);
+            let msg = StyleUpdateMsg::new(SegmentAction::On, DrawStyle::default());
+            segment.update_segment_state(&msg, clock.time());
+            segment.update_segment_style(clock.time());
+            assert!(segment.is_lit() && !segment.is_active());
+
+            // flash and fade together take 0.264s
+            clock.tick(0.0);
+            clock.tick(0.0);
+            segment.update_segment_style(clock.time());
+            assert!(!segment.is_active());
+
+            clock.tick(0.0);
+            segment.update_segment_style(clock.time());
+            assert!(segment.is_active());
+        }
+
+        #[test]
+        fn test_overlay_segments_stay_on_the_overlay() {
+            let viewbox = create_test_viewbox();
+            let path = PathElement::Line {
+                x1: 0.0,
+                y1: 0.0,
+                x2: 10.0,
+                y2: 10.0,
+            };
+            let mut segment = CachedSegment::new(
+                SegmentKey::off_grid("test"),
+                (1, 1),
+                &path,
+                EdgeType::None,
+                &viewbox,
+                TEST_GRID_DIMS,
+            );
+            assert_eq!(segment.layer(), Layer::Background);
+
+            let overlay = |action| StyleUpdateMsg {
+                layer: Some(Layer::Overlay),
+                ..StyleUpdateMsg::new(action, DrawStyle::default())
+            };
+            segment.update_segment_state(&overlay(SegmentAction::On), 0.0);
+            assert_eq!(segment.layer(), Layer::Overlay);
+            segment.update_segment_style(1.0);
+            assert!(segment.is_active());
+            assert_eq!(segment.layer(), Layer::Overlay);
+
+            segment.update_segment_state(&overlay(SegmentAction::Off), 1.0);
+            assert_eq!(segment.layer(), Layer::Overlay);
+            segment.update_segment_style(2.0);
+            assert_eq!(segment.layer(), Layer::Background);
+
+            // unmarked messages keep the usual layers
+            let msg = StyleUpdateMsg::new(SegmentAction::On, DrawStyle::default());
+            segment.update_segment_state(&msg, 2.0);
+            assert_eq!(segment.layer(), Layer::Foreground);
+            let msg = StyleUpdateMsg::new(SegmentAction::Off, DrawStyle::default());
+            segment.update_segment_state(&msg, 3.0);
+            assert_eq!(segment.layer(), Layer::Middle);
+
+            assert!(Layer::ALL.windows(2).all(|w| w[0].order() < w[1].order()));
+        }
+    }
+
+    mod envelope_tests {
+        use super::*;
+
+        fn test_segment() -> CachedSegment {
+            let path = PathElement::Line {
+                x1: 0.0,
+                y1: 0.0,
+                x2: 10.0,
+                y2: 10.0,
+            };
+            CachedSegment::new(
+                SegmentKey::off_grid("test"),
+                (1, 1),
+                &path,
+                EdgeType::None,
+                &create_test_viewbox(),
+                TEST_GRID_DIMS,
+            )
+        }
+
+        fn style_at(segment: &mut CachedSegment, time: f32) -> Rgba<f32> {
+            segment.update_segment_style(time);
+            segment.current_style.color
+        }
+
+        fn assert_color_eq(a: Rgba<f32>, b: Rgba<f32>) {
+            let channels = [
+                (a.red, b.red),
+                (a.green, b.green),
+                (a.blue, b.blue),
+                (a.alpha, b.alpha),
+            ];
+            for (x, y) in channels {
+                assert!((x - y).abs() < 1e-4, "{:?} != {:?}", a, b);
+            }
+        }
+
+        fn power_on(segment: &mut CachedSegment, target: Rgba<f32>, envelope: Envelope) {
+            let mut msg = StyleUpdateMsg::new(
+                SegmentAction::On,
+                DrawStyle {
+                    color: target,
+                    stroke_weight: 5.0,
+                },
+            );
+            msg.envelope = Some(envelope);
+            segment.update_segment_state(&msg, 0.0);
+        }
+
+        #[test]
+        fn test_flash_preset_matches_original_power_on() {
+            let blue = rgba(0.0, 0.0, 1.0, 1.0);
+            let red = rgba(1.0, 0.0, 0.0, 1.0);
+            let mut segment = test_segment();
+            power_on(&mut segment, blue, Envelope::FLASH);
+
+            // held flash, then the same eased fade as before
+            assert_color_eq(style_at(&mut segment, 0.1), red);
+            assert_color_eq(
+                style_at(&mut segment, 0.2),
+                easing::color_exp_ease(red, blue, (0.2 - 0.132) / 0.132, 6.0),
+            );
+            assert_color_eq(style_at(&mut segment, 0.3), blue);
+            assert!(segment.is_active());
+        }
+
+        #[test]
+        fn test_envelope_levels_at_key_times() {
+            let from = DrawStyle::default().color;
+            let blue = rgba(0.0, 0.0, 1.0, 1.0);
+            let red = rgba(1.0, 0.0, 0.0, 1.0);
+
+            // a slow swell with no flash
+            let mut segment = test_segment();
+            power_on(&mut segment, blue, Envelope::new(1.0, 0.0, 0.0, 1.0, 2.0));
+            assert_color_eq(style_at(&mut segment, 0.5), lerp_color(from, blue, 0.5));
+            assert!(!segment.is_active());
+            assert_color_eq(style_at(&mut segment, 1.0), blue);
+            assert!(segment.is_active());
+
+            // the glyph color halfway up the attack, the flash at the top
+            let mut segment = test_segment();
+            power_on(&mut segment, blue, Envelope::new(1.0, 0.5, 1.0, 0.5, 2.0));
+            assert_color_eq(style_at(&mut segment, 0.25), lerp_color(from, blue, 0.5));
+            assert_color_eq(style_at(&mut segment, 0.5), blue);
+            assert_color_eq(style_at(&mut segment, 0.75), lerp_color(blue, red, 0.5));
+            assert_color_eq(style_at(&mut segment, 1.25), red);
+            assert_color_eq(
+                style_at(&mut segment, 2.0),
+                easing::color_exp_ease(red, blue, 0.5, 6.0),
+            );
+            assert_color_eq(style_at(&mut segment, 2.5), blue);
+
+            // release fades back to the backbone
+            let backbone = rgba(0.1, 0.1, 0.1, 1.0);
+            let mut msg = StyleUpdateMsg::new(
+                SegmentAction::Off,
+                DrawStyle {
+                    color: backbone,
+                    stroke_weight: 5.0,
+                },
+            );
+            msg.envelope = Some(Envelope::new(1.0, 0.5, 1.0, 0.5, 2.0));
+            segment.update_segment_state(&msg, 3.0);
+            assert_color_eq(
+                style_at(&mut segment, 4.0),
+                easing::color_exp_ease(blue, backbone, 0.5, 6.0),
+            );
+            assert_color_eq(style_at(&mut segment, 5.0), backbone);
+        }
     }
 
     mod cached_grid_tests {
@@ -981,6 +1877,26 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_glow_layers_widen_and_fade_outward() {
+            let glow = GlowParams {
+                layers: 2,
+                spread: 0.5,
+                alpha_falloff: 0.5,
+            };
+            let style = DrawStyle {
+                color: rgba(1.0, 0.0, 0.0, 1.0),
+                stroke_weight: 10.0,
+            };
+
+            let layers: Vec<DrawStyle> = glow.layer_styles(&style).collect();
+            assert_eq!(layers.len(), 2);
+            assert_eq!(layers[0].stroke_weight, 20.0);
+            assert_eq!(layers[0].color.alpha, 0.25);
+            assert_eq!(layers[1].stroke_weight, 15.0);
+            assert_eq!(layers[1].color.alpha, 0.5);
+        }
+
         #[test]
         fn test_grid_creation() {
             let project = create_test_project();
@@ -1009,5 +1925,30 @@ mod tests {
                 !(top_edges.contains(&EdgeType::South) && bottom_edges.contains(&EdgeType::North))
             );
         }
+
+        #[test]
+        fn test_purged_segments_resolve_to_their_duplicate() {
+            let grid = CachedGrid::new(&Project::load("projects/ulsan.json").unwrap());
+
+            // 16 segments in each of 16 tiles, less the 48 halves of interior edges
+            assert_eq!(grid.segments.len(), 208);
+            assert_eq!(grid.aliases.len(), 48);
+
+            // the tile above keeps the edge, the tile below refers to it
+            let key = |id: &str| grid.key(id).unwrap();
+            assert_eq!(key("1,3 : hor-1-2"), "1,2 : hor-3-2");
+            assert_eq!(key("2,1 : ver-1-1"), "1,1 : ver-3-1");
+            assert_eq!(key("1,2 : hor-3-2"), "1,2 : hor-3-2");
+            let purged = grid.segment(&SegmentKey::new((1, 3), "hor-1-2")).unwrap();
+            assert_eq!(purged.id, "1,2 : hor-3-2");
+
+            // outer edges have nothing to overlap
+            assert!(grid
+                .segments
+                .contains_key(&SegmentKey::new((1, 1), "hor-1-1")));
+            assert!(grid
+                .segments
+                .contains_key(&SegmentKey::new((4, 4), "ver-3-2")));
+        }
     }
 }
@@ -8,27 +8,170 @@
 // the system.
 
 use nannou::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
     rc::Rc,
 };
 
 use crate::{
     animation::{
-        stretch, Animation, MovementChange, MovementEngine, SlideAnimation, StretchAnimation,
-        Transition, TransitionAnimationType, TransitionEngine, TransitionTriggerType,
-        TransitionUpdates,
+        crossfade_changes, stretch, Animation, EasingType, GlyphLoop, JitterAnimation, Keyframe,
+        KeyframeSample, KeyframeTrack, MovementChange, MovementEngine, MovementLeg, OrbitAnimation,
+        PathMovement, SlideAnimation, StretchAnimation, Transition, TransitionAnimationType,
+        TransitionEngine, TransitionTriggerType, TransitionUpdates, DEFAULT_INSTANT_MOVE_DURATION,
+        INSTANT_SLIDE_DURATION,
     },
-    config::TransitionConfig,
-    effects::BackboneEffect,
-    models::{Axis, EdgeType, PathElement, Project, ViewBox},
-    services::SegmentGraph,
+    config::{MovementConfig, TransitionConfig},
+    effects::{
+        ActiveEffect, ActiveFadeEffect, ActivePulseEffect, BackboneEffect, ChaseEffect, FadeEffect,
+        FlickerEffect, NoiseColorEffect, SparkleEffect,
+    },
+    models::{Axis, EdgeType, PathElement, Project, SegmentKey, ViewBox},
+    services::{GraphDelta, GraphView, SegmentGraph},
+    utilities::segment_analysis::{self, find_average_point},
     views::{
-        CachedGrid, CachedSegment, DrawStyle, SegmentAction, SegmentType, StyleUpdateMsg,
-        Transform2D,
+        CachedGrid, CachedSegment, DrawStyle, Envelope, GlowParams, Layer, OutlineParams,
+        SegmentAction, SegmentType, StyleUpdateMsg, Transform2D,
     },
 };
 
+const MIN_TRANSITION_SPEED: f32 = 0.05;
+const MAX_TRANSITION_SPEED: f32 = 20.0;
+
+// Zero-duration moves further apart than this aren't treated as a stream
+const MAX_INSTANT_MOVE_INTERVAL: f32 = 0.25;
+
+// Picks of random chase endpoints before giving up on finding a path
+const MAX_RANDOM_CHASE_TRIES: usize = 20;
+
+// Heatmap decay time in seconds, until /grid/heatmap sets one
+const DEFAULT_HEATMAP_DECAY: f32 = 10.0;
+const MIN_HEATMAP_DECAY: f32 = 0.1;
+
+// The backbone effect that fades the backbone out before it's hidden
+const BACKBONE_HIDE_EFFECT: &str = "hide";
+
+// The active effect /grid/pulse sets
+const ACTIVE_PULSE_EFFECT: &str = "pulse";
+
+// Size of the dots the graph debug overlay draws at connection points
+const GRAPH_DEBUG_POINT_RADIUS: f32 = 3.0;
+
+// Sparkles snap on in their color and fade out quickly
+const SPARKLE_ENVELOPE: Envelope = Envelope {
+    attack: 0.0,
+    hold: 0.0,
+    decay: 0.0,
+    sustain_level: 1.0,
+    release: 0.1,
+};
+
+// Heatmap gradient: dim blue when cold through to bright red when just lit
+fn heat_color(heat: f32) -> Rgba<f32> {
+    Rgba::from(hsla(0.66 * (1.0 - heat), 1.0, 0.15 + 0.35 * heat, 1.0))
+}
+
+// Hue cycling of the lit glyph, optionally spread across the grid
+#[derive(Debug, Clone, Copy)]
+pub struct RainbowStyle {
+    pub rate_hz: f32,        // turns of the color wheel per second
+    pub spatial_spread: f32, // fraction of the wheel across the grid's width
+}
+
+// How colorful_flag colors the glyph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorfulMode {
+    #[default]
+    Uniform, // one new random color for the whole glyph every frame
+    PerSegment, // each segment keeps its own random color while it's lit
+}
+
+impl TryFrom<&str> for ColorfulMode {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "uniform" => Ok(ColorfulMode::Uniform),
+            "persegment" | "per_segment" => Ok(ColorfulMode::PerSegment),
+            _ => Err(format!(
+                "Invalid colorful mode: '{}'. Expected 'uniform' or 'persegment'",
+                value
+            )),
+        }
+    }
+}
+
+// Two-color gradient across the lit glyph along one of the grid's own axes
+#[derive(Debug, Clone)]
+pub struct GradientStyle {
+    pub color_a: Rgba<f32>, // left or bottom edge of the grid
+    pub color_b: Rgba<f32>, // right or top edge
+    pub axis: Axis,
+}
+
+// Stripes of tiles in alternating colors across the lit glyph. Along Y the
+// bands are rows, along X they're columns.
+#[derive(Debug, Clone, Copy)]
+pub struct BandStyle {
+    pub axis: Axis,
+    pub width: u32, // tiles per band
+    pub color_a: Rgba<f32>,
+    pub color_b: Rgba<f32>,
+}
+
+// A grid's look at one moment, from /grid/snapshot, to be put back later
+// with restore(). Animations in flight aren't captured; restoring lands on
+// where they were headed for slides and where they'd got to for the rest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GridSnapshot {
+    pub active_segments: BTreeSet<SegmentKey>,
+    pub target_style: SnapshotStyle,
+    pub backbone_style: SnapshotStyle,
+    pub glyph_index: usize,
+    pub position: [f32; 2],
+    pub rotation: f32,
+    pub scale: [f32; 2],
+    pub row_offsets: Vec<(i32, f32)>, // sorted by index
+    pub col_offsets: Vec<(i32, f32)>,
+    pub is_visible: bool,
+    pub dimmer: f32,
+    pub use_power_on_effect: bool,
+    pub colorful_flag: bool,
+    pub use_stroke_order: bool,
+    pub use_reverse_erase: bool,
+    pub use_stroke_tint: bool,
+    pub use_crossfade: bool,
+}
+
+// DrawStyle as plain numbers, since Rgba doesn't serialize
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotStyle {
+    pub color: [f32; 4],
+    pub stroke_weight: f32,
+}
+
+impl From<&DrawStyle> for SnapshotStyle {
+    fn from(style: &DrawStyle) -> Self {
+        let color = style.color;
+        Self {
+            color: [color.red, color.green, color.blue, color.alpha],
+            stroke_weight: style.stroke_weight,
+        }
+    }
+}
+
+impl From<SnapshotStyle> for DrawStyle {
+    fn from(style: SnapshotStyle) -> Self {
+        let [red, green, blue, alpha] = style.color;
+        Self {
+            color: rgba(red, green, blue, alpha),
+            stroke_weight: style.stroke_weight,
+        }
+    }
+}
+
 pub struct GridInstance {
     // grid data
     pub id: String,
@@ -38,7 +181,9 @@ pub struct GridInstance {
     pub grid: CachedGrid,
 
     // The network of connections between segments. Shared among grids of the same CachedGrid.
-    pub graph: Rc<SegmentGraph>,
+    // Queries go through graph(), which adds this grid's own segments from graph_delta.
+    base_graph: Rc<SegmentGraph>,
+    graph_delta: GraphDelta,
 
     // glyph state:
     // The Show attached to this Grid.
@@ -46,16 +191,30 @@ pub struct GridInstance {
     show: String,
     pub current_glyph_index: usize,
     index_max: usize,
+    // Alternates between two glyphs while idle
+    glyph_loop: Option<GlyphLoop>,
 
     // effects state
     // The currently active transition
     active_transition: Option<Transition>,
     // Parameters that help define the next transition when created
-    pub transition_config: Option<TransitionConfig>, // probably don't need this
+    pub transition_config: Option<TransitionConfig>, // from /transition/update, the engine's default when None
     pub transition_trigger_type: TransitionTriggerType,
     pub transition_next_animation_type: TransitionAnimationType,
     pub transition_trigger_received: bool,
     pub transition_use_stroke_order: bool,
+    // Writing transitions erase the old glyph stroke by stroke instead of all at once
+    pub transition_use_reverse_erase: bool,
+    // Writing transitions tint each stroke a little lighter than the last
+    pub transition_use_stroke_tint: bool,
+    // An interrupted transition keeps playing its remaining steps alongside the new one
+    pub transition_use_crossfade: bool,
+    // Freezes the active transition and any segment power on/off animations
+    transition_paused: bool,
+    // Time driving segment state animations. Only advances while not paused.
+    segment_clock: f32,
+    // Playback rate for transition steps and segment power on/off fades
+    transition_speed: f32,
 
     // Turns on/off the golden flash when a segment is activated. The segment then
     // fades to the target color.
@@ -63,45 +222,132 @@ pub struct GridInstance {
 
     // enables random-ish color effect target style
     pub colorful_flag: bool,
+    colorful_mode: ColorfulMode,
+    // PerSegment colors, rolled when a segment turns on
+    segment_colors: HashMap<SegmentKey, Rgba<f32>>,
+    colorful_rng: StdRng,
+
+    // Bloom around lit segments. None skips the extra draw calls entirely.
+    pub glow: Option<GlowParams>,
+    pub outline: Option<OutlineParams>,
+    // Stroke weight multipliers by segment type, applied to every staged
+    // style. Types without one draw at 1.0.
+    type_weights: HashMap<SegmentType, f32>,
+
+    // How segments power on and off
+    pub envelope: Envelope,
 
     // Segment update messages for the next frame
-    // String is the segment_id
+    // SegmentKey is the segment_id
     // StyleUpdateMsg is the update message for the segment
-    update_batch: HashMap<String, StyleUpdateMsg>,
+    update_batch: HashMap<SegmentKey, StyleUpdateMsg>,
 
     // The Glyph segments that will be displayed after any Transition animation
-    pub target_segments: Option<HashSet<String>>,
+    pub target_segments: Option<HashSet<SegmentKey>>,
 
     // Currently active segments for this frame
-    pub current_active_segments: HashSet<String>,
+    pub current_active_segments: HashSet<SegmentKey>,
 
     // The target Active Segment style when an effect is complete
     pub target_style: DrawStyle,
 
+    // Per-segment styles that take precedence over target_style, kept across glyph changes
+    segment_color_overrides: HashMap<SegmentKey, DrawStyle>,
+
+    // Effects layered over target_style for the active segments, applied in
+    // the order they were added
+    active_effects: Vec<(String, Box<dyn ActiveEffect>)>,
+    // Fade of target_style itself toward a new glyph color
+    glyph_color_fade: Option<ActiveFadeEffect>,
+    // Random per-segment dropouts, applied after the other effects
+    flicker: Option<FlickerEffect>,
+    // Per-segment hue drift, applied before flicker
+    noise_color: Option<NoiseColorEffect>,
+    // Each segment's grid-local position, scaled to the grid's longest side
+    noise_positions: HashMap<SegmentKey, Vec2>,
+    // Replaces target_style's color for active segments while set
+    gradient: Option<GradientStyle>,
+    bands: Option<BandStyle>,
+    // Each segment's place along the gradient axis, 0.0 to 1.0 in grid-local space
+    gradient_positions: HashMap<SegmentKey, f32>,
+    // Replaces target_style's hue for active segments while set
+    rainbow: Option<RainbowStyle>,
+    rainbow_phase: f32,
+    rainbow_positions: HashMap<SegmentKey, f32>,
+
     // backbone state (non-active segments)
-    backbone_effects: HashMap<String, Box<dyn BackboneEffect>>,
-    pub backbone_style: DrawStyle,
+    // (name, priority, effect), applied in ascending priority order
+    backbone_effects: Vec<(String, i32, Box<dyn BackboneEffect>)>,
+    pub backbone_style: DrawStyle, // settled style, before modulations
+    displayed_backbone_style: DrawStyle,
+    // Bumped whenever displayed_backbone_style changes, so idle segments
+    // that already show it aren't restaged every frame
+    backbone_style_version: u64,
+    backbone_versions: HashMap<SegmentKey, u64>, // the version each idle segment was staged with
+    pub backbone_visible: bool,                  // idle segments are drawn when true
+    // The backbone_style a fade out started from, put back when it's shown
+    backbone_style_before_hide: Option<DrawStyle>,
+    // Random backbone segments briefly lit, outside the glyph
+    sparkle: Option<SparkleEffect>,
+    chase: Option<ChaseEffect>,
+    chase_lit: HashSet<SegmentKey>, // overlay lit by the chase, apart from the glyph
+
+    // When each segment last left a glyph, kept whether or not the heatmap
+    // is showing so turning it on mid-show shows the history so far
+    last_active_time: HashMap<SegmentKey, f32>,
+    heatmap: bool,
+    heatmap_decay: f32, // seconds for a segment to go from hot to cold
+
+    // Draws the segment graph over the grid, to see what it thinks is connected
+    graph_debug: bool,
+    graph_debug_labels: bool, // each segment's degree at its midpoint
 
     // grid transform state
     //
     // The currently active time-based movement animation
     pub active_movement: Option<Box<dyn Animation>>,
 
+    // Movements that start one after another as each completes
+    movement_queue: VecDeque<MovementLeg>,
+    active_leg: Option<usize>, // queue leg number of active_movement
+    legs_started: usize,       // legs started since the queue was last empty
+    completed_legs: Vec<(usize, usize)>, // (leg number, legs remaining) finished this frame
+
+    // Measured pacing of zero-duration moves streamed from a controller
+    last_instant_move_time: Option<f32>,
+    instant_move_interval: f32,
+
+    // Combined position, rotation and scale choreography
+    keyframe_track: Option<KeyframeTrack>,
+
+    // Circling a point, optionally turning to face along the path
+    orbit: Option<OrbitAnimation>,
+
     // Current transform state
     pub current_position: Point2,
     pub current_rotation: f32,
-    pub current_scale: f32,
-
-    pub is_visible: bool,   // draw this grid to screen when true
+    pub current_scale: Vec2, // along the grid's own axes, see Transform2D
+
+    pub is_visible: bool,  // draw this grid to screen when true
+    pub dimmer: f32,       // 0.0 to 1.0, scales every color this grid draws
+    pub master_level: f32, // the show-wide fader, set by the app each frame
+    // Tiles left undrawn. Their segments carry on underneath, so a glyph
+    // shows as it should when they're unmasked.
+    tile_mask: HashSet<(u32, u32)>,
     spawn_location: Point2, // the original location of the grid
 
     // Slide animation states
     row_positions: HashMap<i32, f32>, // <index, position offset>
     col_positions: HashMap<i32, f32>, // <index, position offset>
     slide_animations: Vec<SlideAnimation>,
+    slide_wrap_offsets: HashMap<SegmentKey, f32>, // <segment_id, distance teleported by wrapping>
+    slide_clamp: Option<Vec2>,                    // texture size to keep slid rows/columns within
 
     // Stretch segment state
     stretch_animation: Option<StretchAnimation>,
+
+    // Positional shake layered on top of current_position
+    jitter: Option<JitterAnimation>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -117,15 +363,37 @@ impl GridInstance {
         stroke_weight: f32,
         backbone_stroke_weight: f32,
     ) -> Self {
-        let transform = Transform2D {
-            translation: position,
-            scale: 1.0,
+        Self::with_grid(
+            id,
+            project,
+            show,
+            base_grid.clone(),
+            base_graph,
+            position,
             rotation,
-        };
-
-        let mut grid = base_grid.clone();
+            stroke_weight,
+            backbone_stroke_weight,
+        )
+    }
 
-        grid.apply_transform(&transform);
+    // Like new(), on a grid that's already a copy of the base grid, e.g.
+    // one from the GridPool. Its segments are expected to be idle.
+    pub fn with_grid(
+        id: String,
+        project: &Project,
+        show: &str,
+        mut grid: CachedGrid,
+        base_graph: Rc<SegmentGraph>,
+        position: Point2,
+        rotation: f32,
+        stroke_weight: f32,
+        backbone_stroke_weight: f32,
+    ) -> Self {
+        grid.set_transform(Transform2D {
+            translation: position,
+            scale: Vec2::ONE,
+            rotation,
+        });
 
         let index_max = project
             .get_show(show)
@@ -138,10 +406,12 @@ impl GridInstance {
         Self {
             id,
             grid,
-            graph: base_graph,
+            base_graph,
+            graph_delta: GraphDelta::default(),
             show: show.to_string(),
             current_glyph_index: 1,
             index_max,
+            glyph_loop: None,
 
             target_segments: None,
             current_active_segments: HashSet::new(),
@@ -150,38 +420,102 @@ impl GridInstance {
                 stroke_weight,
             },
 
+            segment_color_overrides: HashMap::new(),
+
             active_transition: None,
             transition_config: None,
             transition_trigger_type: TransitionTriggerType::Auto,
             transition_next_animation_type: TransitionAnimationType::default(),
             transition_trigger_received: false,
             transition_use_stroke_order: true,
+            transition_use_reverse_erase: false,
+            transition_use_stroke_tint: false,
+            transition_use_crossfade: false,
+            transition_paused: false,
+            segment_clock: 0.0,
+            transition_speed: 1.0,
             use_power_on_effect: false,
             colorful_flag: false,
+            colorful_mode: ColorfulMode::Uniform,
+            segment_colors: HashMap::new(),
+            colorful_rng: StdRng::from_entropy(),
+            glow: None,
+            outline: None,
+            type_weights: HashMap::new(),
+            envelope: Envelope::default(),
 
             update_batch: HashMap::new(),
 
-            backbone_effects: HashMap::new(),
+            active_effects: Vec::new(),
+            glyph_color_fade: None,
+            flicker: None,
+            noise_color: None,
+            noise_positions: HashMap::new(),
+            gradient: None,
+            bands: None,
+            gradient_positions: HashMap::new(),
+            rainbow: None,
+            rainbow_phase: 0.0,
+            rainbow_positions: HashMap::new(),
+            backbone_effects: Vec::new(),
+            sparkle: None,
+            chase: None,
+            chase_lit: HashSet::new(),
+            last_active_time: HashMap::new(),
+            heatmap: false,
+            heatmap_decay: DEFAULT_HEATMAP_DECAY,
+            graph_debug: false,
+            graph_debug_labels: false,
             backbone_style: DrawStyle {
                 color: rgba(0.19, 0.19, 0.19, 1.0),
                 stroke_weight: backbone_stroke_weight,
             },
+            displayed_backbone_style: DrawStyle {
+                color: rgba(0.19, 0.19, 0.19, 1.0),
+                stroke_weight: backbone_stroke_weight,
+            },
+            backbone_style_version: 0,
+            backbone_versions: HashMap::new(),
+            backbone_visible: true,
+            backbone_style_before_hide: None,
 
             active_movement: None,
+            movement_queue: VecDeque::new(),
+            active_leg: None,
+            legs_started: 0,
+            completed_legs: Vec::new(),
+            last_instant_move_time: None,
+            instant_move_interval: DEFAULT_INSTANT_MOVE_DURATION,
+            keyframe_track: None,
+            orbit: None,
             current_position: position,
             current_rotation: rotation,
-            current_scale: 1.0,
+            current_scale: Vec2::ONE,
             is_visible: false,
+            dimmer: 1.0,
+            master_level: 1.0,
+            tile_mask: HashSet::new(),
             spawn_location: position,
 
             row_positions: HashMap::new(),
             col_positions: HashMap::new(),
             slide_animations: Vec::new(),
+            slide_wrap_offsets: HashMap::new(),
+            slide_clamp: None,
 
             stretch_animation: None,
+
+            jitter: None,
         }
     }
 
+    // Tears the instance down, handing back its grid reset for reuse
+    pub fn into_grid(self) -> CachedGrid {
+        let mut grid = self.grid;
+        grid.reset();
+        grid
+    }
+
     /****************************** Update Flow ***************************** */
 
     // The highest level update orchestrator
@@ -192,18 +526,29 @@ impl GridInstance {
         time: f32,
         dt: f32,
     ) {
+        // transitions and segment fades run on their own scaled time
+        let transition_dt = dt * self.transition_speed;
+        if !self.transition_paused {
+            self.segment_clock += transition_dt;
+        }
+
         // 1. Generate new transitions
         if self.has_target_segments() {
-            self.build_transition(transition_engine, self.transition_next_animation_type);
+            self.build_transition(
+                transition_engine,
+                self.transition_next_animation_type.clone(),
+            );
         }
 
         // 2. Update positioning
 
         // a. handle movement
-        if self.has_active_movement() {
-            if let Some(change) = self.advance_movement(time, dt) {
-                self.apply_movement_change(&change);
-            }
+        self.update_movement(time, dt);
+        if self.has_keyframe_track() {
+            self.update_keyframes(time);
+        }
+        if self.has_orbit() {
+            self.update_orbit(time);
         }
 
         // b. handle slide animations
@@ -216,94 +561,204 @@ impl GridInstance {
         //    todo!();
         //}
 
-        // 3. Stage any backbone style change
+        // d. handle jitter (applied on top of any movement this frame)
+        if self.has_active_jitter() {
+            self.update_jitter(time);
+        }
+
+        // 3. Stage any backbone style change: settling effects like fades
+        // first, then modulations like pulse on top
         if self.has_backbone_effects() {
             self.backbone_style = self.generate_backbone_style(time);
-            self.cleanup_backbone_effects(time);
+        }
+        let displayed_backbone_style = self.modulate_backbone_style(time);
+        if displayed_backbone_style != self.displayed_backbone_style {
+            self.displayed_backbone_style = displayed_backbone_style;
+            self.backbone_style_version += 1;
+        }
+        self.cleanup_backbone_effects(time);
+        if self.backbone_style_before_hide.is_some()
+            && self.backbone_visible
+            && !self.has_backbone_effect(BACKBONE_HIDE_EFFECT)
+        {
+            // faded out, so it can go
+            self.backbone_visible = false;
         }
 
+        // Glyph color fades move target_style itself, so glyphs staged
+        // mid-fade turn on in the color of that instant
+        let glyph_color_fading = self.update_glyph_color_fade(time);
+
         // 4. Advance any active transition & generate update messages
         if self.has_active_transition() {
-            if let Some(updates) = self.process_active_transition(dt) {
-                self.track_active_segments(&updates);
+            if let Some(updates) = self.process_active_transition(transition_dt) {
+                self.track_active_segments(&updates, time);
                 self.generate_transition_updates(&updates);
+                self.apply_stagger(transition_engine, &updates.segments_on);
             }
         }
 
-        // 5. Generate update messages for remaining segments (backbone)
+        // 5. Generate update messages for active segment effects
+        if let Some(flicker) = &mut self.flicker {
+            flicker.update(&self.current_active_segments, time);
+        }
+        if self.has_active_effects()
+            || glyph_color_fading
+            || self.has_rainbow()
+            || self.flicker.is_some()
+            || self.noise_color.is_some()
+        {
+            self.cleanup_active_effects(time);
+            self.stage_active_style_updates(time);
+        }
+
+        // 5b. Light and put out backbone sparkles
+        if self.sparkle.is_some() {
+            self.update_sparkles(time, dt);
+        }
+
+        // 5c. Move the chase along its path
+        if self.chase.is_some() {
+            self.update_chase(time);
+        }
+
+        // 5d. Heatmap colors every settled segment by how recently it was lit
+        if self.heatmap {
+            self.stage_heatmap_updates(time);
+        }
+
+        // 6. Generate update messages for remaining segments (backbone)
         self.stage_backbone_updates();
 
-        // 6. Push updates to grid segments
+        // 7. Push updates to grid segments
         self.push_updates();
 
-        // 7. Draw
+        // 8. Draw, all but the overlay. See draw_overlay().
         if self.is_visible {
             self.draw_grid(draw);
+            if self.graph_debug {
+                self.draw_graph_debug(draw);
+            }
         }
 
-        // 8. Clean up
+        // 9. Clean up
         self.clear_update_batch();
     }
 
     fn push_updates(&mut self) {
-        self.grid.apply_updates(&self.update_batch);
+        self.grid
+            .apply_updates(&self.update_batch, self.segment_clock, &self.type_weights);
+    }
+
+    // Draws the grid as update() last left it, to another render target such
+    // as the recording. Like update(), it leaves out the overlay.
+    pub fn draw_again(&self, draw: &Draw) {
+        if self.is_visible {
+            self.draw_grid(draw);
+        }
+    }
+
+    // The overlay goes on once every grid has been drawn, so it's above all
+    // of them
+    pub fn draw_overlay(&self, draw: &Draw) {
+        if self.is_visible {
+            self.grid.draw_overlay(
+                draw,
+                self.glow.as_ref(),
+                self.outline.as_ref(),
+                self.dimmer * self.master_level,
+                &self.tile_mask,
+            );
+        }
     }
 
     fn draw_grid(&self, draw: &Draw) {
-        self.grid.draw(draw);
+        self.grid.draw_below_overlay(
+            draw,
+            self.glow.as_ref(),
+            self.outline.as_ref(),
+            self.dimmer * self.master_level,
+            &self.tile_mask,
+            self.backbone_visible,
+        );
     }
 
     /************************** Update messages and state ******************************/
 
-    fn stage_segments_on(&mut self, segments: &HashSet<String>, target_style: &DrawStyle) {
+    fn stage_segments_on(&mut self, segments: &HashSet<SegmentKey>, target_style: &DrawStyle) {
         for segment_id in segments {
             self.update_batch.insert(
                 segment_id.clone(),
                 StyleUpdateMsg {
                     action: Some(SegmentAction::On),
                     target_style: Some(target_style.clone()),
+                    delay: None,
+                    envelope: Some(self.envelope),
+                    layer: None,
                 },
             );
         }
     }
 
-    fn stage_segments_instant_on(&mut self, segments: &HashSet<String>, target_style: &DrawStyle) {
+    fn stage_segments_instant_on(
+        &mut self,
+        segments: &HashSet<SegmentKey>,
+        target_style: &DrawStyle,
+    ) {
         for segment_id in segments {
             self.update_batch.insert(
                 segment_id.clone(),
                 StyleUpdateMsg {
                     action: Some(SegmentAction::InstantStyleChange),
                     target_style: Some(target_style.clone()),
+                    delay: None,
+                    envelope: None,
+                    layer: None,
                 },
             );
         }
     }
 
-    fn stage_segments_off(&mut self, segments: &HashSet<String>, backbone_style: &DrawStyle) {
+    fn stage_segments_off(&mut self, segments: &HashSet<SegmentKey>, backbone_style: &DrawStyle) {
         for segment_id in segments {
             self.update_batch.insert(
                 segment_id.clone(),
                 StyleUpdateMsg {
                     action: Some(SegmentAction::Off),
                     target_style: Some(backbone_style.clone()),
+                    delay: None,
+                    envelope: Some(self.envelope),
+                    layer: None,
                 },
             );
         }
     }
 
+    // Idle segments already showing the current backbone style are skipped
     fn stage_backbone_updates(&mut self) {
+        // whatever else was staged this frame replaces the backbone style
+        for segment_id in self.update_batch.keys() {
+            self.backbone_versions.remove(segment_id);
+        }
+
+        let version = self.backbone_style_version;
         for (segment_id, segment) in self.grid.segments.iter() {
             if !self.update_batch.contains_key(segment_id)
-                && self.grid.segments[segment_id].is_background()
+                && segment.is_background()
                 && segment.is_idle()
+                && self.backbone_versions.get(segment_id) != Some(&version)
             {
                 self.update_batch.insert(
                     segment_id.clone(),
                     StyleUpdateMsg {
                         action: Some(SegmentAction::BackboneUpdate),
-                        target_style: Some(self.backbone_style.clone()),
+                        target_style: Some(self.displayed_backbone_style.clone()),
+                        delay: None,
+                        envelope: None,
+                        layer: None,
                     },
                 );
+                self.backbone_versions.insert(segment_id.clone(), version);
             }
         }
     }
@@ -313,6 +768,7 @@ impl GridInstance {
     }
 
     pub fn set_effect_target_style(&mut self, style: DrawStyle) {
+        self.glyph_color_fade = None;
         self.target_style = style;
     }
 
@@ -325,9 +781,15 @@ impl GridInstance {
             Some(show) => match show.show_order.get(&(index as u32)) {
                 Some(show_element) => match project.get_glyph(&show_element.name) {
                     Some(glyph) => {
+                        // glyphs can name either of two overlapping edges
                         self.current_glyph_index = index;
-                        self.target_segments = (!glyph.segments.is_empty())
-                            .then(|| glyph.segments.iter().cloned().collect());
+                        self.target_segments = (!glyph.segments.is_empty()).then(|| {
+                            glyph
+                                .segments
+                                .iter()
+                                .filter_map(|id| self.grid.key(id))
+                                .collect()
+                        });
                     }
                     None => self.stage_empty_glyph(),
                 },
@@ -337,6 +799,84 @@ impl GridInstance {
         }
     }
 
+    // The segment graph with this grid's stretch segments joined in
+    pub fn graph(&self) -> GraphView<'_> {
+        GraphView::new(&self.base_graph, &self.graph_delta)
+    }
+
+    // The name of the Show this grid takes its glyphs from
+    pub fn show(&self) -> &str {
+        &self.show
+    }
+
+    // Lights or puts out every segment connected to `segment_id`, through the
+    // next transition like a glyph would. Handy for isolating one figure on
+    // a grid; anything else staged or lit stays as it is.
+    pub fn stage_component(&mut self, segment_id: &str, on: bool) {
+        let component = self
+            .grid
+            .key(segment_id)
+            .and_then(|key| self.graph().component_of(&key));
+        let Some(component) = component else {
+            println!(
+                "Warning: unknown segment '{}' on grid {}",
+                segment_id, self.id
+            );
+            return;
+        };
+
+        let mut segments = self
+            .target_segments
+            .take()
+            .unwrap_or_else(|| self.current_active_segments.clone());
+        if on {
+            segments.extend(component.iter().cloned());
+        } else {
+            segments.retain(|id| !component.contains(id));
+        }
+        self.target_segments = Some(segments);
+    }
+
+    // Lights every segment tagged `tag` in the SVG through the next
+    // transition, alongside whatever else is lit. A color sets their
+    // overrides first, so they come on in it.
+    pub fn stage_tag_on(&mut self, tag: &str, color: Option<Rgba<f32>>) {
+        let tagged = self.tagged_segments(tag);
+        if let Some(color) = color {
+            for segment_id in &tagged {
+                self.override_segment_color(segment_id.clone(), color);
+            }
+        }
+        let mut segments = self
+            .target_segments
+            .take()
+            .unwrap_or_else(|| self.current_active_segments.clone());
+        segments.extend(tagged);
+        self.target_segments = Some(segments);
+    }
+
+    pub fn stage_tag_off(&mut self, tag: &str) {
+        let tagged = self.tagged_segments(tag);
+        let mut segments = self
+            .target_segments
+            .take()
+            .unwrap_or_else(|| self.current_active_segments.clone());
+        segments.retain(|id| !tagged.contains(id));
+        self.target_segments = Some(segments);
+    }
+
+    fn tagged_segments(&self, tag: &str) -> HashSet<SegmentKey> {
+        let tagged: HashSet<SegmentKey> = self
+            .grid
+            .tagged(tag)
+            .map(|segment| segment.id.clone())
+            .collect();
+        if tagged.is_empty() {
+            println!("Warning: no segments tagged '{}' on grid {}", tag, self.id);
+        }
+        tagged
+    }
+
     pub fn stage_empty_glyph(&mut self) {
         self.target_segments = Some(HashSet::new());
     }
@@ -346,6 +886,41 @@ impl GridInstance {
         self.stage_glyph_by_index(project, self.current_glyph_index);
     }
 
+    // process OSC /grid/loop
+    pub fn set_loop(
+        &mut self,
+        index_a: usize,
+        index_b: usize,
+        hold_time: f32,
+        animation_type: TransitionAnimationType,
+    ) {
+        self.glyph_loop = Some(GlyphLoop::new(index_a, index_b, hold_time, animation_type));
+    }
+
+    // process OSC /grid/loopstop, and any explicit glyph command
+    pub fn stop_loop(&mut self) {
+        self.glyph_loop = None;
+    }
+
+    pub fn has_glyph_loop(&self) -> bool {
+        self.glyph_loop.is_some()
+    }
+
+    // Stages the loop's next glyph once the current one has been held long enough.
+    // Runs before update() so the new transition is built the same frame.
+    pub fn update_glyph_loop(&mut self, project: &Project, time: f32) {
+        let transitioning = self.has_active_transition() || self.has_target_segments();
+        let Some(glyph_loop) = self.glyph_loop.as_mut() else {
+            return;
+        };
+
+        if let Some(index) = glyph_loop.next_index(transitioning, time) {
+            let animation_type = glyph_loop.animation_type.clone();
+            self.stage_glyph_by_index(project, index);
+            self.transition_next_animation_type = animation_type;
+        }
+    }
+
     fn advance_glyph_index(&mut self, index: usize) {
         if index + 1 > self.index_max {
             self.current_glyph_index = 1;
@@ -363,18 +938,66 @@ impl GridInstance {
             return;
         }
 
-        let changes = engine.generate_changes(self, typ);
+        let mut changes = engine.generate_changes(self, typ);
+
+        // A new glyph arriving mid-transition interrupts the running one.
+        // The new changes are already diffed against current_active_segments,
+        // so only the segments the old transition left in flight need fixing.
+        if let Some(mut interrupted) = self.active_transition.take() {
+            self.stage_orphan_corrections();
+
+            if self.transition_use_crossfade {
+                let target_segments = self.target_segments.as_ref().unwrap();
+                changes = crossfade_changes(
+                    interrupted.take_remaining_changes(),
+                    changes,
+                    target_segments,
+                );
+            }
+        }
 
-        self.active_transition = Some(Transition::new(
-            self.transition_next_animation_type,
+        let mut transition = Transition::new(
+            self.transition_next_animation_type.clone(),
             changes,
-            engine.default_config.frame_duration,
-        ));
+            engine.config_for(self).frame_duration,
+        );
+        if self.transition_paused {
+            transition.pause();
+        }
+        self.active_transition = Some(transition);
 
         // reset target segments
         self.target_segments = None;
     }
 
+    // (completed steps, total steps, animation name) of the running transition
+    pub fn transition_progress(&self) -> Option<(usize, usize, &'static str)> {
+        self.active_transition.as_ref().map(|transition| {
+            let (step, total) = transition.progress();
+            (step, total, transition.animation_type.name())
+        })
+    }
+
+    // Turns off segments still lit on screen that the grid no longer
+    // considers active, e.g. ones caught powering on by an interruption
+    fn stage_orphan_corrections(&mut self) {
+        let orphans: HashSet<SegmentKey> = self
+            .grid
+            .segments
+            .values()
+            .filter(|segment| segment.is_lit())
+            .filter(|segment| !self.current_active_segments.contains(&segment.id))
+            .filter(|segment| !self.is_sparkling(&segment.id))
+            .filter(|segment| !self.chase_lit.contains(&segment.id))
+            .map(|segment| segment.id.clone())
+            .collect();
+
+        if !orphans.is_empty() {
+            let backbone_style = self.displayed_backbone_style.clone();
+            self.stage_segments_off(&orphans, &backbone_style);
+        }
+    }
+
     // Obtain TransitionUpdates by advancing the Transition
     // Todo?: extract functionality requiring mutable self
     fn process_active_transition(&mut self, dt: f32) -> Option<TransitionUpdates> {
@@ -385,6 +1008,11 @@ impl GridInstance {
 
         let transition = self.active_transition.as_mut().unwrap();
 
+        // A paused transition holds its place, including any pending manual trigger
+        if transition.is_paused() {
+            return None;
+        }
+
         // Determine if transition should advance based on trigger type
         let should_advance = transition.is_immediate_type()
             || match self.transition_trigger_type {
@@ -412,20 +1040,27 @@ impl GridInstance {
     }
 
     // Update the active segments field based on TransitionUpdates
-    fn track_active_segments(&mut self, updates: &TransitionUpdates) {
+    fn track_active_segments(&mut self, updates: &TransitionUpdates, time: f32) {
         for segment_id in &updates.segments_on {
             self.current_active_segments.insert(segment_id.clone());
+            self.last_active_time.insert(segment_id.clone(), time);
         }
 
         for segment_id in &updates.segments_off {
             self.current_active_segments.remove(segment_id);
+            self.segment_colors.remove(segment_id);
+            self.last_active_time.insert(segment_id.clone(), time);
+        }
+
+        if self.colorful_flag && self.colorful_mode == ColorfulMode::PerSegment {
+            self.roll_segment_colors(&updates.segments_on);
         }
     }
 
     // Create style update messages
     fn generate_transition_updates(&mut self, updates: &TransitionUpdates) {
         let target_style = self.target_style.clone();
-        let backbone_style = self.backbone_style.clone();
+        let backbone_style = self.displayed_backbone_style.clone();
 
         if !updates.segments_on.is_empty() {
             if self.use_power_on_effect {
@@ -433,6 +1068,9 @@ impl GridInstance {
             } else {
                 self.stage_segments_instant_on(&updates.segments_on, &target_style);
             }
+            self.apply_active_base_styles(&updates.segments_on);
+            self.apply_change_styles(&updates.styles);
+            self.apply_segment_color_overrides(&updates.segments_on);
         }
 
         if !updates.segments_off.is_empty() {
@@ -440,12 +1078,36 @@ impl GridInstance {
         }
     }
 
+    // Spreads the segments turning on this step over the engine's stagger time
+    fn apply_stagger(&mut self, engine: &TransitionEngine, segments_on: &HashSet<SegmentKey>) {
+        for (segment_id, delay) in engine.stagger_delays(self, segments_on) {
+            if let Some(msg) = self.update_batch.get_mut(&segment_id) {
+                if matches!(
+                    msg.action,
+                    Some(SegmentAction::On | SegmentAction::InstantStyleChange)
+                ) {
+                    msg.delay = Some(delay);
+                }
+            }
+        }
+    }
+
+    // Per-change styles from the transition take the place of target_style
+    fn apply_change_styles(&mut self, styles: &HashMap<SegmentKey, DrawStyle>) {
+        for (segment_id, style) in styles {
+            if let Some(msg) = self.update_batch.get_mut(segment_id) {
+                msg.target_style = Some(style.clone());
+            }
+        }
+    }
+
     pub fn update_transition_config(
         &mut self,
         steps: Option<usize>,
         frame_duration: Option<f32>,
         wandering: Option<f32>,
         density: Option<f32>,
+        stroke_gap_steps: Option<usize>,
         default_config: &TransitionConfig,
     ) {
         let config = TransitionConfig {
@@ -453,6 +1115,9 @@ impl GridInstance {
             frame_duration: frame_duration.unwrap_or(default_config.frame_duration),
             wandering: wandering.unwrap_or(default_config.wandering),
             density: density.unwrap_or(default_config.density),
+            seed: default_config.seed,
+            stagger: default_config.stagger,
+            stroke_gap_steps: stroke_gap_steps.unwrap_or(default_config.stroke_gap_steps),
         };
         self.transition_config = Some(config);
     }
@@ -466,465 +1131,3586 @@ impl GridInstance {
         };
 
         // Update target style for future transitions
+        self.glyph_color_fade = None;
         self.target_style = new_style.clone();
 
         // create update messages for active segments
         for segment_id in &self.current_active_segments {
+            let style = match self.segment_color_overrides.get(segment_id) {
+                Some(style) => style.clone(),
+                None => self.active_base_style(segment_id),
+            };
             self.update_batch.insert(
                 segment_id.clone(),
-                StyleUpdateMsg::new(SegmentAction::InstantStyleChange, new_style.clone()),
+                StyleUpdateMsg::new(SegmentAction::InstantStyleChange, style),
             );
         }
     }
 
-    // process OSC /grid/transitiontrigger
-    pub fn receive_transition_trigger(&mut self) {
-        match self.transition_trigger_type {
-            TransitionTriggerType::Auto => {
-                self.transition_trigger_type = TransitionTriggerType::Manual;
-                if self.has_active_transition() {
-                    self.transition_trigger_received = true;
-                }
-            }
-            TransitionTriggerType::Manual => {
-                if self.has_active_transition() {
-                    self.transition_trigger_received = true;
-                }
-            }
+    // Fades the lit glyph, and the color of glyphs to come, to `new_color`
+    pub fn glyph_color_fade(&mut self, new_color: Rgba<f32>, duration: f32, time: f32) {
+        if duration <= 0.0 {
+            self.instant_color_change(new_color);
+            return;
         }
+        self.glyph_color_fade = Some(ActiveFadeEffect::new(
+            self.target_style.color,
+            new_color,
+            duration,
+            time,
+        ));
     }
 
-    /**************************** Grid movement & transform **********************************/
-
-    pub fn rotate_in_place(&mut self, angle: f32) {
-        let angle_delta = angle - self.current_rotation;
-
-        // 1. Transform to pivot-relative space
-        let to_local = Transform2D {
-            translation: -self.current_position,
-            scale: 1.0,
-            rotation: 0.0,
+    // Returns true if a fade moved target_style this frame
+    fn update_glyph_color_fade(&mut self, time: f32) -> bool {
+        let Some(fade) = &self.glyph_color_fade else {
+            return false;
         };
 
-        // 2. Just rotation
-        let rotate = Transform2D {
-            translation: Vec2::ZERO,
-            scale: 1.0,
-            rotation: angle_delta,
-        };
+        self.target_style = fade.update(&self.target_style, time);
+        if fade.is_finished(time) {
+            self.glyph_color_fade = None;
+        }
+        true
+    }
 
-        // 3. Transform back
-        let to_world = Transform2D {
-            translation: self.current_position,
-            scale: 1.0,
-            rotation: 0.0,
-        };
+    /*********************** Gradient and rainbow ******************************/
 
-        // Apply each transform in sequence
-        self.grid.apply_transform(&to_local);
-        self.grid.apply_transform(&rotate);
-        self.grid.apply_transform(&to_world);
+    // Segment positions are measured once here, in grid-local space, so later
+    // moves, rotations and scales don't change which end a segment belongs to.
+    pub fn set_gradient(&mut self, gradient: GradientStyle) {
+        self.gradient_positions = self.local_axis_positions(gradient.axis);
+        self.gradient = Some(gradient);
+        self.restyle_active_segments();
+    }
 
-        // Update location's rotation (but not position)
-        self.current_rotation = angle;
+    pub fn clear_gradient(&mut self) {
+        if self.gradient.take().is_some() {
+            self.gradient_positions.clear();
+            self.restyle_active_segments();
+        }
     }
 
-    pub fn scale_in_place(&mut self, new_scale: f32) {
-        // clamp scale value to a minimum of 0.001
-        let safe_scale = if new_scale < 0.001 { 0.001 } else { new_scale };
+    pub fn set_bands(&mut self, bands: BandStyle) {
+        self.bands = Some(BandStyle {
+            width: bands.width.max(1),
+            ..bands
+        });
+        self.restyle_active_segments();
+    }
 
-        let scale_factor = safe_scale / self.current_scale;
+    pub fn clear_bands(&mut self) {
+        if self.bands.take().is_some() {
+            self.restyle_active_segments();
+        }
+    }
 
-        // 1. Transform to pivot-relative space
-        let to_local = Transform2D {
-            translation: -self.current_position,
-            scale: 1.0,
-            rotation: 0.0,
-        };
+    // A rate of zero turns the rainbow off. Spread is how much of the color
+    // wheel lies across the grid from left to right.
+    pub fn set_rainbow(&mut self, rate_hz: f32, spatial_spread: f32) {
+        if rate_hz == 0.0 {
+            if self.rainbow.take().is_some() {
+                self.rainbow_positions.clear();
+                self.restyle_active_segments();
+            }
+            return;
+        }
 
-        // 2. Just scaling
-        let scale = Transform2D {
-            translation: Vec2::ZERO,
-            scale: scale_factor,
-            rotation: 0.0,
-        };
+        if self.rainbow.is_none() {
+            self.rainbow_positions = self.local_axis_positions(Axis::X);
+        }
+        self.rainbow = Some(RainbowStyle {
+            rate_hz,
+            spatial_spread,
+        });
+    }
 
-        // 3. Transform back
-        let to_world = Transform2D {
-            translation: self.current_position,
-            scale: 1.0,
-            rotation: 0.0,
-        };
+    // Sets this frame's place on the color wheel
+    pub fn update_rainbow(&mut self, time: f32) {
+        if let Some(rainbow) = &self.rainbow {
+            self.rainbow_phase = (time * rainbow.rate_hz).rem_euclid(1.0);
+        }
+    }
 
-        // Apply each transform in sequence
-        self.grid.apply_transform(&to_local);
-        self.grid.apply_transform(&scale);
-        self.grid.apply_transform(&to_world);
+    pub fn has_rainbow(&self) -> bool {
+        self.rainbow.is_some()
+    }
 
-        // Scale current and any future stroke weights
-        self.grid.scale_stroke_weights(scale_factor);
-        self.backbone_style.stroke_weight *= scale_factor;
-        self.target_style.stroke_weight *= scale_factor;
+    // Each segment's center in grid-local space
+    fn local_positions(&self) -> Vec<(SegmentKey, Vec2)> {
+        self.grid
+            .segments
+            .values()
+            .map(|segment| {
+                (
+                    segment.id.clone(),
+                    find_average_point(&segment.draw_commands),
+                )
+            })
+            .collect()
+    }
 
-        // Update scale state
-        self.current_scale = safe_scale;
+    // Local positions from 0.0 at the bottom left corner, scaled so the
+    // grid's longest side runs to 1.0
+    fn local_unit_positions(&self) -> HashMap<SegmentKey, Vec2> {
+        let positions = self.local_positions();
+        let min = positions
+            .iter()
+            .fold(Vec2::splat(f32::MAX), |min, (_, p)| min.min(*p));
+        let max = positions
+            .iter()
+            .fold(Vec2::splat(f32::MIN), |max, (_, p)| max.max(*p));
+        let span = (max - min).max_element().max(f32::EPSILON);
+        positions
+            .into_iter()
+            .map(|(segment_id, position)| (segment_id, (position - min) / span))
+            .collect()
     }
 
-    // Sets up a Movement over a specified duration
-    pub fn stage_movement(
-        &mut self,
-        target_x: f32,
-        target_y: f32,
-        duration: f32,
-        engine: &MovementEngine,
-        time: f32,
-    ) {
-        // If duration is specified, use the existing MovementEngine
-        if duration > 0.0 {
-            self.active_movement = Some(Box::new(
-                engine.build_timed_movement(self, target_x, target_y),
-            ));
-        } else {
-            // For immediate movements (duration = 0.0), use time-based interpolation
-            self.active_movement = Some(Box::new(engine.build_zero_duration_movement(
-                pt2(target_x, target_y),
-                self.current_position,
-                time,
-            )));
-        }
+    // Each segment's position along `axis` in grid-local space, from 0.0 at the
+    // left or bottom of the grid to 1.0 at the right or top
+    fn local_axis_positions(&self, axis: Axis) -> HashMap<SegmentKey, f32> {
+        let positions: Vec<(SegmentKey, f32)> = self
+            .local_positions()
+            .into_iter()
+            .map(|(segment_id, local)| {
+                let position = match axis {
+                    Axis::X => local.x,
+                    Axis::Y => local.y,
+                };
+                (segment_id, position)
+            })
+            .collect();
+
+        let min = positions.iter().map(|(_, p)| *p).fold(f32::MAX, f32::min);
+        let max = positions.iter().map(|(_, p)| *p).fold(f32::MIN, f32::max);
+        let span = (max - min).max(f32::EPSILON);
+        positions
+            .into_iter()
+            .map(|(segment_id, position)| (segment_id, (position - min) / span))
+            .collect()
     }
 
-    fn advance_movement(&mut self, time: f32, dt: f32) -> Option<MovementChange> {
-        let movement = self.active_movement.as_mut().unwrap();
+    // The style an active segment rests at before effects: target_style,
+    // colored by its PerSegment color, the rainbow or the gradient when set
+    fn active_base_style(&self, segment_id: &SegmentKey) -> DrawStyle {
+        if let Some(&color) = self.segment_colors.get(segment_id) {
+            return DrawStyle {
+                color,
+                stroke_weight: self.target_style.stroke_weight,
+            };
+        }
+
+        if let (Some(rainbow), Some(&x)) = (&self.rainbow, self.rainbow_positions.get(segment_id)) {
+            let base = Hsla::from(self.target_style.color);
+            let hue = (self.rainbow_phase + rainbow.spatial_spread * x) * 360.0;
+            return DrawStyle {
+                color: Rgba::from(Hsla::new(hue, base.saturation, base.lightness, base.alpha)),
+                stroke_weight: self.target_style.stroke_weight,
+            };
+        }
+
+        if let (Some(bands), Some(segment)) = (&self.bands, self.grid.segment(segment_id)) {
+            let (x, y) = segment.tile_coordinate;
+            let tile = match bands.axis {
+                Axis::X => x,
+                Axis::Y => y,
+            };
+            let color = if (tile / bands.width) % 2 == 0 {
+                bands.color_a
+            } else {
+                bands.color_b
+            };
+            return DrawStyle {
+                color,
+                stroke_weight: self.target_style.stroke_weight,
+            };
+        }
+
+        let (Some(gradient), Some(&t)) = (&self.gradient, self.gradient_positions.get(segment_id))
+        else {
+            return self.target_style.clone();
+        };
+
+        let (a, b) = (gradient.color_a, gradient.color_b);
+        DrawStyle {
+            color: rgba(
+                a.red + (b.red - a.red) * t,
+                a.green + (b.green - a.green) * t,
+                a.blue + (b.blue - a.blue) * t,
+                a.alpha + (b.alpha - a.alpha) * t,
+            ),
+            stroke_weight: self.target_style.stroke_weight,
+        }
+    }
+
+    fn apply_active_base_styles(&mut self, segments: &HashSet<SegmentKey>) {
+        if self.gradient.is_none()
+            && self.bands.is_none()
+            && self.rainbow.is_none()
+            && self.segment_colors.is_empty()
+        {
+            return;
+        }
+        for segment_id in segments {
+            let style = self.active_base_style(segment_id);
+            if let Some(msg) = self.update_batch.get_mut(segment_id) {
+                msg.target_style = Some(style);
+            }
+        }
+    }
+
+    /*********************** Sparkles ******************************/
+
+    // None stops sparkling. Any sparkles still lit fade out.
+    pub fn set_sparkle(&mut self, sparkle: Option<SparkleEffect>) {
+        if let Some(mut old) = self.sparkle.take() {
+            let lit: HashSet<SegmentKey> = old.clear().into_iter().collect();
+            self.stage_sparkles_off(&lit);
+        }
+        self.sparkle = sparkle;
+    }
+
+    fn is_sparkling(&self, segment_id: &SegmentKey) -> bool {
+        self.sparkle
+            .as_ref()
+            .is_some_and(|sparkle| sparkle.is_sparkling(segment_id))
+    }
+
+    fn update_sparkles(&mut self, time: f32, dt: f32) {
+        let Some(sparkle) = self.sparkle.as_mut() else {
+            return;
+        };
+
+        // a glyph turning a sparkle on takes the segment over
+        for segment_id in &self.current_active_segments {
+            sparkle.forget(segment_id);
+        }
+
+        // hidden grids drop their sparkles on the spot
+        if !self.is_visible {
+            for segment_id in sparkle.clear() {
+                self.update_batch
+                    .entry(segment_id)
+                    .or_insert(StyleUpdateMsg {
+                        action: Some(SegmentAction::BackboneUpdate),
+                        target_style: Some(self.displayed_backbone_style.clone()),
+                        delay: None,
+                        envelope: None,
+                        layer: None,
+                    });
+            }
+            return;
+        }
+
+        let expired: HashSet<SegmentKey> = sparkle.expire(time).into_iter().collect();
+
+        let mut candidates: Vec<SegmentKey> = self
+            .grid
+            .segments
+            .values()
+            .filter(|segment| {
+                segment.is_idle()
+                    && !self.current_active_segments.contains(&segment.id)
+                    && !self.update_batch.contains_key(&segment.id)
+                    && !expired.contains(&segment.id)
+            })
+            .map(|segment| segment.id.clone())
+            .collect();
+        candidates.sort();
+
+        let style = DrawStyle {
+            color: sparkle.color,
+            stroke_weight: self.target_style.stroke_weight,
+        };
+        // on the overlay, clear of the glyph's foreground
+        for segment_id in sparkle.spawn(&candidates, dt, time) {
+            self.update_batch.insert(
+                segment_id,
+                StyleUpdateMsg {
+                    action: Some(SegmentAction::On),
+                    target_style: Some(style.clone()),
+                    delay: None,
+                    envelope: Some(SPARKLE_ENVELOPE),
+                    layer: Some(Layer::Overlay),
+                },
+            );
+        }
+
+        self.stage_sparkles_off(&expired);
+    }
+
+    fn stage_sparkles_off(&mut self, segments: &HashSet<SegmentKey>) {
+        for segment_id in segments {
+            if self.current_active_segments.contains(segment_id) {
+                continue;
+            }
+            self.update_batch
+                .entry(segment_id.clone())
+                .or_insert(StyleUpdateMsg {
+                    action: Some(SegmentAction::Off),
+                    target_style: Some(self.displayed_backbone_style.clone()),
+                    delay: None,
+                    envelope: Some(SPARKLE_ENVELOPE),
+                    layer: Some(Layer::Overlay),
+                });
+        }
+    }
+
+    /*********************** Chase ******************************/
+
+    // Endpoints of None are picked at random. Fails when either segment
+    // doesn't exist or no path joins them.
+    pub fn start_chase(
+        &mut self,
+        start: Option<&str>,
+        end: Option<&str>,
+        speed: f32,
+        width: usize,
+        time: f32,
+        mut rng: StdRng,
+    ) -> Result<(), String> {
+        let key = |segment_id: Option<&str>| match segment_id {
+            None => Ok(None),
+            Some(segment_id) => match self.grid.key(segment_id) {
+                Some(key) if self.grid.segments.contains_key(&key) => Ok(Some(key)),
+                _ => Err(format!("Grid {} has no segment {}", self.id, segment_id)),
+            },
+        };
+        let (start, end) = (key(start)?, key(end)?);
+
+        let mut segment_ids: Vec<&SegmentKey> = self.grid.segments.keys().collect();
+        segment_ids.sort();
+
+        // random ends may land on unconnected segments, so have a few goes
+        let tries = if start.is_none() || end.is_none() {
+            MAX_RANDOM_CHASE_TRIES
+        } else {
+            1
+        };
+        let path = (0..tries).find_map(|_| {
+            let start = start
+                .as_ref()
+                .unwrap_or_else(|| segment_ids[rng.gen_range(0..segment_ids.len())]);
+            let end = end
+                .as_ref()
+                .unwrap_or_else(|| segment_ids[rng.gen_range(0..segment_ids.len())]);
+            self.graph()
+                .find_path(start, end)
+                .filter(|path| path.len() > 1 || tries == 1)
+        });
+
+        let Some(path) = path else {
+            return Err(format!("No chase path found on grid {}", self.id));
+        };
+        self.chase = Some(ChaseEffect::new(path, speed, width, time));
+        Ok(())
+    }
+
+    pub fn stop_chase(&mut self) {
+        self.chase = None;
+        let lit = std::mem::take(&mut self.chase_lit);
+        self.stage_chase_off(&lit);
+    }
+
+    // Lights the chase's window in the glyph color and puts out what it left.
+    // Segments in the glyph are the glyph's, so the chase passes over them.
+    // The window is lit on the overlay, above every grid's glyph.
+    fn update_chase(&mut self, time: f32) {
+        let Some(chase) = &self.chase else {
+            return;
+        };
+        let window: HashSet<SegmentKey> = chase
+            .window(time)
+            .iter()
+            .filter(|segment_id| !self.current_active_segments.contains(*segment_id))
+            .cloned()
+            .collect();
+
+        for segment_id in &window {
+            let unlit = self
+                .grid
+                .segment(segment_id)
+                .is_some_and(|segment| !segment.is_lit());
+            if unlit && !self.update_batch.contains_key(segment_id) {
+                self.update_batch.insert(
+                    segment_id.clone(),
+                    StyleUpdateMsg {
+                        layer: Some(Layer::Overlay),
+                        ..StyleUpdateMsg::new(
+                            SegmentAction::InstantStyleChange,
+                            self.target_style.clone(),
+                        )
+                    },
+                );
+            }
+        }
+
+        let left: HashSet<SegmentKey> = self.chase_lit.difference(&window).cloned().collect();
+        self.chase_lit = window;
+        self.stage_chase_off(&left);
+    }
+
+    fn stage_chase_off(&mut self, segments: &HashSet<SegmentKey>) {
+        for segment_id in segments {
+            if self.current_active_segments.contains(segment_id) || self.is_sparkling(segment_id) {
+                continue;
+            }
+            self.update_batch
+                .entry(segment_id.clone())
+                .or_insert(StyleUpdateMsg {
+                    action: Some(SegmentAction::Off),
+                    target_style: Some(self.displayed_backbone_style.clone()),
+                    delay: None,
+                    envelope: Some(SPARKLE_ENVELOPE),
+                    layer: Some(Layer::Overlay),
+                });
+        }
+    }
+
+    /*********************** Heatmap ******************************/
+
+    // Decay is kept when None, so the heatmap can be toggled on its own
+    pub fn set_heatmap(&mut self, enabled: bool, decay: Option<f32>) {
+        if let Some(decay) = decay {
+            self.heatmap_decay = decay.max(MIN_HEATMAP_DECAY);
+        }
+        if self.heatmap && !enabled {
+            // backbone segments are restaged every frame, so only the
+            // glyph needs its colors back
+            self.restyle_active_segments();
+        }
+        self.heatmap = enabled;
+    }
+
+    pub fn is_heatmap(&self) -> bool {
+        self.heatmap
+    }
+
+    // 1.0 for segments in the glyph, falling to 0.0 over the decay time
+    // after they leave it. Segments never lit are cold.
+    fn segment_heat(&self, segment_id: &SegmentKey, time: f32) -> f32 {
+        if self.current_active_segments.contains(segment_id) {
+            return 1.0;
+        }
+        self.last_active_time
+            .get(segment_id)
+            .map_or(0.0, |&last| 1.0 - (time - last) / self.heatmap_decay)
+            .clamp(0.0, 1.0)
+    }
+
+    // Glyph segments are restyled once settled, so power-on animations still
+    // play. Anything already staged this frame by a transition is left alone.
+    fn stage_heatmap_updates(&mut self, time: f32) {
+        let mut updates = Vec::new();
+        for (segment_id, segment) in &self.grid.segments {
+            let staged = self.update_batch.get(segment_id).map(|msg| &msg.action);
+            let (action, stroke_weight) = if segment.is_active()
+                && self.current_active_segments.contains(segment_id)
+                && matches!(staged, None | Some(Some(SegmentAction::InstantStyleChange)))
+            {
+                (
+                    SegmentAction::InstantStyleChange,
+                    self.target_style.stroke_weight,
+                )
+            } else if segment.is_background() && segment.is_idle() && staged.is_none() {
+                (
+                    SegmentAction::BackboneUpdate,
+                    self.displayed_backbone_style.stroke_weight,
+                )
+            } else {
+                continue;
+            };
+            let style = DrawStyle {
+                color: heat_color(self.segment_heat(segment_id, time)),
+                stroke_weight,
+            };
+            updates.push((segment_id.clone(), StyleUpdateMsg::new(action, style)));
+        }
+        self.update_batch.extend(updates);
+    }
+
+    /*********************** Graph debug overlay ******************************/
+
+    pub fn set_graph_debug(&mut self, enabled: bool, labels: bool) {
+        self.graph_debug = enabled;
+        self.graph_debug_labels = labels;
+    }
+
+    pub fn is_graph_debug(&self) -> bool {
+        self.graph_debug
+    }
+
+    // A line between the midpoints of every two connected segments and a dot
+    // where they meet. The graph is in the grid's local space, so connection
+    // points go through the current transform; midpoints come from the
+    // segments as drawn, so they follow slides too.
+    fn draw_graph_debug(&self, draw: &Draw) {
+        let graph = self.graph();
+        let transform = self.transform();
+        let midpoint = |id: &SegmentKey| {
+            self.grid.segment(id).map(|segment| {
+                self.grid
+                    .segment_to_world(segment)
+                    .transform_point2(find_average_point(&segment.draw_commands))
+            })
+        };
+
+        let mut ids: Vec<&SegmentKey> = self.grid.segments.keys().collect();
+        ids.sort();
+        for id in ids {
+            let Some(start) = midpoint(id) else {
+                continue;
+            };
+            for neighbor in graph.neighbors(id).iter().filter(|n| id < *n) {
+                if let Some(end) = midpoint(neighbor) {
+                    draw.line()
+                        .points(start, end)
+                        .color(rgba(0.0, 0.8, 1.0, 0.6))
+                        .stroke_weight(1.0);
+                }
+                if let Some(point) = graph.connection_point(id, neighbor) {
+                    draw.ellipse()
+                        .xy(transform.apply_to_point(point))
+                        .radius(GRAPH_DEBUG_POINT_RADIUS)
+                        .color(rgba(1.0, 0.8, 0.0, 1.0));
+                }
+            }
+            if self.graph_debug_labels {
+                draw.text(&graph.degree(id).to_string())
+                    .xy(start)
+                    .font_size(10)
+                    .color(WHITE);
+            }
+        }
+    }
+
+    /*********************** Tile mask ******************************/
+
+    pub fn set_tile_visible(&mut self, x: u32, y: u32, visible: bool) {
+        if visible {
+            self.tile_mask.remove(&(x, y));
+        } else {
+            self.tile_mask.insert((x, y));
+        }
+    }
+
+    // Every tile between two corner tiles, the corners included
+    pub fn set_tile_rect_visible(&mut self, from: (u32, u32), to: (u32, u32), visible: bool) {
+        for x in from.0.min(to.0)..=from.0.max(to.0) {
+            for y in from.1.min(to.1)..=from.1.max(to.1) {
+                self.set_tile_visible(x, y, visible);
+            }
+        }
+    }
+
+    pub fn clear_tile_mask(&mut self) {
+        self.tile_mask.clear();
+    }
+
+    pub fn is_tile_visible(&self, x: u32, y: u32) -> bool {
+        !self.tile_mask.contains(&(x, y))
+    }
+
+    /*********************** Colorful mode ******************************/
+
+    // Switching to PerSegment colors the lit segments right away; switching
+    // back to Uniform returns them to target_style
+    pub fn set_colorful_mode(&mut self, mode: ColorfulMode, rng: StdRng) {
+        self.colorful_mode = mode;
+        self.colorful_rng = rng;
+
+        let lit: HashSet<SegmentKey> = match mode {
+            ColorfulMode::PerSegment if self.colorful_flag => {
+                let uncolored = self
+                    .current_active_segments
+                    .iter()
+                    .filter(|segment_id| !self.segment_colors.contains_key(*segment_id))
+                    .cloned()
+                    .collect();
+                self.roll_segment_colors(&uncolored);
+                uncolored
+            }
+            ColorfulMode::PerSegment => HashSet::new(),
+            ColorfulMode::Uniform => self.segment_colors.drain().map(|(id, _)| id).collect(),
+        };
+
+        for segment_id in lit {
+            if self.current_active_segments.contains(&segment_id)
+                && !self.segment_color_overrides.contains_key(&segment_id)
+            {
+                let style = self.active_base_style(&segment_id);
+                self.update_batch.insert(
+                    segment_id,
+                    StyleUpdateMsg::new(SegmentAction::InstantStyleChange, style),
+                );
+            }
+        }
+    }
+
+    pub fn colorful_mode(&self) -> ColorfulMode {
+        self.colorful_mode
+    }
+
+    // Seeded from the transition engine, so offline renders color alike
+    pub fn set_colorful_rng(&mut self, rng: StdRng) {
+        self.colorful_rng = rng;
+    }
+
+    // Same range of colors as the Uniform mode picks from.
+    // Sorted so a seeded rng gives every segment the same color each run.
+    fn roll_segment_colors(&mut self, segments: &HashSet<SegmentKey>) {
+        let mut segment_ids: Vec<&SegmentKey> = segments.iter().collect();
+        segment_ids.sort();
+
+        for segment_id in segment_ids {
+            let color = Rgba::from(hsla(
+                self.colorful_rng.gen_range(0.0..=1.0),
+                self.colorful_rng.gen_range(0.2..=1.0),
+                0.4,
+                1.0,
+            ));
+            self.segment_colors.insert(segment_id.clone(), color);
+        }
+    }
+
+    /*********************** Segment color overrides ******************************/
+
+    // Record an override for a single segment. If the segment is lit, it changes now,
+    // otherwise the override stays dormant until a glyph turns it on.
+    pub fn set_segment_color_override(&mut self, segment_id: &str, color: Rgba<f32>) {
+        match self.grid.key(segment_id) {
+            Some(key) if self.grid.segments.contains_key(&key) => {
+                self.override_segment_color(key, color)
+            }
+            _ => println!(
+                "Warning: unknown segment '{}' on grid {}. Valid tile coordinates are 1,1 to {},{} (ids look like \"x,y : element\")",
+                segment_id, self.id, self.grid.dimensions.0, self.grid.dimensions.1
+            ),
+        }
+    }
+
+    fn override_segment_color(&mut self, segment_id: SegmentKey, color: Rgba<f32>) {
+        let style = DrawStyle {
+            color,
+            stroke_weight: self.target_style.stroke_weight,
+        };
+
+        if self.current_active_segments.contains(&segment_id) {
+            self.update_batch.insert(
+                segment_id.clone(),
+                StyleUpdateMsg::new(SegmentAction::InstantStyleChange, style.clone()),
+            );
+        }
+        self.segment_color_overrides.insert(segment_id, style);
+    }
+
+    // Clear one override, or all of them when no segment is given.
+    // Lit segments return to target_style, or the gradient if one is set.
+    pub fn clear_segment_color_overrides(&mut self, segment_id: Option<&str>) {
+        let cleared: Vec<SegmentKey> = match segment_id {
+            Some(id) => self
+                .grid
+                .key(id)
+                .and_then(|key| self.segment_color_overrides.remove_entry(&key))
+                .map(|(id, _)| id)
+                .into_iter()
+                .collect(),
+            None => self
+                .segment_color_overrides
+                .drain()
+                .map(|(id, _)| id)
+                .collect(),
+        };
+
+        for segment_id in cleared {
+            if self.current_active_segments.contains(&segment_id) {
+                let style = self.active_base_style(&segment_id);
+                self.update_batch.insert(
+                    segment_id,
+                    StyleUpdateMsg::new(SegmentAction::InstantStyleChange, style),
+                );
+            }
+        }
+    }
+
+    // Swap in the override style for any staged segments that have one
+    fn apply_segment_color_overrides(&mut self, segments: &HashSet<SegmentKey>) {
+        for segment_id in segments {
+            if let Some(style) = self.segment_color_overrides.get(segment_id) {
+                if let Some(msg) = self.update_batch.get_mut(segment_id) {
+                    msg.target_style = Some(style.clone());
+                }
+            }
+        }
+    }
+
+    // process OSC /grid/transitiontrigger
+    pub fn receive_transition_trigger(&mut self) {
+        match self.transition_trigger_type {
+            TransitionTriggerType::Auto => {
+                self.transition_trigger_type = TransitionTriggerType::Manual;
+                if self.has_active_transition() {
+                    self.transition_trigger_received = true;
+                }
+            }
+            TransitionTriggerType::Manual => {
+                if self.has_active_transition() {
+                    self.transition_trigger_received = true;
+                }
+            }
+        }
+    }
+
+    // process OSC /grid/transitionpause
+    pub fn set_transition_paused(&mut self, paused: bool) {
+        self.transition_paused = paused;
+        if let Some(transition) = self.active_transition.as_mut() {
+            if paused {
+                transition.pause();
+            } else {
+                transition.resume();
+            }
+        }
+    }
+
+    pub fn is_transition_paused(&self) -> bool {
+        self.transition_paused
+    }
+
+    // process OSC /grid/speed
+    pub fn set_transition_speed(&mut self, speed: f32) {
+        let clamped = speed.clamp(MIN_TRANSITION_SPEED, MAX_TRANSITION_SPEED);
+        if clamped != speed {
+            println!(
+                "Transition speed {} out of range for grid '{}', using {}",
+                speed, self.id, clamped
+            );
+        }
+        self.transition_speed = clamped;
+    }
+
+    pub fn transition_speed(&self) -> f32 {
+        self.transition_speed
+    }
+
+    /**************************** Grid movement & transform **********************************/
+
+    // Takes the grid's local space, centered on the origin, to where it is now
+    pub fn transform(&self) -> Transform2D {
+        Transform2D {
+            translation: self.current_position,
+            scale: self.current_scale,
+            rotation: self.current_rotation,
+        }
+    }
+
+    // What stroke weights and other sizes without a direction scale by
+    pub fn mean_scale(&self) -> f32 {
+        self.transform().mean_scale()
+    }
+
+    // Segments within `radius` of a world point, e.g. under the mouse. A
+    // squashed grid is searched with its least scale, so this can include
+    // a few extra.
+    pub fn segments_near(&self, point: Point2, radius: f32) -> Vec<SegmentKey> {
+        let transform = self.transform();
+        self.grid.spatial_index.segments_near(
+            transform.invert_point(point),
+            radius / transform.scale.min_element(),
+        )
+    }
+
+    // The segment whose draw commands pass closest to a world point, if any
+    // come within `tolerance`
+    pub fn segment_at(&self, point: Point2, tolerance: f32) -> Option<SegmentKey> {
+        self.hit_test(point, tolerance).map(|(id, _)| id)
+    }
+
+    // Like segment_at, with the segment's distance from the point. The
+    // spatial index only knows where segments sit before the instance
+    // transform, so while a slide or jitter has moved some of them, the
+    // segments are scanned where they're drawn instead. So are squashed
+    // grids, where local distances don't scale evenly.
+    pub fn hit_test(&self, point: Point2, tolerance: f32) -> Option<(SegmentKey, f32)> {
+        let index_is_current = self.jitter.is_none()
+            && self.current_scale.x == self.current_scale.y
+            && !self.has_slide_animations()
+            && self.slide_wrap_offsets.is_empty()
+            && self
+                .slide_offsets()
+                .iter()
+                .all(|(_, _, offset)| *offset == 0.0);
+        if index_is_current {
+            let transform = self.transform();
+            return self
+                .grid
+                .spatial_index
+                .nearest(transform.invert_point(point), tolerance / transform.scale.x)
+                .map(|(id, distance)| (id, distance * transform.scale.x));
+        }
+
+        self.grid
+            .segments
+            .values()
+            .map(|segment| {
+                let commands = self.grid.world_commands(segment);
+                let distance = segment_analysis::distance_to_point(&commands, point);
+                (&segment.id, distance)
+            })
+            .filter(|(_, distance)| *distance <= tolerance)
+            .min_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)))
+            .map(|(id, distance)| (id.clone(), distance))
+    }
+
+    // Segments overlapping a world rect. A rotated grid is searched over the
+    // local bounds of the rect's corners, so this can include a few extra.
+    pub fn segments_in_rect(&self, rect: Rect) -> Vec<SegmentKey> {
+        let transform = self.transform();
+        let corners = rect
+            .corners_iter()
+            .map(|[x, y]| transform.invert_point(pt2(x, y)));
+        let (min, max) = corners.fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), corner| (min.min(corner), max.max(corner)),
+        );
+        self.grid
+            .spatial_index
+            .segments_in_rect(Rect::from_corners(min, max))
+    }
+
+    // World-space bounds of everything the grid draws, stretch segments and
+    // slid rows and columns included
+    pub fn bounding_box(&self) -> Rect {
+        self.grid
+            .stretch_segments
+            .values()
+            .filter_map(|segment| self.grid.segment_bounds(segment))
+            .fold(self.grid.bounding_box(), Rect::max)
+    }
+
+    // Moves the grid so its bounding box is centered on `point`
+    pub fn center_on(&mut self, point: Point2) {
+        self.move_by(point - self.bounding_box().xy());
+    }
+
+    // Moves the grid right away, without a movement animation
+    pub fn move_by(&mut self, offset: Vec2) {
+        self.apply_transform(&Transform2D {
+            translation: offset,
+            scale: Vec2::ONE,
+            rotation: 0.0,
+        });
+    }
+
+    // How close endpoints must be to connect on this grid as it's drawn
+    pub fn connection_threshold(&self) -> f32 {
+        self.base_graph.threshold() * self.mean_scale()
+    }
+
+    // The segments meeting at a world point, e.g. a junction under the mouse
+    pub fn segments_at_point(&self, point: Point2) -> Vec<SegmentKey> {
+        let transform = self.transform();
+        segment_analysis::find_connected_segments_at_point(
+            &self.base_graph,
+            transform.invert_point(point),
+            self.connection_threshold() / transform.mean_scale(),
+        )
+    }
+
+    pub fn rotate_in_place(&mut self, angle: f32) {
+        // Update location's rotation (but not position)
+        self.current_rotation = angle;
+        self.place_grid();
+    }
+
+    // Scales both axes to `new_scale`, undoing any squash
+    pub fn scale_in_place(&mut self, new_scale: f32) {
+        self.scale_xy_in_place(Vec2::splat(new_scale));
+    }
+
+    // Scales the grid along its own axes about its position. A rotated grid
+    // is squashed along its rotated axes, not the screen's.
+    pub fn scale_xy_in_place(&mut self, new_scale: Vec2) {
+        // clamp scale values to a minimum of 0.001
+        let safe_scale = new_scale.max(Vec2::splat(0.001));
+
+        let scale_factor = safe_scale / self.current_scale;
+        self.current_scale = safe_scale;
+        self.place_grid();
+        self.grid.fit_arc_resolution();
+
+        // Scale current and any future stroke weights by the mean
+        let scale_factor = (scale_factor.x * scale_factor.y).sqrt();
+        self.grid.scale_stroke_weights(scale_factor);
+        self.backbone_style.stroke_weight *= scale_factor;
+        if let Some(style) = &mut self.backbone_style_before_hide {
+            style.stroke_weight *= scale_factor;
+        }
+        self.target_style.stroke_weight *= scale_factor;
+        for style in self.segment_color_overrides.values_mut() {
+            style.stroke_weight *= scale_factor;
+        }
+        if let Some(outline) = &mut self.outline {
+            outline.extra_weight *= scale_factor;
+        }
+    }
+
+    // Hands the grid where to draw its segments: the instance transform
+    // with any jitter on top
+    fn place_grid(&mut self) {
+        let mut transform = self.transform();
+        if let Some(jitter) = &self.jitter {
+            transform.translation += jitter.current_offset();
+        }
+        self.grid.set_transform(transform);
+    }
+
+    // Sets up a Movement over a specified duration
+    pub fn stage_movement(
+        &mut self,
+        target_x: f32,
+        target_y: f32,
+        duration: f32,
+        engine: &MovementEngine,
+        time: f32,
+    ) {
+        // any queued leg in progress is abandoned
+        self.active_leg = None;
+
+        // If duration is specified, use the existing MovementEngine
+        if duration > 0.0 {
+            self.active_movement = Some(Box::new(
+                engine.build_timed_movement(self, target_x, target_y),
+            ));
+        } else {
+            // For immediate movements (duration = 0.0), interpolate over the
+            // time until the next position command is expected
+            let duration = self.track_instant_move_interval(time);
+            self.active_movement = Some(Box::new(engine.build_zero_duration_movement(
+                pt2(target_x, target_y),
+                self.current_position,
+                time,
+                duration,
+            )));
+        }
+    }
+
+    // Smoothed interval between consecutive zero-duration moves. A long gap
+    // means the stream of commands restarted, so the default is used.
+    fn track_instant_move_interval(&mut self, time: f32) -> f32 {
+        if let Some(last_time) = self.last_instant_move_time {
+            let interval = time - last_time;
+            if interval > 0.0 && interval <= MAX_INSTANT_MOVE_INTERVAL {
+                self.instant_move_interval = (self.instant_move_interval + interval) / 2.0;
+            } else if interval > MAX_INSTANT_MOVE_INTERVAL {
+                self.instant_move_interval = DEFAULT_INSTANT_MOVE_DURATION;
+            }
+        }
+        self.last_instant_move_time = Some(time);
+        self.instant_move_interval
+    }
+
+    // Sets up a Movement along a curved path through the waypoints
+    pub fn stage_path_movement(&mut self, waypoints: &[Point2], duration: f32, time: f32) {
+        if waypoints.is_empty() {
+            return;
+        }
+        self.active_leg = None;
+        self.active_movement = Some(Box::new(PathMovement::new(
+            self.current_position,
+            waypoints,
+            duration,
+            time,
+        )));
+    }
+
+    fn update_movement(&mut self, time: f32, dt: f32) {
+        if self.has_active_movement() {
+            if let Some(change) = self.advance_movement(time, dt) {
+                self.apply_movement_change(&change);
+            }
+        }
+
+        // start the next queued leg as soon as the grid is free
+        if !self.has_active_movement() {
+            self.start_next_leg(time);
+        }
+    }
+
+    fn advance_movement(&mut self, time: f32, dt: f32) -> Option<MovementChange> {
+        let movement = self.active_movement.as_mut().unwrap();
+
+        if movement.should_update(dt) {
+            let movement_change = movement.advance(self.current_position, time);
+            if movement.is_complete() {
+                self.active_movement = None;
+                if let Some(leg) = self.active_leg.take() {
+                    self.completed_legs.push((leg, self.movement_queue.len()));
+                }
+            }
+            movement_change
+        } else {
+            None
+        }
+    }
+
+    // process OSC /grid/movequeue
+    pub fn queue_movements(&mut self, legs: Vec<MovementLeg>) {
+        if self.movement_queue.is_empty() && self.active_leg.is_none() {
+            self.legs_started = 0;
+        }
+        self.movement_queue.extend(legs);
+    }
+
+    pub fn clear_movement_queue(&mut self) {
+        self.movement_queue.clear();
+        self.active_leg = None;
+    }
+
+    pub fn has_queued_movements(&self) -> bool {
+        !self.movement_queue.is_empty()
+    }
+
+    // Legs that finished since the last call, as (leg number, legs remaining)
+    pub fn take_completed_legs(&mut self) -> Vec<(usize, usize)> {
+        std::mem::take(&mut self.completed_legs)
+    }
+
+    fn start_next_leg(&mut self, time: f32) {
+        let Some(leg) = self.movement_queue.pop_front() else {
+            return;
+        };
+
+        let engine = MovementEngine::new(MovementConfig {
+            duration: leg.duration,
+            easing: leg.easing,
+        });
+        self.stage_movement(leg.target.x, leg.target.y, leg.duration, &engine, time);
+
+        self.legs_started += 1;
+        self.active_leg = Some(self.legs_started);
+    }
+
+    fn apply_movement_change(&mut self, change: &MovementChange) {
+        self.apply_transform(&change.transform);
+    }
+
+    fn apply_transform(&mut self, transform: &Transform2D) {
+        // movements only ever translate
+        self.current_position += transform.translation;
+        self.place_grid();
+    }
+
+    // go back to where grid spawned
+    pub fn reset_location(&mut self) {
+        let transform = Transform2D {
+            translation: self.spawn_location - self.current_position,
+            scale: Vec2::ONE,
+            rotation: 0.0,
+        };
+        self.apply_transform(&transform);
+    }
+
+    /**************************** Keyframes *****************************/
+
+    // process OSC /grid/keyframes. The track takes over position, rotation
+    // and scale, so any movement in progress or queued is dropped.
+    pub fn stage_keyframes(&mut self, keyframes: Vec<Keyframe>, time: f32) {
+        self.active_movement = None;
+        self.clear_movement_queue();
+        self.stop_orbit();
+
+        let initial = KeyframeSample {
+            position: self.current_position,
+            rotation: self.current_rotation,
+            scale: self.mean_scale(),
+        };
+        self.keyframe_track = Some(KeyframeTrack::new(initial, keyframes, time));
+    }
+
+    // Any other transform command stops the track where it is
+    pub fn stop_keyframes(&mut self) {
+        self.keyframe_track = None;
+    }
+
+    pub fn has_keyframe_track(&self) -> bool {
+        self.keyframe_track.is_some()
+    }
+
+    fn update_keyframes(&mut self, time: f32) {
+        let Some(track) = &self.keyframe_track else {
+            return;
+        };
+        let sample = track.sample(time);
+        let complete = track.is_complete(time);
+
+        // keyframes scale the grid's size, keeping any squash
+        let scale = self.current_scale * sample.scale / self.mean_scale();
+        self.move_to_pose(sample.position, sample.rotation, scale);
+
+        if complete {
+            // land exactly on the last keyframe
+            self.current_position = sample.position;
+            self.keyframe_track = None;
+        }
+    }
+
+    // Applies the deltas to reach an absolute position, rotation and scale.
+    // Moves first so rotation and scale pivot on the new position.
+    fn move_to_pose(&mut self, position: Point2, rotation: f32, scale: Vec2) {
+        let translation = position - self.current_position;
+        if translation != Vec2::ZERO {
+            self.apply_transform(&Transform2D {
+                translation,
+                scale: Vec2::ONE,
+                rotation: 0.0,
+            });
+        }
+        if rotation != self.current_rotation {
+            self.rotate_in_place(rotation);
+        }
+        if scale != self.current_scale {
+            self.scale_xy_in_place(scale);
+        }
+    }
+
+    /**************************** Orbit *****************************/
+
+    // process OSC /grid/orbit. Like keyframes, the orbit takes over the
+    // grid's position from any other movement.
+    pub fn stage_orbit(
+        &mut self,
+        center: Point2,
+        radius: Option<f32>,
+        degrees_per_second: f32,
+        duration: f32,
+        face_path: bool,
+        time: f32,
+    ) {
+        self.active_movement = None;
+        self.clear_movement_queue();
+        self.stop_keyframes();
+
+        self.orbit = Some(OrbitAnimation::new(
+            center,
+            radius,
+            degrees_per_second,
+            duration,
+            face_path,
+            self.current_position,
+            self.current_rotation,
+            time,
+        ));
+    }
+
+    // The grid stays wherever the orbit left it
+    pub fn stop_orbit(&mut self) {
+        self.orbit = None;
+    }
+
+    pub fn has_orbit(&self) -> bool {
+        self.orbit.is_some()
+    }
+
+    fn update_orbit(&mut self, time: f32) {
+        let Some(orbit) = &self.orbit else {
+            return;
+        };
+        let (position, rotation) = orbit.sample(time);
+        let complete = orbit.is_complete(time);
+
+        self.move_to_pose(position, rotation, self.current_scale);
+
+        if complete {
+            self.orbit = None;
+        }
+    }
+
+    // Called by other transform commands so they take over from the current pose
+    pub fn stop_transform_animations(&mut self) {
+        self.stop_keyframes();
+        self.stop_orbit();
+    }
+
+    /**************************** Jitter Effect *****************************/
+
+    // Start a positional shake. Any jitter already in progress is removed first
+    // so the offsets never accumulate.
+    pub fn stage_jitter(
+        &mut self,
+        amplitude: f32,
+        frequency: f32,
+        duration: f32,
+        time: f32,
+        rng: StdRng,
+    ) {
+        self.jitter = Some(JitterAnimation::new(
+            amplitude, frequency, duration, time, rng,
+        ));
+        self.place_grid();
+    }
+
+    // The jitter offset only moves where the segments are drawn.
+    // current_position is left alone, so movements keep interpolating from
+    // the logical position and the grid lands exactly where it was when the
+    // jitter ends.
+    fn update_jitter(&mut self, time: f32) {
+        let jitter = self.jitter.as_mut().unwrap();
+        let moved = jitter.advance(time).is_some();
+
+        if jitter.is_complete() {
+            self.jitter = None;
+        }
+        if moved {
+            self.place_grid();
+        }
+    }
+
+    /**************************** WIP Stretch Effect *****************************/
+    pub fn stretch(&mut self, axis: Axis, target_amount: f32, start_time: f32) {
+        self.end_stretch();
+        let stretch_animation = StretchAnimation::new(
+            &mut self.grid,
+            &self.base_graph,
+            &mut self.graph_delta,
+            axis,
+            target_amount,
+            start_time,
+        );
+        self.stretch_animation = Some(stretch_animation);
+    }
+
+    // Takes the stretch segments back out of the grid and the graph
+    pub fn end_stretch(&mut self) {
+        let Some(stretch_animation) = self.stretch_animation.take() else {
+            return;
+        };
+        for id in &stretch_animation.segment_ids {
+            self.grid.remove_stretch_segment(id);
+            self.graph_delta.remove_segment(id, &self.base_graph);
+        }
+    }
+
+    pub fn boundary_test(&mut self, axis: Axis) {
+        let mut boundary_segments = stretch::boundary_segments(&self.grid, axis);
+        let target_style = DrawStyle {
+            color: rgba(0.0, 1.0, 0.0, 1.0),
+            stroke_weight: 10.0,
+        };
+
+        // throw out the boundaries on the edge of the grid
+        boundary_segments
+            .retain(|id| !stretch::is_outer_boundary(&self.grid, self.grid.segment(id).unwrap()));
+
+        self.stage_segments_instant_on(&boundary_segments, &target_style);
+
+        let _neighbor_style = DrawStyle {
+            color: rgba(0.0, 0.0, 1.0, 1.0),
+            stroke_weight: 10.0,
+        };
+        let _active_neighbor_style = DrawStyle {
+            color: rgba(1.0, 1.0, 0.0, 1.0),
+            stroke_weight: 10.0,
+        };
+
+        let (mut neighbors, stretch_nodes) =
+            stretch::boundary_crossings(&self.grid, &self.base_graph, &boundary_segments, axis);
+        let stretch_points = stretch_nodes
+            .into_iter()
+            .filter_map(|node| self.base_graph.node_point(node));
+
+        // try putting a stretch segment at every stretch point
+        for point in stretch_points {
+            let _stretch_segment = CachedSegment::new(
+                SegmentKey::off_grid(format!("Stretch-{:?}", point)),
+                (9, 9),
+                &PathElement::Line {
+                    x1: point.x + self.current_position.x,
+                    x2: point.x + self.current_position.x + 50.0,
+                    y1: point.y,
+                    y2: point.y,
+                },
+                EdgeType::None,
+                &ViewBox {
+                    min_x: 0.0,
+                    min_y: 0.0,
+                    height: 0.0,
+                    width: 0.0,
+                },
+                (4, 4),
+            );
+
+            // track the stretch segment ids
+            //self.stretch_segments.insert(stretch_segment.id.clone());
+
+            // insert the streetch segments into the grid. grid now owns the segment.
+            //self.grid.add_stretch_segment(stretch_segment);
+        }
+
+        // clone the neighbors set for processing later
+        let mut active_neighbors = neighbors.clone();
+
+        // differentiate between active and non-active neighbors
+        active_neighbors.retain(|s| self.current_active_segments.contains(s));
+        neighbors.retain(|s| !active_neighbors.contains(s));
+
+        //self.stage_segments_instant_on(&neighbors, &neighbor_style);
+        //self.stage_segments_instant_on(&active_neighbors, &active_neighbor_style);
+    }
+
+    /**************************** Row/column Slide Effect *****************************/
+    // todo: refactor with the Animation trait?
+
+    pub fn slide(
+        &mut self,
+        axis: Axis,
+        index: i32,
+        position: f32,
+        duration: f32,
+        easing: EasingType,
+        wrap: bool,
+        time: f32,
+    ) {
+        let duration = duration.max(INSTANT_SLIDE_DURATION);
+
+        // wrapped slides never leave the grid, so they don't need clamping
+        let position = match self.slide_clamp {
+            Some(texture_size) if !wrap => self.clamp_slide_offset(axis, position, texture_size),
+            _ => position,
+        };
+
+        // Get current row/col positions
+        let positions = match axis {
+            Axis::X => &mut self.row_positions,
+            Axis::Y => &mut self.col_positions,
+        };
+
+        // Get current position (default to 0.0 if not set)
+        let current_position = *positions.get(&index).unwrap_or(&0.0);
+
+        // Update stored position
+        positions.insert(index, position);
+
+        // Find existing animation or create new
+        let existing_index = self
+            .slide_animations
+            .iter()
+            .position(|anim| anim.axis == axis && anim.index == index);
+
+        if let Some(idx) = existing_index {
+            // Update existing animation
+            let anim = &mut self.slide_animations[idx];
+            anim.start_position = anim.current_position;
+            anim.target_position = position;
+            anim.start_time = time;
+            anim.duration = duration;
+            anim.easing = easing;
+            anim.wrap = wrap;
+        } else {
+            // Create new animation
+            let animation = SlideAnimation {
+                axis,
+                index,
+                start_position: current_position,
+                current_position,
+                target_position: position,
+                start_time: time,
+                duration,
+                easing,
+                wrap,
+            };
+
+            self.slide_animations.push(animation);
+        }
+    }
+
+    fn update_slide_animations(&mut self, time: f32) {
+        let mut transforms_to_apply: Vec<(i32, Axis, Transform2D, bool)> = Vec::new();
+        let mut completed = Vec::new();
+        let mut returned_home: Vec<(i32, Axis)> = Vec::new();
+
+        // Calculate all transforms without applying them yet
+        for (i, animation) in self.slide_animations.iter_mut().enumerate() {
+            let elapsed = time - animation.start_time;
+            let progress = (elapsed / animation.duration).clamp(0.0, 1.0);
+
+            if progress < 1.0 {
+                // Calculate interpolated position
+                let eased_progress = animation.easing.apply(progress);
+                let new_position = animation.start_position
+                    + (animation.target_position - animation.start_position) * eased_progress;
+
+                // Calculate movement delta from last frame
+                let delta = new_position - animation.current_position;
+
+                // Create transform if there's significant movement
+                if delta.abs() > 0.001 {
+                    let translation = match animation.axis {
+                        Axis::X => vec2(delta, 0.0),
+                        Axis::Y => vec2(0.0, delta),
+                    };
+
+                    let transform = Transform2D {
+                        translation,
+                        scale: Vec2::ONE,
+                        rotation: 0.0,
+                    };
+
+                    transforms_to_apply.push((
+                        animation.index,
+                        animation.axis,
+                        transform,
+                        animation.wrap,
+                    ));
+
+                    // Small deltas carry over to the next frame instead of being dropped
+                    animation.current_position = new_position;
+                }
+            } else {
+                // Ensure we reach exactly the target position
+                let delta = animation.target_position - animation.current_position;
+
+                if delta.abs() > 0.001 {
+                    let translation = match animation.axis {
+                        Axis::X => vec2(delta, 0.0),
+                        Axis::Y => vec2(0.0, delta),
+                    };
+
+                    let transform = Transform2D {
+                        translation,
+                        scale: Vec2::ONE,
+                        rotation: 0.0,
+                    };
+
+                    transforms_to_apply.push((
+                        animation.index,
+                        animation.axis,
+                        transform,
+                        animation.wrap,
+                    ));
+                }
+
+                animation.current_position = animation.target_position;
+                if animation.target_position == 0.0 {
+                    returned_home.push((animation.index, animation.axis));
+                }
+                completed.push(i);
+            }
+        }
+
+        // Apply all calculated transforms. Slides only ever translate, so
+        // they add to the segments' offsets.
+        for (index, axis, transform, wrap) in transforms_to_apply {
+            for segment in self.slide_segments_mut(index, axis) {
+                segment.offset += transform.translation;
+            }
+            if wrap {
+                self.wrap_slide_segments(index, axis);
+            }
+        }
+
+        // A row or column back at offset 0 drops any wrap it picked up on the way
+        for (index, axis) in returned_home {
+            self.unwrap_slide_segments(index, axis);
+        }
+
+        // Remove completed animations
+        for i in completed.iter().rev() {
+            self.slide_animations.remove(*i);
+        }
+    }
+
+    // Limits an offset so the whole row or column stays on the texture
+    fn clamp_slide_offset(&self, axis: Axis, offset: f32, texture_size: Vec2) -> f32 {
+        let (texture_extent, line_extent, center) = match axis {
+            Axis::X => (
+                texture_size.x,
+                self.grid.dimensions.0 as f32 * self.grid.viewbox.width,
+                self.current_position.x,
+            ),
+            Axis::Y => (
+                texture_size.y,
+                self.grid.dimensions.1 as f32 * self.grid.viewbox.height,
+                self.current_position.y,
+            ),
+        };
+        let scale = match axis {
+            Axis::X => self.current_scale.x,
+            Axis::Y => self.current_scale.y,
+        };
+        let limit = ((texture_extent - line_extent * scale) / 2.0).max(0.0);
+
+        let clamped = offset.clamp(-limit - center, limit - center);
+        if clamped != offset {
+            println!(
+                "Slide offset {:.1} clamped to {:.1} to stay on screen",
+                offset, clamped
+            );
+        }
+        clamped
+    }
+
+    // None turns clamping off
+    pub fn set_slide_clamp(&mut self, texture_size: Option<Vec2>) {
+        self.slide_clamp = texture_size;
+    }
+
+    // process OSC /grid/slidereset and /grid/slidezero. Slides still running
+    // are turned around from wherever they've got to.
+    pub fn slide_reset(&mut self, duration: f32, easing: EasingType, time: f32) {
+        for (axis, index, _) in self.slide_offsets() {
+            self.slide(axis, index, 0.0, duration, easing.clone(), false, time);
+        }
+    }
+
+    // Target offset of every row that has been slid, by row index
+    pub fn row_positions(&self) -> &HashMap<i32, f32> {
+        &self.row_positions
+    }
+
+    // Target offset of every column that has been slid, by column index
+    pub fn col_positions(&self) -> &HashMap<i32, f32> {
+        &self.col_positions
+    }
+
+    // Target offsets of every row and column that has been slid, by index
+    pub fn slide_offsets(&self) -> Vec<(Axis, i32, f32)> {
+        let mut offsets: Vec<(Axis, i32, f32)> = self
+            .row_positions
+            .iter()
+            .map(|(index, offset)| (Axis::X, *index, *offset))
+            .chain(
+                self.col_positions
+                    .iter()
+                    .map(|(index, offset)| (Axis::Y, *index, *offset)),
+            )
+            .collect();
+        offsets.sort_by_key(|(axis, index, _)| (matches!(axis, Axis::Y), *index));
+        offsets
+    }
+
+    fn slide_segments_mut(&mut self, index: i32, axis: Axis) -> Vec<&mut CachedSegment> {
+        match axis {
+            Axis::X => self.grid.row_mut(index),
+            Axis::Y => self.grid.col_mut(index),
+        }
+    }
+
+    // Teleports whole segments that slid past the grid edge to the opposite
+    // side, judged by their average point. Slides move along world axes, so
+    // this assumes an unrotated grid.
+    fn wrap_slide_segments(&mut self, index: i32, axis: Axis) {
+        let span = match axis {
+            Axis::X => {
+                self.grid.dimensions.0 as f32 * self.grid.viewbox.width * self.current_scale.x
+            }
+            Axis::Y => {
+                self.grid.dimensions.1 as f32 * self.grid.viewbox.height * self.current_scale.y
+            }
+        };
+        if span <= 0.0 {
+            return;
+        }
+        let half_span = span / 2.0;
+        let center = self.current_position;
+        let to_world = self.grid.transform().to_affine();
+
+        let mut wraps: Vec<(SegmentKey, f32)> = Vec::new();
+        for segment in self.slide_segments_mut(index, axis) {
+            let average = to_world.transform_point2(find_average_point(&segment.draw_commands))
+                + segment.offset;
+            let local = match axis {
+                Axis::X => average.x - center.x,
+                Axis::Y => average.y - center.y,
+            };
+
+            let laps = ((local + half_span) / span).floor();
+            if laps == 0.0 {
+                continue;
+            }
+            let shift = -laps * span;
+            segment.offset += match axis {
+                Axis::X => vec2(shift, 0.0),
+                Axis::Y => vec2(0.0, shift),
+            };
+            wraps.push((segment.id.clone(), shift));
+        }
+
+        for (segment_id, shift) in wraps {
+            *self.slide_wrap_offsets.entry(segment_id).or_insert(0.0) += shift;
+        }
+    }
+
+    fn unwrap_slide_segments(&mut self, index: i32, axis: Axis) {
+        let mut offsets = std::mem::take(&mut self.slide_wrap_offsets);
+        for segment in self.slide_segments_mut(index, axis) {
+            let Some(shift) = offsets.remove(&segment.id) else {
+                continue;
+            };
+            segment.offset -= match axis {
+                Axis::X => vec2(shift, 0.0),
+                Axis::Y => vec2(0.0, shift),
+            };
+        }
+        self.slide_wrap_offsets = offsets;
+    }
+
+    /******************** Backbone style and effects **************************** */
+
+    fn generate_backbone_style(&self, time: f32) -> DrawStyle {
+        let mut style = self.backbone_style.clone();
+
+        // finished effects still apply this frame so they land on their final
+        // style before cleanup removes them
+        for (_, _, effect) in &self.backbone_effects {
+            if effect.is_modulation() {
+                continue;
+            }
+            style = effect.update(&style, time);
+        }
+        style
+    }
+
+    fn modulate_backbone_style(&self, time: f32) -> DrawStyle {
+        let mut style = self.backbone_style.clone();
+
+        for (_, _, effect) in &self.backbone_effects {
+            if effect.is_modulation() {
+                style = effect.update(&style, time);
+            }
+        }
+        style
+    }
 
-        if movement.should_update(dt) {
-            let movement_change = movement.advance(self.current_position, time);
-            if movement.is_complete() {
-                self.active_movement = None;
+    fn cleanup_backbone_effects(&mut self, time: f32) {
+        self.backbone_effects.retain(|(effect_type, _, effect)| {
+            let finished = effect.is_finished(time);
+            if finished {
+                println!("Removing effect {}", effect_type);
             }
-            movement_change
+            !finished
+        });
+    }
+
+    pub fn add_backbone_effect(&mut self, effect_type: &str, effect: Box<dyn BackboneEffect>) {
+        self.add_backbone_effect_with_priority(effect_type, 0, effect);
+    }
+
+    // Lower priorities apply first. Effects with the same priority apply in
+    // the order they were added. Adding under an existing name replaces it.
+    pub fn add_backbone_effect_with_priority(
+        &mut self,
+        effect_type: &str,
+        priority: i32,
+        effect: Box<dyn BackboneEffect>,
+    ) {
+        self.remove_backbone_effect(effect_type);
+        let index = self
+            .backbone_effects
+            .partition_point(|(_, existing, _)| *existing <= priority);
+        self.backbone_effects
+            .insert(index, (effect_type.to_string(), priority, effect));
+    }
+
+    pub fn remove_backbone_effect(&mut self, effect_type: &str) {
+        self.backbone_effects
+            .retain(|(existing, _, _)| existing != effect_type);
+    }
+
+    fn has_backbone_effect(&self, effect_type: &str) -> bool {
+        self.backbone_effects
+            .iter()
+            .any(|(existing, _, _)| existing == effect_type)
+    }
+
+    // Hiding over a duration fades the backbone's alpha to zero first, and
+    // showing it puts back the style it had before that fade. Lit and
+    // powering segments aren't part of the backbone and carry on as they are.
+    pub fn set_backbone_visible(&mut self, visible: bool, duration: f32, time: f32) {
+        self.remove_backbone_effect(BACKBONE_HIDE_EFFECT);
+        if visible {
+            if let Some(style) = self.backbone_style_before_hide.take() {
+                self.backbone_style = style;
+            }
+            self.backbone_visible = true;
+        } else if duration > 0.0 && self.backbone_visible {
+            // a second fade out starts where the first got to
+            self.backbone_style_before_hide
+                .get_or_insert_with(|| self.backbone_style.clone());
+            let color = self.backbone_style.color;
+            let effect = FadeEffect {
+                base_style: self.backbone_style.clone(),
+                target_style: DrawStyle {
+                    color: rgba(color.red, color.green, color.blue, 0.0),
+                    stroke_weight: self.backbone_style.stroke_weight,
+                },
+                duration,
+                start_time: time,
+                is_active: true,
+            };
+            self.add_backbone_effect(BACKBONE_HIDE_EFFECT, Box::new(effect));
         } else {
-            None
+            self.backbone_visible = false;
+        }
+    }
+
+    pub fn set_backbone_stroke_weight(&mut self, stroke_weight: f32) {
+        self.backbone_style = DrawStyle {
+            color: self.backbone_style.color,
+            stroke_weight,
+        }
+    }
+
+    pub fn type_weight(&self, segment_type: SegmentType) -> f32 {
+        self.type_weights.get(&segment_type).copied().unwrap_or(1.0)
+    }
+
+    // Lit and idle backbone segments are restaged so they take the new
+    // weight now. Anything powering on or off keeps the weight it started
+    // with.
+    pub fn set_type_weight(&mut self, segment_type: SegmentType, multiplier: f32) {
+        if multiplier == 1.0 {
+            self.type_weights.remove(&segment_type);
+        } else {
+            self.type_weights.insert(segment_type, multiplier);
+        }
+        self.backbone_style_version += 1;
+        self.restyle_active_segments();
+
+        for (segment_id, style) in &self.segment_color_overrides {
+            if self.current_active_segments.contains(segment_id)
+                && !self.update_batch.contains_key(segment_id)
+                && self
+                    .grid
+                    .segment(segment_id)
+                    .is_some_and(|segment| segment.is_active())
+            {
+                self.update_batch.insert(
+                    segment_id.clone(),
+                    StyleUpdateMsg::new(SegmentAction::InstantStyleChange, style.clone()),
+                );
+            }
+        }
+    }
+
+    /******************** Active segment effects **************************** */
+
+    fn generate_active_style(&self, base_style: &DrawStyle, time: f32) -> DrawStyle {
+        let mut style = base_style.clone();
+
+        for (_, effect) in &self.active_effects {
+            if effect.is_finished(time) {
+                continue;
+            }
+            style = effect.update(&style, time);
+        }
+        style
+    }
+
+    // Only fully active segments are restyled, so power on/off animations and
+    // segments already staged by a transition this frame are left alone.
+    fn restylable_active_segments(&self) -> Vec<SegmentKey> {
+        self.current_active_segments
+            .iter()
+            .filter(|segment_id| {
+                !self.update_batch.contains_key(*segment_id)
+                    && !self.segment_color_overrides.contains_key(*segment_id)
+                    && self
+                        .grid
+                        .segment(segment_id)
+                        .is_some_and(|segment| segment.is_active())
+            })
+            .cloned()
+            .collect()
+    }
+
+    // Restaged every frame while flickering, so segments coming back from a
+    // dropout land on whatever their base style is by then
+    fn stage_active_style_updates(&mut self, time: f32) {
+        for segment_id in self.restylable_active_segments() {
+            let mut style = self.generate_active_style(&self.active_base_style(&segment_id), time);
+            if let (Some(noise), Some(&position)) =
+                (&self.noise_color, self.noise_positions.get(&segment_id))
+            {
+                style = noise.shift(&style, position, time);
+            }
+            if let Some(flicker) = &self.flicker {
+                if flicker.is_dropped(&segment_id, time) {
+                    style = flicker.dim(&style);
+                }
+            }
+            self.update_batch.insert(
+                segment_id,
+                StyleUpdateMsg::new(SegmentAction::InstantStyleChange, style),
+            );
+        }
+    }
+
+    // Returns the active segments to their style without effects
+    fn restyle_active_segments(&mut self) {
+        for segment_id in self.restylable_active_segments() {
+            let style = self.active_base_style(&segment_id);
+            self.update_batch.insert(
+                segment_id,
+                StyleUpdateMsg::new(SegmentAction::InstantStyleChange, style),
+            );
+        }
+    }
+
+    fn cleanup_active_effects(&mut self, time: f32) {
+        self.active_effects
+            .retain(|(_, effect)| !effect.is_finished(time));
+    }
+
+    // Adding under an existing name replaces it, and the replacement applies
+    // after the effects already running
+    pub fn add_active_effect(&mut self, effect_type: &str, effect: Box<dyn ActiveEffect>) {
+        self.active_effects
+            .retain(|(existing, _)| existing != effect_type);
+        self.active_effects.push((effect_type.to_string(), effect));
+    }
+
+    // process OSC /grid/pulse. Depth 0 stops the pulse.
+    pub fn set_pulse(&mut self, period: f32, depth: f32, time: f32) {
+        if depth <= 0.0 {
+            self.remove_active_effect(ACTIVE_PULSE_EFFECT);
+        } else {
+            let effect = ActivePulseEffect::new(period, depth, time);
+            self.add_active_effect(ACTIVE_PULSE_EFFECT, Box::new(effect));
+        }
+    }
+
+    // None stops flickering
+    pub fn set_flicker(&mut self, flicker: Option<FlickerEffect>) {
+        let stopped = flicker.is_none() && self.flicker.is_some();
+        self.flicker = flicker;
+        if stopped {
+            self.restyle_active_segments();
+        }
+    }
+
+    // None stops the drift
+    pub fn set_noise_color(&mut self, noise_color: Option<NoiseColorEffect>) {
+        match noise_color {
+            Some(noise_color) => {
+                if self.noise_color.is_none() {
+                    self.noise_positions = self.local_unit_positions();
+                }
+                self.noise_color = Some(noise_color);
+            }
+            None => {
+                if self.noise_color.take().is_some() {
+                    self.noise_positions.clear();
+                    self.restyle_active_segments();
+                }
+            }
+        }
+    }
+
+    // Removes the effect and returns the active segments to their base style.
+    // Any effects still running restyle them again next frame.
+    pub fn remove_active_effect(&mut self, effect_type: &str) {
+        let count = self.active_effects.len();
+        self.active_effects
+            .retain(|(existing, _)| existing != effect_type);
+        if self.active_effects.len() < count {
+            self.restyle_active_segments();
+        }
+    }
+
+    /*********************** Snapshots ******************************/
+
+    // process OSC /grid/snapshot
+    pub fn snapshot(&self) -> GridSnapshot {
+        let sorted = |positions: &HashMap<i32, f32>| {
+            let mut offsets: Vec<(i32, f32)> = positions
+                .iter()
+                .map(|(index, offset)| (*index, *offset))
+                .collect();
+            offsets.sort_by_key(|(index, _)| *index);
+            offsets
+        };
+        GridSnapshot {
+            active_segments: self.current_active_segments.iter().cloned().collect(),
+            target_style: (&self.target_style).into(),
+            backbone_style: (&self.backbone_style).into(),
+            glyph_index: self.current_glyph_index,
+            position: self.current_position.to_array(),
+            rotation: self.current_rotation,
+            scale: self.current_scale.to_array(),
+            row_offsets: sorted(&self.row_positions),
+            col_offsets: sorted(&self.col_positions),
+            is_visible: self.is_visible,
+            dimmer: self.dimmer,
+            use_power_on_effect: self.use_power_on_effect,
+            colorful_flag: self.colorful_flag,
+            use_stroke_order: self.transition_use_stroke_order,
+            use_reverse_erase: self.transition_use_reverse_erase,
+            use_stroke_tint: self.transition_use_stroke_tint,
+            use_crossfade: self.transition_use_crossfade,
+        }
+    }
+
+    // process OSC /grid/restore. Anything in motion is stopped and the
+    // snapshot's glyph is put straight up, without a transition, on the next
+    // update. Rows and columns come back unwrapped.
+    pub fn restore(&mut self, snapshot: &GridSnapshot, project: &Project) {
+        self.active_transition = None;
+        self.target_segments = None;
+        self.stop_transform_animations();
+        self.active_movement = None;
+        self.clear_movement_queue();
+        self.jitter = None;
+        self.slide_animations.clear();
+        self.glyph_color_fade = None;
+
+        self.is_visible = snapshot.is_visible;
+        self.dimmer = snapshot.dimmer;
+        self.use_power_on_effect = snapshot.use_power_on_effect;
+        self.colorful_flag = snapshot.colorful_flag;
+        self.transition_use_stroke_order = snapshot.use_stroke_order;
+        self.transition_use_reverse_erase = snapshot.use_reverse_erase;
+        self.transition_use_stroke_tint = snapshot.use_stroke_tint;
+        self.transition_use_crossfade = snapshot.use_crossfade;
+
+        // stroke weights follow the scale, then the snapshot's styles,
+        // already at that scale, replace the glyph and backbone ones
+        self.current_position = Vec2::from(snapshot.position);
+        self.current_rotation = snapshot.rotation;
+        self.scale_xy_in_place(Vec2::from(snapshot.scale));
+        self.target_style = snapshot.target_style.into();
+        self.backbone_style = snapshot.backbone_style.into();
+        self.backbone_style_version += 1;
+
+        self.index_max = project
+            .get_show(&self.show)
+            .map_or(0, |show| show.show_order.len());
+        self.current_glyph_index = snapshot.glyph_index;
+
+        for segment in self.grid.segments.values_mut() {
+            segment.offset = Vec2::ZERO;
+        }
+        self.slide_wrap_offsets.clear();
+        self.row_positions = snapshot.row_offsets.iter().copied().collect();
+        self.col_positions = snapshot.col_offsets.iter().copied().collect();
+        for (axis, index, offset) in self.slide_offsets() {
+            let shift = match axis {
+                Axis::X => vec2(offset, 0.0),
+                Axis::Y => vec2(0.0, offset),
+            };
+            for segment in self.slide_segments_mut(index, axis) {
+                segment.offset += shift;
+            }
+        }
+
+        let active: HashSet<SegmentKey> = snapshot
+            .active_segments
+            .iter()
+            .filter(|id| self.grid.segments.contains_key(*id))
+            .cloned()
+            .collect();
+        let leaving: Vec<SegmentKey> = self
+            .grid
+            .segments
+            .values()
+            .filter(|segment| !segment.is_background() && !active.contains(&segment.id))
+            .filter(|segment| !self.is_sparkling(&segment.id))
+            .filter(|segment| !self.chase_lit.contains(&segment.id))
+            .map(|segment| segment.id.clone())
+            .collect();
+        for segment_id in leaving {
+            self.update_batch.insert(
+                segment_id,
+                StyleUpdateMsg::new(
+                    SegmentAction::BackboneUpdate,
+                    self.displayed_backbone_style.clone(),
+                ),
+            );
+        }
+        self.segment_colors.retain(|id, _| active.contains(id));
+        if self.colorful_flag && self.colorful_mode == ColorfulMode::PerSegment {
+            let new: HashSet<SegmentKey> = active
+                .difference(&self.current_active_segments)
+                .cloned()
+                .collect();
+            self.roll_segment_colors(&new);
+        }
+        let target_style = self.target_style.clone();
+        self.stage_segments_instant_on(&active, &target_style);
+        self.apply_active_base_styles(&active);
+        self.apply_segment_color_overrides(&active);
+        self.current_active_segments = active;
+    }
+
+    /*********************** Utility Methods **************************** */
+
+    pub fn has_target_segments(&self) -> bool {
+        self.target_segments.is_some()
+    }
+
+    pub fn has_active_transition(&self) -> bool {
+        self.active_transition.is_some()
+    }
+
+    pub fn has_active_movement(&self) -> bool {
+        self.active_movement.is_some()
+    }
+
+    pub fn has_active_effects(&self) -> bool {
+        !self.active_effects.is_empty()
+    }
+
+    pub fn has_backbone_effects(&self) -> bool {
+        !self.backbone_effects.is_empty()
+    }
+
+    pub fn has_slide_animations(&self) -> bool {
+        !self.slide_animations.is_empty()
+    }
+
+    pub fn has_active_jitter(&self) -> bool {
+        self.jitter.is_some()
+    }
+
+    /*********************** Debug Helper ******************************* */
+
+    pub fn print_grid_info(&self) {
+        println!("<====== Grid Instance: {} ======>", self.id);
+        println!("\nGrid Info:");
+        println!("Location: {:?}", self.current_position);
+        println!("Dimensions: {:?}", self.grid.dimensions);
+        println!("Viewbox: {:?}", self.grid.viewbox);
+        println!("Segment count: {}\n", self.grid.segments.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{test_config, test_engine, test_grid_instance};
+    use crate::views::DrawCommand;
+
+    #[test]
+    fn test_scaled_grid_finds_the_same_junctions() {
+        let mut grid = test_grid_instance();
+        let graph = Rc::clone(&grid.base_graph);
+        let junctions: Vec<(Point2, Vec<SegmentKey>)> = graph
+            .nodes()
+            .map(|(_, junction)| (junction.point, junction.segments.clone()))
+            .collect();
+        assert!(!junctions.is_empty());
+
+        grid.scale_in_place(0.2);
+        assert!((grid.connection_threshold() - graph.threshold() * 0.2).abs() < 1e-9);
+        for (point, segments) in &junctions {
+            let world = grid.transform().apply_to_point(*point);
+            assert_eq!(&grid.segments_at_point(world), segments);
+        }
+    }
+
+    #[test]
+    fn test_glyphs_naming_purged_segments_stage_their_duplicates() {
+        use crate::models::data_model::{Glyph, Show, ShowElement};
+
+        let mut project = Project::load("projects/ulsan.json").unwrap();
+        let segments = ["1,2 : hor-3-2", "1,3 : hor-1-2", "1,3 : hor-1-1"];
+        project.glyphs.insert(
+            "edges".to_string(),
+            Glyph {
+                name: "edges".to_string(),
+                segments: segments.iter().map(|id| id.to_string()).collect(),
+            },
+        );
+        let element = ShowElement {
+            name: "edges".to_string(),
+            element_type: "glyph".to_string(),
+            position: 1,
+            metadata: HashMap::new(),
+        };
+        project.shows.insert(
+            "edges".to_string(),
+            Show {
+                name: "edges".to_string(),
+                metadata: HashMap::new(),
+                show_order: HashMap::from([(1, element)]),
+            },
+        );
+        let cached = CachedGrid::new(&project);
+        let graph = Rc::new(SegmentGraph::new(&cached));
+        let mut grid = GridInstance::new(
+            "test".to_string(),
+            &project,
+            "edges",
+            &cached,
+            graph,
+            pt2(0.0, 0.0),
+            0.0,
+            10.0,
+            5.0,
+        );
+
+        grid.stage_glyph_by_index(&project, 1);
+        let expected = HashSet::from([
+            SegmentKey::new((1, 2), "hor-3-2"),
+            SegmentKey::new((1, 2), "hor-3-1"),
+        ]);
+        assert_eq!(grid.target_segments, Some(expected));
+    }
+
+    #[test]
+    fn test_stage_component_lights_one_figure() {
+        // two strokes joined at a corner, and one off on its own
+        let project = Project {
+            svg_base_tile: r#"<svg id="test" viewBox="0 0 100 100">
+                <path id="a" d="M10,10 L30,10"/>
+                <path id="b" d="M30,10 L30,30"/>
+                <path id="c" d="M60,60 L80,60"/>
+            </svg>"#
+                .to_string(),
+            grid_x: 1,
+            grid_y: 1,
+            glyphs: HashMap::new(),
+            shows: HashMap::new(),
+        };
+        let cached = CachedGrid::new(&project);
+        let graph = Rc::new(SegmentGraph::new(&cached));
+        assert_eq!(graph.connected_components().len(), 2);
+
+        let mut grid = GridInstance::new(
+            "test".to_string(),
+            &project,
+            "",
+            &cached,
+            graph,
+            pt2(0.0, 0.0),
+            0.0,
+            10.0,
+            5.0,
+        );
+        let ids = |names: &[&str]| -> HashSet<SegmentKey> {
+            names
+                .iter()
+                .map(|name| SegmentKey::new((1, 1), *name))
+                .collect()
+        };
+
+        grid.stage_component("1,1 : b", true);
+        assert_eq!(grid.target_segments, Some(ids(&["a", "b"])));
+        grid.stage_component("1,1 : c", true);
+        assert_eq!(grid.target_segments, Some(ids(&["a", "b", "c"])));
+        grid.stage_component("1,1 : a", false);
+        assert_eq!(grid.target_segments, Some(ids(&["c"])));
+    }
+
+    fn run_frames(grid: &mut GridInstance, engine: &TransitionEngine, frames: usize) {
+        let draw = Draw::new();
+        for _ in 0..frames {
+            grid.update(&draw, engine, 0.0, 1.0 / 60.0);
+        }
+    }
+
+    fn assert_no_orphans(crossfade: bool) {
+        let mut grid = test_grid_instance();
+        grid.transition_use_crossfade = crossfade;
+        grid.transition_next_animation_type = TransitionAnimationType::Random;
+        let engine = TransitionEngine::new(TransitionConfig {
+            seed: Some(7),
+            ..test_config(20, 0.02)
+        });
+
+        let mut segment_ids: Vec<SegmentKey> = grid.grid.segments.keys().cloned().collect();
+        segment_ids.sort();
+        let glyphs: Vec<HashSet<SegmentKey>> = (2..5)
+            .map(|n| segment_ids.iter().step_by(n).cloned().collect())
+            .collect();
+
+        // each glyph interrupts the previous one part way through
+        for glyph in &glyphs {
+            grid.target_segments = Some(glyph.clone());
+            run_frames(&mut grid, &engine, 5);
+        }
+        run_frames(&mut grid, &engine, 300);
+
+        let lit: HashSet<SegmentKey> = grid
+            .grid
+            .segments
+            .values()
+            .filter(|segment| segment.is_lit())
+            .map(|segment| segment.id.clone())
+            .collect();
+        assert_eq!(grid.current_active_segments, glyphs[2]);
+        assert_eq!(lit, glyphs[2]);
+    }
+
+    #[test]
+    fn test_stagger_spreads_segments_turning_on() {
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = TransitionEngine::new(TransitionConfig {
+            seed: Some(3),
+            stagger: 0.5,
+            ..test_config(20, 0.02)
+        });
+
+        let target: HashSet<SegmentKey> = grid.grid.segments.keys().cloned().collect();
+        grid.target_segments = Some(target.clone());
+
+        let lit_count = |grid: &GridInstance| {
+            grid.grid
+                .segments
+                .values()
+                .filter(|segment| segment.is_active())
+                .count()
+        };
+
+        // partway through, only the segments with short delays are on
+        run_frames(&mut grid, &engine, 15);
+        let partway = lit_count(&grid);
+        assert!(partway > 0 && partway < target.len());
+
+        run_frames(&mut grid, &engine, 60);
+        assert_eq!(lit_count(&grid), target.len());
+    }
+
+    // Frames at 60fps a grid running at `speed` takes to fade a glyph in, to
+    // fade it back out, and to step through a transition to it
+    fn frames_at_speed(speed: f32) -> (usize, usize, usize) {
+        let mut grid = test_grid_instance();
+        grid.set_transition_speed(speed);
+        grid.envelope = Envelope::new(0.0, 0.0, 1.0, 0.5, 1.0);
+        let engine = test_engine(10, 0.1);
+        let glyph: HashSet<SegmentKey> = grid.grid.segments.keys().take(10).cloned().collect();
+        let frames_until = |grid: &mut GridInstance, done: &dyn Fn(&GridInstance) -> bool| {
+            let mut frames = 0;
+            loop {
+                run_frames(grid, &engine, 1);
+                frames += 1;
+                if done(grid) {
+                    return frames;
+                }
+                assert!(frames < 1000, "grid never settled");
+            }
+        };
+
+        let style = grid.target_style.clone();
+        grid.stage_segments_on(&glyph, &style);
+        let powering_on = frames_until(&mut grid, &|grid| {
+            glyph.iter().all(|id| grid.grid.segments[id].is_active())
+        });
+        let style = grid.backbone_style.clone();
+        grid.stage_segments_off(&glyph, &style);
+        let powering_off = frames_until(&mut grid, &|grid| {
+            glyph.iter().all(|id| grid.grid.segments[id].is_idle())
+        });
+
+        grid.transition_next_animation_type = TransitionAnimationType::Random;
+        grid.target_segments = Some(glyph);
+        let stepping = frames_until(&mut grid, &|grid| !grid.has_active_transition());
+        (powering_on, powering_off, stepping)
+    }
+
+    #[test]
+    fn test_transition_speed_scales_steps_and_fades() {
+        let normal = frames_at_speed(1.0);
+        let double = frames_at_speed(2.0);
+        for (normal, double) in [
+            (normal.0, double.0),
+            (normal.1, double.1),
+            (normal.2, double.2),
+        ] {
+            assert!(
+                (double as f32 - normal as f32 / 2.0).abs() <= 1.0,
+                "{} frames at double speed, {} at normal",
+                double,
+                normal
+            );
+        }
+
+        // out of range speeds are clamped
+
+        let mut grid = test_grid_instance();
+        grid.set_transition_speed(100.0);
+        assert_eq!(grid.transition_speed(), MAX_TRANSITION_SPEED);
+        grid.set_transition_speed(0.0);
+        assert_eq!(grid.transition_speed(), MIN_TRANSITION_SPEED);
+    }
+
+    #[test]
+    fn test_hidden_backbone_leaves_the_glyph_alone() {
+        let mut grid = test_grid_instance();
+        grid.is_visible = true;
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        let glyph: HashSet<SegmentKey> = grid.grid.segments.keys().take(4).cloned().collect();
+        grid.target_segments = Some(glyph.clone());
+        run_frames(&mut grid, &engine, 60);
+        grid.set_backbone_stroke_weight(7.5);
+        let before = grid.backbone_style.clone();
+
+        // draw commands for a frame at `time`
+        let drawn = |grid: &mut GridInstance, time: f32| {
+            let draw = Draw::new();
+            grid.update(&draw, &engine, time, 1.0 / 60.0);
+            draw.drain_commands().count()
+        };
+        let all = drawn(&mut grid, 0.0);
+
+        // fading out, the backbone still draws until the fade is done
+        grid.set_backbone_visible(false, 1.0, 0.0);
+        assert_eq!(drawn(&mut grid, 0.5), all);
+        assert!(grid.backbone_visible);
+        assert!(grid.backbone_style.color.alpha < 1.0);
+        let hidden = drawn(&mut grid, 1.1);
+        assert!(!grid.backbone_visible);
+        assert!(hidden > 0 && hidden < all);
+        assert_eq!(grid.current_active_segments, glyph);
+        assert!(glyph.iter().all(|id| grid.grid.segments[id].is_lit()));
+
+        // back in the style it had, stroke weight and all
+        grid.set_backbone_visible(true, 0.0, 1.2);
+        assert!(grid.backbone_visible);
+        assert_eq!(grid.backbone_style, before);
+        assert_eq!(drawn(&mut grid, 1.2), all);
+
+        // hiding at once leaves the style alone
+        grid.set_backbone_visible(false, 0.0, 1.3);
+        assert_eq!(drawn(&mut grid, 1.3), hidden);
+        assert_eq!(grid.backbone_style, before);
+    }
+
+    #[test]
+    fn test_backbone_pulse_layers_over_fade() {
+        use crate::effects::{FadeEffect, PulseEffect};
+
+        let mut grid = test_grid_instance();
+        let engine = test_engine(20, 0.02);
+        let gray = |v: f32| DrawStyle {
+            color: rgba(v, v, v, 1.0),
+            stroke_weight: 5.0,
+        };
+        grid.backbone_style = gray(0.2);
+        grid.add_backbone_effect(
+            "backbone",
+            Box::new(FadeEffect {
+                base_style: gray(0.2),
+                target_style: gray(0.6),
+                duration: 2.0,
+                start_time: 0.0,
+                is_active: true,
+            }),
+        );
+        grid.add_backbone_effect("pulse", Box::new(PulseEffect::new(4.0, 0.5, 0.0)));
+
+        // fade progress, then pulse brightness, which bottoms out at 0.5
+        // halfway through the period. The fade settles after 2s.
+        let lightness = |style: &DrawStyle| Hsla::from(style.color).lightness;
+        let (from, to) = (lightness(&gray(0.2)), lightness(&gray(0.6)));
+        let draw = Draw::new();
+        for (time, fade, pulse) in [
+            (0.0, 0.0, 1.0),
+            (1.0, 0.5, 0.75),
+            (2.0, 1.0, 0.5),
+            (3.0, 1.0, 0.75),
+        ] {
+            grid.update(&draw, &engine, time, 1.0);
+            let expected = (from + (to - from) * fade) * pulse;
+            let actual = lightness(&grid.displayed_backbone_style);
+            assert!(
+                (actual - expected).abs() < 1e-4,
+                "{time}: {actual} != {expected}"
+            );
+        }
+        assert!((lightness(&grid.backbone_style) - to).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_settled_backbone_segments_are_not_restaged() {
+        let mut grid = test_grid_instance();
+        let engine = test_engine(20, 0.02);
+        let draw = Draw::new();
+        grid.update(&draw, &engine, 0.0, 0.0);
+        grid.stage_backbone_updates();
+        assert!(grid.update_batch.is_empty());
+
+        // a new backbone style reaches every idle segment, once
+        let style = DrawStyle {
+            color: rgba(0.5, 0.5, 0.5, 1.0),
+            stroke_weight: 5.0,
+        };
+        grid.backbone_style = style.clone();
+        grid.update(&draw, &engine, 1.0, 1.0);
+        assert!(grid
+            .grid
+            .segments
+            .values()
+            .all(|segment| segment.current_style == style));
+        grid.stage_backbone_updates();
+        assert!(grid.update_batch.is_empty());
+
+        // a segment styled by something else gets the backbone style back
+        let id = grid.grid.segments.keys().next().unwrap().clone();
+        let red = DrawStyle {
+            color: rgba(1.0, 0.0, 0.0, 1.0),
+            stroke_weight: 5.0,
+        };
+        grid.update_batch.insert(
+            id.clone(),
+            StyleUpdateMsg::new(SegmentAction::BackboneUpdate, red.clone()),
+        );
+        grid.stage_backbone_updates();
+        grid.push_updates();
+        grid.clear_update_batch();
+        assert_eq!(grid.grid.segments[&id].current_style, red);
+        grid.stage_backbone_updates();
+        assert_eq!(grid.update_batch.keys().collect::<Vec<_>>(), [&id]);
+        grid.push_updates();
+        assert_eq!(grid.grid.segments[&id].current_style, style);
+    }
+
+    #[test]
+    fn test_glyph_color_fade_restyles_lit_segments() {
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        grid.instant_color_change(rgba(1.0, 0.0, 0.0, 1.0));
+        let segment_ids: Vec<SegmentKey> = grid.grid.segments.keys().cloned().collect();
+        grid.target_segments = Some(segment_ids.iter().take(3).cloned().collect());
+        run_frames(&mut grid, &engine, 5);
+
+        let draw = Draw::new();
+        grid.glyph_color_fade(rgba(0.0, 0.0, 1.0, 1.0), 2.0, 0.0);
+        grid.update(&draw, &engine, 1.0, 1.0);
+        let lit = &grid.grid.segments[&segment_ids[0]];
+        assert_eq!(lit.current_style.color, rgba(0.5, 0.0, 0.5, 1.0));
+
+        // a glyph staged mid-fade turns on in the color of that instant
+        grid.target_segments = Some(segment_ids.iter().skip(3).take(3).cloned().collect());
+        grid.update(&draw, &engine, 1.5, 0.5);
+        let staged = &grid.grid.segments[&segment_ids[3]];
+        assert_eq!(staged.current_style.color, rgba(0.25, 0.0, 0.75, 1.0));
+
+        grid.update(&draw, &engine, 2.5, 1.0);
+        assert_eq!(grid.target_style.color, rgba(0.0, 0.0, 1.0, 1.0));
+        assert!(grid.glyph_color_fade.is_none());
+    }
+
+    #[test]
+    fn test_gradient_follows_grid_through_rotation() {
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        grid.set_gradient(GradientStyle {
+            color_a: rgba(1.0, 0.0, 0.0, 1.0),
+            color_b: rgba(0.0, 0.0, 1.0, 1.0),
+            axis: Axis::X,
+        });
+
+        let all: HashSet<SegmentKey> = grid.grid.segments.keys().cloned().collect();
+        grid.target_segments = Some(all.clone());
+        run_frames(&mut grid, &engine, 5);
+        let colors = |grid: &GridInstance| -> HashMap<SegmentKey, Rgba<f32>> {
+            grid.grid
+                .segments
+                .values()
+                .map(|segment| (segment.id.clone(), segment.current_style.color))
+                .collect()
+        };
+        let before = colors(&grid);
+
+        let left = grid
+            .grid
+            .segments
+            .values()
+            .find(|s| s.tile_coordinate.0 == 1);
+        let right = grid
+            .grid
+            .segments
+            .values()
+            .find(|s| s.tile_coordinate.0 == 4);
+        assert!(left.unwrap().current_style.color.red > right.unwrap().current_style.color.red);
+
+        // relight the whole glyph upside down and at double size
+        grid.rotate_in_place(180.0);
+        grid.scale_in_place(2.0);
+        grid.target_segments = Some(HashSet::new());
+        run_frames(&mut grid, &engine, 5);
+        grid.target_segments = Some(all);
+        run_frames(&mut grid, &engine, 5);
+        assert_eq!(colors(&grid), before);
+    }
+
+    #[test]
+    fn test_rainbow_hue_follows_time_and_position() {
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        grid.target_segments = Some(grid.grid.segments.keys().cloned().collect());
+        run_frames(&mut grid, &engine, 5);
+
+        let hue = |grid: &GridInstance, column: u32| {
+            let segment = grid
+                .grid
+                .segments
+                .values()
+                .find(|segment| segment.tile_coordinate.0 == column)
+                .unwrap();
+            Hsla::from(segment.current_style.color)
+                .hue
+                .to_positive_degrees()
+        };
+
+        // a quarter turn after one second at 0.25 Hz, the same on every tile
+        grid.set_rainbow(0.25, 0.0);
+        grid.update_rainbow(1.0);
+        grid.update(&Draw::new(), &engine, 1.0, 1.0);
+        assert!((hue(&grid, 1) - 90.0).abs() < 0.5);
+        assert!((hue(&grid, 4) - 90.0).abs() < 0.5);
+
+        // with spread, hue increases left to right. Edge segments of tiles 1
+        // and 4 can be as little as half the grid apart, a 90 degree shift.
+        grid.set_rainbow(0.25, 0.5);
+        grid.update(&Draw::new(), &engine, 1.0, 1.0);
+        assert!(hue(&grid, 4) > hue(&grid, 1) + 45.0);
+    }
+
+    #[test]
+    fn test_noise_color_is_seeded_and_bounded() {
+        let engine = test_engine(20, 0.02);
+        let noisy_grid = |rotation: f32| {
+            let mut grid = test_grid_instance();
+            grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+            grid.target_segments = Some(grid.grid.segments.keys().cloned().collect());
+            run_frames(&mut grid, &engine, 5);
+            grid.rotate_in_place(rotation);
+            grid.set_noise_color(Some(NoiseColorEffect::new(2.0, 0.5, 30.0, 9)));
+            grid.update(&Draw::new(), &engine, 3.0, 0.02);
+            grid
+        };
+
+        // the same seed gives the same colors, however the grid is turned
+        let a = noisy_grid(0.0);
+        let b = noisy_grid(45.0);
+        let base_hue = Hsla::from(a.target_style.color).hue;
+        let mut hues = Vec::new();
+        for (segment_id, segment) in &a.grid.segments {
+            let color = segment.current_style.color;
+            let turned = b.grid.segments[segment_id].current_style.color;
+            assert!((color.red - turned.red).abs() < 1e-3);
+            assert!((color.green - turned.green).abs() < 1e-3);
+            assert!((color.blue - turned.blue).abs() < 1e-3);
+
+            let hue = Hsla::from(color).hue;
+            let shift = (hue - base_hue).to_degrees();
+            assert!(shift.abs() <= 30.0 + 1e-3);
+            hues.push(shift);
+        }
+
+        // and the field varies across the grid
+        let spread = hues.iter().fold(f32::MIN, |a, b| a.max(*b))
+            - hues.iter().fold(f32::MAX, |a, b| a.min(*b));
+        assert!(spread > 1.0);
+    }
+
+    #[test]
+    fn test_chase_lights_path_without_joining_the_glyph() {
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        let draw = Draw::new();
+        let mut segment_ids: Vec<SegmentKey> = grid.grid.segments.keys().cloned().collect();
+        segment_ids.sort();
+        let path = segment_ids
+            .iter()
+            .find_map(|end| {
+                grid.graph()
+                    .find_path(&segment_ids[0], end)
+                    .filter(|path| path.len() >= 4)
+            })
+            .unwrap();
+
+        // the glyph holds the path's second segment
+        let glyph: HashSet<SegmentKey> = HashSet::from([path[1].clone()]);
+        grid.target_segments = Some(glyph.clone());
+        grid.update(&draw, &engine, 0.0, 1.0 / 60.0);
+
+        grid.start_chase(
+            Some(&path[0].to_string()),
+            Some(&path.last().unwrap().to_string()),
+            1.0,
+            3,
+            0.0,
+            StdRng::seed_from_u64(1),
+        )
+        .unwrap();
+        grid.update(&draw, &engine, 2.5, 1.0 / 60.0);
+
+        assert_eq!(grid.current_active_segments, glyph);
+        assert_eq!(
+            grid.chase_lit,
+            HashSet::from([path[0].clone(), path[2].clone()])
+        );
+        for segment_id in &path[..3] {
+            assert!(grid.grid.segments[segment_id].is_lit());
+        }
+        // the chase draws over the glyph's foreground, not in it
+        assert_eq!(grid.grid.segments[&path[0]].layer(), Layer::Overlay);
+        assert_eq!(grid.grid.segments[&path[1]].layer(), Layer::Foreground);
+
+        // stopping leaves the glyph lit
+        grid.stop_chase();
+        for _ in 0..30 {
+            grid.update(&draw, &engine, 2.6, 1.0 / 60.0);
+        }
+        assert!(grid.grid.segments[&path[1]].is_lit());
+        assert!(!grid.grid.segments[&path[0]].is_lit());
+        assert!(!grid.grid.segments[&path[2]].is_lit());
+    }
+
+    #[test]
+    fn test_bands_color_new_segments_and_clear_next_frame() {
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        let (color_a, color_b) = (rgba(1.0, 0.0, 0.0, 1.0), rgba(0.0, 0.0, 1.0, 1.0));
+        grid.set_bands(BandStyle {
+            axis: Axis::Y,
+            width: 1,
+            color_a,
+            color_b,
+        });
+
+        let glyph: HashSet<SegmentKey> = grid.grid.segments.keys().cloned().collect();
+        grid.target_segments = Some(glyph.clone());
+        run_frames(&mut grid, &engine, 1);
+        for segment in grid.grid.segments.values() {
+            let expected = if segment.tile_coordinate.1 % 2 == 0 {
+                color_a
+            } else {
+                color_b
+            };
+            assert_eq!(segment.current_style.color, expected);
         }
+
+        grid.clear_bands();
+        run_frames(&mut grid, &engine, 1);
+        for segment_id in &glyph {
+            assert_eq!(
+                grid.grid.segments[segment_id].current_style,
+                grid.target_style
+            );
+        }
+    }
+
+    #[test]
+    fn test_outline_scales_with_stroke_weights() {
+        let mut grid = test_grid_instance();
+        grid.outline = Some(OutlineParams {
+            color: rgba(0.0, 0.0, 0.0, 1.0),
+            extra_weight: 4.0,
+        });
+        grid.scale_in_place(2.0);
+        grid.scale_in_place(0.5);
+        grid.scale_in_place(1.5);
+
+        let outline = grid.outline.unwrap();
+        assert_eq!(outline.extra_weight, 6.0);
+        let style = outline.style(&grid.target_style);
+        assert_eq!(style.stroke_weight, 15.0 + 6.0);
+    }
+
+    #[test]
+    fn test_masked_tiles_are_tracked_but_not_drawn() {
+        let mut grid = test_grid_instance();
+        grid.is_visible = true;
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        let drawn = |grid: &GridInstance| {
+            let draw = Draw::new();
+            grid.draw_again(&draw);
+            draw.drain_commands().count()
+        };
+        let all_tiles = drawn(&grid);
+
+        let glyph: HashSet<SegmentKey> = grid
+            .grid
+            .get_tile_segments_iter(1, 1)
+            .map(|segment| segment.id.clone())
+            .collect();
+        grid.set_tile_visible(1, 1, false);
+        grid.target_segments = Some(glyph.clone());
+        run_frames(&mut grid, &engine, 5);
+        assert!(!grid.is_tile_visible(1, 1));
+        assert_eq!(grid.current_active_segments, glyph);
+        assert!(drawn(&grid) < all_tiles);
+
+        // the glyph shows again once unmasked
+        grid.set_tile_rect_visible((2, 2), (1, 1), false);
+        grid.clear_tile_mask();
+        assert_eq!(drawn(&grid), all_tiles);
+        assert!(glyph
+            .iter()
+            .all(|id| grid.grid.segments[id].current_style.color == grid.target_style.color));
+    }
+
+    #[test]
+    fn test_heatmap_shows_history_from_before_it_was_on() {
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        let draw = Draw::new();
+        let mut segment_ids: Vec<SegmentKey> = grid.grid.segments.keys().cloned().collect();
+        segment_ids.sort();
+        let first: HashSet<SegmentKey> = segment_ids[..10].iter().cloned().collect();
+        let second: HashSet<SegmentKey> = segment_ids[5..15].iter().cloned().collect();
+
+        grid.target_segments = Some(first);
+        grid.update(&draw, &engine, 0.0, 1.0 / 60.0);
+        grid.target_segments = Some(second);
+        grid.update(&draw, &engine, 1.0, 1.0 / 60.0);
+
+        // two seconds after leaving the glyph, half way through the decay
+        grid.set_heatmap(true, Some(4.0));
+        for _ in 0..30 {
+            grid.update(&draw, &engine, 3.0, 1.0 / 60.0);
+        }
+
+        let color_of =
+            |grid: &GridInstance, id: &SegmentKey| grid.grid.segments[id].current_style.color;
+        assert_eq!(color_of(&grid, &segment_ids[0]), heat_color(0.5));
+        assert_eq!(color_of(&grid, &segment_ids[10]), heat_color(1.0));
+        assert_eq!(color_of(&grid, &segment_ids[20]), heat_color(0.0));
+
+        // off again, the glyph gets its own color back
+        grid.set_heatmap(false, None);
+        grid.update(&draw, &engine, 3.1, 1.0 / 60.0);
+        assert_eq!(color_of(&grid, &segment_ids[10]), grid.target_style.color);
     }
 
-    fn apply_movement_change(&mut self, change: &MovementChange) {
-        self.apply_transform(&change.transform);
+    #[test]
+    fn test_transitions_run_on_through_blackout() {
+        let mut grid = test_grid_instance();
+        grid.is_visible = true;
+        grid.master_level = 0.0;
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        let glyph: HashSet<SegmentKey> = grid.grid.segments.keys().take(10).cloned().collect();
+        grid.target_segments = Some(glyph.clone());
+        run_frames(&mut grid, &engine, 5);
+
+        assert_eq!(grid.current_active_segments, glyph);
+        for segment_id in &glyph {
+            let segment = &grid.grid.segments[segment_id];
+            assert!(segment.is_active());
+            assert_eq!(segment.current_style, grid.target_style);
+        }
+
+        let dimmed = grid.target_style.dimmed(0.5);
+        assert_eq!(dimmed.color.red, grid.target_style.color.red * 0.5);
+        assert_eq!(dimmed.color.alpha, grid.target_style.color.alpha);
     }
 
-    fn apply_transform(&mut self, transform: &Transform2D) {
-        // update self.current_location here only.
-        // the rotation and and scale states aren't as straightforward.
-        self.current_position += transform.translation;
-        self.grid.apply_transform(transform);
+    #[test]
+    fn test_sparkles_stay_out_of_the_glyph() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut grid = test_grid_instance();
+        grid.is_visible = true;
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        let glyph: HashSet<SegmentKey> = grid.grid.segments.keys().take(10).cloned().collect();
+        grid.target_segments = Some(glyph.clone());
+        run_frames(&mut grid, &engine, 5);
+
+        let draw = Draw::new();
+        let rng = StdRng::seed_from_u64(2);
+        grid.set_sparkle(Some(SparkleEffect::new(
+            60.0,
+            0.5,
+            rgba(1.0, 1.0, 1.0, 1.0),
+            rng,
+        )));
+        let mut time = 0.0;
+        for _ in 0..10 {
+            time += 0.05;
+            grid.update(&draw, &engine, time, 0.05);
+        }
+
+        let lit_outside_glyph = |grid: &GridInstance| {
+            grid.grid
+                .segments
+                .values()
+                .filter(|segment| segment.is_lit() && !glyph.contains(&segment.id))
+                .count()
+        };
+        // 60 a second for half a second, none old enough to go out yet
+        assert_eq!(lit_outside_glyph(&grid), 30);
+        assert_eq!(grid.current_active_segments, glyph);
+
+        // hiding the grid puts them all out at once
+        grid.is_visible = false;
+        grid.update(&draw, &engine, time + 0.05, 0.05);
+        assert_eq!(lit_outside_glyph(&grid), 0);
+        assert!(grid
+            .grid
+            .segments
+            .values()
+            .filter(|segment| !glyph.contains(&segment.id))
+            .all(|segment| segment.is_idle()));
     }
 
-    // go back to where grid spawned
-    pub fn reset_location(&mut self) {
-        let transform = Transform2D {
-            translation: self.spawn_location - self.current_position,
-            scale: 1.0,
-            rotation: 0.0,
+    #[test]
+    fn test_per_segment_colors_last_until_reactivated() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        grid.colorful_flag = true;
+        grid.set_colorful_mode(ColorfulMode::PerSegment, StdRng::seed_from_u64(3));
+
+        let all: HashSet<SegmentKey> = grid.grid.segments.keys().cloned().collect();
+        grid.target_segments = Some(all.clone());
+        run_frames(&mut grid, &engine, 5);
+
+        let colors = |grid: &GridInstance| -> HashMap<SegmentKey, Rgba<f32>> {
+            grid.current_active_segments
+                .iter()
+                .map(|id| (id.clone(), grid.grid.segments[id].current_style.color))
+                .collect()
         };
-        self.apply_transform(&transform);
+        let before = colors(&grid);
+        assert_eq!(before, grid.segment_colors);
+
+        // moving the grid doesn't re-roll anything
+        grid.rotate_in_place(30.0);
+        grid.scale_in_place(1.5);
+        run_frames(&mut grid, &engine, 5);
+        assert_eq!(colors(&grid), before);
+
+        // a segment that turns off and back on gets a new color
+        let mut sorted: Vec<&SegmentKey> = all.iter().collect();
+        sorted.sort();
+        let cycled = sorted[0].clone();
+        grid.target_segments = Some(all.iter().filter(|id| **id != cycled).cloned().collect());
+        run_frames(&mut grid, &engine, 5);
+        grid.target_segments = Some(all.clone());
+        run_frames(&mut grid, &engine, 5);
+
+        let after = colors(&grid);
+        assert_ne!(after[&cycled], before[&cycled]);
+        for (segment_id, color) in &before {
+            if *segment_id != cycled {
+                assert_eq!(after[segment_id], *color);
+            }
+        }
     }
-    /**************************** WIP Stretch Effect *****************************/
-    pub fn stretch(&mut self, axis: Axis, target_amount: f32, start_time: f32) {
-        let stretch_animation = StretchAnimation::new(
-            &mut self.grid,
-            &self.current_position,
-            &self.graph,
-            axis,
-            target_amount,
-            start_time,
-        );
-        self.stretch_animation = Some(stretch_animation);
+
+    #[test]
+    fn test_flicker_recovers_to_changing_base_color() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        grid.target_segments = Some(grid.grid.segments.keys().cloned().collect());
+        run_frames(&mut grid, &engine, 5);
+
+        let rng = StdRng::seed_from_u64(5);
+        grid.set_flicker(Some(FlickerEffect::new(4.0, 1.0, 0.05, rng)));
+        grid.set_rainbow(0.5, 0.0);
+
+        let draw = Draw::new();
+        let mut dropped_frames = 0;
+        for frame in 0..120 {
+            let time = frame as f32 / 60.0;
+            grid.update_rainbow(time);
+            grid.update(&draw, &engine, time, 1.0 / 60.0);
+
+            let flicker = grid.flicker.as_ref().unwrap();
+            for segment_id in &grid.current_active_segments {
+                let mut expected = grid.active_base_style(segment_id);
+                if flicker.is_dropped(segment_id, time) {
+                    expected = flicker.dim(&expected);
+                    dropped_frames += 1;
+                }
+                assert_eq!(grid.grid.segments[segment_id].current_style, expected);
+            }
+        }
+        assert!(dropped_frames > 0);
+
+        // stopping brings everything back
+        grid.set_flicker(None);
+        grid.update(&draw, &engine, 2.0, 1.0 / 60.0);
+        for segment_id in &grid.current_active_segments {
+            assert_eq!(
+                grid.grid.segments[segment_id].current_style,
+                grid.active_base_style(segment_id)
+            );
+        }
     }
 
-    pub fn boundary_test(&mut self, axis: Axis) {
-        let mut boundary_segments = stretch::boundary_segments(&self.grid, axis);
-        let mut stretch_points = Vec::new();
-        let target_style = DrawStyle {
-            color: rgba(0.0, 1.0, 0.0, 1.0),
-            stroke_weight: 10.0,
+    #[test]
+    fn test_pulse_dims_lit_segments_until_depth_zero() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        grid.target_segments = Some(grid.grid.segments.keys().cloned().collect());
+        run_frames(&mut grid, &engine, 5);
+
+        let pulse = ActivePulseEffect::new(2.0, 0.5, 0.0);
+        grid.set_pulse(2.0, 0.5, 0.0);
+        let assert_pulsed = |grid: &GridInstance, time: f32| {
+            assert!(!grid.current_active_segments.is_empty());
+            for segment_id in &grid.current_active_segments {
+                let base = grid.active_base_style(segment_id);
+                let style = &grid.grid.segments[segment_id].current_style;
+                assert_eq!(*style, pulse.update(&base, time));
+                assert_ne!(*style, base);
+            }
         };
 
-        // throw out the boundaries on the edge of the grid
-        boundary_segments
-            .retain(|id| !stretch::is_outer_boundary(&self.grid, self.grid.segment(id).unwrap()));
+        // half a period in, the glyph is at its dimmest
+        let draw = Draw::new();
+        grid.update(&draw, &engine, 1.0, 1.0 / 60.0);
+        assert_pulsed(&grid, 1.0);
+
+        // colorful picks each segment's color and the pulse dims that
+        grid.colorful_flag = true;
+        grid.set_colorful_mode(ColorfulMode::PerSegment, StdRng::seed_from_u64(3));
+        grid.update(&draw, &engine, 1.2, 1.0 / 60.0);
+        grid.update(&draw, &engine, 1.4, 1.0 / 60.0);
+        assert!(!grid.segment_colors.is_empty());
+        assert_pulsed(&grid, 1.4);
+
+        grid.set_pulse(2.0, 0.0, 1.5);
+        assert!(!grid.has_active_effects());
+        grid.update(&draw, &engine, 1.6, 1.0 / 60.0);
+        for segment_id in &grid.current_active_segments {
+            assert_eq!(
+                grid.grid.segments[segment_id].current_style,
+                grid.active_base_style(segment_id)
+            );
+        }
+    }
 
-        self.stage_segments_instant_on(&boundary_segments, &target_style);
+    #[test]
+    fn test_backbone_effects_compose_in_priority_order() {
+        use crate::effects::{FadeEffect, StrobeEffect};
 
-        let mut neighbors = HashSet::new();
-        let neighbor_style = DrawStyle {
-            color: rgba(0.0, 0.0, 1.0, 1.0),
-            stroke_weight: 10.0,
+        let gray = |v: f32| DrawStyle {
+            color: rgba(v, v, v, 1.0),
+            stroke_weight: 5.0,
         };
-        let active_neighbor_style = DrawStyle {
-            color: rgba(1.0, 1.0, 0.0, 1.0),
-            stroke_weight: 10.0,
+        let fade = || FadeEffect {
+            base_style: gray(0.2),
+            target_style: gray(0.6),
+            duration: 2.0,
+            start_time: 0.0,
+            is_active: true,
         };
+        let strobe = || StrobeEffect::new(gray(0.2), rgba(1.0, 0.0, 0.0, 1.0), 2.0, 0.5, 2.0, 0.0);
+
+        // added in opposite orders, but the strobe always goes on top
+        let mut first = test_grid_instance();
+        first.add_backbone_effect_with_priority("fade", 0, Box::new(fade()));
+        first.add_backbone_effect_with_priority("strobe", 1, Box::new(strobe()));
+        let mut second = test_grid_instance();
+        second.add_backbone_effect_with_priority("strobe", 1, Box::new(strobe()));
+        second.add_backbone_effect_with_priority("fade", 0, Box::new(fade()));
+
+        for time in [0.1, 0.4, 0.9, 1.3] {
+            let style = first.generate_backbone_style(time);
+            assert_eq!(style, second.generate_backbone_style(time));
+        }
+        // during the strobe's on phase its color wins
+        assert_eq!(
+            first.generate_backbone_style(0.1).color,
+            rgba(1.0, 0.0, 0.0, 1.0)
+        );
+
+        // replacing by name keeps a single entry
+        first.add_backbone_effect_with_priority("fade", 2, Box::new(fade()));
+        assert_eq!(first.backbone_effects.len(), 2);
+        assert_eq!(first.backbone_effects[1].0, "fade");
+    }
+
+    #[test]
+    fn test_interrupted_transitions_leave_no_orphans() {
+        assert_no_orphans(false);
+        assert_no_orphans(true);
+    }
 
-        let neighbor_segment_type = match axis {
-            Axis::X => SegmentType::Horizontal,
-            Axis::Y => SegmentType::Vertical,
+    #[test]
+    fn test_movement_queue_runs_legs_in_order() {
+        let mut grid = test_grid_instance();
+        let leg = |x, y| MovementLeg {
+            target: pt2(x, y),
+            duration: 0.5,
+            easing: EasingType::EaseInOut,
         };
+        grid.queue_movements(vec![leg(100.0, 0.0), leg(100.0, 100.0), leg(-50.0, 20.0)]);
 
-        for segment in &boundary_segments {
-            self.graph
-                .neighbors_of(segment)
-                .iter()
-                .filter_map(|id| self.grid.segment(id))
-                .filter(|s| s.segment_type == neighbor_segment_type)
-                .for_each(|s| {
-                    neighbors.insert(s.id.clone());
-                    stretch_points.push(self.graph.get_connection_point(segment, &s.id).unwrap());
-                });
+        let dt = 1.0 / 60.0;
+        let mut time = 0.0;
+        let mut completed = Vec::new();
+        let mut finish_time = None;
+
+        while time < 3.0 {
+            time += dt;
+            grid.update_movement(time, dt);
+            completed.extend(grid.take_completed_legs());
+            if finish_time.is_none() && completed.len() == 3 {
+                finish_time = Some(time);
+            }
         }
 
-        // try putting a stretch segment at every stretch point
-        for point in stretch_points {
-            let stretch_segment = CachedSegment::new(
-                format!("Stretch-{:?}", point),
-                (9, 9),
-                &PathElement::Line {
-                    x1: point.x + self.current_position.x,
-                    x2: point.x + self.current_position.x + 50.0,
-                    y1: point.y,
-                    y2: point.y,
-                },
-                EdgeType::None,
-                &ViewBox {
-                    min_x: 0.0,
-                    min_y: 0.0,
-                    height: 0.0,
-                    width: 0.0,
-                },
-                (4, 4),
-            );
+        assert_eq!(completed, vec![(1, 2), (2, 1), (3, 0)]);
+        assert!((grid.current_position - pt2(-50.0, 20.0)).length() < 1e-3);
 
-            // track the stretch segment ids
-            //self.stretch_segments.insert(stretch_segment.id.clone());
+        // three half-second legs, each starting the frame the last one ends
+        let finish_time = finish_time.unwrap();
+        assert!((finish_time - 1.5).abs() < 4.0 * dt);
+    }
 
-            // insert the streetch segments into the grid. grid now owns the segment.
-            //self.grid.add_stretch_segment(stretch_segment);
+    #[test]
+    fn test_streamed_moves_pace_to_command_interval() {
+        let mut grid = test_grid_instance();
+        let engine = MovementEngine::new(MovementConfig {
+            duration: 0.0,
+            easing: EasingType::Linear,
+        });
+
+        // a controller sending positions at 30 Hz while frames arrive unevenly
+        let command_interval = 1.0 / 30.0;
+        let frame_times = [0.009, 0.02, 0.024, 0.041, 0.047, 0.062];
+        let mut time = 0.0;
+        let mut target = grid.current_position;
+        for command in 0..10 {
+            let command_time = command as f32 * command_interval;
+            target += vec2(10.0, 0.0);
+            grid.stage_movement(target.x, target.y, 0.0, &engine, command_time);
+
+            for offset in frame_times.iter().take_while(|t| **t < command_interval) {
+                let frame_time = command_time + offset;
+                grid.update_movement(frame_time, frame_time - time);
+                time = frame_time;
+            }
         }
+        assert!((grid.instant_move_interval - command_interval).abs() < 1e-3);
 
-        // clone the neighbors set for processing later
-        let mut active_neighbors = neighbors.clone();
-
-        // differentiate between active and non-active neighbors
-        active_neighbors.retain(|s| self.current_active_segments.contains(s));
-        neighbors.retain(|s| !active_neighbors.contains(s));
-
-        //self.stage_segments_instant_on(&neighbors, &neighbor_style);
-        //self.stage_segments_instant_on(&active_neighbors, &active_neighbor_style);
+        // the last move finishes exactly on target
+        grid.update_movement(time + 1.0, 1.0);
+        assert_eq!(grid.current_position, target);
     }
 
-    /**************************** Row/column Slide Effect *****************************/
-    // todo: refactor with the Animation trait?
-
-    pub fn slide(&mut self, axis: Axis, index: i32, position: f32, time: f32) {
-        // Get current row/col positions
-        let positions = match axis {
-            Axis::X => &mut self.row_positions,
-            Axis::Y => &mut self.col_positions,
+    #[test]
+    fn test_keyframes_land_on_last_values() {
+        let mut grid = test_grid_instance();
+        let keyframe = |time, x, y, rotation, scale| Keyframe {
+            time,
+            position: pt2(x, y),
+            rotation,
+            scale,
+            easing: EasingType::EaseInOut,
         };
+        grid.stage_keyframes(
+            vec![
+                keyframe(0.5, 40.0, 0.0, 30.0, 1.5),
+                keyframe(1.2, -20.0, 60.0, -45.0, 0.75),
+            ],
+            0.0,
+        );
 
-        // Get current position (default to 0.0 if not set)
-        let current_position = *positions.get(&index).unwrap_or(&0.0);
+        let dt = 1.0 / 60.0;
+        let mut time = 0.0;
+        while grid.has_keyframe_track() {
+            time += dt;
+            grid.update_keyframes(time);
+        }
 
-        // Update stored position
-        positions.insert(index, position);
+        assert!((time - 1.2).abs() < 2.0 * dt);
+        assert_eq!(grid.current_position, pt2(-20.0, 60.0));
+        assert_eq!(grid.current_rotation, -45.0);
+        assert_eq!(grid.current_scale, Vec2::splat(0.75));
+    }
 
-        // Find existing animation or create new
-        let existing_index = self
-            .slide_animations
-            .iter()
-            .position(|anim| anim.axis == axis && anim.index == index);
+    #[test]
+    fn test_orbit_tracks_current_position() {
+        let mut grid = test_grid_instance();
+        let center = grid.current_position + vec2(-100.0, 0.0);
+        grid.stage_orbit(center, None, 180.0, 1.0, false, 0.0);
 
-        if let Some(idx) = existing_index {
-            // Update existing animation
-            let anim = &mut self.slide_animations[idx];
-            anim.start_position = anim.current_position;
-            anim.target_position = position;
-            anim.start_time = time;
-        } else {
-            // Create new animation
-            let animation = SlideAnimation {
-                axis,
-                index,
-                start_position: current_position,
-                current_position,
-                target_position: position,
-                start_time: time,
-                duration: 1.0 / 60.0,
-            };
+        grid.update_orbit(0.5);
+        assert!(grid.current_position.distance(center + vec2(0.0, 100.0)) < 1e-3);
+        assert_eq!(grid.current_rotation, 0.0);
 
-            self.slide_animations.push(animation);
+        // a move takes over from the orbit's position
+        grid.stop_transform_animations();
+        grid.update_orbit(1.0);
+        assert!(grid.current_position.distance(center + vec2(0.0, 100.0)) < 1e-3);
+    }
+
+    // Where a segment's first point is drawn
+    fn first_point(grid: &GridInstance, segment_id: &SegmentKey) -> Point2 {
+        let segment = &grid.grid.segments[segment_id];
+        match &grid.grid.world_commands(segment)[0] {
+            DrawCommand::Line { start, .. } => *start,
+            DrawCommand::Arc { points } => points[0],
+            DrawCommand::Circle { center, .. } => *center,
         }
     }
 
-    fn update_slide_animations(&mut self, time: f32) {
-        let mut transforms_to_apply: Vec<(i32, Axis, Transform2D)> = Vec::new();
-        let mut completed = Vec::new();
+    #[test]
+    fn test_small_turns_leave_no_drift() {
+        let mut grid = test_grid_instance();
+        grid.move_by(vec2(40.0, -30.0));
+        let mut ids: Vec<SegmentKey> = grid.grid.segments.keys().cloned().collect();
+        ids.sort();
+        let before: Vec<Point2> = ids.iter().map(|id| first_point(&grid, id)).collect();
+
+        // ten whole turns, a tenth of a degree at a time
+        for step in 1..=10_000 {
+            grid.rotate_in_place(step as f32 * 0.36);
+        }
+        for (id, point) in ids.iter().zip(before) {
+            assert!(
+                first_point(&grid, id).distance(point) < 1e-2,
+                "{} drifted",
+                id
+            );
+        }
+        let size = grid.bounding_box().wh();
+        assert!(size.distance(vec2(400.0, 400.0)) < 1e-2, "{:?}", size);
+    }
 
-        // Calculate all transforms without applying them yet
-        for (i, animation) in self.slide_animations.iter_mut().enumerate() {
-            let elapsed = time - animation.start_time;
-            let progress = (elapsed / animation.duration).clamp(0.0, 1.0);
+    #[test]
+    fn test_eased_slide_reaches_target() {
+        let mut grid = test_grid_instance();
+        let segment_id = grid.grid.row_mut(1)[0].id.clone();
+        let start_x = first_point(&grid, &segment_id).x;
 
-            if progress < 1.0 {
-                // Calculate interpolated position
-                let new_position = animation.start_position
-                    + (animation.target_position - animation.start_position) * progress;
+        grid.slide(Axis::X, 1, 50.0, 2.0, EasingType::EaseInOut, false, 0.0);
 
-                // Calculate movement delta from last frame
-                let delta = new_position - animation.current_position;
+        let dt = 1.0 / 60.0;
+        let mut time = 0.0;
+        let mut previous_x = start_x;
+        while grid.has_slide_animations() {
+            time += dt;
+            grid.update_slide_animations(time);
 
-                // Create transform if there's significant movement
-                if delta.abs() > 0.001 {
-                    let translation = match animation.axis {
-                        Axis::X => vec2(delta, 0.0),
-                        Axis::Y => vec2(0.0, delta),
-                    };
+            let x = first_point(&grid, &segment_id).x;
+            assert!(x >= previous_x, "slide moved backwards at {}", time);
+            previous_x = x;
+        }
 
-                    let transform = Transform2D {
-                        translation,
-                        scale: 1.0,
-                        rotation: 0.0,
-                    };
+        assert!((time - 2.0).abs() < 2.0 * dt);
+        assert!((previous_x - (start_x + 50.0)).abs() < 1e-3);
+    }
 
-                    transforms_to_apply.push((animation.index, animation.axis, transform));
-                }
+    #[test]
+    fn test_slide_clamp_keeps_row_on_texture() {
+        let mut grid = test_grid_instance();
+        let row_width = grid.grid.dimensions.0 as f32 * grid.grid.viewbox.width;
+        let texture_width = row_width * 2.0;
+        grid.set_slide_clamp(Some(vec2(texture_width, texture_width)));
+
+        grid.slide(Axis::X, 1, 10_000.0, 0.0, EasingType::Linear, false, 0.0);
+        grid.slide(Axis::Y, 2, 10.0, 0.0, EasingType::Linear, false, 0.0);
+        assert_eq!(
+            grid.slide_offsets(),
+            vec![(Axis::X, 1, row_width / 2.0), (Axis::Y, 2, 10.0)]
+        );
 
-                // Update current position
-                animation.current_position = new_position;
-            } else {
-                // Ensure we reach exactly the target position
-                let delta = animation.target_position - animation.current_position;
+        // wrap wins over clamping
+        grid.slide(Axis::X, 1, 10_000.0, 0.0, EasingType::Linear, true, 0.0);
+        assert_eq!(grid.slide_offsets()[0], (Axis::X, 1, 10_000.0));
 
-                if delta.abs() > 0.001 {
-                    let translation = match animation.axis {
-                        Axis::X => vec2(delta, 0.0),
-                        Axis::Y => vec2(0.0, delta),
-                    };
+        grid.slide_reset(1.0, EasingType::Linear, 0.0);
+        assert!(grid
+            .slide_offsets()
+            .iter()
+            .all(|(_, _, offset)| *offset == 0.0));
+    }
 
-                    let transform = Transform2D {
-                        translation,
-                        scale: 1.0,
-                        rotation: 0.0,
-                    };
+    #[test]
+    fn test_slide_reset_turns_running_slides_around() {
+        let mut grid = test_grid_instance();
+        let row = grid.grid.row_mut(1)[0].id.clone();
+        let col = grid.grid.col_mut(2)[0].id.clone();
+        let home = (first_point(&grid, &row), first_point(&grid, &col));
+
+        grid.slide(Axis::X, 1, 120.0, 2.0, EasingType::Linear, false, 0.0);
+        grid.slide(Axis::Y, 2, -80.0, 0.5, EasingType::Linear, false, 0.0);
+        grid.update_slide_animations(1.0);
+        assert_eq!(
+            grid.slide_offsets(),
+            vec![(Axis::X, 1, 120.0), (Axis::Y, 2, -80.0)]
+        );
+        assert!(first_point(&grid, &row).distance(home.0) > 1.0);
 
-                    transforms_to_apply.push((animation.index, animation.axis, transform));
-                }
+        // the row is still on its way out
+        grid.slide_reset(1.0, EasingType::Linear, 1.0);
+        assert!(grid
+            .slide_animations
+            .iter()
+            .all(|animation| animation.target_position == 0.0));
+        grid.update_slide_animations(2.5);
 
-                animation.current_position = animation.target_position;
-                completed.push(i);
-            }
-        }
+        assert!(!grid.has_slide_animations());
+        assert_eq!(
+            grid.slide_offsets(),
+            vec![(Axis::X, 1, 0.0), (Axis::Y, 2, 0.0)]
+        );
+        assert_eq!(grid.row_positions()[&1], 0.0);
+        assert_eq!(grid.col_positions()[&2], 0.0);
+        assert!(first_point(&grid, &row).distance(home.0) < 1e-3);
+        assert!(first_point(&grid, &col).distance(home.1) < 1e-3);
+    }
 
-        // Apply all calculated transforms
-        for (index, axis, transform) in transforms_to_apply {
-            match axis {
-                Axis::X => {
-                    // Get row segments from CachedGrid and apply transform
-                    let segments = self.grid.row_mut(index);
-                    for segment in segments {
-                        segment.apply_transform(&transform);
-                    }
-                }
-                Axis::Y => {
-                    // Get column segments from CachedGrid and apply transform
-                    let segments = self.grid.col_mut(index);
-                    for segment in segments {
-                        segment.apply_transform(&transform);
-                    }
-                }
-            }
+    #[test]
+    fn test_restore_puts_a_snapshot_back_exactly() {
+        let project = Project::load("projects/debug.json").unwrap();
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        let draw = Draw::new();
+        let mut ids: Vec<SegmentKey> = grid.grid.segments.keys().cloned().collect();
+        ids.sort();
+
+        grid.instant_color_change(rgba(1.0, 0.0, 0.0, 1.0));
+        grid.target_segments = Some(ids.iter().step_by(3).cloned().collect());
+        run_frames(&mut grid, &engine, 5);
+        grid.move_by(vec2(40.0, -30.0));
+        grid.rotate_in_place(30.0);
+        grid.scale_xy_in_place(vec2(1.5, 0.8));
+        grid.slide(Axis::X, 1, 120.0, 0.0, EasingType::Linear, false, 0.0);
+        grid.update(&draw, &engine, 1.0, 1.0 / 60.0);
+        grid.dimmer = 0.7;
+
+        let snapshot = grid.snapshot();
+        let points: Vec<Point2> = ids.iter().map(|id| first_point(&grid, id)).collect();
+        let red = grid.grid.segments[&ids[0]].current_style.clone();
+
+        // a different glyph, pose, color and slide, some still in motion
+        grid.instant_color_change(rgba(0.0, 0.0, 1.0, 1.0));
+        grid.target_segments = Some(ids.iter().step_by(2).cloned().collect());
+        run_frames(&mut grid, &engine, 5);
+        grid.move_by(vec2(-100.0, 10.0));
+        grid.rotate_in_place(-45.0);
+        grid.scale_in_place(0.5);
+        grid.slide(Axis::Y, 2, -80.0, 5.0, EasingType::Linear, false, 1.0);
+        grid.update(&draw, &engine, 2.0, 1.0 / 60.0);
+        grid.dimmer = 0.2;
+        grid.is_visible = false;
+        assert_ne!(grid.snapshot(), snapshot);
+
+        grid.restore(&snapshot, &project);
+        grid.update(&draw, &engine, 3.0, 1.0 / 60.0);
+
+        assert_eq!(grid.snapshot(), snapshot);
+        assert!(!grid.has_slide_animations());
+        for (id, point) in ids.iter().zip(points) {
+            assert!(
+                first_point(&grid, id).distance(point) < 1e-3,
+                "{} moved",
+                id
+            );
         }
+        let lit: BTreeSet<SegmentKey> = grid
+            .grid
+            .segments
+            .values()
+            .filter(|segment| segment.is_lit())
+            .map(|segment| segment.id.clone())
+            .collect();
+        assert_eq!(lit, snapshot.active_segments);
+        assert_eq!(grid.grid.segments[&ids[0]].current_style, red);
+    }
 
-        // Remove completed animations
-        for i in completed.iter().rev() {
-            self.slide_animations.remove(*i);
-        }
+    #[test]
+    fn test_snapshots_survive_json() {
+        let mut grid = test_grid_instance();
+        grid.move_by(vec2(12.5, -3.0));
+        grid.slide(Axis::Y, 3, 42.0, 0.0, EasingType::Linear, false, 0.0);
+        grid.current_active_segments = grid.grid.segments.keys().take(4).cloned().collect();
+
+        let snapshot = grid.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let parsed: GridSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, snapshot);
     }
 
-    /******************** Backbone style and effects **************************** */
+    #[test]
+    fn test_wrapped_slide_stays_in_grid_and_resets() {
+        let mut grid = test_grid_instance();
+        let row: Vec<SegmentKey> = grid
+            .grid
+            .row_mut(1)
+            .into_iter()
+            .map(|segment| segment.id.clone())
+            .collect();
+        let original: HashMap<&SegmentKey, Point2> =
+            row.iter().map(|id| (id, first_point(&grid, id))).collect();
+
+        let span = grid.grid.dimensions.0 as f32 * grid.grid.viewbox.width;
+        grid.slide(Axis::X, 1, span * 0.75, 0.0, EasingType::Linear, true, 0.0);
+        grid.update_slide_animations(1.0);
+
+        for id in &row {
+            let commands = grid.grid.world_commands(&grid.grid.segments[id]);
+            assert!(find_average_point(&commands).x.abs() <= span / 2.0);
+        }
 
-    fn generate_backbone_style(&self, time: f32) -> DrawStyle {
-        let mut style = self.backbone_style.clone();
+        grid.slide(Axis::X, 1, 0.0, 0.0, EasingType::Linear, false, 1.0);
+        grid.update_slide_animations(2.0);
 
-        for effect in self.backbone_effects.values() {
-            if effect.is_finished(time) {
-                continue;
-            }
-            style = effect.update(&style, time);
+        for id in &row {
+            assert!(first_point(&grid, id).distance(original[id]) < 1e-3);
         }
-        style
     }
 
-    fn cleanup_backbone_effects(&mut self, time: f32) {
-        for effect_type in self.finished_effects(time) {
-            println!("Removing effect {}", effect_type);
-            self.backbone_effects.remove(&effect_type);
-        }
+    #[test]
+    fn test_tagged_segments_turn_on_and_off() {
+        let mut project = Project::load("projects/debug.json").unwrap();
+        project.svg_base_tile = project
+            .svg_base_tile
+            .replace("<path id=\"arc-", "<path class=\"arc curve\" id=\"arc-");
+        let grid = CachedGrid::new(&project);
+        let graph = Rc::new(SegmentGraph::new(&grid));
+        let mut grid = GridInstance::new(
+            "test".to_string(),
+            &project,
+            "",
+            &grid,
+            graph,
+            pt2(0.0, 0.0),
+            0.0,
+            10.0,
+            5.0,
+        );
+        let counts = grid.grid.tag_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["arc"], 4 * 16);
+        assert_eq!(counts["curve"], counts["arc"]);
+
+        let red = rgba(1.0, 0.0, 0.0, 1.0);
+        grid.current_active_segments = HashSet::from([SegmentKey::new((1, 1), "hor-1-1")]);
+        grid.stage_tag_on("arc", Some(red));
+        let target = grid.target_segments.clone().unwrap();
+        assert_eq!(target.len(), 4 * 16 + 1);
+        assert!(grid
+            .grid
+            .tagged("arc")
+            .all(|segment| target.contains(&segment.id)));
+        assert_eq!(grid.segment_color_overrides.len(), 4 * 16);
+
+        grid.stage_tag_off("curve");
+        assert_eq!(
+            grid.target_segments,
+            Some(HashSet::from([SegmentKey::new((1, 1), "hor-1-1")]))
+        );
     }
 
-    fn finished_effects(&self, time: f32) -> Vec<String> {
-        let mut finished = Vec::new();
-        for effect_type in self.backbone_effects.keys() {
-            if let Some(effect) = self.backbone_effects.get(effect_type) {
-                if effect.is_finished(time) {
-                    finished.push(effect_type.clone());
-                }
-            }
-        }
-        finished
-    }
+    #[test]
+    fn test_type_weights_thicken_only_their_type() {
+        let mut grid = test_grid_instance();
+        grid.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+
+        // the left column lit, the rest backbone
+        let lit: HashSet<SegmentKey> = grid
+            .grid
+            .segments
+            .values()
+            .filter(|segment| segment.tile_coordinate.0 == 1)
+            .map(|segment| segment.id.clone())
+            .collect();
+        grid.target_segments = Some(lit);
+        run_frames(&mut grid, &engine, 60);
+        let weights = |grid: &GridInstance| -> HashMap<SegmentKey, f32> {
+            grid.grid
+                .segments
+                .values()
+                .map(|segment| (segment.id.clone(), segment.current_style.stroke_weight))
+                .collect()
+        };
+        let before = weights(&grid);
 
-    pub fn add_backbone_effect(&mut self, effect_type: &str, effect: Box<dyn BackboneEffect>) {
-        self.backbone_effects
-            .insert(effect_type.to_string(), effect);
-    }
+        for &segment_type in SegmentType::named("arc").unwrap() {
+            grid.set_type_weight(segment_type, 1.5);
+        }
+        run_frames(&mut grid, &engine, 1);
+        let after = weights(&grid);
+        for segment in grid.grid.segments.values() {
+            let expected = match segment.segment_type {
+                SegmentType::ArcTopLeft
+                | SegmentType::ArcTopRight
+                | SegmentType::ArcBottomLeft
+                | SegmentType::ArcBottomRight => before[&segment.id] * 1.5,
+                _ => before[&segment.id],
+            };
+            assert_eq!(after[&segment.id], expected, "{}", segment.id);
+        }
 
-    pub fn set_backbone_stroke_weight(&mut self, stroke_weight: f32) {
-        self.backbone_style = DrawStyle {
-            color: self.backbone_style.color,
-            stroke_weight,
+        // back to 1.0 is exactly as before
+        for &segment_type in &SegmentType::ARCS {
+            grid.set_type_weight(segment_type, 1.0);
         }
+        run_frames(&mut grid, &engine, 1);
+        assert_eq!(weights(&grid), before);
+        assert_eq!(grid.type_weight(SegmentType::ArcTopLeft), 1.0);
     }
 
-    /*********************** Utility Methods **************************** */
+    #[test]
+    fn test_bounding_box_follows_the_grid() {
+        let mut grid = test_grid_instance();
+        let close = |a: Vec2, b: Vec2| a.distance(b) < 1e-2;
+        let size = |grid: &GridInstance| grid.bounding_box().wh();
+        let center = |grid: &GridInstance| grid.bounding_box().xy();
 
-    pub fn has_target_segments(&self) -> bool {
-        self.target_segments.is_some()
-    }
+        // 4x4 tiles of 100
+        assert!(close(size(&grid), vec2(400.0, 400.0)));
+        assert!(close(center(&grid), pt2(0.0, 0.0)));
 
-    pub fn has_active_transition(&self) -> bool {
-        self.active_transition.is_some()
+        grid.apply_transform(&Transform2D {
+            translation: vec2(30.0, -20.0),
+            scale: Vec2::ONE,
+            rotation: 0.0,
+        });
+        assert!(close(size(&grid), vec2(400.0, 400.0)));
+        assert!(close(center(&grid), pt2(30.0, -20.0)));
+
+        // the square's corners swing out to the diagonal
+        grid.rotate_in_place(45.0);
+        assert!(close(size(&grid), Vec2::splat(400.0 * 2.0.sqrt())));
+        assert!(close(center(&grid), pt2(30.0, -20.0)));
+        grid.rotate_in_place(0.0);
+
+        grid.scale_in_place(0.5);
+        assert!(close(size(&grid), vec2(200.0, 200.0)));
+        assert!(close(center(&grid), pt2(30.0, -20.0)));
+
+        // a slid row sticks out past the rest
+        grid.slide(Axis::X, 1, 50.0, 0.5, EasingType::Linear, false, 0.0);
+        grid.update_slide_animations(1.0);
+        assert!(close(size(&grid), vec2(250.0, 200.0)));
+
+        grid.center_on(pt2(0.0, 0.0));
+        assert!(close(center(&grid), pt2(0.0, 0.0)));
     }
 
-    pub fn has_active_movement(&self) -> bool {
-        self.active_movement.is_some()
+    #[test]
+    fn test_hit_test_finds_the_closest_segment_as_drawn() {
+        let mut grid = test_grid_instance();
+        grid.apply_transform(&Transform2D {
+            translation: vec2(40.0, 25.0),
+            scale: Vec2::ONE,
+            rotation: 0.0,
+        });
+        grid.rotate_in_place(30.0);
+        grid.scale_in_place(1.5);
+
+        // every drawn segment, scanned where it is now
+        let scan = |grid: &GridInstance, point: Point2, tolerance: f32| {
+            grid.grid
+                .segments
+                .values()
+                .map(|segment| {
+                    let commands = grid.grid.world_commands(segment);
+                    let distance = segment_analysis::distance_to_point(&commands, point);
+                    (segment.id.clone(), distance)
+                })
+                .filter(|(_, distance)| *distance <= tolerance)
+                .min_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)))
+        };
+        for (x, y) in [(40.0, 25.0), (-120.0, 90.0), (200.0, -160.0), (13.0, 170.0)] {
+            let point = pt2(x, y);
+            let hit = grid.hit_test(point, 20.0);
+            let expected = scan(&grid, point, 20.0);
+            assert_eq!(hit.as_ref().map(|h| &h.0), expected.as_ref().map(|e| &e.0));
+            if let (Some((_, distance)), Some((_, expected))) = (hit, expected) {
+                assert!((distance - expected).abs() < 1e-2);
+            }
+        }
+        // the grid's center is a junction
+        assert!(grid.segment_at(pt2(40.0, 25.0), 1.0).is_some());
+        assert_eq!(grid.segment_at(pt2(2000.0, 0.0), 20.0), None);
+
+        // a slid row is found where it went, not where the index has it
+        let segment_id = grid.grid.row_mut(1)[0].id.clone();
+        let before = first_point(&grid, &segment_id);
+        grid.slide(Axis::X, 1, 300.0, 0.5, EasingType::Linear, false, 0.0);
+        grid.update_slide_animations(1.0);
+        let after = first_point(&grid, &segment_id);
+        assert!(before.distance(after) > 100.0);
+        assert_eq!(grid.hit_test(after, 1.0), scan(&grid, after, 1.0));
+        assert!(grid.hit_test(after, 1.0).unwrap().1 < 1e-3);
     }
 
-    pub fn has_backbone_effects(&self) -> bool {
-        !self.backbone_effects.is_empty()
-    }
+    #[test]
+    fn test_arcs_retessellate_as_the_grid_scales() {
+        let arc_points = |grid: &GridInstance| {
+            let segment = &grid.grid.segments[&SegmentKey::new((1, 1), "arc-1")];
+            match grid.grid.world_commands(segment).as_slice() {
+                [DrawCommand::Arc { points }] => points.clone(),
+                _ => panic!("arc-1 isn't an arc"),
+            }
+        };
+        let place = |grid: &mut GridInstance| {
+            grid.move_by(vec2(40.0, -30.0));
+            grid.rotate_in_place(30.0);
+        };
 
-    pub fn has_slide_animations(&self) -> bool {
-        !self.slide_animations.is_empty()
+        let mut grid = test_grid_instance();
+        let original = arc_points(&grid).len();
+        place(&mut grid);
+
+        grid.scale_in_place(3.0);
+        assert!(arc_points(&grid).len() > original);
+        grid.scale_in_place(0.2);
+        assert!(arc_points(&grid).len() < original);
+        grid.scale_in_place(1.0);
+        assert_eq!(arc_points(&grid).len(), original);
+
+        // the arc kept its move and turn through every re-tessellation
+        let mut unscaled = test_grid_instance();
+        place(&mut unscaled);
+        for (point, expected) in arc_points(&grid).iter().zip(arc_points(&unscaled)) {
+            assert!(point.distance(expected) < 1e-2);
+        }
     }
 
-    /*********************** Debug Helper ******************************* */
+    #[test]
+    fn test_squash_follows_the_grid_axes() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-2;
+        let arc_size = |grid: &GridInstance| {
+            let segment = &grid.grid.segments[&SegmentKey::new((1, 1), "arc-1")];
+            grid.grid.segment_bounds(segment).unwrap().wh()
+        };
 
-    pub fn print_grid_info(&self) {
-        println!("<====== Grid Instance: {} ======>", self.id);
-        println!("\nGrid Info:");
-        println!("Location: {:?}", self.current_position);
-        println!("Dimensions: {:?}", self.grid.dimensions);
-        println!("Viewbox: {:?}", self.grid.viewbox);
-        println!("Segment count: {}\n", self.grid.segments.len());
+        // turned a quarter, then squashed to half height along its own y
+        let mut grid = test_grid_instance();
+        let arc = arc_size(&grid);
+        grid.rotate_in_place(90.0);
+        grid.scale_xy_in_place(vec2(1.0, 0.5));
+        let size = grid.bounding_box().wh();
+        assert!(close(size.x, 200.0) && close(size.y, 400.0), "{:?}", size);
+        assert!(close(grid.target_style.stroke_weight, 10.0 * 0.5f32.sqrt()));
+
+        // re-tessellated arcs stay squashed
+        let mut grid = test_grid_instance();
+        grid.scale_xy_in_place(vec2(4.0, 1.0));
+        let squashed = arc_size(&grid);
+        assert!(close(squashed.x, arc.x * 4.0) && close(squashed.y, arc.y));
+
+        // the uniform scale undoes it
+        grid.scale_in_place(1.0);
+        assert_eq!(grid.current_scale, Vec2::ONE);
+        let size = grid.bounding_box().wh();
+        assert!(close(size.x, 400.0) && close(size.y, 400.0), "{:?}", size);
+        assert!(close(arc_size(&grid).x, arc.x) && close(arc_size(&grid).y, arc.y));
     }
 }
@@ -0,0 +1,111 @@
+// src/views/grid/grid_pool.rs
+//
+// Grids left by destroyed GridInstances, kept for the next one created.
+// Cloning the base grid copies every segment, which shows as a hitch when a
+// grid is created mid-show; a pooled grid only has its segments reset.
+
+use crate::views::{CachedGrid, GridInstance};
+
+pub struct GridPool {
+    grids: Vec<CachedGrid>,
+    capacity: usize,
+}
+
+impl GridPool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            grids: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    // Keeps the instance's grid if there's room, otherwise it's dropped
+    pub fn release(&mut self, instance: GridInstance) {
+        if self.grids.len() < self.capacity {
+            self.grids.push(instance.into_grid());
+        }
+    }
+
+    // A grid ready for GridInstance::with_grid, if one is waiting
+    pub fn take(&mut self) -> Option<CachedGrid> {
+        self.grids.pop()
+    }
+
+    pub fn len(&self) -> usize {
+        self.grids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.grids.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{EasingType, TransitionAnimationType};
+    use crate::models::{Axis, Project};
+    use crate::services::SegmentGraph;
+    use crate::test_helpers::test_engine;
+    use crate::views::CachedSegment;
+    use nannou::prelude::*;
+    use std::rc::Rc;
+
+    fn instance(name: &str, grid: CachedGrid, project: &Project) -> GridInstance {
+        let graph = Rc::new(SegmentGraph::new(&grid));
+        GridInstance::with_grid(
+            name.to_string(),
+            project,
+            "",
+            grid,
+            graph,
+            pt2(0.0, 0.0),
+            0.0,
+            10.0,
+            5.0,
+        )
+    }
+
+    #[test]
+    fn test_pooled_grids_come_back_like_new() {
+        let project = Project::load("projects/debug.json").unwrap();
+        let base = CachedGrid::new(&project);
+        let mut pool = GridPool::new(1);
+
+        // lit, moved, turned, scaled and slid
+        let mut used = instance("used", base.clone(), &project);
+        used.transition_next_animation_type = TransitionAnimationType::Immediate;
+        let engine = test_engine(20, 0.02);
+        used.stage_component("1,1 : arc-1", true);
+        used.move_by(vec2(120.0, -40.0));
+        used.rotate_in_place(30.0);
+        used.scale_in_place(2.5);
+        used.slide(Axis::X, 1, 50.0, 0.0, EasingType::Linear, false, 0.0);
+        let draw = Draw::new();
+        for frame in 0..30 {
+            used.update(&draw, &engine, frame as f32 / 60.0, 1.0 / 60.0);
+        }
+        assert!(used.grid.segments.values().any(CachedSegment::is_lit));
+
+        pool.release(used);
+        pool.release(instance("extra", base.clone(), &project));
+        assert_eq!(pool.len(), 1);
+
+        let reused = instance("reused", pool.take().unwrap(), &project);
+        assert!(pool.is_empty());
+        assert!(reused.grid.segments.values().all(CachedSegment::is_idle));
+        assert!(
+            reused
+                .bounding_box()
+                .wh()
+                .distance(base.bounding_box().wh())
+                < 1e-3
+        );
+        for (id, segment) in &base.segments {
+            let pooled = &reused.grid.segments[id];
+            assert_eq!(pooled.draw_commands.len(), segment.draw_commands.len());
+            assert_eq!(pooled.offset, Vec2::ZERO);
+        }
+        assert_eq!(reused.current_scale, Vec2::ONE);
+    }
+}
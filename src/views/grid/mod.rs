@@ -1,5 +1,8 @@
 // src/views/grid/mod.rs
 
+pub mod grid_array;
 pub mod grid_generic;
 pub mod grid_instance;
+pub mod grid_pool;
+pub mod spatial_index;
 pub mod transform;
@@ -0,0 +1,192 @@
+// src/views/grid/spatial_index.rs
+//
+// Finds the segments near a point or inside a rect without scanning them all.
+// Segments are bucketed by the tile they cross, and by sub-cell within the
+// tile, in the grid's local space, before any instance transform. A grid
+// instance moves, rotates and scales as a whole, so queries in world space
+// are brought back into local space with the instance's transform instead of
+// rebuilding the index. Moves of single segments, like slides, aren't seen.
+
+use crate::models::SegmentKey;
+use crate::utilities::segment_analysis;
+use crate::views::{CachedSegment, DrawCommand};
+use nannou::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+const SUBDIVISIONS: i32 = 4; // sub-cells across and down each tile
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CellKey {
+    tile: (i32, i32), // 1,1 is the top left tile, as in segment ids
+    sub: (i32, i32),
+}
+
+#[derive(Default)]
+pub struct SpatialIndex {
+    cells: HashMap<CellKey, Vec<SegmentKey>>,
+    shapes: HashMap<SegmentKey, (Rect, Vec<DrawCommand>)>, // bounds and local geometry
+    top_left: Point2,
+    cell_size: Vec2,
+}
+
+impl SpatialIndex {
+    pub fn new(
+        segments: &HashMap<SegmentKey, CachedSegment>,
+        tile_size: Vec2,
+        dimensions: (u32, u32),
+    ) -> Self {
+        let grid_size = tile_size * vec2(dimensions.0 as f32, dimensions.1 as f32);
+        let mut index = Self {
+            cells: HashMap::new(),
+            shapes: HashMap::new(),
+            top_left: pt2(-grid_size.x / 2.0, grid_size.y / 2.0),
+            cell_size: tile_size / SUBDIVISIONS as f32,
+        };
+
+        let mut ids: Vec<&SegmentKey> = segments.keys().collect();
+        ids.sort();
+        for id in ids {
+            let commands = &segments[id].draw_commands;
+            let Some(bounds) = segment_analysis::command_bounds(commands) else {
+                continue;
+            };
+            for key in index.cells_overlapping(bounds) {
+                index.cells.entry(key).or_default().push(id.clone());
+            }
+            index.shapes.insert(id.clone(), (bounds, commands.clone()));
+        }
+        index
+    }
+
+    // Segments that come within `radius` of `point`, sorted by id
+    pub fn segments_near(&self, point: Point2, radius: f32) -> Vec<SegmentKey> {
+        let area = Rect::from_xy_wh(point, vec2(radius * 2.0, radius * 2.0));
+        let mut found: Vec<SegmentKey> = self
+            .candidates(area)
+            .into_iter()
+            .filter(|id| {
+                let (_, commands) = &self.shapes[*id];
+                segment_analysis::distance_to_point(commands, point) <= radius
+            })
+            .cloned()
+            .collect();
+        found.sort();
+        found
+    }
+
+    // The segment passing closest to `point`, if any come within `radius`,
+    // and how far it is. Ties go to the lower id.
+    pub fn nearest(&self, point: Point2, radius: f32) -> Option<(SegmentKey, f32)> {
+        let area = Rect::from_xy_wh(point, vec2(radius * 2.0, radius * 2.0));
+        self.candidates(area)
+            .into_iter()
+            .map(|id| {
+                let (_, commands) = &self.shapes[id];
+                (id, segment_analysis::distance_to_point(commands, point))
+            })
+            .filter(|(_, distance)| *distance <= radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)))
+            .map(|(id, distance)| (id.clone(), distance))
+    }
+
+    // Segments whose bounds overlap `rect`, sorted by id
+    pub fn segments_in_rect(&self, rect: Rect) -> Vec<SegmentKey> {
+        let mut found: Vec<SegmentKey> = self
+            .candidates(rect)
+            .into_iter()
+            .filter(|id| overlaps(&self.shapes[*id].0, &rect))
+            .cloned()
+            .collect();
+        found.sort();
+        found
+    }
+
+    fn candidates(&self, area: Rect) -> HashSet<&SegmentKey> {
+        self.cells_overlapping(area)
+            .filter_map(|key| self.cells.get(&key))
+            .flatten()
+            .collect()
+    }
+
+    fn cells_overlapping(&self, area: Rect) -> impl Iterator<Item = CellKey> {
+        // cell columns count right from the top left corner, rows down
+        let column = |x: f32| ((x - self.top_left.x) / self.cell_size.x).floor() as i32;
+        let row = |y: f32| ((self.top_left.y - y) / self.cell_size.y).floor() as i32;
+        let (left, right) = (column(area.left()), column(area.right()));
+        let (top, bottom) = (row(area.top()), row(area.bottom()));
+
+        (top..=bottom).flat_map(move |row| {
+            (left..=right).map(move |column| CellKey {
+                tile: (
+                    column.div_euclid(SUBDIVISIONS) + 1,
+                    row.div_euclid(SUBDIVISIONS) + 1,
+                ),
+                sub: (
+                    column.rem_euclid(SUBDIVISIONS),
+                    row.rem_euclid(SUBDIVISIONS),
+                ),
+            })
+        })
+    }
+}
+
+// Touching edges count, so a line lying along a rect's side is in it
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.left() <= b.right() && b.left() <= a.right() && a.bottom() <= b.top() && b.bottom() <= a.top()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Project;
+    use crate::views::CachedGrid;
+
+    #[test]
+    fn test_queries_match_a_linear_scan() {
+        let grid = CachedGrid::new(&Project::load("projects/debug.json").unwrap());
+        let index = &grid.spatial_index;
+        let scan = |keep: &dyn Fn(&CachedSegment) -> bool| {
+            let mut ids: Vec<SegmentKey> = grid
+                .segments
+                .values()
+                .filter(|segment| keep(segment))
+                .map(|segment| segment.id.clone())
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        for (x, y, radius) in [(0.0, 0.0, 5.0), (-100.0, 80.0, 30.0), (250.0, -40.0, 1.0)] {
+            let point = pt2(x, y);
+            let expected = scan(&|segment| {
+                segment_analysis::distance_to_point(&segment.draw_commands, point) <= radius
+            });
+            assert_eq!(index.segments_near(point, radius), expected);
+        }
+        assert!(!index.segments_near(pt2(0.0, 0.0), 5.0).is_empty());
+
+        let rect = Rect::from_corners(pt2(-120.0, -30.0), pt2(40.0, 90.0));
+        let expected = scan(&|segment| {
+            let bounds = segment_analysis::command_bounds(&segment.draw_commands).unwrap();
+            overlaps(&bounds, &rect)
+        });
+        assert_eq!(index.segments_in_rect(rect), expected);
+        assert!(!expected.is_empty());
+
+        for (x, y, radius) in [(3.0, 47.0, 10.0), (-160.0, 120.0, 25.0), (75.0, -75.0, 2.0)] {
+            let point = pt2(x, y);
+            let expected = grid
+                .segments
+                .values()
+                .map(|segment| {
+                    let distance =
+                        segment_analysis::distance_to_point(&segment.draw_commands, point);
+                    (segment.id.clone(), distance)
+                })
+                .filter(|(_, distance)| *distance <= radius)
+                .min_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+            assert_eq!(index.nearest(point, radius), expected);
+        }
+        assert_eq!(index.nearest(pt2(1000.0, 1000.0), 10.0), None);
+    }
+}
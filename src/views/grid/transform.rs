@@ -5,10 +5,14 @@
 use nannou::prelude::*;
 use std::f32::consts::PI;
 
+// Scales, then rotates, then translates. Scale is along the x and y axes
+// before rotation, so a squash follows whatever it's applied to as that
+// turns: a grid squashed in y and then rotated 90 degrees ends up narrow
+// rather than short.
 #[derive(Debug, Clone)]
 pub struct Transform2D {
     pub translation: Vec2,
-    pub scale: f32,
+    pub scale: Vec2,
     pub rotation: f32,
 }
 
@@ -16,13 +20,33 @@ impl Default for Transform2D {
     fn default() -> Self {
         Self {
             translation: Vec2::ZERO,
-            scale: 1.0,
+            scale: Vec2::ONE,
             rotation: 0.0,
         }
     }
 }
 
 impl Transform2D {
+    // One factor for sizes that have no direction, like stroke weights and
+    // circle radii: the geometric mean of the x and y scales
+    pub fn mean_scale(&self) -> f32 {
+        (self.scale.x * self.scale.y).abs().sqrt()
+    }
+
+    pub fn is_uniform(&self) -> bool {
+        self.scale.x == self.scale.y
+    }
+
+    // The same map as one matrix and offset, for applying to many points
+    // without working out the rotation for each
+    pub fn to_affine(&self) -> Affine2 {
+        Affine2::from_scale_angle_translation(
+            self.scale,
+            self.rotation.to_radians(),
+            self.translation,
+        )
+    }
+
     // new function to combine two transforms
     pub fn combine(&self, other: &Transform2D) -> Transform2D {
         Transform2D {
@@ -49,6 +73,21 @@ impl Transform2D {
         // 3. Translate
         rotated + self.translation
     }
+
+    // The point that apply_to_point would have moved to `point`
+    pub fn invert_point(&self, point: Point2) -> Point2 {
+        let untranslated = point - self.translation;
+
+        let rotation = -self.rotation * PI / 180.0;
+        let cos_rot = rotation.cos();
+        let sin_rot = rotation.sin();
+        let unrotated = pt2(
+            untranslated.x * cos_rot - untranslated.y * sin_rot,
+            untranslated.x * sin_rot + untranslated.y * cos_rot,
+        );
+
+        unrotated / self.scale
+    }
 }
 
 #[cfg(test)]
@@ -60,7 +99,7 @@ mod tests {
     fn test_default_transform() {
         let transform = Transform2D::default();
         assert_eq!(transform.translation, Vec2::ZERO);
-        assert_eq!(transform.scale, 1.0);
+        assert_eq!(transform.scale, Vec2::ONE);
         assert_eq!(transform.rotation, 0.0);
     }
 
@@ -68,19 +107,19 @@ mod tests {
     fn test_combine_transforms() {
         let t1 = Transform2D {
             translation: Vec2::new(1.0, 2.0),
-            scale: 2.0,
+            scale: Vec2::splat(2.0),
             rotation: PI / 4.0,
         };
 
         let t2 = Transform2D {
             translation: Vec2::new(3.0, 4.0),
-            scale: 3.0,
+            scale: Vec2::splat(3.0),
             rotation: PI / 2.0,
         };
 
         let combined = t1.combine(&t2);
         assert_eq!(combined.translation, Vec2::new(4.0, 6.0));
-        assert_eq!(combined.scale, 6.0);
+        assert_eq!(combined.scale, Vec2::splat(6.0));
         assert_eq!(combined.rotation, 3.0 * PI / 4.0);
     }
 
@@ -89,7 +128,7 @@ mod tests {
         // Test translation only
         let transform = Transform2D {
             translation: Vec2::new(1.0, 1.0),
-            scale: 1.0,
+            scale: Vec2::ONE,
             rotation: 0.0,
         };
         let point = pt2(1.0, 1.0);
@@ -100,7 +139,7 @@ mod tests {
         // Test scale only
         let transform = Transform2D {
             translation: Vec2::ZERO,
-            scale: 2.0,
+            scale: Vec2::splat(2.0),
             rotation: 0.0,
         };
         let transformed = transform.apply_to_point(point);
@@ -110,7 +149,7 @@ mod tests {
         // Test rotation only (90 degrees)
         let transform = Transform2D {
             translation: Vec2::ZERO,
-            scale: 1.0,
+            scale: Vec2::ONE,
             rotation: PI / 2.0,
         };
         let transformed = transform.apply_to_point(point);
@@ -120,11 +159,44 @@ mod tests {
         // Test combined transformation
         let transform = Transform2D {
             translation: Vec2::new(1.0, 1.0),
-            scale: 2.0,
+            scale: Vec2::splat(2.0),
             rotation: PI / 2.0,
         };
         let transformed = transform.apply_to_point(point);
         assert!((transformed.x - -1.0).abs() < 1e-6);
         assert!((transformed.y - 3.0).abs() < 1e-6);
     }
+
+    #[test]
+    fn test_squash_is_along_the_local_axes() {
+        // squashed to half height, then turned a quarter
+        let transform = Transform2D {
+            translation: Vec2::new(10.0, 0.0),
+            scale: Vec2::new(1.0, 0.5),
+            rotation: 90.0,
+        };
+        let close = |a: Vec2, b: Vec2| a.distance(b) < 1e-4;
+
+        // local y is halved and now points along -x
+        assert!(close(
+            transform.apply_to_point(pt2(0.0, 4.0)),
+            pt2(8.0, 0.0)
+        ));
+        assert!(close(
+            transform.apply_to_point(pt2(4.0, 0.0)),
+            pt2(10.0, 4.0)
+        ));
+
+        let point = pt2(3.0, -7.0);
+        assert!(close(
+            transform.invert_point(transform.apply_to_point(point)),
+            point
+        ));
+        assert!(close(
+            transform.to_affine().transform_point2(point),
+            transform.apply_to_point(point)
+        ));
+        assert!((transform.mean_scale() - 0.5f32.sqrt()).abs() < 1e-6);
+        assert!(!transform.is_uniform());
+    }
 }
@@ -4,9 +4,14 @@ pub mod background;
 pub mod grid;
 
 pub use background::BackgroundManager;
+pub use grid::grid_array::grid_array_layout;
 pub use grid::grid_generic::{
-    CachedGrid, CachedSegment, DrawCommand, DrawStyle, Layer, SegmentAction, SegmentStateType,
-    SegmentType, StyleUpdateMsg,
+    CachedGrid, CachedSegment, DrawCommand, DrawStyle, Envelope, GlowParams, Layer, OutlineParams,
+    SegmentAction, SegmentStateType, SegmentType, StyleUpdateMsg,
 };
-pub use grid::grid_instance::GridInstance;
+pub use grid::grid_instance::{
+    BandStyle, ColorfulMode, GradientStyle, GridInstance, GridSnapshot, SnapshotStyle,
+};
+pub use grid::grid_pool::GridPool;
+pub use grid::spatial_index::SpatialIndex;
 pub use grid::transform::Transform2D;